@@ -0,0 +1,97 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks of artifact decoding.
+//!
+//! Decoding a public input is dominated by the decompression of its table commitments,
+//! which runs across threads with the `parallel` feature. Compare the results of
+//! `cargo bench --bench decode` with and without `--features parallel`.
+
+use ark_std::test_rng;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use proof_of_sql::{
+    base::{
+        commitment::{QueryCommitments, QueryCommitmentsExt},
+        database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+    },
+    proof_primitive::dory::{
+        DoryEvaluationProof, DoryProverPublicSetup, DoryVerifierPublicSetup, ProverSetup,
+        PublicParameters, VerifierSetup,
+    },
+    sql::{
+        parse::QueryExpr,
+        proof::{ProofPlan, VerifiableQueryResult},
+    },
+};
+use proof_of_sql_verifier::PublicInput;
+
+/// Serializes a public input committing to `tables` tables of 16 columns each.
+fn setup(tables: usize) -> Vec<u8> {
+    let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+    let ps = ProverSetup::from(&public_parameters);
+    let vs = VerifierSetup::from(&public_parameters);
+    let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+
+    let mut accessor =
+        OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+    accessor.add_table(
+        "sxt.table".parse().unwrap(),
+        owned_table((0..16).map(|i| bigint(format!("c{i}").as_str(), [i, i + 1, i + 2]))),
+        0,
+    );
+    let query = QueryExpr::try_new(
+        "SELECT * FROM table WHERE c0 > 0".parse().unwrap(),
+        "sxt".parse().unwrap(),
+        &accessor,
+    )
+    .unwrap();
+    let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+        query.proof_expr(),
+        &accessor,
+        &prover_setup,
+    );
+    let query_data = proof
+        .verify(
+            query.proof_expr(),
+            &accessor,
+            &DoryVerifierPublicSetup::new(&vs, 4),
+        )
+        .unwrap();
+    let columns = query.proof_expr().get_column_references();
+    let mut commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+    let commitment = commitments[0].clone();
+    for i in 1..tables {
+        let table_ref = format!("sxt.table{i}").parse().unwrap();
+        commitments.insert(table_ref, commitment.clone());
+    }
+    PublicInput::new(query.proof_expr(), commitments, query_data)
+        .try_to_bytes()
+        .unwrap()
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_public_input");
+    group.sample_size(10);
+    for tables in [1, 8, 48] {
+        let bytes = setup(tables);
+        group.bench_with_input(BenchmarkId::from_parameter(tables), &bytes, |b, bytes| {
+            b.iter(|| PublicInput::try_from(black_box(&bytes[..])).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks of the verification path.
+//!
+//! `verify_proof` borrows the proof, so its cost should not depend on how large the
+//! proof is beyond the verification work itself. The `clone_then_verify` case measures
+//! what an owned API would pay on every call.
+
+use ark_std::test_rng;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use proof_of_sql::{
+    base::{
+        commitment::{QueryCommitments, QueryCommitmentsExt},
+        database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+    },
+    proof_primitive::dory::{
+        DoryEvaluationProof, DoryProverPublicSetup, DoryVerifierPublicSetup, ProverSetup,
+        PublicParameters, VerifierSetup,
+    },
+    sql::{
+        parse::QueryExpr,
+        proof::{ProofPlan, VerifiableQueryResult},
+    },
+};
+use proof_of_sql_verifier::{verify_proof, Proof, PublicInput, VerificationKey};
+
+/// Proves a filter query over a table of `rows` rows.
+fn setup(rows: i64) -> (Proof, PublicInput, VerificationKey) {
+    let public_parameters = PublicParameters::test_rand(6, &mut test_rng());
+    let ps = ProverSetup::from(&public_parameters);
+    let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+    let vs = VerifierSetup::from(&public_parameters);
+    let vk = VerificationKey::new(&public_parameters, 4);
+
+    let mut accessor =
+        OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+    accessor.add_table(
+        "sxt.table".parse().unwrap(),
+        owned_table([
+            bigint("a", 0..rows),
+            varchar("b", (0..rows).map(|i| i.to_string())),
+        ]),
+        0,
+    );
+    let query = QueryExpr::try_new(
+        "SELECT a, b FROM table WHERE a > 5".parse().unwrap(),
+        "sxt".parse().unwrap(),
+        &accessor,
+    )
+    .unwrap();
+    let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+        query.proof_expr(),
+        &accessor,
+        &prover_setup,
+    );
+    let query_data = proof
+        .verify(
+            query.proof_expr(),
+            &accessor,
+            &DoryVerifierPublicSetup::new(&vs, 4),
+        )
+        .unwrap();
+    let columns = query.proof_expr().get_column_references();
+    let commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+    let pubs = PublicInput::new(query.proof_expr(), commitments, query_data);
+    (Proof::new(proof), pubs, vk)
+}
+
+fn verify(c: &mut Criterion) {
+    let (proof, pubs, vk) = setup(1024);
+    let mut group = c.benchmark_group("verify");
+    group.sample_size(10);
+    group.bench_function("by_reference", |b| {
+        b.iter(|| verify_proof(black_box(&proof), &pubs, &vk).unwrap())
+    });
+    group.bench_function("clone_then_verify", |b| {
+        b.iter(|| {
+            let proof = black_box(&proof).clone();
+            verify_proof(&proof, &pubs, &vk).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, verify);
+criterion_main!(benches);
@@ -0,0 +1,180 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Measures the verification cost of a query as its result grows, to check the cost
+//! model behind [`CostThresholds`] against the hardware running it.
+//!
+//! A filter over a table of `ROWS` rows, 1024 by default, is proved for result sizes
+//! doubling from one row to the whole table. Each proof is verified `REPETITIONS` times,
+//! 5 by default, and one CSV line is printed per result size with the inputs of the cost
+//! model, the cost class it assigns, the median verification time and the peak heap
+//! usage of verification:
+//!
+//! ```sh
+//! cargo run --release --example cost_sweep -- 4096 10 > cost.csv
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use ark_std::test_rng;
+use proof_of_sql::{
+    base::{
+        commitment::{QueryCommitments, QueryCommitmentsExt},
+        database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+    },
+    proof_primitive::dory::{
+        DoryEvaluationProof, DoryProverPublicSetup, DoryVerifierPublicSetup, ProverSetup,
+        PublicParameters, VerifierSetup,
+    },
+    sql::{
+        parse::QueryExpr,
+        proof::{ProofPlan, VerifiableQueryResult},
+    },
+};
+use proof_of_sql_verifier::{
+    verify_proof, CostThresholds, Proof, PublicInput, QueryCost, VerificationKey,
+};
+
+/// The system allocator, tracking the peak of the allocated heap.
+struct PeakAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl PeakAllocator {
+    /// Restarts the tracking of the peak from the current usage.
+    fn reset_peak(&self) {
+        self.peak
+            .store(self.current.load(Ordering::SeqCst), Ordering::SeqCst);
+    }
+
+    /// Returns the peak usage since the last reset, above the usage at that reset.
+    fn peak_since(&self, baseline: usize) -> usize {
+        self.peak.load(Ordering::SeqCst).saturating_sub(baseline)
+    }
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = self.current.fetch_add(layout.size(), Ordering::SeqCst);
+            self.peak
+                .fetch_max(current + layout.size(), Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.current.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let parse = |index: usize, default: usize| {
+        args.get(index)
+            .map_or(Some(default), |arg| arg.parse().ok())
+    };
+    let (Some(rows @ 1..), Some(repetitions @ 1..), ..=2) =
+        (parse(0, 1024), parse(1, 5), args.len())
+    else {
+        eprintln!("Usage: cost_sweep [ROWS] [REPETITIONS]");
+        std::process::exit(2);
+    };
+
+    // Dory verifies tables of up to 2^(max_nu + sigma) rows, see `max_rows_supported`
+    let num_vars = rows.next_power_of_two().trailing_zeros() as usize;
+    let sigma = num_vars / 2;
+    let max_nu = (num_vars - sigma).max(1);
+    let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+    let ps = ProverSetup::from(&public_parameters);
+    let vs = VerifierSetup::from(&public_parameters);
+    let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+    let vk = VerificationKey::new(&public_parameters, sigma);
+    let mut accessor =
+        OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+    accessor.add_table(
+        "sxt.table".parse().unwrap(),
+        owned_table([
+            bigint("a", 0..rows as i64),
+            varchar("b", (0..rows).map(|i| i.to_string())),
+        ]),
+        0,
+    );
+
+    let thresholds = CostThresholds::default();
+    println!(
+        "result_rows,scanned_cells,result_cells,total_cells,cost_class,verify_micros,peak_bytes"
+    );
+    let result_sizes = std::iter::successors(Some(1), |size| Some(size * 2))
+        .take_while(|&size| size < rows)
+        .chain([rows]);
+    for result_rows in result_sizes {
+        let sql = format!("SELECT a, b FROM table WHERE a < {result_rows}");
+        let query =
+            QueryExpr::try_new(sql.parse().unwrap(), "sxt".parse().unwrap(), &accessor).unwrap();
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(
+                query.proof_expr(),
+                &accessor,
+                &DoryVerifierPublicSetup::new(&vs, sigma),
+            )
+            .unwrap();
+        let columns = query.proof_expr().get_column_references();
+        let commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+        let pubs = PublicInput::new(query.proof_expr(), commitments, query_data);
+        let proof = Proof::new(proof);
+
+        let mut times = Vec::with_capacity(repetitions);
+        let mut peak = 0;
+        for _ in 0..repetitions {
+            let baseline = ALLOCATOR.current.load(Ordering::SeqCst);
+            ALLOCATOR.reset_peak();
+            let start = Instant::now();
+            verify_proof(&proof, &pubs, &vk).expect("the proof is valid");
+            times.push(start.elapsed());
+            peak = peak.max(ALLOCATOR.peak_since(baseline));
+        }
+        times.sort();
+        let median: Duration = times[times.len() / 2];
+
+        let cost = QueryCost::of(&pubs);
+        println!(
+            "{result_rows},{},{},{},{:?},{},{peak}",
+            cost.scanned_cells,
+            cost.result_cells,
+            cost.total_cells(),
+            thresholds.classify(&cost),
+            median.as_micros(),
+        );
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Minimal stub of a Substrate runtime verifying proofs, as a reference for pallets.
+//!
+//! The verifier is pulled with `default-features = false`, which is all a runtime
+//! needs: `std` and `parallel` must stay off in the wasm runtime, and `blst` links C
+//! code that the wasm runtime can't. A pallet builds on the two steps of this stub:
+//!
+//! - [`dispatch_weight`] runs while the extrinsic is weighed, before it is dispatched.
+//!   It only decodes the public input and maps the [`CostClass`] of its query to a
+//!   weight, rejecting the queries of the `Reject` class up front.
+//! - [`verify`] runs in the call itself and returns the weight actually consumed,
+//!   which is lower when the artifacts are rejected before the proof is verified.
+//!
+//! Wired into a pallet, the call looks like:
+//!
+//! ```ignore
+//! #[pallet::call_index(0)]
+//! #[pallet::weight(Weight::from_parts(
+//!     proof_of_sql_verifier_runtime_stub::dispatch_weight(&pubs).unwrap_or(u64::MAX),
+//!     0,
+//! ))]
+//! pub fn submit_proof(
+//!     origin: OriginFor<T>,
+//!     vk: Vec<u8>,
+//!     proof: Vec<u8>,
+//!     pubs: Vec<u8>,
+//! ) -> DispatchResultWithPostInfo {
+//!     ensure_signed(origin)?;
+//!     let (weight, result) = proof_of_sql_verifier_runtime_stub::verify(&vk, &proof, &pubs);
+//!     result.map_err(|_| Error::<T>::InvalidProof)?;
+//!     Ok(Some(Weight::from_parts(weight, 0)).into())
+//! }
+//! ```
+//!
+//! The weights below are placeholders, in picoseconds of reference time: they must be
+//! replaced by the results of the pallet benchmarks, run with the key sizes and
+//! [`CostThresholds`] of the target chain.
+
+#![no_std]
+
+use core::fmt::{self, Display, Formatter};
+
+use proof_of_sql_verifier::{
+    precheck_with_config, verify_proof_with_config, CostClass, CostThresholds, Proof, PublicInput,
+    QueryCost, VerificationKey, VerifierConfig, VerifyError,
+};
+
+/// The weight of decoding the artifacts and running the checks that don't involve the
+/// proof, charged whatever the outcome.
+pub const PRECHECK_WEIGHT: u64 = 50_000_000;
+
+/// The weight of verifying a proof, by cost class of its query.
+pub const SMALL_QUERY_WEIGHT: u64 = 2_000_000_000;
+/// See [`SMALL_QUERY_WEIGHT`].
+pub const MEDIUM_QUERY_WEIGHT: u64 = 4_000_000_000;
+/// See [`SMALL_QUERY_WEIGHT`].
+pub const LARGE_QUERY_WEIGHT: u64 = 8_000_000_000;
+
+/// The reasons the runtime refuses a submission.
+#[derive(Debug)]
+pub enum SubmissionError {
+    /// The artifacts can't be decoded, fail the checks or don't verify.
+    Invalid(VerifyError),
+    /// The query is of the `Reject` cost class, too expensive for the chain.
+    TooCostly,
+}
+
+impl Display for SubmissionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(error) => error.fmt(f),
+            Self::TooCostly => f.write_str("Query is too costly to verify"),
+        }
+    }
+}
+
+impl From<VerifyError> for SubmissionError {
+    fn from(error: VerifyError) -> Self {
+        Self::Invalid(error)
+    }
+}
+
+/// Returns the configuration the runtime verifies proofs with, where chains set the
+/// cost thresholds their weights were benchmarked for.
+pub fn config() -> VerifierConfig {
+    VerifierConfig::new().with_cost_thresholds(CostThresholds::default())
+}
+
+/// Maps the cost class of a query to the weight of verifying its proof, or `None` for
+/// queries that must not be accepted.
+pub fn class_weight(class: CostClass) -> Option<u64> {
+    match class {
+        CostClass::Small => Some(SMALL_QUERY_WEIGHT),
+        CostClass::Medium => Some(MEDIUM_QUERY_WEIGHT),
+        CostClass::Large => Some(LARGE_QUERY_WEIGHT),
+        CostClass::Reject => None,
+    }
+}
+
+/// Computes the weight to charge before dispatching the verification of a proof of
+/// `pubs`.
+///
+/// # Errors
+///
+/// Returns the decoding error of the public input, or [`SubmissionError::TooCostly`] if
+/// its query is of the `Reject` cost class.
+pub fn dispatch_weight(pubs: &[u8]) -> Result<u64, SubmissionError> {
+    let config = config();
+    let pubs = PublicInput::try_from_bytes_with_config(pubs, &config)?;
+    let class = config.cost_thresholds().classify(&QueryCost::of(&pubs));
+    let weight = class_weight(class).ok_or(SubmissionError::TooCostly)?;
+    Ok(PRECHECK_WEIGHT + weight)
+}
+
+/// Verifies a proof, returning the weight actually consumed along with the outcome.
+///
+/// Artifacts rejected before the proof is verified only consume [`PRECHECK_WEIGHT`].
+pub fn verify(vk: &[u8], proof: &[u8], pubs: &[u8]) -> (u64, Result<(), SubmissionError>) {
+    let config = config();
+    let prechecked = || {
+        let vk = VerificationKey::try_from(vk)?;
+        let proof = Proof::try_from(proof)?;
+        let pubs = PublicInput::try_from_bytes_with_config(pubs, &config)?;
+        precheck_with_config(&pubs, &vk, &config)?;
+        let class = config.cost_thresholds().classify(&QueryCost::of(&pubs));
+        let weight = class_weight(class).ok_or(SubmissionError::TooCostly)?;
+        Ok((vk, proof, pubs, weight))
+    };
+    match prechecked() {
+        Ok((vk, proof, pubs, weight)) => (
+            PRECHECK_WEIGHT + weight,
+            verify_proof_with_config(&proof, &pubs, &vk, &config).map_err(Into::into),
+        ),
+        Err(error) => (PRECHECK_WEIGHT, Err(error)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn weights_grow_with_cost_class() {
+        let weights = [CostClass::Small, CostClass::Medium, CostClass::Large].map(class_weight);
+        assert!(weights.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(class_weight(CostClass::Reject), None);
+    }
+
+    #[test]
+    fn malformed_artifacts_only_pay_the_precheck() {
+        let (weight, result) = verify(&[0xff], &[0xff], &[0xff]);
+        assert_eq!(weight, PRECHECK_WEIGHT);
+        assert!(matches!(result, Err(SubmissionError::Invalid(_))));
+        assert!(matches!(
+            dispatch_weight(&[0xff]),
+            Err(SubmissionError::Invalid(_))
+        ));
+    }
+}
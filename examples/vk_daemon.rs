@@ -0,0 +1,200 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A long-running verifier that hot-reloads its verification keys.
+//!
+//! The daemon watches two directories:
+//!
+//! * `VK_DIR` holds verification keys, each in a file named after the hex encoding of
+//!   its fingerprint with the `.vk` extension. Keys whose content doesn't match their
+//!   name are refused, so a truncated or misplaced file never replaces a good key.
+//! * `SUBMISSIONS_DIR` holds submissions, one directory each, as saved by
+//!   `save_submission`. They are verified against the registered key they reference.
+//!
+//! Verifications run concurrently and hold a read guard on the reload gate. Applying a
+//! new set of keys takes the write guard, so the swap waits for in-flight
+//! verifications to drain, and verifications starting meanwhile see the new keys.
+//!
+//! ```sh
+//! cargo run --release --example vk_daemon -- VK_DIR SUBMISSIONS_DIR
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, PoisonError, RwLock},
+    thread,
+    time::Duration,
+};
+
+use proof_of_sql_verifier::{
+    load_submission, verify_proof, KeyValidity, SharedVkRegistry, VerificationKey, VkRefOrBytes,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const VK_EXTENSION: &str = "vk";
+
+/// The state shared by the reloader and the verifications.
+#[derive(Default)]
+struct Daemon {
+    registry: SharedVkRegistry,
+    /// Held for reading by verifications, and for writing while keys are swapped.
+    gate: RwLock<()>,
+}
+
+fn main() {
+    let args: Vec<PathBuf> = std::env::args_os().skip(1).map(PathBuf::from).collect();
+    let [vk_dir, submissions_dir] = &args[..] else {
+        eprintln!("Usage: vk_daemon VK_DIR SUBMISSIONS_DIR");
+        std::process::exit(2);
+    };
+
+    let daemon = Arc::new(Daemon::default());
+    let reloader = {
+        let daemon = daemon.clone();
+        let vk_dir = vk_dir.clone();
+        thread::spawn(move || loop {
+            reload(&daemon, &vk_dir);
+            thread::sleep(POLL_INTERVAL);
+        })
+    };
+
+    let mut seen = BTreeSet::new();
+    while !reloader.is_finished() {
+        for dir in new_submissions(submissions_dir, &mut seen) {
+            let daemon = daemon.clone();
+            thread::spawn(move || match verify(&daemon, &dir) {
+                Ok(()) => println!("{}: verified", dir.display()),
+                Err(e) => println!("{}: {e}", dir.display()),
+            });
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Registers the keys added to `vk_dir` since the last call and removes the deleted ones.
+fn reload(daemon: &Daemon, vk_dir: &Path) {
+    let wanted = match read_keys(daemon, vk_dir) {
+        Ok(wanted) => wanted,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let stale: Vec<_> = daemon
+        .registry
+        .snapshot()
+        .entries()
+        .map(|(fingerprint, _)| *fingerprint)
+        .filter(|fingerprint| !wanted.contains_key(fingerprint))
+        .collect();
+    let added: Vec<_> = wanted.into_values().flatten().collect();
+    if stale.is_empty() && added.is_empty() {
+        return;
+    }
+
+    // Wait for the verifications in flight, and hold the new ones back, while swapping
+    let _swap = daemon.gate.write().unwrap_or_else(PoisonError::into_inner);
+    for fingerprint in &stale {
+        daemon.registry.remove(fingerprint);
+    }
+    for vk in added {
+        daemon.registry.register(vk, KeyValidity::ALWAYS);
+    }
+    eprintln!(
+        "reloaded keys: {} registered, {} removed",
+        daemon.registry.len(),
+        stale.len()
+    );
+}
+
+/// Returns the fingerprints of the keys found in `vk_dir`, along with the decoded keys
+/// for those not registered yet.
+fn read_keys(
+    daemon: &Daemon,
+    vk_dir: &Path,
+) -> Result<BTreeMap<[u8; 32], Option<VerificationKey>>, String> {
+    let entries = fs::read_dir(vk_dir)
+        .map_err(|e| format!("cannot read directory {}: {e}", vk_dir.display()))?;
+    let mut keys = BTreeMap::new();
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if path
+            .extension()
+            .is_none_or(|extension| extension != VK_EXTENSION)
+        {
+            continue;
+        }
+        let Some(expected) = path.file_stem().and_then(|stem| parse_hex(stem.to_str()?)) else {
+            eprintln!("{}: not named after a fingerprint", path.display());
+            continue;
+        };
+        // Registered keys are immutable, only new fingerprints need to be decoded
+        if daemon.registry.get(&expected).is_some() {
+            keys.insert(expected, None);
+            continue;
+        }
+        let Ok(bytes) = fs::read(&path) else {
+            continue;
+        };
+        if VkRefOrBytes::Bytes(bytes.clone()).fingerprint() != expected {
+            eprintln!("{}: content doesn't match its fingerprint", path.display());
+            continue;
+        }
+        match VerificationKey::try_from(&bytes[..]) {
+            Ok(vk) => {
+                keys.insert(expected, Some(vk));
+            }
+            Err(e) => eprintln!("{}: {e}", path.display()),
+        }
+    }
+    Ok(keys)
+}
+
+/// Returns the submission directories that appeared since the last call.
+fn new_submissions(dir: &Path, seen: &mut BTreeSet<PathBuf>) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_dir() && seen.insert(path.clone()))
+        .collect()
+}
+
+/// Verifies a submission against the registered key it references.
+fn verify(daemon: &Daemon, dir: &Path) -> Result<(), String> {
+    let (proof, pubs, vk) = load_submission(dir).map_err(|e| e.to_string())?;
+    let _in_flight = daemon.gate.read().unwrap_or_else(PoisonError::into_inner);
+    // Submitted keys are only trusted when registered, so both forms resolve through
+    // the registry
+    let entry = daemon
+        .registry
+        .get(&vk.fingerprint())
+        .ok_or("unknown verification key")?;
+    verify_proof(&proof, &pubs, entry.vk()).map_err(|e| e.to_string())
+}
+
+/// Parses 32 bytes encoded as 64 hex digits.
+fn parse_hex(text: &str) -> Option<[u8; 32]> {
+    if text.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0; 32];
+    for (byte, i) in bytes.iter_mut().zip((0..64).step_by(2)) {
+        *byte = u8::from_str_radix(text.get(i..i + 2)?, 16).ok()?;
+    }
+    Some(bytes)
+}
@@ -0,0 +1,82 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Minimal guest program re-verifying a proof inside a zkVM.
+//!
+//! The guest reads the proof, the public input and the verification key, verifies the
+//! proof and commits the statement hash together with the key fingerprint, so that the
+//! outer proof attests which statement was verified under which key. Wiring it into a
+//! RISC Zero guest only takes an entry point:
+//!
+//! ```ignore
+//! #![no_main]
+//! risc0_zkvm::guest::entry!(main);
+//!
+//! fn main() {
+//!     let input: Vec<u8> = risc0_zkvm::guest::env::read();
+//!     let journal = proof_of_sql_verifier_zkvm_guest::run(&input).unwrap();
+//!     risc0_zkvm::guest::env::commit_slice(&journal);
+//! }
+//! ```
+//!
+//! SP1 guests are wired the same way, through `sp1_zkvm::entrypoint!`,
+//! `sp1_zkvm::io::read_vec` and `sp1_zkvm::io::commit_slice`.
+//!
+//! The pairings computed by the verifier itself, when validating verification keys,
+//! go through `proof_of_sql_verifier::PairingBackend`, available with the `params`
+//! feature, which guests can implement on top of their precompiles. The Dory
+//! verification inside `proof-of-sql` calls arkworks directly, so accelerating it
+//! requires patching the arkworks crates in the guest manifest, under
+//! `[patch.crates-io]`, with precompile-backed forks.
+
+#![no_std]
+
+use proof_of_sql_verifier::{
+    verify_proof, HashVersion, Proof, PublicInput, VerificationKey, VerifyError,
+};
+
+/// Verifies the proof held in `input` and returns the journal to commit: the statement
+/// hash followed by the fingerprint of the verification key.
+///
+/// `input` holds the serialized proof, public input and verification key, in this
+/// order, each prefixed by its length as a little-endian `u32`.
+pub fn run(input: &[u8]) -> Result<[u8; 64], VerifyError> {
+    let (proof, rest) = split_blob(input)?;
+    let (pubs, rest) = split_blob(rest)?;
+    let (vk, rest) = split_blob(rest)?;
+    if !rest.is_empty() {
+        return Err(VerifyError::InvalidInput);
+    }
+
+    let proof = Proof::try_from(proof)?;
+    let pubs = PublicInput::try_from(pubs)?;
+    let vk = VerificationKey::try_from(vk)?;
+    verify_proof(&proof, &pubs, &vk)?;
+
+    let mut journal = [0; 64];
+    journal[..32].copy_from_slice(&pubs.hash(HashVersion::LATEST)?);
+    journal[32..].copy_from_slice(&vk.fingerprint());
+    Ok(journal)
+}
+
+/// Splits a length-prefixed blob off the front of `input`.
+fn split_blob(input: &[u8]) -> Result<(&[u8], &[u8]), VerifyError> {
+    let (len, rest) = input.split_first_chunk().ok_or(VerifyError::InvalidInput)?;
+    let len = usize::try_from(u32::from_le_bytes(*len)).map_err(|_| VerifyError::InvalidInput)?;
+    if rest.len() < len {
+        return Err(VerifyError::InvalidInput);
+    }
+    Ok(rest.split_at(len))
+}
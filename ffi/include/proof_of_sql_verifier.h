@@ -0,0 +1,50 @@
+/* SPDX-License-Identifier: Apache-2.0 */
+
+#ifndef PROOF_OF_SQL_VERIFIER_H
+#define PROOF_OF_SQL_VERIFIER_H
+
+/* Generated with cbindgen, do not edit. Run `cargo make ffi-header` instead. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The outcome of a call into the verifier.
+typedef enum PosqlStatus {
+  // The proof is valid.
+  POSQL_STATUS_OK = 0,
+  // The public input is invalid.
+  POSQL_STATUS_INVALID_INPUT = 1,
+  // The proof data is invalid.
+  POSQL_STATUS_INVALID_PROOF_DATA = 2,
+  // The proof doesn't verify.
+  POSQL_STATUS_VERIFICATION_FAILED = 3,
+  // The verification key is invalid.
+  POSQL_STATUS_INVALID_VERIFICATION_KEY = 4,
+  // A required pointer argument is null.
+  POSQL_STATUS_NULL_POINTER = 5,
+  // The proof was rejected by one of the other checks of the verifier.
+  POSQL_STATUS_REJECTED = 6,
+  // The verifier panicked.
+  POSQL_STATUS_PANIC = 7,
+} PosqlStatus;
+
+// Verifies a serialized proof against a serialized public input and verification key.
+//
+// # Safety
+//
+// Each pointer must be valid for reads of the matching length, and the buffers must not
+// be mutated for the duration of the call.
+enum PosqlStatus posql_verify(const uint8_t *proof,
+                              size_t proof_len,
+                              const uint8_t *pubs,
+                              size_t pubs_len,
+                              const uint8_t *vk,
+                              size_t vk_len);
+
+// Returns the version of the library as a null-terminated string with static lifetime.
+const char *posql_version(void);
+
+#endif  /* PROOF_OF_SQL_VERIFIER_H */
@@ -0,0 +1,157 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! C bindings for the verifier.
+//!
+//! Artifacts are passed as byte buffers in the same encodings accepted by
+//! `TryFrom<&[u8]>` for `Proof`, `PublicInput` and `VerificationKey`.
+
+use core::{ffi::c_char, slice};
+use std::panic::catch_unwind;
+
+use proof_of_sql_verifier::{Proof, PublicInput, VerificationKey, VerifyError};
+
+/// The outcome of a call into the verifier.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PosqlStatus {
+    /// The proof is valid.
+    Ok = 0,
+    /// The public input is invalid.
+    InvalidInput = 1,
+    /// The proof data is invalid.
+    InvalidProofData = 2,
+    /// The proof doesn't verify.
+    VerificationFailed = 3,
+    /// The verification key is invalid.
+    InvalidVerificationKey = 4,
+    /// A required pointer argument is null.
+    NullPointer = 5,
+    /// The proof was rejected by one of the other checks of the verifier.
+    Rejected = 6,
+    /// The verifier panicked.
+    Panic = 7,
+}
+
+impl From<VerifyError> for PosqlStatus {
+    fn from(error: VerifyError) -> Self {
+        match error {
+            VerifyError::InvalidInput
+            | VerifyError::MissingTableCommitment { .. }
+            | VerifyError::MissingColumnMetadata { .. }
+            | VerifyError::ColumnTypeMismatch { .. }
+            | VerifyError::DegenerateCommitment { .. } => PosqlStatus::InvalidInput,
+            VerifyError::InvalidProofData | VerifyError::NonCanonicalProofEncoding => {
+                PosqlStatus::InvalidProofData
+            }
+            VerifyError::VerificationFailed | VerifyError::ProofRejected { .. } => {
+                PosqlStatus::VerificationFailed
+            }
+            VerifyError::InvalidVerificationKey => PosqlStatus::InvalidVerificationKey,
+            _ => PosqlStatus::Rejected,
+        }
+    }
+}
+
+/// Builds a slice from a C buffer, treating a null pointer as an error unless the buffer is empty.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes when it is not null.
+unsafe fn buffer<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], PosqlStatus> {
+    match (ptr.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(PosqlStatus::NullPointer),
+        (false, _) => Ok(slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/// Verifies a serialized proof against a serialized public input and verification key.
+///
+/// # Safety
+///
+/// Each pointer must be valid for reads of the matching length, and the buffers must not
+/// be mutated for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn posql_verify(
+    proof: *const u8,
+    proof_len: usize,
+    pubs: *const u8,
+    pubs_len: usize,
+    vk: *const u8,
+    vk_len: usize,
+) -> PosqlStatus {
+    let verify = || -> Result<(), PosqlStatus> {
+        let proof = Proof::try_from(buffer(proof, proof_len)?)?;
+        let pubs = PublicInput::try_from(buffer(pubs, pubs_len)?)?;
+        let vk = VerificationKey::try_from(buffer(vk, vk_len)?)?;
+        Ok(proof_of_sql_verifier::verify_proof(&proof, &pubs, &vk)?)
+    };
+    match catch_unwind(verify) {
+        Ok(Ok(())) => PosqlStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => PosqlStatus::Panic,
+    }
+}
+
+/// Returns the version of the library as a null-terminated string with static lifetime.
+#[no_mangle]
+pub extern "C" fn posql_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    #[test]
+    fn null_pointers_are_rejected() {
+        let status =
+            unsafe { posql_verify(core::ptr::null(), 1, [0].as_ptr(), 1, [0].as_ptr(), 1) };
+        assert_eq!(status, PosqlStatus::NullPointer);
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        let garbage = [0xffu8; 16];
+        let status = unsafe {
+            posql_verify(
+                garbage.as_ptr(),
+                garbage.len(),
+                garbage.as_ptr(),
+                garbage.len(),
+                garbage.as_ptr(),
+                garbage.len(),
+            )
+        };
+        assert_eq!(status, PosqlStatus::InvalidProofData);
+    }
+
+    #[test]
+    fn version_is_null_terminated() {
+        let version = unsafe { CStr::from_ptr(posql_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn header_declares_the_exports() {
+        let header = include_str!("../include/proof_of_sql_verifier.h");
+        for symbol in ["posql_verify", "posql_version", "POSQL_STATUS_PANIC"] {
+            assert!(header.contains(symbol), "{symbol} missing from header");
+        }
+    }
+}
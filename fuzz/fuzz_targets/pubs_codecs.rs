@@ -0,0 +1,44 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decodes public inputs from CBOR and checks that they survive a round-trip through
+//! JSON, catching lossy or ambiguous mappings between the two codecs.
+//!
+//! Random bytes rarely decode, so seed the corpus with encoded public inputs, e.g. the
+//! `pubs.bin` written by `generate-sample-proof`:
+//!
+//! ```sh
+//! mkdir -p fuzz/corpus/pubs_codecs && cp pubs.bin fuzz/corpus/pubs_codecs/
+//! cargo +nightly fuzz run pubs_codecs
+//! ```
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use proof_of_sql_verifier::{HashVersion, PublicInput};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(pubs) = PublicInput::try_from(data) else {
+        return;
+    };
+    let json = serde_json::to_vec(&pubs).expect("valid public inputs encode to JSON");
+    let decoded: PublicInput =
+        serde_json::from_slice(&json).expect("encoded public inputs decode from JSON");
+    assert_eq!(decoded, pubs);
+    for version in [HashVersion::V1, HashVersion::V2, HashVersion::V3] {
+        assert_eq!(decoded.hash(version).ok(), pubs.hash(version).ok());
+    }
+    assert_eq!(decoded.json_hash().ok(), pubs.json_hash().ok());
+});
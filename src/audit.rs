@@ -0,0 +1,318 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Opt-in transcripts of verifications, for archiving what was verified and by what
+//! software.
+//!
+//! An [`AuditTrail`] only holds digests of the artifacts, never the artifacts
+//! themselves, so that it stays compact whatever the size of the proof. Submissions that
+//! may have to be verified again, e.g. before upgrading the verifier, are logged as
+//! [`ReplayEntry`] lines instead, which `posql-verifier replay` reads.
+
+use alloc::{string::String, vec::Vec};
+use proof_of_sql::{
+    base::commitment::QueryCommitments,
+    proof_primitive::dory::{DoryCommitment, DoryScalar},
+    sql::proof::QueryData,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    rejection_reason,
+    serde::{base64, serialize_sorted_commitments, HashingWriter, QueryDataDef},
+    verify::{
+        check_column_bindings, check_public_input_shapes, check_table_offsets,
+        check_verification_key, verify_normalized_proof,
+    },
+    Check, HashVersion, Proof, PublicInput, SemVer, VerificationKey, VerifierConfig, VerifyError,
+    SUPPORTED_PROVER_VERSION, WIRE_FORMAT_VERSION,
+};
+
+/// The Keccak-256 digests of every section of the verified artifacts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ArtifactDigests {
+    /// The digest of the serialized proof.
+    pub proof: [u8; 32],
+    /// The [`HashVersion::LATEST`] statement hash of the public input.
+    pub statement: [u8; 32],
+    /// The digest of the serialized plan.
+    pub plan: [u8; 32],
+    /// The digest of the serialized table commitments, in canonical order.
+    pub commitments: [u8; 32],
+    /// The digest of the serialized claimed query data.
+    pub query_data: [u8; 32],
+    /// The fingerprint of the verification key, see [`VerificationKey::fingerprint`].
+    pub verification_key: [u8; 32],
+}
+
+impl ArtifactDigests {
+    /// Computes the digests of the given artifacts.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if the public input can't be serialized.
+    pub fn new(
+        proof: &Proof,
+        pubs: &PublicInput,
+        vk: &VerificationKey,
+    ) -> Result<Self, VerifyError> {
+        Ok(Self {
+            proof: Keccak256::digest(proof.to_bytes()).into(),
+            statement: pubs.hash(HashVersion::LATEST)?,
+            plan: cbor_digest(pubs.expr())?,
+            commitments: cbor_digest(&SortedCommitments(pubs.commitments()))?,
+            query_data: cbor_digest(&QueryDataRef(pubs.query_data()))?,
+            verification_key: vk.fingerprint(),
+        })
+    }
+}
+
+/// Computes the Keccak-256 digest of the CBOR encoding of `value`.
+fn cbor_digest<T: Serialize + ?Sized>(value: &T) -> Result<[u8; 32], VerifyError> {
+    let mut hasher = Keccak256::new();
+    ciborium::into_writer(value, HashingWriter(&mut hasher))
+        .map_err(|_| VerifyError::InvalidInput)?;
+    Ok(hasher.finalize().into())
+}
+
+/// Table commitments serialized like in a [`PublicInput`].
+struct SortedCommitments<'a>(&'a QueryCommitments<DoryCommitment>);
+
+impl Serialize for SortedCommitments<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_sorted_commitments(self.0, serializer)
+    }
+}
+
+/// Query data serialized like in a [`PublicInput`].
+struct QueryDataRef<'a>(&'a QueryData<DoryScalar>);
+
+impl Serialize for QueryDataRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        QueryDataDef::serialize(self.0, serializer)
+    }
+}
+
+/// The versions of the software that ran a verification.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SoftwareVersions {
+    /// The version of this crate.
+    pub crate_version: &'static str,
+    /// The prover release whose proof format is supported, see
+    /// [`SUPPORTED_PROVER_VERSION`].
+    pub prover: SemVer,
+    /// The version of the wire formats of the artifacts, see [`WIRE_FORMAT_VERSION`].
+    pub wire_format: u32,
+}
+
+impl SoftwareVersions {
+    /// Returns the versions of this build of the verifier.
+    pub fn current() -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION"),
+            prover: SUPPORTED_PROVER_VERSION,
+            wire_format: WIRE_FORMAT_VERSION,
+        }
+    }
+}
+
+/// The outcome of a check recorded in an [`AuditTrail`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum AuditOutcome {
+    /// The check passed.
+    Passed,
+    /// The check failed, with the reason of the rejection, see [`rejection_reason`].
+    Failed(&'static str),
+    /// The check was not run because an earlier check failed.
+    Skipped,
+}
+
+/// A transcript of a verification: what was verified, by what software, with what
+/// outcome.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AuditTrail {
+    /// The versions of the verifier.
+    pub versions: SoftwareVersions,
+    /// The digests of the verified artifacts.
+    pub digests: ArtifactDigests,
+    /// The outcome of every check, in the order they were run.
+    pub checks: Vec<(Check, AuditOutcome)>,
+    /// The duration of the verification in microseconds, only measured with the `std`
+    /// feature.
+    pub elapsed_micros: Option<u64>,
+}
+
+impl AuditTrail {
+    /// Returns whether every check passed, i.e. whether the proof verified.
+    pub fn is_verified(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|(_, outcome)| *outcome == AuditOutcome::Passed)
+    }
+
+    /// Serializes the trail as canonical JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Vec<u8> {
+        // Trails only hold strings, digests and small integers, which always serialize
+        crate::json::to_canonical_json(self).unwrap()
+    }
+}
+
+/// A submission logged to be verified again, one JSON object per line of the log.
+///
+/// The artifacts are serialized as base64 strings. The verification key is optional, for
+/// logs of submissions all verified with the same key, which is then given to the replay
+/// separately.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayEntry {
+    /// The id of the submission, chosen by the logger.
+    pub id: String,
+    /// The serialized proof.
+    #[serde(with = "base64")]
+    pub proof: Vec<u8>,
+    /// The serialized public input.
+    #[serde(with = "base64")]
+    pub pubs: Vec<u8>,
+    /// The serialized verification key, if logged.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "base64::serialize_optional",
+        deserialize_with = "base64::deserialize_optional"
+    )]
+    pub vk: Option<Vec<u8>>,
+    /// Whether the submission verified when logged.
+    pub verified: bool,
+}
+
+impl ReplayEntry {
+    /// Creates the entry of a submission, `trail` being the transcript of its
+    /// verification, without its verification key.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if the public input can't be serialized.
+    pub fn new(
+        id: impl Into<String>,
+        proof: &Proof,
+        pubs: &PublicInput,
+        trail: &AuditTrail,
+    ) -> Result<Self, VerifyError> {
+        Ok(Self {
+            id: id.into(),
+            proof: proof.to_bytes(),
+            pubs: pubs.try_to_bytes()?,
+            vk: None,
+            verified: trail.is_verified(),
+        })
+    }
+
+    /// Logs the verification key along with the submission.
+    pub fn with_vk(mut self, vk: &VerificationKey) -> Self {
+        self.vk = Some(vk.to_bytes());
+        self
+    }
+
+    /// Serializes the entry as a line of canonical JSON, without the line feed.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Vec<u8> {
+        // Entries only hold strings and a boolean, which always serialize
+        crate::json::to_canonical_json(self).unwrap()
+    }
+}
+
+/// Runs `check` and records its outcome, unless an earlier check failed, in which case
+/// it is recorded as skipped.
+fn record(
+    checks: &mut Vec<(Check, AuditOutcome)>,
+    check: Check,
+    run: impl FnOnce() -> Result<(), VerifyError>,
+) {
+    let failed = checks
+        .iter()
+        .any(|(_, outcome)| matches!(outcome, AuditOutcome::Failed(_)));
+    let outcome = if failed {
+        AuditOutcome::Skipped
+    } else {
+        match run() {
+            Ok(()) => AuditOutcome::Passed,
+            Err(error) => AuditOutcome::Failed(rejection_reason(&error)),
+        }
+    };
+    checks.push((check, outcome));
+}
+
+/// Verifies a Dory proof like [`verify_proof_with_config`](crate::verify_proof_with_config),
+/// recording a transcript of the verification.
+///
+/// Verification stops at the first failing check, the following ones being recorded as
+/// skipped. The plan limits and the table offsets are recorded along with the plan
+/// policy, as [`Check::PlanPolicy`]. Rejections are recorded in the trail rather than
+/// returned.
+///
+/// # Arguments
+///
+/// * `proof` - The Dory proof to be verified.
+/// * `pubs` - The public input for the proof.
+/// * `vk` - The verification key used to verify the proof.
+/// * `config` - The verifier configuration.
+///
+/// # Returns
+///
+/// * `Result<AuditTrail, VerifyError>` - The transcript of the verification, or
+///   `InvalidInput` if the public input can't be serialized to be hashed.
+pub fn verify_audited(
+    proof: &Proof,
+    pubs: &PublicInput,
+    vk: &VerificationKey,
+    config: &VerifierConfig,
+) -> Result<AuditTrail, VerifyError> {
+    let digests = ArtifactDigests::new(proof, pubs, vk)?;
+    #[cfg(feature = "std")]
+    let start = std::time::Instant::now();
+
+    let mut checks = Vec::new();
+    record(&mut checks, Check::PlanPolicy, || {
+        config.plan_limits().check(pubs.expr())?;
+        config.plan_policy().check(&pubs.plan_info())?;
+        config.plan_policy().check_query_data(pubs)?;
+        check_table_offsets(pubs, config)
+    });
+    record(&mut checks, Check::ColumnBindings, || {
+        check_column_bindings(pubs.expr(), pubs.commitments())?;
+        check_public_input_shapes(pubs)
+    });
+    record(&mut checks, Check::VerificationKey, || {
+        check_verification_key(pubs.expr(), pubs.commitments(), vk)
+    });
+    record(&mut checks, Check::Proof, || {
+        config
+            .parallelism()
+            .install(|| verify_normalized_proof(proof, pubs, vk, config.result_normalization()))
+            .map(|_| ())
+    });
+
+    #[cfg(feature = "std")]
+    let elapsed_micros = Some(u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX));
+    #[cfg(not(feature = "std"))]
+    let elapsed_micros = None;
+    Ok(AuditTrail {
+        versions: SoftwareVersions::current(),
+        digests,
+        checks,
+        elapsed_micros,
+    })
+}
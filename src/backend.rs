@@ -0,0 +1,225 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ark_bls12_381::{Bls12_381, G1Affine, G2Affine};
+use ark_ec::pairing::{Pairing, PairingOutput};
+
+/// The target group of the BLS12-381 pairing.
+pub type Gt = PairingOutput<Bls12_381>;
+
+/// Abstraction over the BLS12-381 pairing arithmetic performed by this crate.
+///
+/// Pairings dominate the cost of Dory verification. Every pairing computed
+/// directly by this crate (as opposed to inside `proof-of-sql`) goes through a
+/// `PairingBackend`, so that faster implementations can be selected at compile
+/// time without changing the calling code. These are the pairings checked by
+/// [`validate_verifier_setup`](crate::validate_verifier_setup): verifying a proof
+/// computes none outside `proof-of-sql`.
+///
+/// The trait is public so that other crates can provide their own backend,
+/// e.g. one routing to the precompiles of a zkVM, and pass it as the type
+/// parameter of the functions generic over it. Only `multi_pairing` must be
+/// implemented: `pairing` defaults to a multi-pairing of a single pair.
+pub trait PairingBackend {
+    /// Computes the product of the pairings `e(g1[i], g2[i])`.
+    ///
+    /// # Panics
+    ///
+    /// Implementations may panic if `g1` and `g2` have different lengths.
+    fn multi_pairing(g1: &[G1Affine], g2: &[G2Affine]) -> Gt;
+
+    /// Computes the pairing `e(g1, g2)`.
+    fn pairing(g1: G1Affine, g2: G2Affine) -> Gt {
+        Self::multi_pairing(&[g1], &[g2])
+    }
+}
+
+/// Pairing backend based on the arkworks implementation.
+pub struct Arkworks;
+
+impl PairingBackend for Arkworks {
+    fn multi_pairing(g1: &[G1Affine], g2: &[G2Affine]) -> Gt {
+        assert_eq!(g1.len(), g2.len());
+        Bls12_381::multi_pairing(g1.iter().copied(), g2.iter().copied())
+    }
+}
+
+#[cfg(feature = "blst")]
+mod blst_backend {
+    use ark_bls12_381::{Fq, Fq12, Fq2, Fq6, G1Affine, G2Affine};
+    use ark_ec::pairing::PairingOutput;
+    use ark_ff::PrimeField;
+    use ark_serialize::CanonicalSerialize;
+    use blst::{
+        blst_bendian_from_fp, blst_final_exp, blst_fp, blst_fp12, blst_fp12_mul, blst_fp12_one,
+        blst_fp2, blst_fp6, blst_miller_loop, blst_p1_affine, blst_p1_deserialize, blst_p2_affine,
+        blst_p2_deserialize, BLST_ERROR,
+    };
+
+    use super::{Gt, PairingBackend};
+
+    /// Pairing backend based on the `blst` library.
+    pub struct Blst;
+
+    impl PairingBackend for Blst {
+        fn multi_pairing(g1: &[G1Affine], g2: &[G2Affine]) -> Gt {
+            assert_eq!(g1.len(), g2.len());
+            // SAFETY: `blst_fp12_one` returns a pointer to a static constant.
+            let mut acc = unsafe { *blst_fp12_one() };
+            for (p, q) in g1.iter().zip(g2) {
+                let (p, q) = (to_blst_p1(p), to_blst_p2(q));
+                let mut ml = blst_fp12::default();
+                // SAFETY: all pointers reference valid, initialized values.
+                unsafe {
+                    blst_miller_loop(&mut ml, &q, &p);
+                    blst_fp12_mul(&mut acc, &acc, &ml);
+                }
+            }
+            let mut out = blst_fp12::default();
+            // SAFETY: all pointers reference valid, initialized values.
+            unsafe { blst_final_exp(&mut out, &acc) };
+            PairingOutput(from_blst_fp12(&out))
+        }
+    }
+
+    /// Both libraries use the ZCash encoding for uncompressed points.
+    fn to_blst_p1(p: &G1Affine) -> blst_p1_affine {
+        let mut bytes = [0u8; 96];
+        p.serialize_uncompressed(&mut bytes[..]).unwrap();
+        let mut out = blst_p1_affine::default();
+        // SAFETY: `bytes` holds exactly the 96 bytes read by `blst_p1_deserialize`.
+        let res = unsafe { blst_p1_deserialize(&mut out, bytes.as_ptr()) };
+        assert_eq!(res, BLST_ERROR::BLST_SUCCESS);
+        out
+    }
+
+    fn to_blst_p2(q: &G2Affine) -> blst_p2_affine {
+        let mut bytes = [0u8; 192];
+        q.serialize_uncompressed(&mut bytes[..]).unwrap();
+        let mut out = blst_p2_affine::default();
+        // SAFETY: `bytes` holds exactly the 192 bytes read by `blst_p2_deserialize`.
+        let res = unsafe { blst_p2_deserialize(&mut out, bytes.as_ptr()) };
+        assert_eq!(res, BLST_ERROR::BLST_SUCCESS);
+        out
+    }
+
+    fn from_blst_fp(fp: &blst_fp) -> Fq {
+        let mut bytes = [0u8; 48];
+        // SAFETY: `bytes` has room for the 48 bytes written by `blst_bendian_from_fp`.
+        unsafe { blst_bendian_from_fp(bytes.as_mut_ptr(), fp) };
+        Fq::from_be_bytes_mod_order(&bytes)
+    }
+
+    fn from_blst_fp2(fp2: &blst_fp2) -> Fq2 {
+        Fq2::new(from_blst_fp(&fp2.fp[0]), from_blst_fp(&fp2.fp[1]))
+    }
+
+    fn from_blst_fp6(fp6: &blst_fp6) -> Fq6 {
+        Fq6::new(
+            from_blst_fp2(&fp6.fp2[0]),
+            from_blst_fp2(&fp6.fp2[1]),
+            from_blst_fp2(&fp6.fp2[2]),
+        )
+    }
+
+    /// Both libraries represent Fp12 with the same tower of extensions.
+    fn from_blst_fp12(fp12: &blst_fp12) -> Fq12 {
+        Fq12::new(from_blst_fp6(&fp12.fp6[0]), from_blst_fp6(&fp12.fp6[1]))
+    }
+}
+
+#[cfg(feature = "blst")]
+pub use blst_backend::Blst;
+
+/// The pairing backend selected by the enabled features.
+///
+/// zkVM guests keep the arkworks backend, which their precompiles accelerate.
+#[cfg(any(not(feature = "blst"), feature = "zkvm"))]
+pub type DefaultBackend = Arkworks;
+
+/// The pairing backend selected by the enabled features.
+#[cfg(all(feature = "blst", not(feature = "zkvm")))]
+pub type DefaultBackend = Blst;
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::{G1Projective, G2Projective};
+    use ark_ec::CurveGroup;
+    use ark_std::{test_rng, UniformRand};
+    use rstest::*;
+
+    use super::*;
+
+    fn random_points(n: usize) -> (Vec<G1Affine>, Vec<G2Affine>) {
+        let rng = &mut test_rng();
+        (0..n)
+            .map(|_| {
+                (
+                    G1Projective::rand(rng).into_affine(),
+                    G2Projective::rand(rng).into_affine(),
+                )
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn arkworks_pairing_is_bilinear() {
+        let (g1, g2) = random_points(1);
+        let lhs = Arkworks::pairing((g1[0] + g1[0]).into(), g2[0]);
+        let rhs = Arkworks::pairing(g1[0], (g2[0] + g2[0]).into());
+        assert_eq!(lhs, rhs);
+    }
+
+    #[rstest]
+    #[case::empty(0)]
+    #[case::single(1)]
+    #[case::many(5)]
+    fn multi_pairing_is_product_of_pairings(#[case] n: usize) {
+        let (g1, g2) = random_points(n);
+        let expected = g1
+            .iter()
+            .zip(&g2)
+            .map(|(p, q)| Arkworks::pairing(*p, *q))
+            .sum::<Gt>();
+        assert_eq!(DefaultBackend::multi_pairing(&g1, &g2), expected);
+    }
+
+    #[cfg(feature = "blst")]
+    #[rstest]
+    #[case::empty(0)]
+    #[case::single(1)]
+    #[case::many(5)]
+    fn blst_matches_arkworks(#[case] n: usize) {
+        let (g1, g2) = random_points(n);
+        assert_eq!(
+            Blst::multi_pairing(&g1, &g2),
+            Arkworks::multi_pairing(&g1, &g2)
+        );
+    }
+
+    #[cfg(feature = "blst")]
+    #[test]
+    fn blst_handles_identity() {
+        let (g1, g2) = random_points(1);
+        assert_eq!(
+            Blst::pairing(G1Affine::identity(), g2[0]),
+            Arkworks::pairing(G1Affine::identity(), g2[0])
+        );
+        assert_eq!(
+            Blst::pairing(g1[0], G2Affine::identity()),
+            Arkworks::pairing(g1[0], G2Affine::identity())
+        );
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Plumbing shared by the command line binaries: argument parsing, file access and
+//! error reporting.
+//!
+//! Commands report errors as messages meant for the user, naming the argument or file
+//! at fault. Binaries exit with 0 on success, 1 when a command fails and 2 on invalid
+//! arguments, after printing the usage.
+//!
+//! With `--output json`, commands print their results as JSON objects holding the
+//! [`SCHEMA_VERSION`] and the command name along with the fields of the command.
+
+// Each binary only uses part of the module
+#![allow(dead_code)]
+
+#[cfg(feature = "remote-artifacts")]
+mod remote;
+
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+/// The version of the JSON output of the commands. Fields may be added without a new
+/// version, which is only bumped when fields are removed, renamed or change meaning.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The output format of a command, chosen with `--output`.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Output {
+    /// Text meant for humans.
+    #[default]
+    Text,
+    /// JSON objects meant for scripts, see [`print_json`].
+    Json,
+}
+
+impl FromStr for Output {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Output::Text),
+            "json" => Ok(Output::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An error ending a command line binary.
+pub enum CliError {
+    /// The arguments are invalid.
+    Usage(String),
+    /// The command failed.
+    Failure(String),
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::Failure(message)
+    }
+}
+
+/// Reports the outcome of a binary, printing errors to stderr, and the usage after
+/// invalid arguments.
+pub fn exit(result: Result<(), CliError>, usage: &str) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {message}\n\n{usage}");
+            ExitCode::from(2)
+        }
+        Err(CliError::Failure(message)) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Takes the value following the argument `arg`.
+pub fn value<'a>(
+    args: &mut impl Iterator<Item = &'a String>,
+    arg: &str,
+) -> Result<&'a String, String> {
+    args.next()
+        .ok_or_else(|| format!("missing value for {arg}"))
+}
+
+/// Takes and parses the value following the argument `arg`.
+pub fn parse_value<'a, T: FromStr>(
+    args: &mut impl Iterator<Item = &'a String>,
+    arg: &str,
+) -> Result<T, String> {
+    let value = value(args, arg)?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value {value} for {arg}"))
+}
+
+/// Prints the JSON output of `command`, made of the fields of `report` along with the
+/// schema version and the command name.
+#[cfg(feature = "json")]
+pub fn print_json(command: &str, report: &impl Serialize) -> Result<(), String> {
+    let mut json = serde_json::to_value(report).map_err(|e| e.to_string())?;
+    let fields = json
+        .as_object_mut()
+        .ok_or("the output of a command must be an object")?;
+    fields.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+    fields.insert("command".to_string(), command.into());
+    println!("{json}");
+    Ok(())
+}
+
+/// Encodes bytes as lowercase hex.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Reads a file.
+pub fn read(path: &Path) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("cannot read {}: {e}", path.display()))
+}
+
+/// Returns whether `source` names a remote artifact rather than a local file.
+pub fn is_remote(source: &str) -> bool {
+    source.starts_with("https://") || source.starts_with("s3://")
+}
+
+/// Reads an artifact of at most `max_size` bytes from a local file or, with the
+/// `remote-artifacts` feature, from an `https://` or `s3://` URI.
+pub fn read_artifact(source: &str, max_size: u64) -> Result<Vec<u8>, String> {
+    if is_remote(source) {
+        #[cfg(feature = "remote-artifacts")]
+        return remote::fetch(source, max_size);
+        #[cfg(not(feature = "remote-artifacts"))]
+        return Err(format!(
+            "cannot read {source}: remote artifacts require the remote-artifacts feature"
+        ));
+    }
+    let path = Path::new(source);
+    let size = fs::metadata(path)
+        .map_err(|e| format!("cannot read {}: {e}", path.display()))?
+        .len();
+    if size > max_size {
+        return Err(format!("{source} is larger than {max_size} bytes"));
+    }
+    read(path)
+}
+
+/// Reads a text file.
+pub fn read_to_string(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("cannot read {}: {e}", path.display()))
+}
+
+/// Writes a file, replacing it if it exists.
+pub fn write(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    fs::write(path, bytes).map_err(|e| format!("cannot write {}: {e}", path.display()))
+}
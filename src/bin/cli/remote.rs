@@ -0,0 +1,70 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Retrieval of artifacts from object storage, over HTTPS.
+//!
+//! `s3://BUCKET/KEY` URIs are fetched from the virtual-hosted URL of the object on AWS,
+//! or from `ENDPOINT/BUCKET/KEY` when `AWS_ENDPOINT_URL_S3` or `AWS_ENDPOINT_URL` is
+//! set, e.g. for S3 compatible stores. Requests are not signed, so objects of private
+//! buckets must be passed as presigned `https://` URLs instead.
+
+use std::io::Read;
+
+/// Fetches the object at `uri`, an `https://` or `s3://` URI, failing if it is larger
+/// than `max_size` bytes.
+pub fn fetch(uri: &str, max_size: u64) -> Result<Vec<u8>, String> {
+    let url = match uri.strip_prefix("s3://") {
+        Some(object) => s3_url(object).ok_or_else(|| format!("invalid S3 URI {uri}"))?,
+        None => uri.to_string(),
+    };
+    let response = ureq::get(&url)
+        .call()
+        .map_err(|e| format!("cannot fetch {uri}: {e}"))?;
+    let length = response
+        .header("Content-Length")
+        .and_then(|length| length.parse::<u64>().ok());
+    if length.is_some_and(|length| length > max_size) {
+        return Err(too_large(uri, max_size));
+    }
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(max_size + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("cannot fetch {uri}: {e}"))?;
+    if bytes.len() as u64 > max_size {
+        return Err(too_large(uri, max_size));
+    }
+    Ok(bytes)
+}
+
+/// Resolves the `BUCKET/KEY` part of an S3 URI into the URL of the object.
+fn s3_url(object: &str) -> Option<String> {
+    let (bucket, key) = object.split_once('/')?;
+    if bucket.is_empty() || key.is_empty() {
+        return None;
+    }
+    let endpoint = std::env::var("AWS_ENDPOINT_URL_S3")
+        .or_else(|_| std::env::var("AWS_ENDPOINT_URL"))
+        .ok();
+    Some(match endpoint {
+        Some(endpoint) => format!("{}/{bucket}/{key}", endpoint.trim_end_matches('/')),
+        None => format!("https://{bucket}.s3.amazonaws.com/{key}"),
+    })
+}
+
+fn too_large(uri: &str, max_size: u64) -> String {
+    format!("{uri} is larger than {max_size} bytes")
+}
@@ -13,9 +13,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::fs::File;
-use std::io::prelude::*;
+#[path = "cli/mod.rs"]
+mod cli;
 
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use cli::CliError;
 use proof_of_sql::base::commitment::QueryCommitments;
 use proof_of_sql::proof_primitive::dory::{
     DoryEvaluationProof, DoryProverPublicSetup, DoryVerifierPublicSetup, ProverSetup,
@@ -34,9 +38,67 @@ pub use proof_of_sql::{
 use proof_of_sql_verifier::{Proof, PublicInput, VerificationKey};
 use rand::thread_rng;
 
-fn main() {
+const USAGE: &str = "\
+Usage: generate-sample-proof [--max-nu NU] [--out DIR]
+
+Proves a sample query over random public parameters, and writes the proof, public
+input and verification key to proof.bin, pubs.bin and vk.bin in DIR, the current
+directory by default.
+
+Options:
+  --max-nu NU  The max_nu of the public parameters, from 1 to 16, 4 by default.
+  --out DIR    The existing directory to write the artifacts to.
+
+Exits with 0 on success, 1 when generation fails and 2 on invalid arguments.";
+
+/// Setups for a larger `max_nu` take too long to generate for a sample.
+const MAX_NU_LIMIT: usize = 16;
+
+/// Arguments of the binary.
+struct Args {
+    max_nu: usize,
+    out: PathBuf,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut max_nu = 4;
+        let mut out = PathBuf::from(".");
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--max-nu" => max_nu = cli::parse_value(&mut args, arg)?,
+                "--out" => out = PathBuf::from(cli::value(&mut args, arg)?),
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+        // The sample table has 4 rows, which needs max_nu of at least 1
+        if !(1..=MAX_NU_LIMIT).contains(&max_nu) {
+            return Err(format!(
+                "invalid value {max_nu} for --max-nu, expected 1 to {MAX_NU_LIMIT}"
+            ));
+        }
+        if !out.is_dir() {
+            return Err(format!(
+                "invalid value {} for --out, expected an existing directory",
+                out.display()
+            ));
+        }
+        Ok(Self { max_nu, out })
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = Args::parse(&args)
+        .map_err(CliError::Usage)
+        .and_then(|args| run(&args).map_err(CliError::Failure));
+    cli::exit(result, USAGE)
+}
+
+fn run(args: &Args) -> Result<(), String> {
     // Initialize setup
-    let max_nu = 4;
+    let max_nu = args.max_nu;
     let sigma = max_nu;
     let public_parameters = PublicParameters::rand(max_nu, &mut thread_rng());
     let ps = ProverSetup::from(&public_parameters);
@@ -61,7 +123,7 @@ fn main() {
         "sxt".parse().unwrap(),
         &accessor,
     )
-    .unwrap();
+    .map_err(|e| format!("cannot plan the sample query: {e}"))?;
 
     // Generate proof
     let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
@@ -74,7 +136,7 @@ fn main() {
     let vk = VerificationKey::new(&public_parameters, sigma);
     let query_data = proof
         .verify(query.proof_expr(), &accessor, &verifier_setup)
-        .unwrap();
+        .map_err(|e| format!("the generated proof doesn't verify upstream: {e}"))?;
 
     let columns = query.proof_expr().get_column_references();
     let query_commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
@@ -82,13 +144,14 @@ fn main() {
     // Verify proof
     let proof = Proof::new(proof);
     let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
-    let _result = proof_of_sql_verifier::verify_proof(&proof, &pubs, &vk);
+    proof_of_sql_verifier::verify_proof(&proof, &pubs, &vk)
+        .map_err(|e| format!("the generated proof doesn't verify: {e}"))?;
 
     // Write proof, pubs, and vk to binary files
-    let mut proof_bin = File::create("proof.bin").unwrap();
-    proof_bin.write_all(&proof.to_bytes()).unwrap();
-    let mut pubs_bin = File::create("pubs.bin").unwrap();
-    pubs_bin.write_all(&pubs.try_to_bytes().unwrap()).unwrap();
-    let mut vk_bin = File::create("vk.bin").unwrap();
-    vk_bin.write_all(&vk.to_bytes()).unwrap();
+    let pubs = pubs
+        .try_to_bytes()
+        .map_err(|e| format!("cannot encode the public input: {e}"))?;
+    cli::write(&args.out.join("proof.bin"), &proof.to_bytes())?;
+    cli::write(&args.out.join("pubs.bin"), &pubs)?;
+    cli::write(&args.out.join("vk.bin"), &vk.to_bytes())
 }
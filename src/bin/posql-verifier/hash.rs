@@ -0,0 +1,185 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Digests of artifacts, as computed on-chain, to reproduce leaf values by hand.
+//!
+//! The plain digest of a public input is not its statement hash, which is only printed
+//! with `--statement-hash`.
+
+use std::path::PathBuf;
+
+use proof_of_sql_verifier::{
+    Artifact, HashFunction, HashVersion, Proof, PublicInput, VerificationKey,
+};
+use serde::Serialize;
+
+use crate::cli::{self, Output};
+
+/// Arguments of the `hash` command.
+pub struct HashArgs {
+    kind: Kind,
+    file: PathBuf,
+    domain: Option<String>,
+    function: HashFunction,
+    statement_hash: Option<HashVersion>,
+    output: Output,
+}
+
+/// The artifacts that can be hashed.
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    Proof,
+    Pubs,
+    Vk,
+}
+
+/// The names of the statement hash versions, as given to `--statement-hash`.
+const STATEMENT_HASH_VERSIONS: [(&str, HashVersion); 3] = [
+    ("v1", HashVersion::V1),
+    ("v2", HashVersion::V2),
+    ("v3", HashVersion::V3),
+];
+
+/// The names of the hash functions, as given to `--algo`.
+const ALGORITHMS: [(&str, HashFunction); 3] = [
+    ("keccak", HashFunction::Keccak256),
+    ("blake2", HashFunction::Blake2b256),
+    ("sha256", HashFunction::Sha256),
+];
+
+impl HashArgs {
+    /// Parses the arguments following the command name.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut args = args.iter();
+        let kind = match args.next().map(String::as_str) {
+            Some("proof") => Kind::Proof,
+            Some("pubs") => Kind::Pubs,
+            Some("vk") => Kind::Vk,
+            Some(arg) => return Err(format!("unexpected argument {arg}")),
+            None => return Err("missing artifact, one of proof, pubs and vk".to_string()),
+        };
+        let (mut file, mut domain) = (None, None);
+        let (mut function, mut algo) = (HashFunction::Keccak256, false);
+        let mut statement_hash = None;
+        let mut output = Output::Text;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--domain" => domain = Some(cli::value(&mut args, arg)?.clone()),
+                "--algo" => {
+                    let value = cli::value(&mut args, arg)?;
+                    function = ALGORITHMS
+                        .iter()
+                        .find(|(name, _)| name == value)
+                        .map(|(_, function)| *function)
+                        .ok_or_else(|| format!("invalid value {value} for {arg}"))?;
+                    algo = true;
+                }
+                "--statement-hash" => {
+                    let value = cli::value(&mut args, arg)?;
+                    statement_hash = Some(
+                        STATEMENT_HASH_VERSIONS
+                            .iter()
+                            .find(|(name, _)| name == value)
+                            .map(|(_, version)| *version)
+                            .ok_or_else(|| format!("invalid value {value} for {arg}"))?,
+                    );
+                }
+                "--output" => output = cli::parse_value(&mut args, arg)?,
+                _ if file.is_none() && !arg.starts_with("--") => file = Some(PathBuf::from(arg)),
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+        if statement_hash.is_some() {
+            if !matches!(kind, Kind::Pubs) {
+                return Err("--statement-hash only applies to pubs".to_string());
+            }
+            if domain.is_some() || algo {
+                return Err("--statement-hash excludes --domain and --algo".to_string());
+            }
+        }
+        Ok(Self {
+            kind,
+            file: file.ok_or("missing FILE")?,
+            domain,
+            function,
+            statement_hash,
+            output,
+        })
+    }
+}
+
+/// The JSON output of the command.
+#[derive(Serialize)]
+struct Report<'a> {
+    artifact: Kind,
+    algorithm: &'static str,
+    domain: Option<&'a str>,
+    statement_hash: Option<&'static str>,
+    digest: String,
+}
+
+/// Prints the hex encoded digest of the artifact.
+///
+/// The artifact is decoded first, so that only valid artifacts are hashed, and the
+/// digest covers its canonical encoding. Without a domain tag, the digest is the hash of
+/// that encoding alone, which for keys is their fingerprint. With a domain tag, it is the
+/// domain-separated digest computed by [`Artifact::hash_with`]. With a statement hash
+/// version, it is the statement hash of the public input, see [`PublicInput::hash`].
+pub fn run(args: &HashArgs) -> Result<(), String> {
+    let bytes = cli::read(&args.file)?;
+    let digest = match (args.kind, args.statement_hash) {
+        (Kind::Pubs, Some(version)) => {
+            PublicInput::from_canonical_bytes(&bytes).and_then(|pubs| pubs.hash(version))
+        }
+        (Kind::Proof, _) => digest::<Proof>(&bytes, args),
+        (Kind::Pubs, None) => digest::<PublicInput>(&bytes, args),
+        (Kind::Vk, _) => digest::<VerificationKey>(&bytes, args),
+    }
+    .map_err(|e| format!("invalid artifact {}: {e}", args.file.display()))?;
+    match args.output {
+        Output::Text => println!("{}", cli::hex(&digest)),
+        Output::Json => {
+            let (algorithm, _) = ALGORITHMS
+                .iter()
+                .find(|(_, function)| *function == args.function)
+                .expect("every hash function is named");
+            let report = Report {
+                artifact: args.kind,
+                algorithm,
+                domain: args.domain.as_deref(),
+                statement_hash: STATEMENT_HASH_VERSIONS
+                    .iter()
+                    .find(|(_, version)| Some(*version) == args.statement_hash)
+                    .map(|(name, _)| *name),
+                digest: cli::hex(&digest),
+            };
+            cli::print_json("hash", &report)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decodes an artifact and computes its digest.
+fn digest<A: Artifact>(
+    bytes: &[u8],
+    args: &HashArgs,
+) -> Result<[u8; 32], proof_of_sql_verifier::VerifyError> {
+    let artifact = A::from_canonical_bytes(bytes)?;
+    match &args.domain {
+        Some(domain) => artifact.hash_with(args.function, domain.as_bytes()),
+        None => Ok(args.function.hash(&[&artifact.to_canonical_bytes()?])),
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[path = "../cli/mod.rs"]
+mod cli;
+mod hash;
+mod params;
+mod replay;
+mod verify;
+mod vk;
+mod watch;
+
+use std::process::ExitCode;
+
+use cli::{CliError, Output};
+
+const USAGE: &str = "\
+Usage: posql-verifier <COMMAND>
+
+Commands:
+  verify --proof SOURCE --pubs SOURCE --vk SOURCE [--expect-hash ARTIFACT=HEX]...
+         [--max-size BYTES] [--sandbox [--sandbox-memory BYTES] [--sandbox-cpu SECS]
+         [--sandbox-timeout SECS]]
+      Verify a single submission. Sources are files or, with the remote-artifacts
+      feature, https:// and s3:// URIs. Artifacts larger than 64 MiB, or
+      --max-size, are refused, and each of proof, pubs and vk can be pinned to
+      the Keccak-256 digest of its bytes. With the sandbox feature, --sandbox
+      decodes and verifies them in a sandbox-serve subprocess limited to 4 GiB of
+      address space, 60 seconds of CPU time and 120 seconds of wall-clock time,
+      or the given limits.
+  sandbox-serve
+      Verify the artifacts sent on stdin by verify --sandbox, and write the
+      outcome to stdout.
+  watch --dir DIR --vk FILE --out FILE [--interval-ms MS] [--once]
+      Verify the submissions appearing in DIR, appending one JSON line per
+      submission to the output file.
+  replay --log FILE [--vk FILE]
+      Verify again the base64 encoded submissions logged one JSON object per
+      line, reporting those whose outcome differs from the logged one.
+  hash proof|pubs|vk FILE [--domain TAG] [--algo keccak|blake2|sha256]
+  hash pubs FILE --statement-hash v1|v2|v3
+      Print the hex encoded digest of an artifact, Keccak-256 by default. With a
+      domain tag, the length prefixed tag and the artifact kind are hashed
+      before the artifact. The digest of pubs is not its statement hash, which
+      --statement-hash prints instead, v3 being the latest version.
+  params validate [--params FILE] [--vk FILE]
+      Check public parameters, as saved by PublicParameters::save_to_file, and
+      verification keys for corrupted or inconsistent elements.
+  vk inspect FILE | vk inspect --max-nu N --sigma S
+      Print the max_nu, sigma and fingerprint of a verification key, and the
+      maximum number of rows of the tables it can verify proofs for, or that
+      number for a setup yet to be generated.
+  capabilities
+      Print the schemes, column types and encoding versions the verifier
+      supports as JSON.
+
+Every command accepts --output text|json. JSON output is versioned by its
+schema_version field, documented in the README.
+
+Exits with 0 on success, 1 when the command fails and 2 on invalid arguments.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    cli::exit(run(&args), USAGE)
+}
+
+/// Parses the arguments and runs the command.
+fn run(args: &[String]) -> Result<(), CliError> {
+    let Some(command) = args.first() else {
+        return Err(CliError::Usage("missing command".to_string()));
+    };
+    let args = &args[1..];
+    match command.as_str() {
+        "verify" => verify::run(&verify::VerifyArgs::parse(args).map_err(CliError::Usage)?)?,
+        "watch" => watch::run(&watch::WatchArgs::parse(args).map_err(CliError::Usage)?)?,
+        "replay" => replay::run(&replay::ReplayArgs::parse(args).map_err(CliError::Usage)?)?,
+        "hash" => hash::run(&hash::HashArgs::parse(args).map_err(CliError::Usage)?)?,
+        "params" => params::run(&params::ParamsArgs::parse(args).map_err(CliError::Usage)?)?,
+        "vk" => vk::run(&vk::VkArgs::parse(args).map_err(CliError::Usage)?)?,
+        #[cfg(all(feature = "sandbox", unix))]
+        "sandbox-serve" => {
+            if let [arg, ..] = args {
+                return Err(CliError::Usage(format!("unexpected argument {arg}")));
+            }
+            proof_of_sql_verifier::serve_sandboxed_verification().map_err(|e| e.to_string())?
+        }
+        "capabilities" => {
+            let output = match args {
+                [] => Output::Text,
+                [arg, value] if arg == "--output" => value
+                    .parse()
+                    .map_err(|_| CliError::Usage(format!("invalid value {value} for {arg}")))?,
+                [arg, ..] => return Err(CliError::Usage(format!("unexpected argument {arg}"))),
+            };
+            let json = proof_of_sql_verifier::capabilities().to_json();
+            match output {
+                Output::Text => println!("{}", String::from_utf8_lossy(&json)),
+                Output::Json => {
+                    let capabilities: serde_json::Value =
+                        serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+                    cli::print_json(
+                        "capabilities",
+                        &serde_json::json!({ "capabilities": capabilities }),
+                    )?;
+                }
+            }
+        }
+        _ => return Err(CliError::Usage(format!("unknown command {command}"))),
+    }
+    Ok(())
+}
@@ -0,0 +1,138 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deep validation of setup files, before a verification key is derived from public
+//! parameters or registered.
+
+use std::path::PathBuf;
+
+use proof_of_sql_verifier::{
+    validate_public_parameters, validate_verifier_setup, Arkworks, VerificationKey,
+};
+use serde::Serialize;
+
+use crate::cli::{self, Output};
+
+/// Arguments of the `params validate` command.
+pub struct ParamsArgs {
+    params: Option<PathBuf>,
+    vk: Option<PathBuf>,
+    output: Output,
+}
+
+impl ParamsArgs {
+    /// Parses the arguments following the command name.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let (mut params, mut vk) = (None, None);
+        let mut output = Output::Text;
+        let mut args = args.iter();
+        match args.next().map(String::as_str) {
+            Some("validate") => {}
+            Some(arg) => return Err(format!("unexpected argument {arg}")),
+            None => return Err("missing subcommand validate".to_string()),
+        }
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--params" => params = Some(PathBuf::from(cli::value(&mut args, arg)?)),
+                "--vk" => vk = Some(PathBuf::from(cli::value(&mut args, arg)?)),
+                "--output" => output = cli::parse_value(&mut args, arg)?,
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+        if params.is_none() && vk.is_none() {
+            return Err("missing --params or --vk".to_string());
+        }
+        Ok(Self { params, vk, output })
+    }
+}
+
+/// The JSON output of the command, printed once every given file is valid.
+#[derive(Serialize)]
+struct Report {
+    params: Option<String>,
+    vk: Option<KeyReport>,
+    derived: Option<bool>,
+}
+
+/// The validated verification key.
+#[derive(Serialize)]
+struct KeyReport {
+    path: String,
+    max_nu: usize,
+    sigma: usize,
+}
+
+/// Validates the given files and, if both are given, that the key was derived from the
+/// public parameters.
+pub fn run(args: &ParamsArgs) -> Result<(), String> {
+    let text = args.output == Output::Text;
+    let params = match &args.params {
+        Some(path) => {
+            let params = validate_public_parameters(&cli::read(path)?)
+                .map_err(|e| format!("invalid public parameters {}: {e}", path.display()))?;
+            if text {
+                println!("{}: valid public parameters", path.display());
+            }
+            Some(params)
+        }
+        None => None,
+    };
+    let vk = match &args.vk {
+        Some(path) => {
+            let vk = validate_verifier_setup::<Arkworks>(&cli::read(path)?)
+                .map_err(|e| format!("invalid verification key {}: {e}", path.display()))?;
+            if text {
+                println!(
+                    "{}: valid verification key, max_nu {}, sigma {}",
+                    path.display(),
+                    vk.max_nu(),
+                    vk.sigma()
+                );
+            }
+            Some(vk)
+        }
+        None => None,
+    };
+    let derived = match (&params, &vk) {
+        (Some(params), Some(vk)) => Some(VerificationKey::new(params, vk.sigma()) == *vk),
+        _ => None,
+    };
+    if !text {
+        let report = Report {
+            params: args.params.as_ref().map(|path| path.display().to_string()),
+            vk: args
+                .vk
+                .as_ref()
+                .zip(vk.as_ref())
+                .map(|(path, vk)| KeyReport {
+                    path: path.display().to_string(),
+                    max_nu: vk.max_nu(),
+                    sigma: vk.sigma(),
+                }),
+            derived,
+        };
+        cli::print_json("params validate", &report)?;
+    }
+    match derived {
+        Some(false) => {
+            Err("the verification key was not derived from the public parameters".into())
+        }
+        Some(true) if text => {
+            println!("the verification key was derived from the public parameters");
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Re-verification of logged submissions, e.g. to qualify an upgrade of the verifier.
+//!
+//! The log holds one [`ReplayEntry`] per line, with the submission `id`, its base64
+//! encoded `proof`, `pubs` and, optionally, `vk`, and whether it was `verified` when
+//! logged. Every entry is verified again by this build, and the entries whose outcome
+//! differs are reported as divergences.
+
+use std::path::PathBuf;
+
+use proof_of_sql_verifier::{verify_proof, Proof, PublicInput, ReplayEntry, VerificationKey};
+use serde::Serialize;
+
+use crate::cli::{self, Output};
+
+/// Arguments of the `replay` command.
+pub struct ReplayArgs {
+    log: PathBuf,
+    vk: Option<PathBuf>,
+    output: Output,
+}
+
+impl ReplayArgs {
+    /// Parses the arguments following the command name.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let (mut log, mut vk) = (None, None);
+        let mut output = Output::Text;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--log" => log = Some(PathBuf::from(cli::value(&mut args, arg)?)),
+                "--vk" => vk = Some(PathBuf::from(cli::value(&mut args, arg)?)),
+                "--output" => output = cli::parse_value(&mut args, arg)?,
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+        Ok(Self {
+            log: log.ok_or("missing --log")?,
+            vk,
+            output,
+        })
+    }
+}
+
+/// The line reported for an entry whose outcome differs from the logged one.
+#[derive(Serialize)]
+struct Divergence {
+    id: String,
+    logged: bool,
+    replayed: bool,
+    error: Option<String>,
+}
+
+/// The JSON output of the command.
+#[derive(Serialize)]
+struct Report {
+    replayed: usize,
+    divergences: Vec<Divergence>,
+}
+
+/// Replays the log and fails if any entry diverges. Divergences are printed as they are
+/// found, one JSON line each, or all at once with `--output json`.
+pub fn run(args: &ReplayArgs) -> Result<(), String> {
+    let default_vk = match &args.vk {
+        Some(path) => {
+            let bytes = cli::read(path)?;
+            Some(
+                VerificationKey::try_from(&bytes[..])
+                    .map_err(|e| format!("invalid verification key {}: {e}", path.display()))?,
+            )
+        }
+        None => None,
+    };
+    let log = cli::read_to_string(&args.log)?;
+
+    let (mut replayed, mut divergences) = (0, Vec::new());
+    for (index, line) in log
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+    {
+        let entry: ReplayEntry = serde_json::from_str(line)
+            .map_err(|e| format!("invalid entry on line {}: {e}", index + 1))?;
+        let error = replay(&entry, default_vk.as_ref()).err();
+        replayed += 1;
+        if error.is_none() != entry.verified {
+            let divergence = Divergence {
+                id: entry.id,
+                logged: entry.verified,
+                replayed: error.is_none(),
+                error,
+            };
+            if args.output == Output::Text {
+                println!(
+                    "{}",
+                    serde_json::to_string(&divergence).map_err(|e| e.to_string())?
+                );
+            }
+            divergences.push(divergence);
+        }
+    }
+    let count = divergences.len();
+    eprintln!("replayed {replayed} entries, {count} divergences");
+    if args.output == Output::Json {
+        cli::print_json(
+            "replay",
+            &Report {
+                replayed,
+                divergences,
+            },
+        )?;
+    }
+    if count > 0 {
+        return Err(format!("{count} entries diverge from the log"));
+    }
+    Ok(())
+}
+
+/// Decodes and verifies a logged submission.
+fn replay(entry: &ReplayEntry, default_vk: Option<&VerificationKey>) -> Result<(), String> {
+    let proof = Proof::try_from(&entry.proof[..]).map_err(|e| e.to_string())?;
+    let pubs = PublicInput::try_from(&entry.pubs[..]).map_err(|e| e.to_string())?;
+    let vk = match &entry.vk {
+        Some(vk) => VerificationKey::try_from(&vk[..]).map_err(|e| e.to_string())?,
+        None => default_vk.ok_or("no verification key")?.clone(),
+    };
+    verify_proof(&proof, &pubs, &vk).map_err(|e| e.to_string())
+}
@@ -0,0 +1,232 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of a single submission, whose artifacts may be stored remotely.
+//!
+//! Artifacts are read from local files or, with the `remote-artifacts` feature, from
+//! `https://` and `s3://` URIs. Each one can be pinned to the Keccak-256 digest of its
+//! bytes, checked before it is decoded.
+//!
+//! With `--sandbox`, and the `sandbox` feature, the artifacts are decoded and verified
+//! by a child process running this binary's `sandbox-serve` command, under memory, CPU
+//! time and wall-clock time limits, so that a decoder crash only ends the child.
+
+use proof_of_sql_verifier::{
+    rejection_reason, verify_proof_with_result, HashFunction, Proof, PublicInput, VerificationKey,
+};
+use serde::Serialize;
+
+use crate::cli::{self, Output};
+
+/// The size limit of artifacts when `--max-size` is not given, 64 MiB.
+const DEFAULT_MAX_SIZE: u64 = 64 << 20;
+/// The address space limit of the sandbox when `--sandbox-memory` is not given, 4 GiB.
+const DEFAULT_SANDBOX_MEMORY: u64 = 4 << 30;
+/// The CPU time limit of the sandbox when `--sandbox-cpu` is not given, in seconds.
+const DEFAULT_SANDBOX_CPU: u64 = 60;
+/// The wall-clock time limit of the sandbox when `--sandbox-timeout` is not given, in
+/// seconds.
+const DEFAULT_SANDBOX_TIMEOUT: u64 = 120;
+
+/// Arguments of the `verify` command.
+pub struct VerifyArgs {
+    proof: String,
+    pubs: String,
+    vk: String,
+    expected_hashes: [Option<[u8; 32]>; 3],
+    max_size: u64,
+    sandbox: Option<(u64, u64, u64)>,
+    output: Output,
+}
+
+/// The artifacts of a submission, indexing [`VerifyArgs::expected_hashes`].
+const ARTIFACTS: [&str; 3] = ["proof", "pubs", "vk"];
+
+impl VerifyArgs {
+    /// Parses the arguments following the command name.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let (mut proof, mut pubs, mut vk) = (None, None, None);
+        let mut expected_hashes = [None; 3];
+        let mut max_size = DEFAULT_MAX_SIZE;
+        let (mut sandbox, mut sandbox_memory, mut sandbox_cpu) = (false, None, None);
+        let mut sandbox_timeout = None;
+        let mut output = Output::Text;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--proof" => proof = Some(cli::value(&mut args, arg)?.clone()),
+                "--pubs" => pubs = Some(cli::value(&mut args, arg)?.clone()),
+                "--vk" => vk = Some(cli::value(&mut args, arg)?.clone()),
+                "--max-size" => max_size = cli::parse_value(&mut args, arg)?,
+                "--sandbox" => sandbox = true,
+                "--sandbox-memory" => sandbox_memory = Some(cli::parse_value(&mut args, arg)?),
+                "--sandbox-cpu" => sandbox_cpu = Some(cli::parse_value(&mut args, arg)?),
+                "--sandbox-timeout" => sandbox_timeout = Some(cli::parse_value(&mut args, arg)?),
+                "--output" => output = cli::parse_value(&mut args, arg)?,
+                "--expect-hash" => {
+                    let value = cli::value(&mut args, arg)?;
+                    let invalid = || format!("invalid value {value} for {arg}");
+                    let (artifact, hex) = value.split_once('=').ok_or_else(invalid)?;
+                    let index = ARTIFACTS
+                        .iter()
+                        .position(|name| *name == artifact)
+                        .ok_or_else(invalid)?;
+                    expected_hashes[index] = Some(parse_digest(hex).ok_or_else(invalid)?);
+                }
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+        if !sandbox
+            && (sandbox_memory.is_some() || sandbox_cpu.is_some() || sandbox_timeout.is_some())
+        {
+            return Err(
+                "--sandbox-memory, --sandbox-cpu and --sandbox-timeout require --sandbox"
+                    .to_string(),
+            );
+        }
+        Ok(Self {
+            proof: proof.ok_or("missing --proof")?,
+            pubs: pubs.ok_or("missing --pubs")?,
+            vk: vk.ok_or("missing --vk")?,
+            expected_hashes,
+            max_size,
+            sandbox: sandbox.then_some((
+                sandbox_memory.unwrap_or(DEFAULT_SANDBOX_MEMORY),
+                sandbox_cpu.unwrap_or(DEFAULT_SANDBOX_CPU),
+                sandbox_timeout.unwrap_or(DEFAULT_SANDBOX_TIMEOUT),
+            )),
+            output,
+        })
+    }
+
+    /// Reads the artifact at `index`, checking its pinned digest if any.
+    fn read(&self, index: usize) -> Result<Vec<u8>, String> {
+        let source = [&self.proof, &self.pubs, &self.vk][index];
+        let bytes = cli::read_artifact(source, self.max_size)?;
+        if let Some(expected) = self.expected_hashes[index] {
+            if HashFunction::Keccak256.hash(&[&bytes]) != expected {
+                return Err(format!(
+                    "{source} doesn't match the expected {} hash",
+                    ARTIFACTS[index]
+                ));
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// Parses a hex encoded 32 bytes digest.
+fn parse_digest(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut digest = [0; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// The JSON output of the command.
+#[derive(Serialize)]
+struct Report {
+    verified: bool,
+    rows: Option<usize>,
+    columns: Option<usize>,
+    reason: Option<String>,
+    error: Option<String>,
+}
+
+/// The number of rows and columns of the verified result, or the reason and message of
+/// the rejection.
+type Outcome = Result<(usize, usize), (String, String)>;
+
+/// Verifies the submission, printing the shape of the verified result, or the reason
+/// of the rejection with `--output json`.
+pub fn run(args: &VerifyArgs) -> Result<(), String> {
+    let outcome = match args.sandbox {
+        Some(limits) => verify_sandboxed(args, limits)?,
+        None => verify(args)?,
+    };
+    if args.output == Output::Json {
+        let report = match &outcome {
+            Ok((rows, columns)) => Report {
+                verified: true,
+                rows: Some(*rows),
+                columns: Some(*columns),
+                reason: None,
+                error: None,
+            },
+            Err((reason, error)) => Report {
+                verified: false,
+                rows: None,
+                columns: None,
+                reason: Some(reason.clone()),
+                error: Some(error.clone()),
+            },
+        };
+        cli::print_json("verify", &report)?;
+    }
+    let (rows, columns) = outcome.map_err(|(_, error)| format!("rejected: {error}"))?;
+    if args.output == Output::Text {
+        println!("verified, {rows} rows and {columns} columns");
+    }
+    Ok(())
+}
+
+/// Decodes and verifies the submission in this process.
+fn verify(args: &VerifyArgs) -> Result<Outcome, String> {
+    let proof = Proof::try_from(&args.read(0)?[..])
+        .map_err(|e| format!("invalid proof {}: {e}", args.proof))?;
+    let pubs = PublicInput::try_from(&args.read(1)?[..])
+        .map_err(|e| format!("invalid public input {}: {e}", args.pubs))?;
+    let vk = VerificationKey::try_from(&args.read(2)?[..])
+        .map_err(|e| format!("invalid verification key {}: {e}", args.vk))?;
+    Ok(verify_proof_with_result(&proof, &pubs, &vk)
+        .map(|result| (result.num_rows(), result.num_columns()))
+        .map_err(|e| (rejection_reason(&e).to_string(), e.to_string())))
+}
+
+/// Decodes and verifies the submission in a `sandbox-serve` child process limited to
+/// `memory` bytes of address space, `cpu` seconds of CPU time and `timeout` seconds of
+/// wall-clock time.
+#[cfg(all(feature = "sandbox", unix))]
+fn verify_sandboxed(
+    args: &VerifyArgs,
+    (memory, cpu, timeout): (u64, u64, u64),
+) -> Result<Outcome, String> {
+    use proof_of_sql_verifier::{verify_sandboxed, SandboxError, SandboxLimits};
+
+    let exe = std::env::current_exe().map_err(|e| format!("cannot locate this binary: {e}"))?;
+    let mut command = std::process::Command::new(exe);
+    command.arg("sandbox-serve");
+    let [proof, pubs, vk] = [args.read(0)?, args.read(1)?, args.read(2)?];
+    match verify_sandboxed(
+        command,
+        SandboxLimits::new(memory, cpu, timeout),
+        &proof,
+        &pubs,
+        &vk,
+    ) {
+        Ok(shape) => Ok(Ok(shape)),
+        Err(SandboxError::Rejected { reason, message }) => Ok(Err((reason, message))),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(not(all(feature = "sandbox", unix)))]
+fn verify_sandboxed(_: &VerifyArgs, _: (u64, u64, u64)) -> Result<Outcome, String> {
+    Err("--sandbox requires the sandbox feature, on unix".to_string())
+}
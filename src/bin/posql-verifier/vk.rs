@@ -0,0 +1,118 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Inspection of verification keys, and of the table sizes a setup would support before
+//! it is generated.
+
+use std::path::PathBuf;
+
+use proof_of_sql_verifier::{max_rows_supported, VerificationKey};
+use serde::Serialize;
+
+use crate::cli::{self, Output};
+
+/// Arguments of the `vk inspect` command.
+pub struct VkArgs {
+    source: Source,
+    output: Output,
+}
+
+/// What `vk inspect` inspects.
+enum Source {
+    /// A serialized key.
+    File(PathBuf),
+    /// The setup a key would be derived from.
+    Setup { max_nu: usize, sigma: usize },
+}
+
+impl VkArgs {
+    /// Parses the arguments following the command name.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut args = args.iter();
+        match args.next().map(String::as_str) {
+            Some("inspect") => {}
+            Some(arg) => return Err(format!("unexpected argument {arg}")),
+            None => return Err("missing subcommand inspect".to_string()),
+        }
+        let (mut file, mut max_nu, mut sigma) = (None, None, None);
+        let mut output = Output::Text;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--max-nu" => max_nu = Some(cli::parse_value(&mut args, arg)?),
+                "--sigma" => sigma = Some(cli::parse_value(&mut args, arg)?),
+                "--output" => output = cli::parse_value(&mut args, arg)?,
+                _ if file.is_none() && !arg.starts_with("--") => file = Some(PathBuf::from(arg)),
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+        let source = match (file, max_nu, sigma) {
+            (Some(file), None, None) => Source::File(file),
+            (None, Some(max_nu), Some(sigma)) => Source::Setup { max_nu, sigma },
+            (Some(_), _, _) => {
+                return Err("FILE can't be combined with --max-nu and --sigma".into())
+            }
+            _ => return Err("missing FILE, or --max-nu and --sigma".to_string()),
+        };
+        Ok(Self { source, output })
+    }
+}
+
+/// The JSON output of the command.
+#[derive(Serialize)]
+struct Report {
+    fingerprint: Option<String>,
+    max_nu: usize,
+    sigma: usize,
+    max_rows: u128,
+}
+
+/// Prints the parameters of the key, and the maximum number of rows of the tables it
+/// can verify proofs for.
+pub fn run(args: &VkArgs) -> Result<(), String> {
+    let (fingerprint, max_nu, sigma) = match &args.source {
+        Source::File(path) => {
+            let vk = VerificationKey::try_from(&cli::read(path)?[..])
+                .map_err(|e| format!("invalid verification key {}: {e}", path.display()))?;
+            (Some(cli::hex(&vk.fingerprint())), vk.max_nu(), vk.sigma())
+        }
+        Source::Setup { max_nu, sigma } => (None, *max_nu, *sigma),
+    };
+    let max_rows = max_rows_supported(max_nu, sigma);
+    match args.output {
+        Output::Text => {
+            if let Some(fingerprint) = &fingerprint {
+                println!("fingerprint {fingerprint}");
+            }
+            println!("max_nu {max_nu}");
+            println!("sigma {sigma}");
+            println!("max_rows {max_rows}");
+        }
+        Output::Json => cli::print_json(
+            "vk inspect",
+            &Report {
+                fingerprint,
+                max_nu,
+                sigma,
+                max_rows,
+            },
+        )?,
+    }
+    if sigma > max_nu {
+        return Err(format!(
+            "sigma {sigma} exceeds max_nu {max_nu}, no table can be verified"
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,181 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of the submissions dropped into a directory.
+//!
+//! A submission `ID` is made of the files `ID.proof.bin` and `ID.pubs.bin`, which must
+//! be moved into the directory once fully written. Submissions are verified in order
+//! of their ids and each one is recorded as a line of the output file, which is also
+//! read at startup so that recorded submissions aren't verified again.
+
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use proof_of_sql_verifier::{verify_proof, Proof, PublicInput, VerificationKey};
+use serde::Serialize;
+
+use crate::cli::{self, Output};
+
+const PROOF_SUFFIX: &str = ".proof.bin";
+const PUBS_SUFFIX: &str = ".pubs.bin";
+
+/// Arguments of the `watch` command.
+pub struct WatchArgs {
+    dir: PathBuf,
+    vk: PathBuf,
+    out: PathBuf,
+    interval: Duration,
+    once: bool,
+    output: Output,
+}
+
+impl WatchArgs {
+    /// Parses the arguments following the command name.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let (mut dir, mut vk, mut out) = (None, None, None);
+        let mut interval = Duration::from_secs(1);
+        let mut once = false;
+        let mut output = Output::Text;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--dir" => dir = Some(PathBuf::from(cli::value(&mut args, arg)?)),
+                "--vk" => vk = Some(PathBuf::from(cli::value(&mut args, arg)?)),
+                "--out" => out = Some(PathBuf::from(cli::value(&mut args, arg)?)),
+                "--interval-ms" => {
+                    interval = Duration::from_millis(cli::parse_value(&mut args, arg)?)
+                }
+                "--once" => once = true,
+                "--output" => output = cli::parse_value(&mut args, arg)?,
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+        Ok(Self {
+            dir: dir.ok_or("missing --dir")?,
+            vk: vk.ok_or("missing --vk")?,
+            out: out.ok_or("missing --out")?,
+            interval,
+            once,
+            output,
+        })
+    }
+}
+
+/// The line recorded for a verified submission.
+#[derive(Serialize)]
+struct Outcome<'a> {
+    id: &'a str,
+    verified: bool,
+    error: Option<String>,
+    timestamp: u64,
+}
+
+/// Verifies the submissions of the watched directory as they appear, or only the
+/// current ones with `--once`. With `--output json`, outcomes are also printed as they
+/// are recorded.
+pub fn run(args: &WatchArgs) -> Result<(), String> {
+    let vk_bytes = cli::read(&args.vk)?;
+    let vk = VerificationKey::try_from(&vk_bytes[..])
+        .map_err(|e| format!("invalid verification key {}: {e}", args.vk.display()))?;
+    let mut recorded = recorded_ids(&args.out)?;
+    loop {
+        for id in pending_ids(&args.dir, &recorded)? {
+            let outcome = Outcome {
+                id: &id,
+                verified: false,
+                error: None,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs()),
+            };
+            let outcome = match verify_submission(&args.dir, &id, &vk) {
+                Ok(()) => Outcome {
+                    verified: true,
+                    ..outcome
+                },
+                Err(error) => Outcome {
+                    error: Some(error),
+                    ..outcome
+                },
+            };
+            append(&args.out, &outcome)?;
+            if args.output == Output::Json {
+                cli::print_json("watch", &outcome)?;
+            }
+            recorded.insert(id);
+        }
+        if args.once {
+            return Ok(());
+        }
+        thread::sleep(args.interval);
+    }
+}
+
+/// Decodes and verifies the submission `id`.
+fn verify_submission(dir: &Path, id: &str, vk: &VerificationKey) -> Result<(), String> {
+    let proof = cli::read(&dir.join(format!("{id}{PROOF_SUFFIX}")))?;
+    let pubs = cli::read(&dir.join(format!("{id}{PUBS_SUFFIX}")))?;
+    let proof = Proof::try_from(&proof[..]).map_err(|e| e.to_string())?;
+    let pubs = PublicInput::try_from(&pubs[..]).map_err(|e| e.to_string())?;
+    verify_proof(&proof, &pubs, vk).map_err(|e| e.to_string())
+}
+
+/// Returns the ids of the complete submissions of `dir` that are not recorded yet.
+fn pending_ids(dir: &Path, recorded: &BTreeSet<String>) -> Result<BTreeSet<String>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("cannot read directory {}: {e}", dir.display()))?;
+    let mut ids = BTreeSet::new();
+    for entry in entries {
+        let name = entry
+            .map_err(|e| format!("cannot read directory {}: {e}", dir.display()))?
+            .file_name();
+        if let Some(id) = name.to_str().and_then(|n| n.strip_suffix(PROOF_SUFFIX)) {
+            if !recorded.contains(id) && dir.join(format!("{id}{PUBS_SUFFIX}")).is_file() {
+                ids.insert(id.to_string());
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Returns the ids of the submissions recorded in the output file, if it exists.
+fn recorded_ids(out: &Path) -> Result<BTreeSet<String>, String> {
+    if !out.exists() {
+        return Ok(BTreeSet::new());
+    }
+    let content = fs::read_to_string(out)
+        .map_err(|e| format!("cannot read output file {}: {e}", out.display()))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|value| value.get("id")?.as_str().map(str::to_string))
+        .collect())
+}
+
+/// Appends an outcome as a JSON line to the output file.
+fn append(out: &Path, outcome: &Outcome) -> Result<(), String> {
+    let mut line = serde_json::to_string(outcome).map_err(|e| e.to_string())?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(out)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("cannot write output file {}: {e}", out.display()))
+}
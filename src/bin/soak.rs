@@ -0,0 +1,327 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verifies a corpus of submissions in a loop to detect memory leaks.
+//!
+//! Every pass decodes the verification key, then decodes and verifies each submission,
+//! so that all the decoders run as often as verification. Between passes no artifact
+//! is alive, and the heap should be back to the same size: the heap and the resident
+//! set size are sampled then, and the run fails once either has grown at each of the
+//! last `--windows` samples, or when the heap ends up more than `--max-growth` bytes
+//! above the first sample.
+
+#[path = "cli/mod.rs"]
+mod cli;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use cli::{CliError, Output};
+use proof_of_sql_verifier::{verify_proof, Proof, PublicInput, VerificationKey};
+use serde::Serialize;
+
+const USAGE: &str = "\
+Usage: soak --dir DIR --vk FILE [--duration SECS] [--report-secs SECS] [--windows N]
+            [--max-growth BYTES] [--output text|json]
+
+Verifies the submissions of DIR, made of the files ID.proof.bin and ID.pubs.bin, in a
+loop for --duration seconds, 3600 by default, decoding the verification key again on
+every pass. Every --report-secs seconds, 60 by default, and after the last pass, prints
+the heap usage, the allocation count and the resident set size measured between
+passes.
+
+Fails when the heap or the resident set size grew at each of the last --windows
+samples, 5 by default, when the heap ends more than --max-growth bytes, 1 MiB by
+default, above the first sample, or when a submission changes outcome.
+
+Exits with 0 on success, 1 when growth is detected or the corpus can't be read and 2
+on invalid arguments.";
+
+const PROOF_SUFFIX: &str = ".proof.bin";
+const PUBS_SUFFIX: &str = ".pubs.bin";
+
+/// The serialized proof and public input of each submission, by id.
+type Corpus = BTreeMap<String, (Vec<u8>, Vec<u8>)>;
+
+/// The system allocator, counting allocations and the allocated heap.
+struct CountingAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    allocations: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = self.current.fetch_add(layout.size(), Ordering::SeqCst);
+            self.peak
+                .fetch_max(current + layout.size(), Ordering::SeqCst);
+            self.allocations.fetch_add(1, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.current.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+    allocations: AtomicUsize::new(0),
+};
+
+/// Arguments of the binary.
+struct Args {
+    dir: PathBuf,
+    vk: PathBuf,
+    duration: Duration,
+    report_interval: Duration,
+    windows: usize,
+    max_growth: usize,
+    output: Output,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let (mut dir, mut vk) = (None, None);
+        let mut duration = Duration::from_secs(3600);
+        let mut report_interval = Duration::from_secs(60);
+        let mut windows = 5;
+        let mut max_growth = 1 << 20;
+        let mut output = Output::Text;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--dir" => dir = Some(PathBuf::from(cli::value(&mut args, arg)?)),
+                "--vk" => vk = Some(PathBuf::from(cli::value(&mut args, arg)?)),
+                "--duration" => duration = Duration::from_secs(cli::parse_value(&mut args, arg)?),
+                "--report-secs" => {
+                    report_interval = Duration::from_secs(cli::parse_value(&mut args, arg)?)
+                }
+                "--windows" => windows = cli::parse_value(&mut args, arg)?,
+                "--max-growth" => max_growth = cli::parse_value(&mut args, arg)?,
+                "--output" => output = cli::parse_value(&mut args, arg)?,
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+        if windows == 0 {
+            return Err("invalid value 0 for --windows".to_string());
+        }
+        Ok(Self {
+            dir: dir.ok_or("missing --dir")?,
+            vk: vk.ok_or("missing --vk")?,
+            duration,
+            report_interval,
+            windows,
+            max_growth,
+            output,
+        })
+    }
+}
+
+/// The memory usage measured between two passes over the corpus.
+#[derive(Clone, Copy, Serialize)]
+struct Sample {
+    elapsed_secs: u64,
+    passes: usize,
+    verifications: usize,
+    heap_bytes: usize,
+    peak_heap_bytes: usize,
+    allocations: usize,
+    rss_bytes: Option<usize>,
+}
+
+impl Sample {
+    fn take(start: Instant, passes: usize, verifications: usize) -> Self {
+        Self {
+            elapsed_secs: start.elapsed().as_secs(),
+            passes,
+            verifications,
+            heap_bytes: ALLOCATOR.current.load(Ordering::SeqCst),
+            peak_heap_bytes: ALLOCATOR.peak.load(Ordering::SeqCst),
+            allocations: ALLOCATOR.allocations.load(Ordering::SeqCst),
+            rss_bytes: resident_set_size(),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    cli::exit(
+        Args::parse(&args)
+            .map_err(CliError::Usage)
+            .and_then(|args| run(&args).map_err(CliError::from)),
+        USAGE,
+    )
+}
+
+/// Verifies the corpus until the duration elapses or growth is detected.
+fn run(args: &Args) -> Result<(), String> {
+    let vk = cli::read(&args.vk)?;
+    let corpus = read_corpus(&args.dir)?;
+    if corpus.is_empty() {
+        return Err(format!("no submission in {}", args.dir.display()));
+    }
+
+    let start = Instant::now();
+    let mut outcomes: Option<Vec<bool>> = None;
+    // Only the first and the recent samples are kept, so that the heap doesn't grow
+    let mut first: Option<Sample> = None;
+    let mut recent = VecDeque::with_capacity(args.windows + 1);
+    let mut last_report = start;
+    let (mut passes, mut verifications) = (0, 0);
+    loop {
+        let pass = verify_corpus(&vk, &corpus)?;
+        passes += 1;
+        verifications += corpus.len();
+        match &outcomes {
+            None => outcomes = Some(pass),
+            Some(expected) => {
+                if let Some(id) = corpus
+                    .keys()
+                    .zip(expected.iter().zip(&pass))
+                    .find_map(|(id, (expected, outcome))| (expected != outcome).then_some(id))
+                {
+                    return Err(format!("submission {id} changed outcome on pass {passes}"));
+                }
+            }
+        }
+
+        let done = start.elapsed() >= args.duration;
+        if !done && first.is_some() && last_report.elapsed() < args.report_interval {
+            continue;
+        }
+        last_report = Instant::now();
+        let sample = Sample::take(start, passes, verifications);
+        report(&sample, args.output)?;
+        first.get_or_insert(sample);
+        if recent.len() > args.windows {
+            recent.pop_front();
+        }
+        recent.push_back(sample);
+        if let Some(series) = growing_series(recent.make_contiguous(), args.windows) {
+            return Err(format!(
+                "{series} grew at each of the last {} samples",
+                args.windows
+            ));
+        }
+        if done {
+            break;
+        }
+    }
+
+    let (Some(first), Some(last)) = (first, recent.back()) else {
+        unreachable!("a sample is taken after the last pass");
+    };
+    let growth = last.heap_bytes.saturating_sub(first.heap_bytes);
+    if growth > args.max_growth {
+        return Err(format!(
+            "the heap grew by {growth} bytes, more than {} bytes",
+            args.max_growth
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the submissions of `dir`, sorted by id.
+fn read_corpus(dir: &Path) -> Result<Corpus, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("cannot read directory {}: {e}", dir.display()))?;
+    let mut corpus = BTreeMap::new();
+    for entry in entries {
+        let name = entry
+            .map_err(|e| format!("cannot read directory {}: {e}", dir.display()))?
+            .file_name();
+        if let Some(id) = name.to_str().and_then(|n| n.strip_suffix(PROOF_SUFFIX)) {
+            let proof = cli::read(&dir.join(format!("{id}{PROOF_SUFFIX}")))?;
+            let pubs = cli::read(&dir.join(format!("{id}{PUBS_SUFFIX}")))?;
+            corpus.insert(id.to_string(), (proof, pubs));
+        }
+    }
+    Ok(corpus)
+}
+
+/// Decodes the verification key, then decodes and verifies every submission, returning
+/// whether each one verified.
+fn verify_corpus(vk: &[u8], corpus: &Corpus) -> Result<Vec<bool>, String> {
+    let vk = VerificationKey::try_from(vk).map_err(|e| format!("invalid verification key: {e}"))?;
+    Ok(corpus
+        .values()
+        .map(|(proof, pubs)| {
+            let (Ok(proof), Ok(pubs)) = (
+                Proof::try_from(&proof[..]),
+                PublicInput::try_from(&pubs[..]),
+            ) else {
+                return false;
+            };
+            verify_proof(&proof, &pubs, &vk).is_ok()
+        })
+        .collect())
+}
+
+/// Prints a sample, as a line of text or as a JSON object.
+fn report(sample: &Sample, output: Output) -> Result<(), String> {
+    match output {
+        Output::Text => println!(
+            "{}s: {} passes, {} verifications, heap {} bytes (peak {}), {} allocations, rss {}",
+            sample.elapsed_secs,
+            sample.passes,
+            sample.verifications,
+            sample.heap_bytes,
+            sample.peak_heap_bytes,
+            sample.allocations,
+            sample
+                .rss_bytes
+                .map_or("unavailable".to_string(), |rss| format!("{rss} bytes")),
+        ),
+        Output::Json => cli::print_json("soak", sample)?,
+    }
+    Ok(())
+}
+
+/// Returns the name of the series that grew at each of the last `windows` samples.
+fn growing_series(samples: &[Sample], windows: usize) -> Option<&'static str> {
+    let recent = &samples[samples.len().checked_sub(windows + 1)?..];
+    let grows = |value: fn(&Sample) -> Option<usize>| {
+        recent
+            .windows(2)
+            .all(|pair| matches!((value(&pair[0]), value(&pair[1])), (Some(a), Some(b)) if b > a))
+    };
+    if grows(|sample| Some(sample.heap_bytes)) {
+        Some("the heap")
+    } else if grows(|sample| sample.rss_bytes) {
+        Some("the resident set size")
+    } else {
+        None
+    }
+}
+
+/// Returns the resident set size of the process, where `/proc` is available.
+fn resident_set_size() -> Option<usize> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find_map(|l| l.strip_prefix("VmRSS:"))?;
+    let kib: usize = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kib * 1024)
+}
@@ -0,0 +1,255 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Crate-owned bindings between a statement and its verified result.
+//!
+//! Proofs of proof-of-sql end with a `verification_hash`, whose computation is defined
+//! upstream and may change with any release. Contracts that need to commit to a verified
+//! result should rather depend on a [`ResultBinding`], whose computation is documented
+//! and versioned here.
+
+use alloc::vec::Vec;
+use proof_of_sql::{
+    base::commitment::QueryCommitments, proof_primitive::dory::DoryCommitment,
+    sql::proof_plans::DynProofPlan,
+};
+use serde::Serialize;
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    serde::{ColumnRef, HashingWriter, NamedColumn},
+    statement::StatementRef,
+    PublicInput, Statement, VerifiedTable, VerifyError,
+};
+
+/// The domain tag prepended to the hashed parts by [`BindingVersion::V1`].
+const BINDING_V1_TAG: &[u8] = b"proof-of-sql-verifier/result-binding/v1";
+
+/// Versions of the result binding computation.
+///
+/// Versions are never changed once released, so a binding computed by one release of
+/// this crate is computed identically by the later ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+pub enum BindingVersion {
+    /// The upstream `verification_hash` claimed by the public input, i.e. the last
+    /// challenge of the proof transcript. Its computation is owned by proof-of-sql, so
+    /// it may change across releases and is only kept for existing integrations.
+    Upstream,
+    /// The Keccak-256 digest of the concatenation of:
+    ///
+    /// 1. the tag `proof-of-sql-verifier/result-binding/v1`;
+    /// 2. the Keccak-256 digest of the serialized [`Statement`], i.e. the plan and the
+    ///    table commitments in canonical order;
+    /// 3. the Keccak-256 digest of the result, serialized as the list of its named
+    ///    columns, as done for the pages of a [`PagedResult`](crate::PagedResult).
+    V1,
+}
+
+impl BindingVersion {
+    /// The version used for new deployments.
+    pub const LATEST: Self = BindingVersion::V1;
+}
+
+/// A digest binding a verified result to the statement it was verified against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ResultBinding {
+    version: BindingVersion,
+    digest: [u8; 32],
+}
+
+impl ResultBinding {
+    /// Computes the binding of `result`, verified against `pubs`, in the given version.
+    ///
+    /// `result` must be the verified result returned for `pubs`, e.g. by
+    /// [`verify_proof_with_result`](crate::verify_proof_with_result), which holds every
+    /// column even when the claim omits some or is paged.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if the statement or result can't be serialized.
+    pub fn compute(
+        pubs: &PublicInput,
+        result: &VerifiedTable,
+        version: BindingVersion,
+    ) -> Result<Self, VerifyError> {
+        let digest = match version {
+            BindingVersion::Upstream => pubs.query_data().verification_hash,
+            BindingVersion::V1 => binding_v1(pubs.expr(), pubs.commitments(), result)?,
+        };
+        Ok(Self { version, digest })
+    }
+
+    /// Computes the [`BindingVersion::V1`] binding of `result`, verified against
+    /// `statement`, e.g. by [`verify_and_extract`](crate::verify_and_extract).
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if the statement or result can't be serialized.
+    pub fn for_statement(
+        statement: &Statement,
+        result: &VerifiedTable,
+    ) -> Result<Self, VerifyError> {
+        Ok(Self {
+            version: BindingVersion::V1,
+            digest: binding_v1(statement.expr(), statement.commitments(), result)?,
+        })
+    }
+
+    /// Returns the version the binding was computed in.
+    pub fn version(&self) -> BindingVersion {
+        self.version
+    }
+
+    /// Returns the digest.
+    pub fn digest(&self) -> [u8; 32] {
+        self.digest
+    }
+}
+
+/// Computes the [`BindingVersion::V1`] digest.
+fn binding_v1(
+    expr: &DynProofPlan<DoryCommitment>,
+    commitments: &QueryCommitments<DoryCommitment>,
+    result: &VerifiedTable,
+) -> Result<[u8; 32], VerifyError> {
+    let mut statement = Keccak256::new();
+    ciborium::into_writer(
+        &StatementRef { expr, commitments },
+        HashingWriter(&mut statement),
+    )
+    .map_err(|_| VerifyError::InvalidInput)?;
+
+    let columns: Vec<_> = result
+        .table()
+        .inner_table()
+        .iter()
+        .map(|(name, column)| NamedColumn {
+            name,
+            column: ColumnRef(column),
+        })
+        .collect();
+    let mut result = Keccak256::new();
+    ciborium::into_writer(&columns, HashingWriter(&mut result))
+        .map_err(|_| VerifyError::InvalidInput)?;
+
+    Ok(Keccak256::new()
+        .chain_update(BINDING_V1_TAG)
+        .chain_update(statement.finalize())
+        .chain_update(result.finalize())
+        .finalize()
+        .into())
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::{
+        base::{
+            commitment::QueryCommitmentsExt,
+            database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+        },
+        proof_primitive::dory::{
+            DoryEvaluationProof, DoryProverPublicSetup, ProverSetup, PublicParameters,
+        },
+        sql::{
+            parse::QueryExpr,
+            proof::{ProofPlan, QueryData},
+        },
+    };
+
+    use super::*;
+
+    fn public_input(values: [i64; 2]) -> PublicInput {
+        let public_parameters = PublicParameters::test_rand(2, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let mut accessor = OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(
+            DoryProverPublicSetup::new(&ps, 2),
+        );
+        accessor.add_table(
+            "sxt.table".parse().unwrap(),
+            owned_table([bigint("a", values)]),
+            0,
+        );
+        let query = QueryExpr::try_new(
+            "SELECT a FROM table".parse().unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        let commitments = QueryCommitments::from_accessor_with_max_bounds(
+            query.proof_expr().get_column_references(),
+            &accessor,
+        );
+        let query_data = QueryData {
+            table: owned_table([bigint("a", values)]),
+            verification_hash: [7; 32],
+        };
+        PublicInput::new(query.proof_expr(), commitments, query_data)
+    }
+
+    fn result(values: [i64; 2]) -> VerifiedTable {
+        VerifiedTable::try_new(owned_table([bigint("a", values)])).unwrap()
+    }
+
+    #[test]
+    fn upstream_binding_is_the_verification_hash() {
+        let binding = ResultBinding::compute(
+            &public_input([1, 2]),
+            &result([1, 2]),
+            BindingVersion::Upstream,
+        )
+        .unwrap();
+        assert_eq!(binding.version(), BindingVersion::Upstream);
+        assert_eq!(binding.digest(), [7; 32]);
+    }
+
+    #[test]
+    fn v1_binding_covers_statement_and_result() {
+        let pubs = public_input([1, 2]);
+        let binding =
+            ResultBinding::compute(&pubs, &result([1, 2]), BindingVersion::LATEST).unwrap();
+        assert_eq!(binding.version(), BindingVersion::V1);
+
+        let statement =
+            Statement::try_from(&pubs.into_statement().try_to_bytes().unwrap()[..]).unwrap();
+        assert_eq!(
+            ResultBinding::for_statement(&statement, &result([1, 2])).unwrap(),
+            binding
+        );
+
+        let other_result = ResultBinding::for_statement(&statement, &result([1, 3])).unwrap();
+        assert_ne!(other_result.digest(), binding.digest());
+        let other_statement =
+            ResultBinding::compute(&public_input([1, 3]), &result([1, 2]), BindingVersion::V1)
+                .unwrap();
+        assert_ne!(other_statement.digest(), binding.digest());
+    }
+
+    #[test]
+    fn statement_ref_is_serialized_like_statement() {
+        let pubs = public_input([1, 2]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(
+            &StatementRef {
+                expr: pubs.expr(),
+                commitments: pubs.commitments(),
+            },
+            &mut bytes,
+        )
+        .unwrap();
+        assert_eq!(bytes, pubs.into_statement().try_to_bytes().unwrap());
+    }
+}
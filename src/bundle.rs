@@ -0,0 +1,157 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of bundles of proofs across threads.
+
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{verify_proof, Proof, PublicInput, VerificationKey, VerifyError};
+
+/// How [`verify_bundle`] reacts to an item failing verification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BundleMode {
+    /// Verifies every item, e.g. to report all the invalid ones.
+    #[default]
+    CollectAll,
+    /// Stops starting new items once one fails, e.g. for all-or-nothing rollup batches.
+    /// Items being verified at that point still complete.
+    AbortOnFirstFailure,
+}
+
+/// The outcome of an item of a bundle.
+#[derive(Debug)]
+pub enum ItemStatus {
+    /// The proof is valid.
+    Verified,
+    /// The proof failed verification.
+    Failed(VerifyError),
+    /// The item was not verified, because another one failed first.
+    Skipped,
+}
+
+/// The outcome of an item of a bundle, along with the time spent verifying it.
+#[derive(Debug)]
+pub struct ItemReport {
+    /// The outcome of the item.
+    pub status: ItemStatus,
+    /// The time spent verifying the item, zero for skipped items.
+    pub elapsed: Duration,
+}
+
+/// The outcomes of the items of a bundle, in the order of the items.
+#[derive(Debug)]
+pub struct BundleReport {
+    items: Vec<ItemReport>,
+}
+
+impl BundleReport {
+    /// Returns the reports of the items, in the order of the items.
+    pub fn items(&self) -> &[ItemReport] {
+        &self.items
+    }
+
+    /// Returns whether every item has been verified.
+    pub fn is_verified(&self) -> bool {
+        self.items
+            .iter()
+            .all(|item| matches!(item.status, ItemStatus::Verified))
+    }
+
+    /// Returns the index and error of the first failed item in bundle order, if any.
+    ///
+    /// With [`BundleMode::AbortOnFirstFailure`], this is not necessarily the item that
+    /// caused the others to be skipped, since items are verified concurrently.
+    pub fn first_failure(&self) -> Option<(usize, &VerifyError)> {
+        self.items
+            .iter()
+            .enumerate()
+            .find_map(|(index, item)| match &item.status {
+                ItemStatus::Failed(error) => Some((index, error)),
+                _ => None,
+            })
+    }
+}
+
+/// Verifies a bundle of proofs against the same key, on as many scoped threads as the
+/// machine provides.
+///
+/// Items are picked in order by the threads, so with
+/// [`BundleMode::AbortOnFirstFailure`] the items following a failure are the ones being
+/// skipped.
+pub fn verify_bundle(
+    items: &[(&Proof, &PublicInput)],
+    vk: &VerificationKey,
+    mode: BundleMode,
+) -> BundleReport {
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let next = AtomicUsize::new(0);
+    let aborted = AtomicBool::new(false);
+    let reports: Vec<_> = items.iter().map(|_| Mutex::new(None)).collect();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.min(items.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some((proof, pubs)) = items.get(index) else {
+                    break;
+                };
+                let report = if aborted.load(Ordering::Relaxed) {
+                    ItemReport {
+                        status: ItemStatus::Skipped,
+                        elapsed: Duration::ZERO,
+                    }
+                } else {
+                    let start = Instant::now();
+                    let status = match verify_proof(proof, pubs, vk) {
+                        Ok(()) => ItemStatus::Verified,
+                        Err(error) => {
+                            if mode == BundleMode::AbortOnFirstFailure {
+                                aborted.store(true, Ordering::Relaxed);
+                            }
+                            ItemStatus::Failed(error)
+                        }
+                    };
+                    ItemReport {
+                        status,
+                        elapsed: start.elapsed(),
+                    }
+                };
+                *reports[index]
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(report);
+            });
+        }
+    });
+
+    BundleReport {
+        items: reports
+            .into_iter()
+            .map(|report| {
+                report
+                    .into_inner()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .expect("every item is picked by a thread")
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Self-description of what this verifier supports, so that submitters can check
+//! compatibility before sending artifacts.
+
+use alloc::{vec, vec::Vec};
+use serde::Serialize;
+
+use crate::{
+    params::MAX_NU_LIMIT, BindingVersion, HashVersion, ProofScheme, SemVer,
+    SUPPORTED_PROVER_VERSION, WIRE_FORMAT_VERSION,
+};
+
+/// The column types of the results and tables this verifier can decode.
+const COLUMN_TYPES: [&str; 9] = [
+    "Boolean",
+    "SmallInt",
+    "Int",
+    "BigInt",
+    "VarChar",
+    "Int128",
+    "Decimal75",
+    "Scalar",
+    "TimestampTZ",
+];
+
+/// The versions of the encodings and digests this verifier produces and accepts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CodecVersions {
+    /// The prover release whose proof format is supported, see
+    /// [`SemVer::is_compatible_with`].
+    pub proof: SemVer,
+    /// The version of the wire formats of the artifacts, see [`WIRE_FORMAT_VERSION`].
+    pub wire_format: u32,
+    /// The supported statement hash versions.
+    pub statement_hash: Vec<HashVersion>,
+    /// The supported result binding versions.
+    pub result_binding: Vec<BindingVersion>,
+    /// Whether public inputs can be encoded as canonical JSON, i.e. whether the crate
+    /// was built with the `json` feature.
+    pub canonical_json: bool,
+}
+
+/// A description of what this verifier supports, returned by [`capabilities`].
+///
+/// Services typically expose its JSON encoding, see [`Capabilities::to_json`], e.g. at
+/// a `/capabilities` endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    /// The supported proof schemes.
+    pub schemes: Vec<ProofScheme>,
+    /// The largest `max_nu` of the verification keys and setups this verifier accepts.
+    pub max_supported_nu: usize,
+    /// The names of the supported column types.
+    pub column_types: Vec<&'static str>,
+    /// The versions of the supported encodings and digests.
+    pub codec_versions: CodecVersions,
+    /// The version of this crate.
+    pub crate_version: &'static str,
+}
+
+impl Capabilities {
+    /// Serializes the capabilities as canonical JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Vec<u8> {
+        // Capabilities only hold strings and small integers, which always serialize
+        crate::json::to_canonical_json(self).unwrap()
+    }
+}
+
+/// Describes what this build of the verifier supports.
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        schemes: vec![ProofScheme::Dory],
+        max_supported_nu: MAX_NU_LIMIT,
+        column_types: COLUMN_TYPES.to_vec(),
+        codec_versions: CodecVersions {
+            proof: SUPPORTED_PROVER_VERSION,
+            wire_format: WIRE_FORMAT_VERSION,
+            statement_hash: vec![HashVersion::V1, HashVersion::V2, HashVersion::V3],
+            result_binding: vec![BindingVersion::Upstream, BindingVersion::V1],
+            canonical_json: cfg!(feature = "json"),
+        },
+        crate_version: env!("CARGO_PKG_VERSION"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn capabilities_include_latest_versions() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.schemes, [ProofScheme::Dory]);
+        let codecs = &capabilities.codec_versions;
+        assert!(codecs.statement_hash.contains(&HashVersion::LATEST));
+        assert!(codecs.result_binding.contains(&BindingVersion::LATEST));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn capabilities_to_json() {
+        let json: serde_json::Value = serde_json::from_slice(&capabilities().to_json()).unwrap();
+        assert_eq!(json["schemes"], serde_json::json!(["Dory"]));
+        assert_eq!(json["max_supported_nu"], 32);
+        assert_eq!(json["codec_versions"]["proof"]["minor"], 28);
+        assert_eq!(json["codec_versions"]["wire_format"], WIRE_FORMAT_VERSION);
+        assert_eq!(
+            json["codec_versions"]["statement_hash"],
+            serde_json::json!(["V1", "V2", "V3"])
+        );
+        assert_eq!(json["crate_version"], env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -0,0 +1,678 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use core::ops::Range;
+use proof_of_sql::{
+    base::{
+        commitment::{Commitment, QueryCommitments, TableCommitment},
+        database::{
+            ColumnField, ColumnRef, CommitmentAccessor, MetadataAccessor, OwnedColumn, OwnedTable,
+            TableRef,
+        },
+    },
+    proof_primitive::dory::{DoryCommitment, DoryProverPublicSetup, DoryScalar},
+};
+use proof_of_sql_parser::Identifier;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use sha3::{Digest, Keccak256};
+
+use crate::{TableId, VerifyError};
+
+/// A table commitment as read from the wire, whose points are neither decompressed nor
+/// checked yet.
+///
+/// Only binary formats keep the commitment undecoded: buffering into a CBOR value is
+/// lossy for human readable formats, e.g. JSON numbers beyond 64 bits, so commitments
+/// read from them are decoded right away and only re-encoded as a CBOR value.
+#[derive(Clone, Debug)]
+pub(crate) struct UncheckedTableCommitment(ciborium::Value);
+
+impl<'de> Deserialize<'de> for UncheckedTableCommitment {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return ciborium::Value::deserialize(deserializer).map(Self);
+        }
+        let commitment = TableCommitment::<DoryCommitment>::deserialize(deserializer)?;
+        ciborium::Value::serialized(&commitment)
+            .map(Self)
+            .map_err(|_| D::Error::custom(VerifyError::InvalidInput))
+    }
+}
+
+impl UncheckedTableCommitment {
+    /// Computes the key identifying the encoded commitment in a [`CommitmentCache`].
+    fn key(&self) -> [u8; 32] {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&self.0, &mut bytes).unwrap();
+        Keccak256::digest(bytes).into()
+    }
+
+    /// Returns whether the commitment only holds the column metadata, without the
+    /// commitment points, see [`StatementSkeleton`](crate::StatementSkeleton).
+    fn is_metadata_only(&self) -> bool {
+        fn field<'a>(value: &'a ciborium::Value, name: &str) -> Option<&'a ciborium::Value> {
+            let fields = value.as_map()?;
+            fields
+                .iter()
+                .find(|(key, _)| key.as_text() == Some(name))
+                .map(|(_, value)| value)
+        }
+        field(&self.0, "column_commitments")
+            .is_some_and(|columns| field(columns, "commitments").is_none())
+    }
+
+    /// Decompresses the commitment, checking that its points are valid.
+    fn validate(self) -> Result<ValidatedTableCommitment, VerifyError> {
+        self.0
+            .deserialized()
+            .map(ValidatedTableCommitment)
+            .map_err(|_| VerifyError::InvalidInput)
+    }
+}
+
+/// A table commitment whose points are decompressed and known to be valid.
+///
+/// Decoding always validates points, so values built from decoded or in-memory
+/// commitments can be reused without repeating the checks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatedTableCommitment(TableCommitment<DoryCommitment>);
+
+impl ValidatedTableCommitment {
+    /// Wraps an in-memory commitment, whose points are valid by construction.
+    pub fn new(commitment: TableCommitment<DoryCommitment>) -> Self {
+        Self(commitment)
+    }
+
+    /// Returns a reference to the commitment.
+    pub fn inner(&self) -> &TableCommitment<DoryCommitment> {
+        &self.0
+    }
+
+    /// Returns the commitment.
+    pub fn into_inner(self) -> TableCommitment<DoryCommitment> {
+        self.0
+    }
+}
+
+/// A cache of validated table commitments keyed by their encoding.
+///
+/// Verifiers seeing the same commitments repeatedly, e.g. for tables that are not
+/// updated between queries, can decode public inputs through a cache to skip point
+/// decompression, see [`PublicInput::try_from_bytes_with_cache`](crate::PublicInput::try_from_bytes_with_cache).
+/// The cache is unbounded: callers are expected to [`clear`](Self::clear) it as needed.
+#[derive(Clone, Debug, Default)]
+pub struct CommitmentCache {
+    entries: BTreeMap<[u8; 32], ValidatedTableCommitment>,
+}
+
+impl CommitmentCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of cached commitments.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes every cached commitment.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Computes the commitment to a column whose first row is at `offset` in its table.
+///
+/// # Arguments
+///
+/// * `column` - The column data.
+/// * `offset` - The index of the first row of `column` in the table.
+/// * `setup` - The prover's public setup.
+///
+/// # Returns
+///
+/// * `DoryCommitment` - The commitment to the column.
+pub fn compute_column_commitment(
+    column: &OwnedColumn<DoryScalar>,
+    offset: usize,
+    setup: &DoryProverPublicSetup,
+) -> DoryCommitment {
+    DoryCommitment::compute_commitments(&[column.into()], offset, setup)[0]
+}
+
+/// Checks that `claimed` is the commitment to a column whose first row is at `offset` in
+/// its table, e.g. a column commitment of a [`TableCommitment`], so that auditors holding
+/// the data of a single column can check it without building a query proof.
+///
+/// Committing requires the prover's public setup: the verification key only holds the
+/// verifier's setup, which can't commit to data.
+///
+/// # Arguments
+///
+/// * `column` - The column data.
+/// * `offset` - The index of the first row of `column` in the table.
+/// * `claimed` - The claimed commitment to the column.
+/// * `setup` - The prover's public setup.
+///
+/// # Errors
+///
+/// Returns `ColumnCommitmentMismatch` if `claimed` is not the commitment to the column.
+pub fn verify_column_commitment(
+    column: &OwnedColumn<DoryScalar>,
+    offset: usize,
+    claimed: &DoryCommitment,
+    setup: &DoryProverPublicSetup,
+) -> Result<(), VerifyError> {
+    if compute_column_commitment(column, offset, setup) != *claimed {
+        return Err(VerifyError::ColumnCommitmentMismatch);
+    }
+    Ok(())
+}
+
+/// Serves the commitments to the columns of a single table, so that table commitments
+/// with max bounds can be built with the upstream accessor-based constructor.
+struct SingleTable {
+    columns: Vec<(Identifier, DoryCommitment)>,
+    range: Range<usize>,
+}
+
+impl SingleTable {
+    /// Builds the commitment to the table, with the given column fields.
+    fn into_commitment(self, fields: &[ColumnField]) -> TableCommitment<DoryCommitment> {
+        // The table reference is only passed back to the accessor, which serves a single table
+        let table_ref = TableRef::new("table.table".parse().unwrap());
+        TableCommitment::from_accessor_with_max_bounds(table_ref, fields, &self)
+    }
+}
+
+impl MetadataAccessor for SingleTable {
+    fn get_length(&self, _table_ref: TableRef) -> usize {
+        self.range.len()
+    }
+
+    fn get_offset(&self, _table_ref: TableRef) -> usize {
+        self.range.start
+    }
+}
+
+impl CommitmentAccessor<DoryCommitment> for SingleTable {
+    fn get_commitment(&self, column: ColumnRef) -> DoryCommitment {
+        self.columns
+            .iter()
+            .find(|(id, _)| *id == column.column_id())
+            .map(|(_, commitment)| *commitment)
+            .expect("only columns of the table are requested")
+    }
+}
+
+/// Computes the commitment to a table whose first row is at `offset`, including the
+/// column metadata needed by the verifier.
+///
+/// Column bounds are set to the bounds of the column types, as done by provers, so that
+/// the commitment doesn't reveal the range of the data.
+///
+/// # Arguments
+///
+/// * `table` - The table data.
+/// * `offset` - The index of the first row of `table`.
+/// * `setup` - The prover's public setup.
+///
+/// # Returns
+///
+/// * `TableCommitment<DoryCommitment>` - The commitment to the table.
+pub fn compute_table_commitment(
+    table: &OwnedTable<DoryScalar>,
+    offset: usize,
+    setup: &DoryProverPublicSetup,
+) -> TableCommitment<DoryCommitment> {
+    let columns: Vec<_> = table.inner_table().values().map(Into::into).collect();
+    let commitments = DoryCommitment::compute_commitments(&columns, offset, setup);
+    let fields: Vec<_> = table
+        .inner_table()
+        .iter()
+        .map(|(id, column)| ColumnField::new(*id, column.column_type()))
+        .collect();
+    SingleTable {
+        columns: table
+            .inner_table()
+            .keys()
+            .copied()
+            .zip(commitments)
+            .collect(),
+        range: offset..offset + table.num_rows(),
+    }
+    .into_commitment(&fields)
+}
+
+/// Computes the commitments to several tables starting at row 0, as expected by a
+/// [`PublicInput`](crate::PublicInput).
+///
+/// # Arguments
+///
+/// * `tables` - The tables and their references.
+/// * `setup` - The prover's public setup.
+///
+/// # Returns
+///
+/// * `QueryCommitments<DoryCommitment>` - The commitments to the tables.
+pub fn compute_query_commitments<'a>(
+    tables: impl IntoIterator<Item = (TableRef, &'a OwnedTable<DoryScalar>)>,
+    setup: &DoryProverPublicSetup,
+) -> QueryCommitments<DoryCommitment> {
+    tables
+        .into_iter()
+        .map(|(table_ref, table)| (table_ref, compute_table_commitment(table, 0, setup)))
+        .collect()
+}
+
+/// A table commitment exchanged as the raw arkworks encoding of its column commitments.
+///
+/// Partners often hand over commitments as one serialized GT element per column, rather
+/// than as serde structures. Since raw encodings carry no column bounds, commitments
+/// built from them use the bounds of the column types, as done by provers, see
+/// [`compute_table_commitment`].
+///
+/// Conversions from bytes are strict: every element must be exactly as long as its
+/// encoding, lie in the target group, and columns may not repeat.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableCommitmentBytes(TableCommitment<DoryCommitment>);
+
+impl TableCommitmentBytes {
+    /// Builds a commitment to the rows in `range` from compressed column commitments.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if `range` is decreasing, a column repeats, or its bytes
+    /// are not a compressed element of the target group.
+    pub fn from_ark_compressed<B: AsRef<[u8]>>(
+        range: Range<usize>,
+        columns: impl IntoIterator<Item = (ColumnField, B)>,
+    ) -> Result<Self, VerifyError> {
+        Self::from_ark(range, columns, Compress::Yes)
+    }
+
+    /// Builds a commitment to the rows in `range` from uncompressed column commitments.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if `range` is decreasing, a column repeats, or its bytes
+    /// are not an uncompressed element of the target group.
+    pub fn from_ark_uncompressed<B: AsRef<[u8]>>(
+        range: Range<usize>,
+        columns: impl IntoIterator<Item = (ColumnField, B)>,
+    ) -> Result<Self, VerifyError> {
+        Self::from_ark(range, columns, Compress::No)
+    }
+
+    fn from_ark<B: AsRef<[u8]>>(
+        range: Range<usize>,
+        columns: impl IntoIterator<Item = (ColumnField, B)>,
+        compress: Compress,
+    ) -> Result<Self, VerifyError> {
+        if range.start > range.end {
+            return Err(VerifyError::InvalidInput);
+        }
+        let mut fields = Vec::new();
+        let mut commitments = Vec::new();
+        for (field, bytes) in columns {
+            if fields
+                .iter()
+                .any(|known: &ColumnField| known.name() == field.name())
+            {
+                return Err(VerifyError::InvalidInput);
+            }
+            let mut reader = bytes.as_ref();
+            let commitment =
+                DoryCommitment::deserialize_with_mode(&mut reader, compress, Validate::Yes)
+                    .map_err(|_| VerifyError::InvalidInput)?;
+            if !reader.is_empty() {
+                return Err(VerifyError::InvalidInput);
+            }
+            commitments.push((field.name(), commitment));
+            fields.push(field);
+        }
+        Ok(Self(
+            SingleTable {
+                columns: commitments,
+                range,
+            }
+            .into_commitment(&fields),
+        ))
+    }
+
+    /// Returns the fields and compressed commitments of the columns, in order.
+    pub fn to_ark_compressed(&self) -> Vec<(ColumnField, Vec<u8>)> {
+        self.to_ark(Compress::Yes)
+    }
+
+    /// Returns the fields and uncompressed commitments of the columns, in order.
+    pub fn to_ark_uncompressed(&self) -> Vec<(ColumnField, Vec<u8>)> {
+        self.to_ark(Compress::No)
+    }
+
+    fn to_ark(&self, compress: Compress) -> Vec<(ColumnField, Vec<u8>)> {
+        let columns = self.0.column_commitments();
+        columns
+            .column_metadata()
+            .iter()
+            .zip(columns.commitments())
+            .map(|((id, metadata), commitment)| {
+                let mut bytes = Vec::new();
+                commitment
+                    .serialize_with_mode(&mut bytes, compress)
+                    .expect("serializing to a vector can't fail");
+                (ColumnField::new(*id, *metadata.column_type()), bytes)
+            })
+            .collect()
+    }
+
+    /// Returns the range of rows the commitment covers.
+    pub fn range(&self) -> &Range<usize> {
+        self.0.range()
+    }
+
+    /// Returns a reference to the commitment.
+    pub fn commitment(&self) -> &TableCommitment<DoryCommitment> {
+        &self.0
+    }
+
+    /// Returns the commitment, whose points are valid by construction.
+    pub fn into_validated(self) -> ValidatedTableCommitment {
+        ValidatedTableCommitment(self.0)
+    }
+}
+
+impl From<TableCommitment<DoryCommitment>> for TableCommitmentBytes {
+    fn from(commitment: TableCommitment<DoryCommitment>) -> Self {
+        Self(commitment)
+    }
+}
+
+/// Validates every unchecked commitment, across threads with the `parallel` feature.
+#[cfg(all(feature = "parallel", not(feature = "zkvm")))]
+fn validate_all(
+    commitments: Vec<UncheckedTableCommitment>,
+) -> Result<Vec<ValidatedTableCommitment>, VerifyError> {
+    use rayon::prelude::*;
+
+    commitments
+        .into_par_iter()
+        .map(UncheckedTableCommitment::validate)
+        .collect()
+}
+
+/// Validates every unchecked commitment, across threads with the `parallel` feature.
+#[cfg(any(not(feature = "parallel"), feature = "zkvm"))]
+fn validate_all(
+    commitments: Vec<UncheckedTableCommitment>,
+) -> Result<Vec<ValidatedTableCommitment>, VerifyError> {
+    commitments
+        .into_iter()
+        .map(UncheckedTableCommitment::validate)
+        .collect()
+}
+
+/// Computes the digest binding a table the plan doesn't read from, i.e. the Keccak-256
+/// digest of its serialized commitment.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if the commitment can't be serialized.
+pub fn table_commitment_digest(
+    commitment: &TableCommitment<DoryCommitment>,
+) -> Result<[u8; 32], VerifyError> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(commitment, &mut bytes).map_err(|_| VerifyError::InvalidInput)?;
+    Ok(Keccak256::digest(bytes).into())
+}
+
+/// Validates the table commitments of a public input, taking the ones found in `cache`
+/// from there and adding the others to it.
+pub(crate) fn validate_commitments(
+    commitments: Vec<(TableRef, UncheckedTableCommitment)>,
+    cache: Option<&mut CommitmentCache>,
+) -> Result<Vec<(TableRef, ValidatedTableCommitment)>, VerifyError> {
+    if let Some((table_ref, _)) = commitments
+        .iter()
+        .find(|(_, unchecked)| unchecked.is_metadata_only())
+    {
+        return Err(VerifyError::MetadataOnlyCommitment {
+            table: TableId::from(*table_ref),
+        });
+    }
+    let Some(cache) = cache else {
+        let (table_refs, unchecked): (Vec<_>, Vec<_>) = commitments.into_iter().unzip();
+        return Ok(table_refs
+            .into_iter()
+            .zip(validate_all(unchecked)?)
+            .collect());
+    };
+
+    let mut cached = Vec::with_capacity(commitments.len());
+    let mut missing = Vec::new();
+    for (table_ref, unchecked) in commitments {
+        let key = unchecked.key();
+        let hit = cache.entries.get(&key).cloned();
+        if hit.is_none() {
+            missing.push((key, unchecked));
+        }
+        cached.push((table_ref, hit));
+    }
+
+    let (keys, unchecked): (Vec<_>, Vec<_>) = missing.into_iter().unzip();
+    let mut validated = keys.into_iter().zip(validate_all(unchecked)?);
+    Ok(cached
+        .into_iter()
+        .map(|(table_ref, hit)| {
+            let commitment = hit.unwrap_or_else(|| {
+                let (key, commitment) = validated.next().unwrap();
+                cache.entries.insert(key, commitment.clone());
+                commitment
+            });
+            (table_ref, commitment)
+        })
+        .collect())
+}
+
+/// Collects validated table commitments into query commitments, rejecting tables that
+/// appear more than once.
+pub(crate) fn collect_commitments(
+    commitments: Vec<(TableRef, ValidatedTableCommitment)>,
+) -> Result<QueryCommitments<DoryCommitment>, VerifyError> {
+    let mut collected = QueryCommitments::default();
+    for (table_ref, commitment) in commitments {
+        let commitment = commitment.into_inner();
+        if let Some(previous) = collected.get(&table_ref) {
+            let table = TableId::from(table_ref);
+            return Err(if previous == &commitment {
+                VerifyError::DuplicateTableCommitment { table }
+            } else {
+                VerifyError::ConflictingTableCommitment { table }
+            });
+        }
+        collected.insert(table_ref, commitment);
+    }
+    Ok(collected)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::{
+        base::{
+            commitment::QueryCommitmentsExt,
+            database::{
+                owned_table_utility::*, ColumnRef, CommitmentAccessor, OwnedTableTestAccessor,
+                SchemaAccessor, TestAccessor,
+            },
+        },
+        proof_primitive::dory::{DoryEvaluationProof, ProverSetup, PublicParameters},
+    };
+
+    use super::*;
+
+    fn unchecked(commitment: &TableCommitment<DoryCommitment>) -> UncheckedTableCommitment {
+        UncheckedTableCommitment(ciborium::Value::serialized(commitment).unwrap())
+    }
+
+    #[test]
+    fn cached_commitments_are_not_decoded_again() {
+        let table_ref: TableRef = "sxt.table".parse().unwrap();
+        let commitment =
+            TableCommitment::<DoryCommitment>::try_new(Default::default(), 0..4).unwrap();
+        let wire = unchecked(&commitment);
+
+        let mut cache = CommitmentCache::new();
+        let validated =
+            validate_commitments(vec![(table_ref, wire.clone())], Some(&mut cache)).unwrap();
+        assert_eq!(validated[0].1.inner(), &commitment);
+        assert_eq!(cache.len(), 1);
+
+        // Poison the cache to observe that the wire commitment is not decoded again
+        let poisoned = ValidatedTableCommitment::new(TableCommitment::default());
+        cache.entries.insert(wire.key(), poisoned.clone());
+        let validated = validate_commitments(vec![(table_ref, wire)], Some(&mut cache)).unwrap();
+        assert_eq!(validated[0].1, poisoned);
+    }
+
+    #[test]
+    fn computed_commitments_match_accessor() {
+        let public_parameters = PublicParameters::test_rand(3, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let setup = DoryProverPublicSetup::new(&ps, 2);
+        let table_ref: TableRef = "sxt.table".parse().unwrap();
+        let table = owned_table([
+            bigint("a", [1, 2, 3, 2]),
+            varchar("b", ["hi", "hello", "there", "world"]),
+        ]);
+        let mut accessor =
+            OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(setup);
+        accessor.add_table(table_ref, table.clone(), 3);
+
+        let columns: Vec<_> = accessor
+            .lookup_schema(table_ref)
+            .into_iter()
+            .map(|(id, column_type)| ColumnRef::new(table_ref, id, column_type))
+            .collect();
+        for (column, data) in columns.iter().zip(table.inner_table().values()) {
+            assert_eq!(
+                compute_column_commitment(data, 3, &setup),
+                accessor.get_commitment(*column)
+            );
+        }
+
+        let expected = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+        assert_eq!(
+            &compute_table_commitment(&table, 3, &setup),
+            &expected[&table_ref]
+        );
+        assert_eq!(
+            compute_query_commitments([(table_ref, &table)], &setup)[&table_ref].num_rows(),
+            4
+        );
+    }
+
+    #[test]
+    fn column_commitments_are_checked_against_data() {
+        let public_parameters = PublicParameters::test_rand(3, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let setup = DoryProverPublicSetup::new(&ps, 2);
+        let table = owned_table([bigint("a", [1, 2, 3]), varchar("b", ["x", "y", "z"])]);
+        let commitment = compute_table_commitment(&table, 2, &setup);
+        let a = "a".parse::<Identifier>().unwrap();
+        let claimed = commitment.column_commitments().get_commitment(&a).unwrap();
+        let data = &table.inner_table()[&a];
+
+        verify_column_commitment(data, 2, &claimed, &setup).unwrap();
+        for (data, offset) in [
+            (data, 0),
+            (&OwnedColumn::BigInt(vec![1, 2, 4]), 2),
+            (&OwnedColumn::BigInt(vec![1, 2]), 2),
+            (&table.inner_table()[&"b".parse::<Identifier>().unwrap()], 2),
+        ] {
+            assert!(matches!(
+                verify_column_commitment(data, offset, &claimed, &setup),
+                Err(VerifyError::ColumnCommitmentMismatch)
+            ));
+        }
+    }
+
+    #[test]
+    fn raw_commitments_round_trip() {
+        let public_parameters = PublicParameters::test_rand(3, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let setup = DoryProverPublicSetup::new(&ps, 2);
+        let table = owned_table([bigint("a", [1, 2, 3]), varchar("b", ["x", "y", "z"])]);
+        let commitment = compute_table_commitment(&table, 2, &setup);
+
+        let raw = TableCommitmentBytes::from(commitment.clone());
+        let compressed = raw.to_ark_compressed();
+        assert_eq!(compressed.len(), 2);
+        assert_eq!(compressed[0].0.name(), "a".parse::<Identifier>().unwrap());
+        let decoded = TableCommitmentBytes::from_ark_compressed(2..5, compressed).unwrap();
+        assert_eq!(decoded.commitment(), &commitment);
+        let decoded =
+            TableCommitmentBytes::from_ark_uncompressed(2..5, raw.to_ark_uncompressed()).unwrap();
+        assert_eq!(decoded.into_validated().into_inner(), commitment);
+    }
+
+    #[test]
+    fn malformed_raw_commitments_are_rejected() {
+        let public_parameters = PublicParameters::test_rand(3, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let setup = DoryProverPublicSetup::new(&ps, 2);
+        let table = owned_table([bigint("a", [1, 2, 3])]);
+        let columns = TableCommitmentBytes::from(compute_table_commitment(&table, 0, &setup))
+            .to_ark_compressed();
+        let (field, bytes) = columns[0].clone();
+        let reject = |range: Range<usize>, columns: Vec<(ColumnField, Vec<u8>)>| {
+            assert!(matches!(
+                TableCommitmentBytes::from_ark_compressed(range, columns),
+                Err(VerifyError::InvalidInput)
+            ));
+        };
+
+        let mut longer = bytes.clone();
+        longer.push(0);
+        reject(0..3, vec![(field, longer)]);
+        reject(0..3, vec![(field, bytes[1..].to_vec())]);
+        let mut corrupted = bytes.clone();
+        corrupted[0] ^= 1;
+        reject(0..3, vec![(field, corrupted)]);
+        reject(0..3, vec![(field, vec![0xff; bytes.len()])]);
+        reject(0..3, vec![(field, bytes.clone()), (field, bytes.clone())]);
+        #[allow(clippy::reversed_empty_ranges)]
+        reject(3..0, vec![(field, bytes)]);
+    }
+
+    #[test]
+    fn invalid_commitments_are_rejected() {
+        let table_ref: TableRef = "sxt.table".parse().unwrap();
+        let wire = UncheckedTableCommitment(ciborium::Value::Integer(0.into()));
+        let mut cache = CommitmentCache::new();
+
+        assert!(validate_commitments(vec![(table_ref, wire.clone())], None).is_err());
+        assert!(validate_commitments(vec![(table_ref, wire)], Some(&mut cache)).is_err());
+        assert!(cache.is_empty());
+    }
+}
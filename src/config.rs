@@ -0,0 +1,369 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use core::{num::NonZeroUsize, time::Duration};
+
+use crate::{
+    CostThresholds, IdentifierPolicy, PlanLimits, PlanPolicy, ResultNormalization, TableId,
+    TextNormalization, TimestampNormalization,
+};
+
+/// Controls how much parallelism the verifier may use.
+///
+/// Multi-scalar multiplications and the Dory reduction rounds can run on
+/// several threads when the `parallel` feature is enabled. Upstream does not
+/// expose MSM window sizes, so the number of worker threads is the only knob.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Parallelism {
+    /// Use the target default: the global thread pool on native targets with
+    /// the `parallel` feature enabled, a single thread everywhere else, including
+    /// zkVM guests built with the `zkvm` feature.
+    #[default]
+    Auto,
+    /// Run verification on the calling thread, without a dedicated pool.
+    ///
+    /// With the `parallel` feature, work the proof system splits across threads still
+    /// runs on the global thread pool: use `Threads(1)` to keep verification on a
+    /// single worker thread.
+    Sequential,
+    /// Use a dedicated pool with the given number of worker threads, built on first use
+    /// and shared by every verification with the same setting.
+    Threads(NonZeroUsize),
+}
+
+impl Parallelism {
+    /// Returns the number of threads this setting resolves to on the current target,
+    /// or `None` when it is left to the global thread pool.
+    pub fn num_threads(&self) -> Option<usize> {
+        if !cfg!(feature = "parallel") || cfg!(feature = "zkvm") || cfg!(target_family = "wasm") {
+            return Some(1);
+        }
+        match self {
+            Parallelism::Auto => None,
+            Parallelism::Sequential => Some(1),
+            Parallelism::Threads(n) => Some(n.get()),
+        }
+    }
+
+    /// Runs `f` with this parallelism setting applied.
+    #[cfg(all(feature = "parallel", not(feature = "zkvm")))]
+    pub(crate) fn install<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        match self {
+            Parallelism::Auto | Parallelism::Sequential => f(),
+            Parallelism::Threads(n) => match thread_pool(*n) {
+                Some(pool) => pool.install(f),
+                // Spawning threads is not possible here: stay on the calling thread
+                None => f(),
+            },
+        }
+    }
+
+    /// Runs `f` with this parallelism setting applied.
+    #[cfg(any(not(feature = "parallel"), feature = "zkvm"))]
+    pub(crate) fn install<R>(&self, f: impl FnOnce() -> R) -> R {
+        f()
+    }
+}
+
+/// Returns the pool with `num_threads` worker threads, building it on first use.
+///
+/// Pools are kept for the lifetime of the process, so that verifications don't pay for
+/// spawning threads: only a handful of distinct settings are expected per process.
+#[cfg(all(feature = "parallel", not(feature = "zkvm")))]
+fn thread_pool(num_threads: NonZeroUsize) -> Option<std::sync::Arc<rayon::ThreadPool>> {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+
+    static POOLS: Mutex<BTreeMap<NonZeroUsize, Arc<rayon::ThreadPool>>> =
+        Mutex::new(BTreeMap::new());
+
+    let mut pools = POOLS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(pool) = pools.get(&num_threads) {
+        return Some(pool.clone());
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads.get())
+        .build()
+        .ok()?;
+    Some(pools.entry(num_threads).or_insert(Arc::new(pool)).clone())
+}
+
+/// Configuration for the `*_with_config` verification entry points and
+/// [`VerifierContext`](crate::VerifierContext).
+///
+/// Settings are applied through the `with_*` builder methods starting from
+/// [`VerifierConfig::new`], so new options can be added without changing the
+/// signature of the entry points.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifierConfig {
+    parallelism: Parallelism,
+    identifier_policy: IdentifierPolicy,
+    plan_policy: PlanPolicy,
+    plan_limits: PlanLimits,
+    cost_thresholds: CostThresholds,
+    table_offsets: Vec<(TableId, usize)>,
+    text_normalization: TextNormalization,
+    timestamp_normalization: TimestampNormalization,
+    trusted_provers: Vec<[u8; 32]>,
+    slow_verification_threshold: Option<Duration>,
+}
+
+impl VerifierConfig {
+    /// Creates a configuration with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the parallelism used during verification.
+    pub fn with_parallelism(mut self, parallelism: Parallelism) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// Returns the parallelism used during verification.
+    pub fn parallelism(&self) -> Parallelism {
+        self.parallelism
+    }
+
+    /// Sets the normalization policy for identifiers.
+    pub fn with_identifier_policy(mut self, identifier_policy: IdentifierPolicy) -> Self {
+        self.identifier_policy = identifier_policy;
+        self
+    }
+
+    /// Returns the normalization policy for identifiers.
+    pub fn identifier_policy(&self) -> IdentifierPolicy {
+        self.identifier_policy
+    }
+
+    /// Sets the restrictions on the accepted plans.
+    pub fn with_plan_policy(mut self, plan_policy: PlanPolicy) -> Self {
+        self.plan_policy = plan_policy;
+        self
+    }
+
+    /// Returns the restrictions on the accepted plans.
+    pub fn plan_policy(&self) -> &PlanPolicy {
+        &self.plan_policy
+    }
+
+    /// Sets the limits on the size of the accepted plans.
+    pub fn with_plan_limits(mut self, plan_limits: PlanLimits) -> Self {
+        self.plan_limits = plan_limits;
+        self
+    }
+
+    /// Returns the limits on the size of the accepted plans.
+    pub fn plan_limits(&self) -> PlanLimits {
+        self.plan_limits
+    }
+
+    /// Sets the thresholds used to assign cost classes.
+    pub fn with_cost_thresholds(mut self, cost_thresholds: CostThresholds) -> Self {
+        self.cost_thresholds = cost_thresholds;
+        self
+    }
+
+    /// Returns the thresholds used to assign cost classes.
+    pub fn cost_thresholds(&self) -> CostThresholds {
+        self.cost_thresholds
+    }
+
+    /// Sets the offset the prover placed `table` at, which its commitment range must
+    /// start at. Setting the offset of a table again replaces the previous one.
+    pub fn with_table_offset(mut self, table: impl Into<TableId>, offset: usize) -> Self {
+        let table = table.into();
+        self.table_offsets.retain(|(t, _)| t != &table);
+        self.table_offsets.push((table, offset));
+        self
+    }
+
+    /// Returns the expected offset of `table`, if set.
+    pub fn table_offset(&self, table: &TableId) -> Option<usize> {
+        self.table_offsets
+            .iter()
+            .find(|(t, _)| t == table)
+            .map(|(_, offset)| *offset)
+    }
+
+    /// Returns the tables with an expected offset.
+    pub fn table_offsets(&self) -> &[(TableId, usize)] {
+        &self.table_offsets
+    }
+
+    /// Sets the normalization applied to varchar values when comparing the verified
+    /// result with the claimed one.
+    pub fn with_text_normalization(mut self, text_normalization: TextNormalization) -> Self {
+        self.text_normalization = text_normalization;
+        self
+    }
+
+    /// Returns the normalization applied to varchar values of results.
+    pub fn text_normalization(&self) -> TextNormalization {
+        self.text_normalization
+    }
+
+    /// Sets the normalization applied to the timezones of timestamp results before
+    /// comparing them.
+    pub fn with_timestamp_normalization(
+        mut self,
+        timestamp_normalization: TimestampNormalization,
+    ) -> Self {
+        self.timestamp_normalization = timestamp_normalization;
+        self
+    }
+
+    /// Returns the normalization applied to the timezones of timestamp results.
+    pub fn timestamp_normalization(&self) -> TimestampNormalization {
+        self.timestamp_normalization
+    }
+
+    /// Returns the normalizations applied to results before comparing them.
+    pub fn result_normalization(&self) -> ResultNormalization {
+        ResultNormalization {
+            text: self.text_normalization,
+            timestamps: self.timestamp_normalization,
+        }
+    }
+
+    /// Adds the ed25519 public key of a prover whose [`SignedArtifact`]s are accepted.
+    ///
+    /// [`SignedArtifact`]: crate::SignedArtifact
+    pub fn with_trusted_prover(mut self, pubkey: [u8; 32]) -> Self {
+        if !self.trusted_provers.contains(&pubkey) {
+            self.trusted_provers.push(pubkey);
+        }
+        self
+    }
+
+    /// Returns the public keys of the trusted provers.
+    pub fn trusted_provers(&self) -> &[[u8; 32]] {
+        &self.trusted_provers
+    }
+
+    /// Sets the duration above which
+    /// [`VerifierContext::verify_recorded`](crate::VerifierContext::verify_recorded)
+    /// reports a verification as slow to its sink, see
+    /// [`SlowVerification`](crate::SlowVerification).
+    ///
+    /// Verifications are only timed with the `std` feature.
+    pub fn with_slow_verification_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_verification_threshold = Some(threshold);
+        self
+    }
+
+    /// Returns the duration above which verifications are reported as slow, if any.
+    pub fn slow_verification_threshold(&self) -> Option<Duration> {
+        self.slow_verification_threshold
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_config_uses_auto_parallelism() {
+        assert_eq!(VerifierConfig::default().parallelism(), Parallelism::Auto);
+    }
+
+    #[cfg(feature = "nfc")]
+    #[test]
+    fn builder_sets_options() {
+        let config = VerifierConfig::new()
+            .with_parallelism(Parallelism::Sequential)
+            .with_identifier_policy(IdentifierPolicy::Strict)
+            .with_text_normalization(TextNormalization::Nfc)
+            .with_timestamp_normalization(TimestampNormalization::Utc)
+            .with_trusted_prover([1; 32])
+            .with_trusted_prover([1; 32])
+            .with_slow_verification_threshold(Duration::from_millis(500));
+        assert_eq!(config.parallelism(), Parallelism::Sequential);
+        assert_eq!(config.identifier_policy(), IdentifierPolicy::Strict);
+        assert_eq!(config.text_normalization(), TextNormalization::Nfc);
+        assert_eq!(
+            config.result_normalization(),
+            ResultNormalization {
+                text: TextNormalization::Nfc,
+                timestamps: TimestampNormalization::Utc,
+            }
+        );
+        assert_eq!(config.trusted_provers(), [[1; 32]]);
+        assert_eq!(
+            config.slow_verification_threshold(),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(VerifierConfig::new().slow_verification_threshold(), None);
+    }
+
+    #[test]
+    fn table_offset_is_replaced() {
+        let table: TableId = "sxt.table".parse().unwrap();
+        let config = VerifierConfig::new()
+            .with_table_offset(table.clone(), 1000)
+            .with_table_offset(table.clone(), 0);
+        assert_eq!(config.table_offset(&table), Some(0));
+        assert_eq!(config.table_offsets().len(), 1);
+        assert_eq!(config.table_offset(&"sxt.other".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn sequential_resolves_to_one_thread() {
+        assert_eq!(Parallelism::Sequential.num_threads(), Some(1));
+    }
+
+    #[test]
+    fn threads_resolve_to_requested_count_only_when_parallel() {
+        let parallelism = Parallelism::Threads(NonZeroUsize::new(4).unwrap());
+        let expected = if cfg!(all(feature = "parallel", not(feature = "zkvm"))) {
+            4
+        } else {
+            1
+        };
+        assert_eq!(parallelism.num_threads(), Some(expected));
+    }
+
+    #[test]
+    fn install_runs_closure() {
+        let parallelism = Parallelism::Threads(NonZeroUsize::new(2).unwrap());
+        assert_eq!(parallelism.install(|| 42), 42);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn sequential_runs_on_the_calling_thread() {
+        let caller = std::thread::current().id();
+        assert_eq!(
+            Parallelism::Sequential.install(|| std::thread::current().id()),
+            caller
+        );
+    }
+
+    #[cfg(all(feature = "parallel", not(feature = "zkvm")))]
+    #[test]
+    fn thread_pools_are_reused() {
+        let two = NonZeroUsize::new(2).unwrap();
+        let pool = thread_pool(two).unwrap();
+        assert!(std::sync::Arc::ptr_eq(&pool, &thread_pool(two).unwrap()));
+        assert_eq!(pool.current_num_threads(), 2);
+        assert_eq!(
+            Parallelism::Threads(two).install(rayon::current_num_threads),
+            2
+        );
+    }
+}
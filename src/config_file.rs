@@ -0,0 +1,393 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! TOML representation of [`VerifierConfig`], so that deployments are configured
+//! declaratively, with the same format whatever embeds the verifier.
+
+use std::{collections::BTreeMap, fs, io, num::NonZeroUsize, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CostThresholds, IdentifierPolicy, Parallelism, PlanLimits, PlanPolicy, TableId,
+    TextNormalization, TimestampNormalization, VerifierConfig,
+};
+
+/// The TOML document of a configuration.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parallelism: Option<ParallelismRepr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identifier_policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text_normalization: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp_normalization: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    trusted_provers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slow_verification_threshold_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plan_limits: Option<PlanLimitsRepr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plan_policy: Option<PlanPolicyRepr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cost_thresholds: Option<CostThresholdsRepr>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    table_offsets: BTreeMap<String, usize>,
+}
+
+/// A parallelism setting, either a name or a number of threads.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ParallelismRepr {
+    Named(String),
+    Threads(NonZeroUsize),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlanLimitsRepr {
+    max_depth: usize,
+    max_nodes: usize,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PlanPolicyRepr {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed_schemas: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_result_columns: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_varchar_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_decimal_precision: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CostThresholdsRepr {
+    medium: u64,
+    large: u64,
+    reject: u64,
+}
+
+impl VerifierConfig {
+    /// Reads a configuration from a TOML file.
+    ///
+    /// Every setting is optional and defaults to its value in [`VerifierConfig::default`],
+    /// and unknown settings are refused. The slow verification threshold is given in
+    /// milliseconds:
+    ///
+    /// ```toml
+    /// parallelism = 4                  # "auto", "sequential" or a number of threads
+    /// identifier_policy = "strict"     # or "case-insensitive"
+    /// text_normalization = "nfc"       # or "none"
+    /// timestamp_normalization = "utc"  # or "none"
+    /// trusted_provers = ["<hex encoded ed25519 public key>"]
+    /// slow_verification_threshold_ms = 500
+    ///
+    /// [plan_limits]
+    /// max_depth = 128
+    /// max_nodes = 65536
+    ///
+    /// [plan_policy]
+    /// allowed_schemas = ["sxt"]
+    /// max_result_columns = 16
+    /// max_varchar_length = 1024
+    /// max_decimal_precision = 38
+    ///
+    /// [cost_thresholds]
+    /// medium = 65536
+    /// large = 1048576
+    /// reject = 16777216
+    ///
+    /// [table_offsets]
+    /// "sxt.table" = 1000
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of reading the file, or an `InvalidData` error if it is not a
+    /// valid configuration, e.g. it holds unknown settings.
+    pub fn from_toml(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let file: ConfigFile =
+            toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        file.try_into()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Converts the configuration into a TOML document, omitting the settings left to
+    /// their default value.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(&ConfigFile::from(self)).expect("configurations are valid TOML")
+    }
+}
+
+impl From<&VerifierConfig> for ConfigFile {
+    fn from(config: &VerifierConfig) -> Self {
+        let default = VerifierConfig::default();
+        let policy = config.plan_policy();
+        let thresholds = config.cost_thresholds();
+        Self {
+            parallelism: non_default(config.parallelism(), default.parallelism()).map(
+                |parallelism| match parallelism {
+                    Parallelism::Auto => ParallelismRepr::Named("auto".into()),
+                    Parallelism::Sequential => ParallelismRepr::Named("sequential".into()),
+                    Parallelism::Threads(n) => ParallelismRepr::Threads(n),
+                },
+            ),
+            identifier_policy: non_default(config.identifier_policy(), default.identifier_policy())
+                .map(|policy| match policy {
+                    IdentifierPolicy::CaseInsensitive => "case-insensitive".into(),
+                    IdentifierPolicy::Strict => "strict".into(),
+                }),
+            text_normalization: non_default(
+                config.text_normalization(),
+                default.text_normalization(),
+            )
+            .map(|normalization| match normalization {
+                TextNormalization::None => "none".into(),
+                #[cfg(feature = "nfc")]
+                TextNormalization::Nfc => "nfc".into(),
+            }),
+            timestamp_normalization: non_default(
+                config.timestamp_normalization(),
+                default.timestamp_normalization(),
+            )
+            .map(|normalization| match normalization {
+                TimestampNormalization::None => "none".into(),
+                TimestampNormalization::Utc => "utc".into(),
+            }),
+            trusted_provers: config
+                .trusted_provers()
+                .iter()
+                .map(|key| hex(key))
+                .collect(),
+            slow_verification_threshold_ms: config
+                .slow_verification_threshold()
+                .map(|threshold| u64::try_from(threshold.as_millis()).unwrap_or(u64::MAX)),
+            plan_limits: non_default(config.plan_limits(), default.plan_limits()).map(|limits| {
+                PlanLimitsRepr {
+                    max_depth: limits.max_depth(),
+                    max_nodes: limits.max_nodes(),
+                }
+            }),
+            plan_policy: non_default(policy, default.plan_policy()).map(|policy| PlanPolicyRepr {
+                allowed_schemas: policy.schemas().map(<[String]>::to_vec),
+                max_result_columns: policy.max_result_columns(),
+                max_varchar_length: policy.max_varchar_length(),
+                max_decimal_precision: policy.max_decimal_precision(),
+            }),
+            cost_thresholds: non_default(thresholds, default.cost_thresholds()).map(|thresholds| {
+                CostThresholdsRepr {
+                    medium: thresholds.medium,
+                    large: thresholds.large,
+                    reject: thresholds.reject,
+                }
+            }),
+            table_offsets: config
+                .table_offsets()
+                .iter()
+                .map(|(table, offset)| (table.to_string(), *offset))
+                .collect(),
+        }
+    }
+}
+
+/// Returns `value` unless it is the default one, so that defaults are left out of files.
+fn non_default<T: PartialEq>(value: T, default: T) -> Option<T> {
+    (value != default).then_some(value)
+}
+
+impl TryFrom<ConfigFile> for VerifierConfig {
+    type Error = String;
+
+    fn try_from(file: ConfigFile) -> Result<Self, Self::Error> {
+        let mut config = VerifierConfig::new();
+        if let Some(parallelism) = file.parallelism {
+            config = config.with_parallelism(match parallelism {
+                ParallelismRepr::Named(name) if name == "auto" => Parallelism::Auto,
+                ParallelismRepr::Named(name) if name == "sequential" => Parallelism::Sequential,
+                ParallelismRepr::Threads(n) => Parallelism::Threads(n),
+                ParallelismRepr::Named(name) => return Err(format!("invalid parallelism {name}")),
+            });
+        }
+        if let Some(policy) = file.identifier_policy {
+            config = config.with_identifier_policy(match policy.as_str() {
+                "case-insensitive" => IdentifierPolicy::CaseInsensitive,
+                "strict" => IdentifierPolicy::Strict,
+                _ => return Err(format!("invalid identifier policy {policy}")),
+            });
+        }
+        if let Some(normalization) = file.text_normalization {
+            config = config.with_text_normalization(match normalization.as_str() {
+                "none" => TextNormalization::None,
+                #[cfg(feature = "nfc")]
+                "nfc" => TextNormalization::Nfc,
+                _ => return Err(format!("invalid text normalization {normalization}")),
+            });
+        }
+        if let Some(normalization) = file.timestamp_normalization {
+            config = config.with_timestamp_normalization(match normalization.as_str() {
+                "none" => TimestampNormalization::None,
+                "utc" => TimestampNormalization::Utc,
+                _ => return Err(format!("invalid timestamp normalization {normalization}")),
+            });
+        }
+        for key in file.trusted_provers {
+            let pubkey = parse_hex(&key).ok_or_else(|| format!("invalid prover key {key}"))?;
+            config = config.with_trusted_prover(pubkey);
+        }
+        if let Some(threshold) = file.slow_verification_threshold_ms {
+            config = config.with_slow_verification_threshold(Duration::from_millis(threshold));
+        }
+        if let Some(limits) = file.plan_limits {
+            config = config.with_plan_limits(PlanLimits::new(limits.max_depth, limits.max_nodes));
+        }
+        let repr = file.plan_policy.unwrap_or_default();
+        let mut policy = PlanPolicy::new();
+        if let Some(schemas) = repr.allowed_schemas {
+            policy = policy.with_allowed_schemas(schemas);
+        }
+        if let Some(max) = repr.max_result_columns {
+            policy = policy.with_max_result_columns(max);
+        }
+        if let Some(max) = repr.max_varchar_length {
+            policy = policy.with_max_varchar_length(max);
+        }
+        if let Some(max) = repr.max_decimal_precision {
+            policy = policy.with_max_decimal_precision(max);
+        }
+        config = config.with_plan_policy(policy);
+        if let Some(thresholds) = file.cost_thresholds {
+            config = config.with_cost_thresholds(CostThresholds {
+                medium: thresholds.medium,
+                large: thresholds.large,
+                reject: thresholds.reject,
+            });
+        }
+        for (table, offset) in file.table_offsets {
+            let table: TableId = table
+                .parse()
+                .map_err(|_| format!("invalid table identifier {table}"))?;
+            config = config.with_table_offset(table, offset);
+        }
+        Ok(config)
+    }
+}
+
+/// Encodes bytes as lowercase hex.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Decodes a hex encoded 32 bytes key.
+fn parse_hex(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut key = [0; 32];
+    for (byte, pair) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Writes `text` to a fresh file of the temporary directory.
+    fn write_temp(name: &str, text: &str) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("posql-config-{}-{name}.toml", std::process::id()));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn round_trip() {
+        let config = VerifierConfig::new()
+            .with_parallelism(Parallelism::Threads(NonZeroUsize::new(4).unwrap()))
+            .with_identifier_policy(IdentifierPolicy::Strict)
+            .with_text_normalization(TextNormalization::Nfc)
+            .with_timestamp_normalization(TimestampNormalization::Utc)
+            .with_trusted_prover([0xab; 32])
+            .with_slow_verification_threshold(Duration::from_millis(500))
+            .with_plan_limits(PlanLimits::new(16, 256))
+            .with_plan_policy(
+                PlanPolicy::allowed_schemas(["sxt"])
+                    .with_max_result_columns(8)
+                    .with_max_decimal_precision(38),
+            )
+            .with_cost_thresholds(CostThresholds {
+                medium: 10,
+                large: 100,
+                reject: 1000,
+            })
+            .with_table_offset("sxt.table".parse::<TableId>().unwrap(), 1000);
+
+        let path = write_temp("round-trip", &config.to_toml());
+        assert_eq!(VerifierConfig::from_toml(&path).unwrap(), config);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(VerifierConfig::default().to_toml(), "");
+    }
+
+    #[test]
+    fn partial_documents_use_defaults() {
+        let path = write_temp(
+            "partial",
+            "parallelism = \"sequential\"\n[plan_policy]\nmax_varchar_length = 64\n",
+        );
+        let config = VerifierConfig::from_toml(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            config,
+            VerifierConfig::new()
+                .with_parallelism(Parallelism::Sequential)
+                .with_plan_policy(PlanPolicy::new().with_max_varchar_length(64))
+        );
+    }
+
+    #[test]
+    fn invalid_documents_are_rejected() {
+        for (name, text) in [
+            ("unknown", "max_threads = 4\n"),
+            ("parallelism", "parallelism = \"all\"\n"),
+            ("zero-threads", "parallelism = 0\n"),
+            ("policy", "identifier_policy = \"lax\"\n"),
+            ("prover", "trusted_provers = [\"abcd\"]\n"),
+            ("table", "[table_offsets]\n\"table\" = 1\n"),
+            ("limits", "[plan_limits]\nmax_depth = 1\n"),
+        ] {
+            let path = write_temp(name, text);
+            let error = VerifierConfig::from_toml(&path).unwrap_err();
+            fs::remove_file(&path).unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{name}");
+        }
+        assert_eq!(
+            VerifierConfig::from_toml(std::env::temp_dir().join("posql-missing.toml"))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(feature = "signed")]
+use crate::SignedArtifact;
+use crate::{
+    precheck_with_config, record_outcome, verify_audited, verify_proof_with_config, AuditTrail,
+    CostClass, HashVersion, OutcomeSink, Proof, PublicInput, QueryCost, SubmissionDeduper,
+    VerificationKey, VerifierConfig, VerifyError, VkBundle,
+};
+
+/// A verification key bundled with the configuration to verify proofs with.
+///
+/// Long-lived verifiers build a context once and reuse it for every proof, so that
+/// each call site doesn't need to carry the key and the configuration separately.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierContext {
+    vk: VerificationKey,
+    config: VerifierConfig,
+}
+
+impl VerifierContext {
+    /// Creates a new context from a verification key and a configuration.
+    pub fn new(vk: VerificationKey, config: VerifierConfig) -> Self {
+        Self { vk, config }
+    }
+
+    /// Returns the verification key of the context.
+    pub fn vk(&self) -> &VerificationKey {
+        &self.vk
+    }
+
+    /// Returns the configuration of the context.
+    pub fn config(&self) -> &VerifierConfig {
+        &self.config
+    }
+
+    /// Registers the keys of a bundle for the schemes the context verifies proofs of,
+    /// replacing the current ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidVerificationKey`, leaving the context unchanged, if the bundle
+    /// has no Dory key.
+    pub fn register_bundle(&mut self, bundle: &VkBundle) -> Result<(), VerifyError> {
+        self.vk = bundle
+            .dory()
+            .ok_or(VerifyError::InvalidVerificationKey)?
+            .clone();
+        Ok(())
+    }
+
+    /// Decodes a public input, enforcing the configured identifier policy and plan limits.
+    pub fn decode_public_input(&self, bytes: &[u8]) -> Result<PublicInput, VerifyError> {
+        PublicInput::try_from_bytes_with_config(bytes, &self.config)
+    }
+
+    /// Decodes a signed artifact, accepting only the configured trusted provers, see
+    /// [`SignedArtifact::try_from_bytes_with_config`].
+    #[cfg(feature = "signed")]
+    pub fn decode_signed_artifact(&self, bytes: &[u8]) -> Result<SignedArtifact, VerifyError> {
+        SignedArtifact::try_from_bytes_with_config(bytes, &self.config)
+    }
+
+    /// Runs the checks that don't involve the proof, see [`precheck_with_config`].
+    pub fn precheck(&self, pubs: &PublicInput) -> Result<(), VerifyError> {
+        precheck_with_config(pubs, &self.vk, &self.config)
+    }
+
+    /// Assigns the cost class of the query of a public input with the configured thresholds.
+    pub fn cost_class(&self, pubs: &PublicInput) -> CostClass {
+        self.config.cost_thresholds().classify(&QueryCost::of(pubs))
+    }
+
+    /// Verifies a proof against the given public input, see [`verify_proof_with_config`].
+    pub fn verify(&self, proof: &Proof, pubs: &PublicInput) -> Result<(), VerifyError> {
+        verify_proof_with_config(proof, pubs, &self.vk, &self.config)
+    }
+
+    /// Verifies a proof like [`VerifierContext::verify`], unless its statement was
+    /// verified recently according to `deduper`, in which case `DuplicateSubmission` is
+    /// returned without verifying the proof.
+    ///
+    /// Statements are only remembered once verified, so that submitting an invalid
+    /// proof of a statement doesn't prevent a valid one from being accepted later.
+    pub fn verify_deduplicated(
+        &self,
+        proof: &Proof,
+        pubs: &PublicInput,
+        deduper: &mut SubmissionDeduper,
+        now: u64,
+    ) -> Result<(), VerifyError> {
+        let statement_hash = pubs.hash(HashVersion::LATEST)?;
+        if deduper.contains(&statement_hash, now) {
+            return Err(VerifyError::DuplicateSubmission);
+        }
+        self.verify(proof, pubs)?;
+        deduper.insert(statement_hash, now);
+        Ok(())
+    }
+
+    /// Verifies a proof like [`VerifierContext::verify`], recording a transcript of the
+    /// verification, see [`verify_audited`].
+    pub fn verify_audited(
+        &self,
+        proof: &Proof,
+        pubs: &PublicInput,
+    ) -> Result<AuditTrail, VerifyError> {
+        verify_audited(proof, pubs, &self.vk, &self.config)
+    }
+
+    /// Verifies a proof like [`VerifierContext::verify`], recording the outcome to `sink`.
+    ///
+    /// With the `std` feature and a
+    /// [slow verification threshold](VerifierConfig::with_slow_verification_threshold),
+    /// verifications taking longer are also recorded as a
+    /// [`SlowVerification`](crate::SlowVerification), whatever their outcome.
+    pub fn verify_recorded(
+        &self,
+        proof: &Proof,
+        pubs: &PublicInput,
+        sink: &dyn OutcomeSink,
+    ) -> Result<(), VerifyError> {
+        #[cfg(feature = "std")]
+        let start = std::time::Instant::now();
+        let result = self.verify(proof, pubs);
+        #[cfg(feature = "std")]
+        if let Some(threshold) = self.config.slow_verification_threshold() {
+            let duration = start.elapsed();
+            if duration > threshold {
+                sink.record_slow(&crate::SlowVerification::new(pubs, &self.vk, duration));
+            }
+        }
+        record_outcome(sink, &self.vk, result)
+    }
+}
+
+impl From<VerificationKey> for VerifierContext {
+    fn from(vk: VerificationKey) -> Self {
+        Self::new(vk, VerifierConfig::default())
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use proof_of_sql::{
+    base::database::TableRef, proof_primitive::dory::DoryCommitment, sql::proof_plans::DynProofPlan,
+};
+
+use crate::{plan::column_references, Proof, ProofSizeBreakdown, PublicInput};
+
+/// The size of a query, as the inputs to its [`CostClass`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct QueryCost {
+    /// The number of committed cells read by the plan, i.e. the sum over the referenced
+    /// columns of the number of rows of their table.
+    pub scanned_cells: u64,
+    /// The number of cells in the claimed result.
+    pub result_cells: u64,
+    /// The size of the proof, if known, see [`QueryCost::with_proof`]. It is reported
+    /// alongside the cells but doesn't count towards the cost class.
+    pub proof_size: Option<ProofSizeBreakdown>,
+}
+
+impl QueryCost {
+    /// Computes the cost of the query of a public input.
+    ///
+    /// Only integer arithmetic on data bound by the statement is involved, so every node
+    /// computes the same cost for the same public input.
+    pub fn of(pubs: &PublicInput) -> Self {
+        let scanned_cells = scanned_cells(pubs.expr(), |table_ref| {
+            pubs.commitments()
+                .get(table_ref)
+                .map(|commitment| commitment.num_rows())
+        });
+        let table = &pubs.query_data().table;
+        let result_cells = (table.num_rows() as u64).saturating_mul(table.num_columns() as u64);
+        Self {
+            scanned_cells,
+            result_cells,
+            proof_size: None,
+        }
+    }
+
+    /// Adds the size breakdown of the proof of the query, to tell what dominates the
+    /// size of the submission.
+    pub fn with_proof(mut self, proof: &Proof) -> Self {
+        self.proof_size = Some(proof.size_breakdown());
+        self
+    }
+
+    /// Returns the total number of cells, on which cost classes are assigned.
+    pub fn total_cells(&self) -> u64 {
+        self.scanned_cells.saturating_add(self.result_cells)
+    }
+}
+
+/// Counts the committed cells read by a plan, given the number of rows of its tables
+/// returned by `num_rows`.
+pub(crate) fn scanned_cells(
+    expr: &DynProofPlan<DoryCommitment>,
+    num_rows: impl Fn(&TableRef) -> Option<usize>,
+) -> u64 {
+    column_references(expr)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|column| num_rows(&column.table_ref()))
+        .fold(0u64, |cells, rows| cells.saturating_add(rows as u64))
+}
+
+/// Discrete cost classes, e.g. to assign fee tiers.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CostClass {
+    /// Below the medium threshold.
+    Small = 0,
+    /// At least the medium threshold.
+    Medium = 1,
+    /// At least the large threshold.
+    Large = 2,
+    /// At least the reject threshold: the query should not be accepted.
+    Reject = 3,
+}
+
+/// The thresholds, in total cells, at which queries move to the next [`CostClass`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CostThresholds {
+    /// Queries with at least this many cells are at least [`CostClass::Medium`].
+    pub medium: u64,
+    /// Queries with at least this many cells are at least [`CostClass::Large`].
+    pub large: u64,
+    /// Queries with at least this many cells are [`CostClass::Reject`].
+    pub reject: u64,
+}
+
+impl Default for CostThresholds {
+    fn default() -> Self {
+        Self {
+            medium: 1 << 16,
+            large: 1 << 20,
+            reject: 1 << 24,
+        }
+    }
+}
+
+impl CostThresholds {
+    /// Assigns the cost class of a query.
+    pub fn classify(&self, cost: &QueryCost) -> CostClass {
+        let cells = cost.total_cells();
+        if cells >= self.reject {
+            CostClass::Reject
+        } else if cells >= self.large {
+            CostClass::Large
+        } else if cells >= self.medium {
+            CostClass::Medium
+        } else {
+            CostClass::Small
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case::empty(0, 0, CostClass::Small)]
+    #[case::below_medium(9, 0, CostClass::Small)]
+    #[case::medium(5, 5, CostClass::Medium)]
+    #[case::large(100, 0, CostClass::Large)]
+    #[case::reject(999, 1, CostClass::Reject)]
+    #[case::saturating(u64::MAX, u64::MAX, CostClass::Reject)]
+    fn classify(#[case] scanned_cells: u64, #[case] result_cells: u64, #[case] class: CostClass) {
+        let thresholds = CostThresholds {
+            medium: 10,
+            large: 100,
+            reject: 1000,
+        };
+        let cost = QueryCost {
+            scanned_cells,
+            result_cells,
+            proof_size: None,
+        };
+        assert_eq!(thresholds.classify(&cost), class);
+    }
+}
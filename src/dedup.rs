@@ -0,0 +1,180 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::collections::{BTreeMap, VecDeque};
+
+use crate::{HashVersion, PublicInput, VerifyError};
+
+/// A bounded memory of the statements seen recently, so that relayers can drop
+/// submissions of already verified statements before spending cycles on them.
+///
+/// Statements are identified by their [`HashVersion::LATEST`] statement hash, and are
+/// forgotten `ttl` seconds after they were first seen, or earlier, oldest first, once
+/// `capacity` statements are remembered. Times are given by the caller, in seconds, and
+/// are expected not to go backwards.
+#[derive(Clone, Debug)]
+pub struct SubmissionDeduper {
+    capacity: usize,
+    ttl: u64,
+    /// The expiry time of every remembered statement.
+    expiries: BTreeMap<[u8; 32], u64>,
+    /// The remembered statements with their expiry time, oldest first.
+    order: VecDeque<([u8; 32], u64)>,
+}
+
+impl SubmissionDeduper {
+    /// Creates a deduper remembering at most `capacity` statements for `ttl` seconds.
+    pub fn new(capacity: usize, ttl: u64) -> Self {
+        Self {
+            capacity,
+            ttl,
+            expiries: BTreeMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Returns the maximum number of remembered statements.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns for how long, in seconds, statements are remembered.
+    pub fn ttl(&self) -> u64 {
+        self.ttl
+    }
+
+    /// Returns the number of remembered statements, including the expired ones not
+    /// pruned yet.
+    pub fn len(&self) -> usize {
+        self.expiries.len()
+    }
+
+    /// Returns whether no statement is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.expiries.is_empty()
+    }
+
+    /// Forgets every statement.
+    pub fn clear(&mut self) {
+        self.expiries.clear();
+        self.order.clear();
+    }
+
+    /// Returns whether the statement with the given hash was seen less than `ttl`
+    /// seconds before `now`.
+    pub fn contains(&self, statement_hash: &[u8; 32], now: u64) -> bool {
+        self.expiries
+            .get(statement_hash)
+            .is_some_and(|expiry| now < *expiry)
+    }
+
+    /// Checks that the statement of `pubs` wasn't seen recently.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DuplicateSubmission` if the statement was seen less than `ttl` seconds
+    /// before `now`, or `InvalidInput` if the public input can't be hashed.
+    pub fn check(&self, pubs: &PublicInput, now: u64) -> Result<(), VerifyError> {
+        if self.contains(&pubs.hash(HashVersion::LATEST)?, now) {
+            return Err(VerifyError::DuplicateSubmission);
+        }
+        Ok(())
+    }
+
+    /// Remembers the statement with the given hash as seen at `now`, returning whether
+    /// it wasn't seen recently.
+    ///
+    /// The expiry of a statement seen recently is not extended.
+    pub fn insert(&mut self, statement_hash: [u8; 32], now: u64) -> bool {
+        self.prune(now);
+        if self.contains(&statement_hash, now) {
+            return false;
+        }
+        if self.capacity == 0 {
+            return true;
+        }
+        while self.expiries.len() >= self.capacity {
+            self.pop_oldest();
+        }
+        let expiry = now.saturating_add(self.ttl);
+        self.expiries.insert(statement_hash, expiry);
+        self.order.push_back((statement_hash, expiry));
+        true
+    }
+
+    /// Forgets the statements expired at `now`.
+    pub fn prune(&mut self, now: u64) {
+        while self.order.front().is_some_and(|(_, expiry)| *expiry <= now) {
+            self.pop_oldest();
+        }
+    }
+
+    /// Forgets the oldest statement.
+    fn pop_oldest(&mut self) {
+        if let Some((hash, expiry)) = self.order.pop_front() {
+            // The statement may have been remembered again since, with a later expiry
+            if self.expiries.get(&hash) == Some(&expiry) {
+                self.expiries.remove(&hash);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn duplicates_are_detected_until_expiry() {
+        let mut deduper = SubmissionDeduper::new(4, 10);
+        assert!(deduper.insert([1; 32], 100));
+        assert!(deduper.contains(&[1; 32], 109));
+        assert!(!deduper.insert([1; 32], 105));
+        // The expiry is not extended by the duplicate
+        assert!(!deduper.contains(&[1; 32], 110));
+        assert!(deduper.insert([1; 32], 110));
+        assert!(deduper.contains(&[1; 32], 119));
+        assert_eq!(deduper.len(), 1);
+    }
+
+    #[test]
+    fn expired_statements_are_pruned() {
+        let mut deduper = SubmissionDeduper::new(4, 10);
+        deduper.insert([1; 32], 100);
+        deduper.insert([2; 32], 105);
+        deduper.prune(110);
+        assert_eq!(deduper.len(), 1);
+        assert!(deduper.contains(&[2; 32], 110));
+        deduper.clear();
+        assert!(deduper.is_empty());
+    }
+
+    #[test]
+    fn oldest_statements_are_evicted_when_full() {
+        let mut deduper = SubmissionDeduper::new(2, 10);
+        for (index, hash) in [[1; 32], [2; 32], [3; 32]].into_iter().enumerate() {
+            assert!(deduper.insert(hash, 100 + index as u64));
+        }
+        assert_eq!(deduper.len(), 2);
+        assert!(!deduper.contains(&[1; 32], 102));
+        assert!(deduper.contains(&[2; 32], 102));
+        assert!(deduper.contains(&[3; 32], 102));
+
+        let mut disabled = SubmissionDeduper::new(0, 10);
+        assert!(disabled.insert([1; 32], 100));
+        assert!(disabled.insert([1; 32], 100));
+        assert!(disabled.is_empty());
+    }
+}
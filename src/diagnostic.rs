@@ -0,0 +1,249 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use serde::Serialize;
+
+use crate::{
+    verify::{
+        check_column_bindings, check_public_input_shapes, check_verification_key,
+        verify_normalized_proof,
+    },
+    Proof, PublicInput, VerificationKey, VerifierConfig, VerifyError,
+};
+
+/// The independent checks run by [`verify_diagnostic`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum Check {
+    /// Decoding the proof.
+    DecodeProof,
+    /// Decoding the public input, enforcing the identifier policy and plan limits.
+    DecodePublicInput,
+    /// Decoding the verification key.
+    DecodeVerificationKey,
+    /// Checking the plan, and the shape of the claimed result, against the plan policy.
+    PlanPolicy,
+    /// Checking that the plan columns are bound to well formed table commitments.
+    ColumnBindings,
+    /// Checking that the verification key supports the sigma and table sizes.
+    VerificationKey,
+    /// Verifying the proof itself, and the result it embeds against the claimed one.
+    Proof,
+}
+
+/// The outcome of a single check.
+#[derive(Debug)]
+pub enum CheckStatus {
+    /// The check passed.
+    Passed,
+    /// The check failed.
+    Failed(VerifyError),
+    /// The check was not run because a check it depends on failed.
+    Skipped,
+}
+
+/// The outcome of every check run by [`verify_diagnostic`], in the order they were run.
+#[derive(Debug, Default)]
+pub struct DiagnosticReport {
+    checks: Vec<(Check, CheckStatus)>,
+}
+
+impl DiagnosticReport {
+    /// Returns whether every check passed, i.e. whether the proof verifies.
+    pub fn is_ok(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|(_, status)| matches!(status, CheckStatus::Passed))
+    }
+
+    /// Returns the outcome of every check.
+    pub fn checks(&self) -> &[(Check, CheckStatus)] {
+        &self.checks
+    }
+
+    /// Returns the outcome of the given check.
+    pub fn status(&self, check: Check) -> Option<&CheckStatus> {
+        self.checks
+            .iter()
+            .find(|(c, _)| *c == check)
+            .map(|(_, status)| status)
+    }
+
+    /// Returns the failed checks together with their error.
+    pub fn failures(&self) -> impl Iterator<Item = (Check, &VerifyError)> {
+        self.checks
+            .iter()
+            .filter_map(|(check, status)| match status {
+                CheckStatus::Failed(error) => Some((*check, error)),
+                _ => None,
+            })
+    }
+
+    /// Records the outcome of `check`, returning whether it passed.
+    fn record(&mut self, check: Check, result: Result<(), VerifyError>) -> bool {
+        let passed = result.is_ok();
+        let status = match result {
+            Ok(()) => CheckStatus::Passed,
+            Err(error) => CheckStatus::Failed(error),
+        };
+        self.checks.push((check, status));
+        passed
+    }
+
+    /// Records the outcome of `check` if `run` holds, and marks it as skipped otherwise.
+    fn record_if(
+        &mut self,
+        run: bool,
+        check: Check,
+        f: impl FnOnce() -> Result<(), VerifyError>,
+    ) -> bool {
+        if run {
+            self.record(check, f())
+        } else {
+            self.checks.push((check, CheckStatus::Skipped));
+            false
+        }
+    }
+}
+
+/// Decodes an artifact and records the outcome of the decoding.
+fn decode<T>(
+    report: &mut DiagnosticReport,
+    check: Check,
+    decode: impl FnOnce() -> Result<T, VerifyError>,
+) -> Option<T> {
+    match decode() {
+        Ok(value) => {
+            report.record(check, Ok(()));
+            Some(value)
+        }
+        Err(error) => {
+            report.record(check, Err(error));
+            None
+        }
+    }
+}
+
+/// Runs every verification check on serialized artifacts without stopping at the first
+/// failure, and reports the outcome of each of them.
+///
+/// Checks that depend on a failed one, e.g. the proof verification when the column
+/// bindings are broken, are reported as skipped. The report is meant for triaging
+/// integration issues; use [`verify_proof`](crate::verify_proof) for verification.
+///
+/// # Arguments
+///
+/// * `proof` - The serialized proof.
+/// * `pubs` - The serialized public input.
+/// * `vk` - The serialized verification key.
+///
+/// # Returns
+///
+/// * `DiagnosticReport` - The outcome of every check.
+pub fn verify_diagnostic(proof: &[u8], pubs: &[u8], vk: &[u8]) -> DiagnosticReport {
+    verify_diagnostic_with_config(proof, pubs, vk, &VerifierConfig::default())
+}
+
+/// Runs every verification check like [`verify_diagnostic`], applying the given configuration.
+///
+/// # Arguments
+///
+/// * `proof` - The serialized proof.
+/// * `pubs` - The serialized public input.
+/// * `vk` - The serialized verification key.
+/// * `config` - The verifier configuration.
+///
+/// # Returns
+///
+/// * `DiagnosticReport` - The outcome of every check.
+pub fn verify_diagnostic_with_config(
+    proof: &[u8],
+    pubs: &[u8],
+    vk: &[u8],
+    config: &VerifierConfig,
+) -> DiagnosticReport {
+    let mut report = DiagnosticReport::default();
+    let proof = decode(&mut report, Check::DecodeProof, || Proof::try_from(proof));
+    let pubs = decode(&mut report, Check::DecodePublicInput, || {
+        PublicInput::try_from_bytes_with_config(pubs, config)
+    });
+    let vk = decode(&mut report, Check::DecodeVerificationKey, || {
+        VerificationKey::try_from(vk)
+    });
+
+    report.record_if(pubs.is_some(), Check::PlanPolicy, || {
+        let pubs = pubs.as_ref().unwrap();
+        config.plan_policy().check(&pubs.plan_info())?;
+        config.plan_policy().check_query_data(pubs)
+    });
+    let bound = report.record_if(pubs.is_some(), Check::ColumnBindings, || {
+        let pubs = pubs.as_ref().unwrap();
+        check_column_bindings(pubs.expr(), pubs.commitments())?;
+        check_public_input_shapes(pubs)
+    });
+    let usable_vk = report.record_if(
+        pubs.is_some() && vk.is_some(),
+        Check::VerificationKey,
+        || {
+            let pubs = pubs.as_ref().unwrap();
+            check_verification_key(pubs.expr(), pubs.commitments(), vk.as_ref().unwrap())
+        },
+    );
+    // Verifying with unbound columns or an unusable key may panic upstream
+    report.record_if(proof.is_some() && bound && usable_vk, Check::Proof, || {
+        let pubs = pubs.as_ref().unwrap();
+        config
+            .parallelism()
+            .install(|| {
+                verify_normalized_proof(
+                    proof.as_ref().unwrap(),
+                    pubs,
+                    vk.as_ref().unwrap(),
+                    config.result_normalization(),
+                )
+            })
+            .map(|_| ())
+    });
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reports_every_decoding_failure() {
+        let report = verify_diagnostic(&[0xff], &[0xff], &[0xff]);
+
+        assert!(!report.is_ok());
+        let failures: Vec<_> = report.failures().map(|(check, _)| check).collect();
+        assert_eq!(
+            failures,
+            [
+                Check::DecodeProof,
+                Check::DecodePublicInput,
+                Check::DecodeVerificationKey
+            ]
+        );
+        for check in [
+            Check::PlanPolicy,
+            Check::ColumnBindings,
+            Check::VerificationKey,
+            Check::Proof,
+        ] {
+            assert!(matches!(report.status(check), Some(CheckStatus::Skipped)));
+        }
+    }
+}
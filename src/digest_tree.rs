@@ -0,0 +1,260 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Digest trees of verified results, to consume large results piece by piece.
+//!
+//! Once a proof is verified, the result can be stored on untrusted storage along with
+//! its [`ResultDigestTree`]. Consumers holding the tree, or only its root, then fetch
+//! and check single chunks of single columns instead of the whole result.
+
+use alloc::vec::Vec;
+use proof_of_sql::{
+    base::database::{OwnedColumn, OwnedTable},
+    proof_primitive::dory::DoryScalar,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    column_digest,
+    paging::{merkle_root, slice_rows},
+    ColumnId, VerifyError,
+};
+
+/// Per-column and per-chunk digests of a result, forming a two-level Merkle tree.
+///
+/// Columns are split into chunks of `rows_per_chunk` consecutive rows, except for the
+/// last one which holds the remaining ones, and a result without rows has a single
+/// empty chunk. The digest of a chunk is its [`column_digest`], so that the digest of a
+/// column held in a single chunk is the one standing for it when omitted from a claim.
+/// The chunk digests of a column are combined into the column root, and the column
+/// roots into the root of the tree, the same way as the pages of a
+/// [`PagedResult`](crate::PagedResult).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultDigestTree {
+    rows_per_chunk: u64,
+    num_rows: u64,
+    columns: Vec<ColumnDigests>,
+    root: [u8; 32],
+}
+
+/// The digests of the chunks of a result column, see [`ResultDigestTree`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnDigests {
+    name: ColumnId,
+    chunks: Vec<[u8; 32]>,
+    root: [u8; 32],
+}
+
+impl ColumnDigests {
+    /// Returns the name of the column.
+    pub fn name(&self) -> ColumnId {
+        self.name
+    }
+
+    /// Returns the digests of the chunks of the column, in order.
+    pub fn chunks(&self) -> &[[u8; 32]] {
+        &self.chunks
+    }
+
+    /// Returns the Merkle root over the chunk digests.
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+}
+
+impl ResultDigestTree {
+    /// Splits the columns of `table` into chunks of `rows_per_chunk` rows and computes
+    /// the digests over them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if `rows_per_chunk` is zero or a chunk can't be serialized.
+    pub fn new(table: &OwnedTable<DoryScalar>, rows_per_chunk: usize) -> Result<Self, VerifyError> {
+        if rows_per_chunk == 0 {
+            return Err(VerifyError::InvalidInput);
+        }
+        let num_rows = table.num_rows();
+        let num_chunks = num_rows.div_ceil(rows_per_chunk).max(1);
+        let chunks: Vec<_> = (0..num_chunks)
+            .map(|index| {
+                let start = index * rows_per_chunk;
+                slice_rows(table, start, num_rows.min(start + rows_per_chunk))
+            })
+            .collect();
+        let columns = table
+            .column_names()
+            .map(|name| {
+                let chunks = chunks
+                    .iter()
+                    .map(|chunk| column_digest(name, &chunk.inner_table()[name]))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(ColumnDigests {
+                    name: ColumnId::from(*name),
+                    root: merkle_root(chunks.clone()),
+                    chunks,
+                })
+            })
+            .collect::<Result<Vec<_>, VerifyError>>()?;
+        Ok(Self {
+            rows_per_chunk: rows_per_chunk as u64,
+            num_rows: num_rows as u64,
+            root: merkle_root(columns.iter().map(ColumnDigests::root).collect()),
+            columns,
+        })
+    }
+
+    /// Returns the number of rows of each chunk but the last.
+    pub fn rows_per_chunk(&self) -> u64 {
+        self.rows_per_chunk
+    }
+
+    /// Returns the number of rows of the result.
+    pub fn num_rows(&self) -> u64 {
+        self.num_rows
+    }
+
+    /// Returns the number of chunks of each column.
+    pub fn num_chunks(&self) -> u64 {
+        self.num_rows.div_ceil(self.rows_per_chunk.max(1)).max(1)
+    }
+
+    /// Returns the digests of the result columns, in order.
+    pub fn columns(&self) -> &[ColumnDigests] {
+        &self.columns
+    }
+
+    /// Returns the root of the tree, which commits to the whole result.
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Checks that the tree is well formed and has the given root, e.g. for a tree read
+    /// back from untrusted storage by a consumer only holding its root.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidResultChunk` if the tree doesn't have the expected shape or root.
+    pub fn check_root(&self, root: &[u8; 32]) -> Result<(), VerifyError> {
+        let num_chunks = self.num_chunks() as usize;
+        let well_formed = self.rows_per_chunk > 0
+            && self.columns.iter().all(|column| {
+                column.chunks.len() == num_chunks
+                    && merkle_root(column.chunks.clone()) == column.root
+            })
+            && merkle_root(self.columns.iter().map(ColumnDigests::root).collect()) == self.root;
+        if !well_formed || self.root != *root {
+            return Err(VerifyError::InvalidResultChunk);
+        }
+        Ok(())
+    }
+
+    /// Checks that `chunk` is the chunk at `index` of the result column `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidResultChunk` if the result has no such column or chunk, or if
+    /// `chunk` doesn't have the expected number of rows or digest.
+    pub fn verify_chunk(
+        &self,
+        name: &ColumnId,
+        index: u64,
+        chunk: &OwnedColumn<DoryScalar>,
+    ) -> Result<(), VerifyError> {
+        let column = self
+            .columns
+            .iter()
+            .find(|column| column.name == *name)
+            .ok_or(VerifyError::InvalidResultChunk)?;
+        let digest = column
+            .chunks
+            .get(index as usize)
+            .ok_or(VerifyError::InvalidResultChunk)?;
+        let start = index * self.rows_per_chunk;
+        let expected_rows = self.num_rows.min(start.saturating_add(self.rows_per_chunk)) - start;
+        if chunk.len() as u64 != expected_rows || column_digest(&(*name).into(), chunk)? != *digest
+        {
+            return Err(VerifyError::InvalidResultChunk);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proof_of_sql::base::database::owned_table_utility::*;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn table(num_rows: usize) -> OwnedTable<DoryScalar> {
+        let a: Vec<i64> = (0..num_rows as i64).collect();
+        let b: Vec<_> = a.iter().map(|i| alloc::format!("row {i}")).collect();
+        owned_table([bigint("a", a), varchar("b", b)])
+    }
+
+    #[rstest]
+    #[case::empty(0, 3)]
+    #[case::single_chunk(2, 3)]
+    #[case::full_chunks(6, 3)]
+    #[case::partial_last_chunk(7, 3)]
+    fn every_chunk_is_verified(#[case] num_rows: usize, #[case] rows_per_chunk: usize) {
+        let table = table(num_rows);
+        let tree = ResultDigestTree::new(&table, rows_per_chunk).unwrap();
+        assert!(tree.check_root(&tree.root()).is_ok());
+        assert_eq!(tree.columns().len(), 2);
+
+        for (name, column) in table.inner_table() {
+            let name = ColumnId::from(*name);
+            for index in 0..tree.num_chunks() {
+                let start = (index * tree.rows_per_chunk()) as usize;
+                let chunk = column.slice(start, num_rows.min(start + rows_per_chunk));
+                assert!(tree.verify_chunk(&name, index, &chunk).is_ok());
+            }
+            let chunk = column.slice(0, 0);
+            assert!(tree.verify_chunk(&name, tree.num_chunks(), &chunk).is_err());
+        }
+    }
+
+    #[test]
+    fn single_chunk_digest_is_column_digest() {
+        let table = table(4);
+        let tree = ResultDigestTree::new(&table, 4).unwrap();
+        let (name, column) = table.inner_table().first().unwrap();
+        assert_eq!(
+            tree.columns()[0].chunks(),
+            [column_digest(name, column).unwrap()]
+        );
+    }
+
+    #[test]
+    fn tampered_chunks_and_trees_are_rejected() {
+        let table = table(7);
+        let tree = ResultDigestTree::new(&table, 3).unwrap();
+        let a = "a".parse().unwrap();
+        assert!(matches!(
+            tree.verify_chunk(&a, 0, &OwnedColumn::BigInt(alloc::vec![0, 1, 5])),
+            Err(VerifyError::InvalidResultChunk)
+        ));
+        let short = OwnedColumn::BigInt(alloc::vec![0, 1]);
+        assert!(tree.verify_chunk(&a, 0, &short).is_err());
+        assert!(tree.verify_chunk(&"c".parse().unwrap(), 0, &short).is_err());
+
+        let mut forged = tree.clone();
+        forged.columns[0].chunks[1] = [0; 32];
+        assert!(forged.check_root(&tree.root()).is_err());
+        assert!(tree.check_root(&[0; 32]).is_err());
+        assert!(ResultDigestTree::new(&table, 0).is_err());
+    }
+}
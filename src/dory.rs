@@ -0,0 +1,23 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The artifacts and the entry point of [`ProofScheme::Dory`](crate::ProofScheme::Dory),
+//! namespaced by scheme.
+//!
+//! Dory is the only scheme supported so far, so these items are also exported at the
+//! crate root, under the same names, and [`verify`] as
+//! [`verify_proof`](crate::verify_proof).
+
+pub use crate::{verify_proof as verify, Proof, PublicInput, VerificationKey};
@@ -0,0 +1,200 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use proof_of_sql::{
+    base::commitment::QueryCommitments, proof_primitive::dory::DoryCommitment,
+    sql::proof_plans::DynProofPlan,
+};
+
+use crate::{PublicInput, TableId};
+
+/// The outcome of comparing two public inputs with [`statements_equivalent`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EquivalenceReport {
+    plans_equal: bool,
+    differing_tables: Vec<TableId>,
+    results_equal: bool,
+}
+
+impl EquivalenceReport {
+    /// Returns whether both statements run the same plan.
+    pub fn plans_equal(&self) -> bool {
+        self.plans_equal
+    }
+
+    /// Returns whether both statements commit to the same tables with the same
+    /// commitments.
+    pub fn commitments_equal(&self) -> bool {
+        self.differing_tables.is_empty()
+    }
+
+    /// Returns the tables committed to by only one of the statements, or with different
+    /// commitments, in canonical order.
+    pub fn differing_tables(&self) -> &[TableId] {
+        &self.differing_tables
+    }
+
+    /// Returns whether both statements claim the same result.
+    pub fn results_equal(&self) -> bool {
+        self.results_equal
+    }
+
+    /// Returns whether both statements claim the same fact, i.e. the same result of the
+    /// same plan over the same tables.
+    pub fn is_equivalent(&self) -> bool {
+        self.plans_equal && self.commitments_equal() && self.results_equal
+    }
+}
+
+/// Compares the facts claimed by two public inputs, e.g. to decide whether two
+/// submissions in a dispute are about the same statement.
+///
+/// Plans are compared through their canonical encoding, and commitments regardless of
+/// the order of the tables. Results are compared as the plan dictates, i.e. in any row
+/// order unless the plan guarantees it, and must omit or page the same columns and rows.
+/// The verification hashes are ignored, as they depend on the proof rather than on the
+/// claimed fact.
+pub fn statements_equivalent(a: &PublicInput, b: &PublicInput) -> EquivalenceReport {
+    let plans_equal = plan_bytes(a.expr()).is_some_and(|bytes| Some(bytes) == plan_bytes(b.expr()));
+    let results_equal = plans_equal
+        && a.omitted_columns() == b.omitted_columns()
+        && a.paged_result() == b.paged_result()
+        && a.plan_info()
+            .result_comparison()
+            .tables_equal(&a.query_data().table, &b.query_data().table);
+    EquivalenceReport {
+        plans_equal,
+        differing_tables: differing_tables(a.commitments(), b.commitments()),
+        results_equal,
+    }
+}
+
+/// Encodes a plan, which is deterministic for decoded plans.
+fn plan_bytes(expr: &DynProofPlan<DoryCommitment>) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(expr, &mut bytes).ok()?;
+    Some(bytes)
+}
+
+/// Returns the tables whose commitments differ between `a` and `b`, sorted.
+fn differing_tables(
+    a: &QueryCommitments<DoryCommitment>,
+    b: &QueryCommitments<DoryCommitment>,
+) -> Vec<TableId> {
+    let mut tables: Vec<TableId> = a
+        .iter()
+        .filter(|(table_ref, commitment)| b.get(*table_ref) != Some(*commitment))
+        .chain(
+            b.iter()
+                .filter(|(table_ref, _)| !a.contains_key(*table_ref)),
+        )
+        .map(|(table_ref, _)| TableId::from(*table_ref))
+        .collect();
+    tables.sort();
+    tables
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::{
+        base::{
+            commitment::QueryCommitmentsExt,
+            database::{owned_table_utility::*, OwnedTable, OwnedTableTestAccessor, TestAccessor},
+        },
+        proof_primitive::dory::{
+            DoryEvaluationProof, DoryProverPublicSetup, DoryScalar, ProverSetup, PublicParameters,
+        },
+        sql::{
+            parse::QueryExpr,
+            proof::{ProofPlan, QueryData},
+        },
+    };
+
+    use super::*;
+
+    /// Builds a public input claiming `result` for `query` over a table of `rows` rows.
+    fn build_pubs(query: &str, rows: i64, result: OwnedTable<DoryScalar>) -> PublicInput {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let mut accessor = OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(
+            DoryProverPublicSetup::new(&ps, 4),
+        );
+        let a: Vec<i64> = (0..rows).collect();
+        accessor.add_table(
+            "sxt.table".parse().unwrap(),
+            owned_table([bigint("a", a)]),
+            0,
+        );
+        let query =
+            QueryExpr::try_new(query.parse().unwrap(), "sxt".parse().unwrap(), &accessor).unwrap();
+        let columns = query.proof_expr().get_column_references();
+        let commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+        let query_data = QueryData {
+            table: result,
+            verification_hash: [0; 32],
+        };
+        PublicInput::new(query.proof_expr(), commitments, query_data)
+    }
+
+    const QUERY: &str = "SELECT a FROM table WHERE a >= 1";
+
+    #[test]
+    fn same_fact_in_other_row_order() {
+        let a = build_pubs(QUERY, 3, owned_table([bigint("a", [1, 2])]));
+        let mut b = build_pubs(QUERY, 3, owned_table([bigint("a", [2, 1])]));
+        b = PublicInput::try_from(&b.try_to_bytes().unwrap()[..]).unwrap();
+
+        let report = statements_equivalent(&a, &b);
+        assert!(report.is_equivalent());
+        assert!(report.differing_tables().is_empty());
+    }
+
+    #[test]
+    fn reports_each_difference() {
+        let a = build_pubs(QUERY, 3, owned_table([bigint("a", [1, 2])]));
+
+        let other_result = build_pubs(QUERY, 3, owned_table([bigint("a", [1])]));
+        let report = statements_equivalent(&a, &other_result);
+        assert!(report.plans_equal() && report.commitments_equal());
+        assert!(!report.results_equal() && !report.is_equivalent());
+
+        let other_plan = build_pubs(
+            "SELECT a FROM table WHERE a >= 0",
+            3,
+            owned_table([bigint("a", [1, 2])]),
+        );
+        let report = statements_equivalent(&a, &other_plan);
+        assert!(!report.plans_equal() && !report.results_equal());
+        assert!(report.commitments_equal());
+
+        let other_table = build_pubs(QUERY, 4, owned_table([bigint("a", [1, 2])]));
+        let report = statements_equivalent(&a, &other_table);
+        assert!(report.plans_equal() && report.results_equal());
+        assert_eq!(report.differing_tables(), ["sxt.table"]);
+    }
+
+    #[test]
+    fn claims_must_have_the_same_shape() {
+        let a = build_pubs(QUERY, 3, owned_table([bigint("a", [1, 2])]));
+        let b = build_pubs(QUERY, 3, owned_table([bigint("a", [1, 2])]))
+            .page_result(1)
+            .unwrap();
+
+        assert!(!statements_equivalent(&a, &b).results_equal());
+        assert!(statements_equivalent(&b, &b).is_equivalent());
+    }
+}
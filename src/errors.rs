@@ -14,8 +14,140 @@
 // limitations under the License.
 
 /// This module defines errors used across the verification library.
+use alloc::{boxed::Box, string::String};
+use core::fmt::{Display, Formatter};
+use proof_of_sql::base::database::ColumnType;
 use snafu::Snafu;
 
+use crate::{ColumnId, TableId};
+
+/// A column whose type in its table commitment differs from the one the plan expects,
+/// reported by [`VerifyError::ColumnTypeMismatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMismatch {
+    /// The table of the column.
+    pub table: TableId,
+    /// The column.
+    pub column: ColumnId,
+    /// The type the plan expects.
+    pub expected: ColumnType,
+    /// The type in the table commitment.
+    pub actual: ColumnType,
+}
+
+impl Display for ColumnMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Column {} of table {} is {}, expected {}",
+            self.column, self.table, self.actual, self.expected
+        )
+    }
+}
+
+/// The stage of proof verification at which a proof was rejected, see
+/// [`VerifyError::ProofRejected`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProofStage {
+    /// The proof doesn't have the shape the plan requires, e.g. the number of its
+    /// commitments and evaluations, or its bit distributions are invalid.
+    Shape,
+    /// A sumcheck round, or the final sumcheck evaluation, doesn't match.
+    Sumcheck,
+    /// The evaluation of the embedded result doesn't match the proof, or the result
+    /// violates a constraint of the plan, e.g. on its indexes or ordering.
+    ResultEvaluation,
+    /// The inner product argument opening the committed evaluations fails.
+    Opening,
+    /// A failure upstream reports that this library doesn't classify yet.
+    Other,
+}
+
+impl ProofStage {
+    /// Classifies the message of an upstream verification error.
+    pub(crate) fn from_message(message: &str) -> Self {
+        match message {
+            "invalid bit distributions"
+            | "invalid proof size"
+            | "expected prover to provide bit distribution"
+            | "incorrect number of bit distributions provided"
+            | "bit distribution outside of acceptable range"
+            | "constant sign bitwise decomposition is invalid"
+            | "constant absolute bitwise decomposition is invalid"
+            | "zero sumcheck variables but non-empty result"
+            | "non-zero sumcheck variables but empty result" => ProofStage::Shape,
+            "round evaluation does not match claimed sum" | "sumcheck evaluation check failed" => {
+                ProofStage::Sumcheck
+            }
+            "result evaluation check failed"
+            | "invalid indexes"
+            | "Result indexes not valid."
+            | "Result does not all correct group by columns."
+            | "Result of group by not ordered as expected." => ProofStage::ResultEvaluation,
+            "Inner product proof of MLE evaluations failed" => ProofStage::Opening,
+            _ => ProofStage::Other,
+        }
+    }
+
+    /// Returns the name of the stage, as used in telemetry.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofStage::Shape => "shape",
+            ProofStage::Sumcheck => "sumcheck",
+            ProofStage::ResultEvaluation => "result_evaluation",
+            ProofStage::Opening => "opening",
+            ProofStage::Other => "other",
+        }
+    }
+}
+
+impl Display for ProofStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The way a table commitment is degenerate, as reported by
+/// [`VerifyError::DegenerateCommitment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DegenerateShape {
+    /// The range of rows ends before it starts.
+    ReversedRange { start: usize, end: usize },
+    /// The number of column commitments differs from the number of columns.
+    ColumnCountMismatch { commitments: usize, columns: usize },
+    /// The commitment covers no row, but a column commitment isn't the identity.
+    NonEmptyColumnCommitment { column: usize },
+    /// The claimed result has more rows than the table it is computed from.
+    ResultExceedsTable {
+        result_rows: usize,
+        table_rows: usize,
+    },
+}
+
+impl Display for DegenerateShape {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DegenerateShape::ReversedRange { start, end } => {
+                write!(f, "its range {start}..{end} is reversed")
+            }
+            DegenerateShape::ColumnCountMismatch {
+                commitments,
+                columns,
+            } => write!(f, "it has {commitments} commitments for {columns} columns"),
+            DegenerateShape::NonEmptyColumnCommitment { column } => {
+                write!(f, "it covers no row, but column {column} commits to values")
+            }
+            DegenerateShape::ResultExceedsTable {
+                result_rows,
+                table_rows,
+            } => write!(
+                f,
+                "it has {table_rows} rows, but the claimed result has {result_rows}"
+            ),
+        }
+    }
+}
+
 #[derive(Debug, Snafu)]
 pub enum VerifyError {
     /// Provided data has invalid public inputs.
@@ -24,6 +156,119 @@ pub enum VerifyError {
     InvalidProofData,
     /// Verify proof failed.
     VerificationFailed, // Renamed for clarity
+    /// The proof was rejected by upstream verification at the given stage.
+    #[snafu(display("Proof rejected at the {stage} stage"))]
+    ProofRejected { stage: ProofStage },
+    /// The proof decodes, but is not in its canonical encoding.
+    NonCanonicalProofEncoding,
     /// Provided an invalid verification key.
     InvalidVerificationKey,
+    /// A chunk of a split verification key is malformed, corrupted, duplicated, missing,
+    /// or belongs to another key.
+    #[snafu(display("Invalid verification key chunk {index}"))]
+    InvalidVerificationKeyChunk { index: usize },
+    /// The verification key is not active yet.
+    VkNotYetActive,
+    /// The verification key has expired.
+    VkExpired,
+    /// The statement is not part of the attested set.
+    InvalidAttestation,
+    /// No commitment is provided for a table referenced by the plan.
+    #[snafu(display("No commitment for table {table}"))]
+    MissingTableCommitment { table: TableId },
+    /// The commitment of a table holds no column referenced by the plan.
+    #[snafu(display("Commitment for table {table} has no column {column}"))]
+    MissingColumnMetadata {
+        table: TableId,
+        column: Box<ColumnId>,
+    },
+    /// A column referenced by the plan has another type in its table commitment.
+    #[snafu(display("{mismatch}"))]
+    ColumnTypeMismatch { mismatch: Box<ColumnMismatch> },
+    /// The public input contains the same table commitment more than once.
+    #[snafu(display("Duplicate commitment for table {table}"))]
+    DuplicateTableCommitment { table: TableId },
+    /// The public input contains different commitments for the same table.
+    #[snafu(display("Conflicting commitments for table {table}"))]
+    ConflictingTableCommitment { table: TableId },
+    /// An identifier is not in canonical lowercase form.
+    #[snafu(display("Identifier {identifier} is not in canonical form"))]
+    NonCanonicalIdentifier { identifier: String },
+    /// A table is too large for the verification key.
+    #[snafu(display("Table has {rows} rows, but at most {max_supported} are supported"))]
+    TableTooLarge { rows: usize, max_supported: u128 },
+    /// An encoded artifact exceeds the size limit of the serialization profile.
+    #[snafu(display("Artifact of {size} bytes exceeds the limit of {max_size} bytes"))]
+    ArtifactTooLarge { size: usize, max_size: usize },
+    /// The plan reads from a table whose schema is not allowed by the plan policy.
+    #[snafu(display("Table {table} belongs to schema {schema}, which is not allowed"))]
+    SchemaNotAllowed { table: TableId, schema: String },
+    /// The plan exceeds the depth or node count limits.
+    PlanTooComplex,
+    /// A table commitment has a shape that no honestly computed commitment has, or that
+    /// contradicts the claimed result.
+    #[snafu(display("Commitment for table {table} is degenerate: {shape}"))]
+    DegenerateCommitment {
+        table: TableId,
+        shape: DegenerateShape,
+    },
+    /// A table commitment doesn't start at the offset the table was proven at.
+    #[snafu(display("Commitment for table {table} starts at {actual}, expected {expected}"))]
+    OffsetMismatch {
+        table: TableId,
+        expected: usize,
+        actual: usize,
+    },
+    /// The proof was produced by a prover version whose proofs can't be verified.
+    #[snafu(display("Proofs of prover version {version} are not supported"))]
+    UnsupportedProverVersion { version: String },
+    /// The proof claims an empty grouped result, which upstream can't verify.
+    UnverifiableEmptyResult,
+    /// The page is not part of the paged result.
+    InvalidPageInclusion,
+    /// A chunk of a result column, or the digest tree it is checked against, doesn't
+    /// match the verified result.
+    InvalidResultChunk,
+    /// The signature of a signed artifact is malformed or doesn't match its payload.
+    InvalidSignature,
+    /// The artifact is signed by a prover identity that is not allowed.
+    UntrustedSigner,
+    /// A table commitment only holds column metadata, which is enough for a
+    /// [`StatementSkeleton`](crate::StatementSkeleton) but not to verify proofs.
+    #[snafu(display("Commitment for table {table} only holds column metadata"))]
+    MetadataOnlyCommitment { table: TableId },
+    /// A table read by the plan is only bound by the digest of its commitment, see
+    /// [`PublicInput::digest_unreferenced_tables`](crate::PublicInput::digest_unreferenced_tables).
+    #[snafu(display("Commitment for table {table} is only given as a digest"))]
+    DigestOnlyCommitment { table: TableId },
+    /// The commitment computed from column data doesn't match the claimed one.
+    ColumnCommitmentMismatch,
+    /// The result embedded in the proof doesn't have the shape of the claimed result, or
+    /// is present or missing when it shouldn't be.
+    ResultPayloadMismatch,
+    /// The statement was already submitted recently, see
+    /// [`SubmissionDeduper`](crate::SubmissionDeduper).
+    DuplicateSubmission,
+    /// The claimed result has more columns than allowed by the plan policy.
+    #[snafu(display("Result has {columns} columns, but at most {max_columns} are allowed"))]
+    TooManyResultColumns { columns: usize, max_columns: usize },
+    /// A string of the claimed result is longer than allowed by the plan policy.
+    #[snafu(display(
+        "Result column {column} holds a string of {length} bytes, but at most {max_length} are allowed"
+    ))]
+    VarCharTooLong {
+        column: ColumnId,
+        length: usize,
+        max_length: usize,
+    },
+    /// A decimal column of the claimed result is more precise than allowed by the plan
+    /// policy.
+    #[snafu(display(
+        "Result column {column} has precision {precision}, but at most {max_precision} is allowed"
+    ))]
+    DecimalPrecisionTooLarge {
+        column: ColumnId,
+        precision: u8,
+        max_precision: u8,
+    },
 }
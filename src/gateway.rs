@@ -0,0 +1,208 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::str::FromStr;
+use proof_of_sql::{
+    base::database::{ColumnField, ColumnType, OwnedColumn, OwnedTable},
+    proof_primitive::dory::DoryScalar,
+    sql::proof::QueryData,
+};
+use proof_of_sql_parser::intermediate_decimal::IntermediateDecimal;
+use serde_json::{Map, Value};
+
+use crate::VerifyError;
+
+/// Builds query data from a query result returned as JSON by the Space and Time
+/// gateway, i.e. an array of rows, each an object mapping column names to values.
+///
+/// Values are converted to the types of `fields`, usually the result fields of the
+/// plan, see `ProofPlan::get_column_result_fields`. Column names are matched ignoring
+/// ASCII case, since the gateway reports them in uppercase. Integers, and decimals, may
+/// be given either as JSON numbers or as strings; decimals with more fractional digits
+/// than their column scale are rejected rather than rounded.
+///
+/// The verification hash is not part of gateway responses, and must be obtained from
+/// the prover.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if the response doesn't have the expected shape, or a value
+/// can't be represented by the type of its column.
+pub fn query_data_from_gateway_json(
+    json: &[u8],
+    fields: &[ColumnField],
+    verification_hash: [u8; 32],
+) -> Result<QueryData<DoryScalar>, VerifyError> {
+    let rows: Vec<Map<String, Value>> =
+        serde_json::from_slice(json).map_err(|_| VerifyError::InvalidInput)?;
+    if rows.iter().any(|row| row.len() != fields.len()) {
+        return Err(VerifyError::InvalidInput);
+    }
+
+    let columns = fields
+        .iter()
+        .map(|field| {
+            let name = field.name();
+            let values = rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .find(|(key, _)| key.eq_ignore_ascii_case(name.as_str()))
+                        .map(|(_, value)| value)
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or(VerifyError::InvalidInput)?;
+            let column = to_column(field.data_type(), &values).ok_or(VerifyError::InvalidInput)?;
+            Ok((name, column))
+        })
+        .collect::<Result<Vec<_>, VerifyError>>()?;
+    let table = OwnedTable::try_from_iter(columns).map_err(|_| VerifyError::InvalidInput)?;
+    Ok(QueryData {
+        table,
+        verification_hash,
+    })
+}
+
+/// Converts JSON values to a column of the given type.
+fn to_column(column_type: ColumnType, values: &[&Value]) -> Option<OwnedColumn<DoryScalar>> {
+    fn convert<T>(values: &[&Value], f: impl Fn(&Value) -> Option<T>) -> Option<Vec<T>> {
+        values.iter().map(|value| f(value)).collect()
+    }
+    Some(match column_type {
+        ColumnType::Boolean => OwnedColumn::Boolean(convert(values, Value::as_bool)?),
+        ColumnType::TinyInt => OwnedColumn::TinyInt(convert(values, integer)?),
+        ColumnType::SmallInt => OwnedColumn::SmallInt(convert(values, integer)?),
+        ColumnType::Int => OwnedColumn::Int(convert(values, integer)?),
+        ColumnType::BigInt => OwnedColumn::BigInt(convert(values, integer)?),
+        ColumnType::Int128 => OwnedColumn::Int128(convert(values, integer)?),
+        ColumnType::VarChar => OwnedColumn::VarChar(convert(values, |value| {
+            value.as_str().map(ToString::to_string)
+        })?),
+        ColumnType::Decimal75(precision, scale) => OwnedColumn::Decimal75(
+            precision,
+            scale,
+            convert(values, |value| {
+                let decimal = IntermediateDecimal::from_str(&number_text(value)?).ok()?;
+                if decimal.scale() > scale {
+                    return None;
+                }
+                decimal
+                    .try_into_bigint_with_precision_and_scale(precision.value(), scale)
+                    .ok()?
+                    .try_into()
+                    .ok()
+            })?,
+        ),
+        ColumnType::TimestampTZ(unit, zone) => {
+            OwnedColumn::TimestampTZ(unit, zone, convert(values, integer)?)
+        }
+        ColumnType::Scalar => return None,
+    })
+}
+
+/// Returns the text of a JSON number or string.
+///
+/// Numbers keep their exact textual representation, so they are never rounded.
+fn number_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(text) => Some(text.clone()),
+        _ => None,
+    }
+}
+
+/// Parses an integer given as a JSON number or string.
+fn integer<T: FromStr>(value: &Value) -> Option<T> {
+    number_text(value)?.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use proof_of_sql::base::{database::owned_table_utility::*, math::decimal::Precision};
+    use rstest::rstest;
+
+    use super::*;
+
+    fn fields() -> Vec<ColumnField> {
+        vec![
+            ColumnField::new("a".parse().unwrap(), ColumnType::BigInt),
+            ColumnField::new("b".parse().unwrap(), ColumnType::VarChar),
+            ColumnField::new(
+                "c".parse().unwrap(),
+                ColumnType::Decimal75(Precision::new(10).unwrap(), 2),
+            ),
+        ]
+    }
+
+    #[test]
+    fn converts_gateway_rows() {
+        let json = br#"[
+            {"A": 1, "B": "x", "C": 12.5},
+            {"A": "-9007199254740993", "B": "y", "C": "-0.01"}
+        ]"#;
+        let query_data = query_data_from_gateway_json(json, &fields(), [7; 32]).unwrap();
+
+        assert_eq!(
+            query_data.table,
+            owned_table([
+                bigint("a", [1_i64, -9_007_199_254_740_993]),
+                varchar("b", ["x", "y"]),
+                decimal75("c", 10, 2, [1250, -1]),
+            ])
+        );
+        assert_eq!(query_data.verification_hash, [7; 32]);
+    }
+
+    #[test]
+    fn keeps_strings_unnormalized() {
+        let json = br#"[
+            {"A": 1, "B": "\ud835\udd18\ud83d\ude00", "C": 0},
+            {"A": 2, "B": "e\u0301", "C": 0}
+        ]"#;
+        let query_data = query_data_from_gateway_json(json, &fields(), [0; 32]).unwrap();
+
+        assert_eq!(
+            query_data.table.inner_table()[1],
+            OwnedColumn::VarChar(vec!["\u{1d518}\u{1f600}".into(), "e\u{301}".into()])
+        );
+    }
+
+    #[test]
+    fn converts_empty_result() {
+        let query_data = query_data_from_gateway_json(b"[]", &fields(), [0; 32]).unwrap();
+
+        assert_eq!(query_data.table.num_columns(), 3);
+        assert_eq!(query_data.table.num_rows(), 0);
+    }
+
+    #[rstest]
+    #[case::not_an_array(br#"{"A": 1, "B": "x", "C": 1}"#)]
+    #[case::missing_column(br#"[{"A": 1, "B": "x"}]"#)]
+    #[case::extra_column(br#"[{"A": 1, "B": "x", "C": 1, "D": 1}]"#)]
+    #[case::wrong_type(br#"[{"A": "x", "B": "x", "C": 1}]"#)]
+    #[case::fractional_integer(br#"[{"A": 1.5, "B": "x", "C": 1}]"#)]
+    #[case::lossy_decimal(br#"[{"A": 1, "B": "x", "C": 1.234}]"#)]
+    #[case::decimal_overflow(br#"[{"A": 1, "B": "x", "C": 123456789.1}]"#)]
+    fn rejects_invalid_responses(#[case] json: &[u8]) {
+        assert!(matches!(
+            query_data_from_gateway_json(json, &fields(), [0; 32]),
+            Err(VerifyError::InvalidInput)
+        ));
+    }
+}
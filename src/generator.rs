@@ -0,0 +1,230 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{string::String, vec::Vec};
+use core::ops::RangeInclusive;
+
+use proof_of_sql::{
+    base::{
+        commitment::CommitmentEvaluationProof,
+        database::{OwnedColumn, OwnedTable, OwnedTableTestAccessor, TableRef, TestAccessor},
+    },
+    proof_primitive::dory::DoryScalar,
+};
+use proof_of_sql_parser::Identifier;
+use rand::{distributions::Uniform, Rng};
+
+/// How the values of a synthetic column are drawn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColumnDistribution {
+    /// `BIGINT` values counting up from `start`, one per row.
+    Sequential { start: i64 },
+    /// `BIGINT` values drawn uniformly from the range.
+    UniformBigInt(RangeInclusive<i64>),
+    /// `INT` values drawn uniformly from the range.
+    UniformInt(RangeInclusive<i32>),
+    /// `SMALLINT` values drawn uniformly from the range.
+    UniformSmallInt(RangeInclusive<i16>),
+    /// `BOOLEAN` values that are `true` with probability `numerator / denominator`.
+    Boolean { numerator: u32, denominator: u32 },
+    /// `VARCHAR` values of lowercase ASCII letters, with lengths drawn uniformly from the range.
+    VarChar { length: RangeInclusive<usize> },
+    /// `VARCHAR` values drawn uniformly from a fixed set, to get low-cardinality columns.
+    Choice(Vec<String>),
+}
+
+impl ColumnDistribution {
+    /// Draws a column of `rows` values.
+    fn sample(&self, rows: usize, rng: &mut impl Rng) -> OwnedColumn<DoryScalar> {
+        match self {
+            ColumnDistribution::Sequential { start } => {
+                OwnedColumn::BigInt((0..rows as i64).map(|i| start + i).collect())
+            }
+            ColumnDistribution::UniformBigInt(range) => {
+                OwnedColumn::BigInt(sample_uniform(range, rows, rng))
+            }
+            ColumnDistribution::UniformInt(range) => {
+                OwnedColumn::Int(sample_uniform(range, rows, rng))
+            }
+            ColumnDistribution::UniformSmallInt(range) => {
+                OwnedColumn::SmallInt(sample_uniform(range, rows, rng))
+            }
+            ColumnDistribution::Boolean {
+                numerator,
+                denominator,
+            } => OwnedColumn::Boolean(
+                (0..rows)
+                    .map(|_| rng.gen_ratio(*numerator, *denominator))
+                    .collect(),
+            ),
+            ColumnDistribution::VarChar { length } => {
+                let lengths = Uniform::from(length.clone());
+                let letters = Uniform::from(b'a'..=b'z');
+                OwnedColumn::VarChar(
+                    (0..rows)
+                        .map(|_| {
+                            let len = rng.sample(lengths);
+                            (0..len).map(|_| char::from(rng.sample(letters))).collect()
+                        })
+                        .collect(),
+                )
+            }
+            ColumnDistribution::Choice(values) => {
+                let indexes = Uniform::from(0..values.len());
+                OwnedColumn::VarChar(
+                    (0..rows)
+                        .map(|_| values[rng.sample(indexes)].clone())
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+fn sample_uniform<T>(range: &RangeInclusive<T>, rows: usize, rng: &mut impl Rng) -> Vec<T>
+where
+    T: rand::distributions::uniform::SampleUniform + Copy,
+{
+    let distribution = Uniform::new_inclusive(*range.start(), *range.end());
+    (0..rows).map(|_| rng.sample(&distribution)).collect()
+}
+
+/// Generates synthetic tables of arbitrary size, e.g. to produce large proofs for
+/// benchmarks and memory-limit tests.
+///
+/// # Example
+///
+/// ```
+/// use proof_of_sql_verifier::{ColumnDistribution, TableGenerator};
+///
+/// let table = TableGenerator::new(1_000)
+///     .column("a", ColumnDistribution::Sequential { start: 0 })
+///     .column("b", ColumnDistribution::VarChar { length: 1..=8 })
+///     .generate(&mut rand::thread_rng());
+/// assert_eq!(table.num_rows(), 1_000);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TableGenerator {
+    rows: usize,
+    columns: Vec<(Identifier, ColumnDistribution)>,
+}
+
+impl TableGenerator {
+    /// Creates a generator for tables with the given number of rows and no columns.
+    pub fn new(rows: usize) -> Self {
+        Self {
+            rows,
+            columns: Vec::new(),
+        }
+    }
+
+    /// Adds a column drawn from the given distribution.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is not a valid identifier.
+    pub fn column(mut self, name: &str, distribution: ColumnDistribution) -> Self {
+        self.columns
+            .push((name.parse().expect("invalid column name"), distribution));
+        self
+    }
+
+    /// Generates a table.
+    pub fn generate(&self, rng: &mut impl Rng) -> OwnedTable<DoryScalar> {
+        OwnedTable::try_from_iter(
+            self.columns
+                .iter()
+                .map(|(name, distribution)| (*name, distribution.sample(self.rows, rng))),
+        )
+        .expect("all columns have the same length")
+    }
+
+    /// Generates a table and adds it to a new accessor.
+    pub fn build_accessor<'a, CP>(
+        &self,
+        table_ref: TableRef,
+        setup: CP::ProverPublicSetup<'a>,
+        rng: &mut impl Rng,
+    ) -> OwnedTableTestAccessor<'a, CP>
+    where
+        CP: CommitmentEvaluationProof<Scalar = DoryScalar>,
+    {
+        let mut accessor = OwnedTableTestAccessor::<CP>::new_empty_with_setup(setup);
+        accessor.add_table(table_ref, self.generate(rng), 0);
+        accessor
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::base::database::OwnedColumn;
+    use rstest::*;
+
+    use super::*;
+
+    fn column<'a>(table: &'a OwnedTable<DoryScalar>, name: &str) -> &'a OwnedColumn<DoryScalar> {
+        table
+            .inner_table()
+            .get(&name.parse::<Identifier>().unwrap())
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case::empty(0)]
+    #[case::some(100)]
+    fn generates_requested_shape(#[case] rows: usize) {
+        let table = TableGenerator::new(rows)
+            .column("a", ColumnDistribution::Sequential { start: 10 })
+            .column("b", ColumnDistribution::UniformBigInt(-5..=5))
+            .column("c", ColumnDistribution::UniformInt(0..=1))
+            .column("d", ColumnDistribution::UniformSmallInt(7..=7))
+            .column(
+                "e",
+                ColumnDistribution::Boolean {
+                    numerator: 1,
+                    denominator: 2,
+                },
+            )
+            .column("f", ColumnDistribution::VarChar { length: 2..=4 })
+            .column(
+                "g",
+                ColumnDistribution::Choice(vec!["x".into(), "y".into()]),
+            )
+            .generate(&mut test_rng());
+
+        assert_eq!(table.num_rows(), rows);
+        assert_eq!(table.num_columns(), 7);
+        assert_eq!(
+            column(&table, "a"),
+            &OwnedColumn::BigInt((10..10 + rows as i64).collect())
+        );
+        let OwnedColumn::BigInt(b) = column(&table, "b") else {
+            panic!("unexpected column type")
+        };
+        assert!(b.iter().all(|v| (-5..=5).contains(v)));
+        assert_eq!(column(&table, "d"), &OwnedColumn::SmallInt(vec![7; rows]));
+        let OwnedColumn::VarChar(f) = column(&table, "f") else {
+            panic!("unexpected column type")
+        };
+        assert!(f
+            .iter()
+            .all(|s| (2..=4).contains(&s.len()) && s.chars().all(|c| c.is_ascii_lowercase())));
+        let OwnedColumn::VarChar(g) = column(&table, "g") else {
+            panic!("unexpected column type")
+        };
+        assert!(g.iter().all(|s| s == "x" || s == "y"));
+    }
+}
@@ -0,0 +1,393 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use ciborium::Value;
+use core::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+use proof_of_sql::base::database::TableRef;
+use proof_of_sql_parser::{Identifier, ResourceId};
+use serde::{Deserialize, Serialize};
+
+use crate::{render::Sanitized, RenderLimits, VerifyError};
+
+/// A table referenced by a query, as `schema.table`.
+///
+/// Identifiers are parsed the way proof-of-sql parses them, so they are lowercased.
+/// Conversions from and to proof-of-sql's `TableRef` are provided for interacting with
+/// upstream types, but the API of the crate only exposes this type. Names are kept as
+/// strings rather than upstream identifiers, which keeps errors carrying them small.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TableId {
+    schema: String,
+    table: String,
+}
+
+impl TableId {
+    /// Creates a table id from the names of its schema and table.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if a name is not a valid identifier.
+    pub fn new(schema: &str, table: &str) -> Result<Self, VerifyError> {
+        ResourceId::try_new(schema, table)
+            .map(|id| TableRef::new(id).into())
+            .map_err(|_| VerifyError::InvalidInput)
+    }
+
+    /// Returns the schema of the table.
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+
+    /// Returns the name of the table within its schema.
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+}
+
+impl FromStr for TableId {
+    type Err = VerifyError;
+
+    /// Parses a table id written as `schema.table`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TableRef::from_str(s)
+            .map(Self::from)
+            .map_err(|_| VerifyError::InvalidInput)
+    }
+}
+
+impl Display for TableId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.schema, self.table)
+    }
+}
+
+impl PartialEq<&str> for TableId {
+    fn eq(&self, other: &&str) -> bool {
+        other.split_once('.') == Some((self.schema(), self.table()))
+    }
+}
+
+impl From<TableRef> for TableId {
+    fn from(table_ref: TableRef) -> Self {
+        Self {
+            schema: table_ref.schema_id().to_string(),
+            table: table_ref.table_id().to_string(),
+        }
+    }
+}
+
+impl From<&TableId> for TableRef {
+    fn from(table_id: &TableId) -> Self {
+        // Table ids are only built from valid identifiers
+        let resource_id = ResourceId::try_new(&table_id.schema, &table_id.table)
+            .expect("table ids hold valid identifiers");
+        TableRef::new(resource_id)
+    }
+}
+
+impl From<TableId> for TableRef {
+    fn from(table_id: TableId) -> Self {
+        TableRef::from(&table_id)
+    }
+}
+
+/// The name of a column, either of a table or of a query result.
+///
+/// Column names are parsed the way proof-of-sql parses them, so they are lowercased.
+/// Conversions from and to proof-of-sql's `Identifier` are provided for interacting
+/// with upstream types. It is serialized exactly as an `Identifier`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ColumnId(Identifier);
+
+impl ColumnId {
+    /// Creates a column id from a column name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if the name is not a valid identifier.
+    pub fn new(name: &str) -> Result<Self, VerifyError> {
+        Identifier::try_new(name)
+            .map(Self)
+            .map_err(|_| VerifyError::InvalidInput)
+    }
+
+    /// Returns the name of the column.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl FromStr for ColumnId {
+    type Err = VerifyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl Display for ColumnId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq<&str> for ColumnId {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl From<Identifier> for ColumnId {
+    fn from(identifier: Identifier) -> Self {
+        Self(identifier)
+    }
+}
+
+impl From<ColumnId> for Identifier {
+    fn from(column_id: ColumnId) -> Self {
+        column_id.0
+    }
+}
+
+/// How identifiers (schemas, tables and columns) are normalized before matching.
+///
+/// Identifiers are case-insensitive and are always lowercased when decoded, so
+/// `SXT.Table` and `sxt.table` refer to the same table. This means that the same
+/// statement has several byte encodings unless identifiers are required to be
+/// in canonical (lowercase) form.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdentifierPolicy {
+    /// Accept identifiers in any case and normalize them to lowercase.
+    #[default]
+    CaseInsensitive,
+    /// Only accept identifiers that are already in canonical lowercase form.
+    Strict,
+}
+
+impl IdentifierPolicy {
+    /// Normalizes an identifier according to the policy.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, VerifyError>` - The normalized identifier, or
+    ///   `NonCanonicalIdentifier` if the policy is strict and the identifier is not lowercase.
+    pub fn normalize(&self, identifier: &str) -> Result<String, VerifyError> {
+        let normalized = identifier.to_lowercase();
+        match self {
+            IdentifierPolicy::Strict if normalized != identifier => {
+                Err(VerifyError::NonCanonicalIdentifier {
+                    identifier: identifier.to_string(),
+                })
+            }
+            _ => Ok(normalized),
+        }
+    }
+
+    /// Checks a decoded CBOR document against its canonical re-encoding.
+    ///
+    /// Decoding normalizes identifiers, so the only text values that can differ
+    /// between `raw` and `canonical` are identifiers that were not in canonical form.
+    pub(crate) fn check_encoding(&self, raw: &Value, canonical: &Value) -> Result<(), VerifyError> {
+        match (self, find_text_mismatch(raw, canonical)) {
+            (IdentifierPolicy::Strict, Some(identifier)) => {
+                Err(VerifyError::NonCanonicalIdentifier { identifier })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns the first text value of `raw` that differs from its counterpart in `canonical`.
+fn find_text_mismatch(raw: &Value, canonical: &Value) -> Option<String> {
+    // Explicit stack, as documents come from untrusted sources and can be deeply nested
+    let mut stack: Vec<(&Value, &Value)> = Vec::from([(raw, canonical)]);
+    while let Some(pair) = stack.pop() {
+        match pair {
+            (Value::Text(raw), Value::Text(canonical)) if raw != canonical => {
+                let limits = RenderLimits::DEFAULT;
+                return Some(
+                    Sanitized {
+                        value: raw,
+                        max_len: limits.max_string_len,
+                    }
+                    .to_string(),
+                );
+            }
+            (Value::Array(raw), Value::Array(canonical)) => {
+                stack.extend(raw.iter().zip(canonical).rev());
+            }
+            (Value::Map(raw), Value::Map(canonical)) => {
+                // Entries are matched by key, as the canonical encoding may order them
+                // differently or skip default fields, ignoring the case of identifiers
+                let mut canonical: Vec<_> = canonical
+                    .iter()
+                    .map(|entry| (folded_key(&entry.0), entry))
+                    .collect();
+                canonical.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (raw_key, raw_value) in raw.iter().rev() {
+                    let folded = folded_key(raw_key);
+                    if let Ok(index) = canonical.binary_search_by(|(key, _)| key.cmp(&folded)) {
+                        let (key, value) = canonical[index].1;
+                        stack.push((raw_value, value));
+                        stack.push((raw_key, key));
+                    }
+                }
+            }
+            (Value::Tag(_, raw), Value::Tag(_, canonical)) => stack.push((raw, canonical)),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Encodes a map key with its text values lowercased, the way identifiers are normalized.
+fn folded_key(key: &Value) -> Vec<u8> {
+    let mut key = key.clone();
+    let mut stack = Vec::from([&mut key]);
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::Text(text) => *text = text.to_lowercase(),
+            Value::Array(values) => stack.extend(values),
+            Value::Map(entries) => stack.extend(entries.iter_mut().flat_map(|(k, v)| [k, v])),
+            Value::Tag(_, value) => stack.push(value),
+            _ => {}
+        }
+    }
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&key, &mut bytes).unwrap();
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn case_insensitive_policy_lowercases() {
+        assert_eq!(
+            IdentifierPolicy::CaseInsensitive
+                .normalize("SXT.Table")
+                .unwrap(),
+            "sxt.table"
+        );
+    }
+
+    #[test]
+    fn strict_policy_rejects_uppercase() {
+        assert_eq!(
+            IdentifierPolicy::Strict.normalize("sxt.table").unwrap(),
+            "sxt.table"
+        );
+        assert!(matches!(
+            IdentifierPolicy::Strict.normalize("SXT.Table"),
+            Err(VerifyError::NonCanonicalIdentifier { identifier }) if identifier == "SXT.Table"
+        ));
+    }
+
+    #[test]
+    fn finds_nested_mismatch() {
+        let doc = |name: &str| {
+            Value::Map(vec![(
+                Value::Text("commitments".into()),
+                Value::Array(vec![Value::Integer(1.into()), Value::Text(name.into())]),
+            )])
+        };
+        assert_eq!(find_text_mismatch(&doc("a"), &doc("a")), None);
+        assert_eq!(
+            find_text_mismatch(&doc("A"), &doc("a")),
+            Some("A".to_string())
+        );
+        assert!(IdentifierPolicy::CaseInsensitive
+            .check_encoding(&doc("A"), &doc("a"))
+            .is_ok());
+        assert!(IdentifierPolicy::Strict
+            .check_encoding(&doc("A"), &doc("a"))
+            .is_err());
+    }
+
+    #[test]
+    fn matches_map_entries_by_key() {
+        let map = |entries: &[(&str, &str)]| {
+            Value::Map(
+                entries
+                    .iter()
+                    .map(|&(key, value)| (Value::Text(key.into()), Value::Text(value.into())))
+                    .collect(),
+            )
+        };
+        let canonical = map(&[("a", "x"), ("b", "y")]);
+        assert_eq!(
+            find_text_mismatch(&map(&[("b", "y"), ("a", "x")]), &canonical),
+            None
+        );
+        // Fields skipped by the canonical encoding are ignored
+        assert_eq!(
+            find_text_mismatch(&map(&[("c", "z"), ("a", "x")]), &canonical),
+            None
+        );
+        assert_eq!(
+            find_text_mismatch(&map(&[("b", "y"), ("A", "x")]), &canonical),
+            Some("A".to_string())
+        );
+        assert_eq!(
+            find_text_mismatch(&map(&[("b", "Y"), ("a", "x")]), &canonical),
+            Some("Y".to_string())
+        );
+    }
+
+    #[test]
+    fn table_id_converts_to_and_from_table_ref() {
+        let table_ref: TableRef = "SXT.Table".parse().unwrap();
+        let table_id = TableId::from(table_ref);
+
+        assert_eq!(table_id, TableId::new("sxt", "table").unwrap());
+        assert_eq!(table_id, "sxt.table".parse::<TableId>().unwrap());
+        assert_eq!((table_id.schema(), table_id.table()), ("sxt", "table"));
+        assert_eq!(table_id.to_string(), table_ref.to_string());
+        assert_eq!(TableRef::from(&table_id), table_ref);
+        assert_eq!(table_id, "sxt.table");
+        assert!(matches!(
+            "sxt".parse::<TableId>(),
+            Err(VerifyError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn column_id_is_encoded_as_identifier() {
+        let identifier: Identifier = "A".parse().unwrap();
+        let column_id = ColumnId::from(identifier);
+
+        assert_eq!(column_id, "a");
+        assert_eq!(Identifier::from(column_id), identifier);
+        assert!(matches!(
+            ColumnId::new("a b"),
+            Err(VerifyError::InvalidInput)
+        ));
+
+        let mut encoded = Vec::new();
+        ciborium::into_writer(&column_id, &mut encoded).unwrap();
+        let mut expected = Vec::new();
+        ciborium::into_writer(&identifier, &mut expected).unwrap();
+        assert_eq!(encoded, expected);
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Canonical JSON serialization following RFC 8785 (JCS).
+//!
+//! Numbers are the only deviation: JCS formats them as IEEE 754 doubles, which cannot
+//! represent every 64 and 128 bits integer and would require floating point. Integers
+//! within the safe range `±(2^53 - 1)` are written as JSON numbers, larger ones as
+//! JSON strings holding their decimal digits. Non-integer numbers are rejected.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::VerifyError;
+
+/// The largest integer magnitude exactly representable as an IEEE 754 double.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Serializes `value` as canonical JSON.
+pub(crate) fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, VerifyError> {
+    let value = serde_json::to_value(value).map_err(|_| VerifyError::InvalidInput)?;
+    let mut out = String::new();
+    write_value(&value, &mut out)?;
+    Ok(out.into_bytes())
+}
+
+fn write_value(value: &Value, out: &mut String) -> Result<(), VerifyError> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(n, out)?,
+        Value::String(s) => write_string(s, out),
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(value, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => write_object(map, out)?,
+    }
+    Ok(())
+}
+
+fn write_number(n: &Number, out: &mut String) -> Result<(), VerifyError> {
+    // With arbitrary precision numbers keep the digits they were serialized with
+    let digits = n.as_str();
+    let magnitude = digits.strip_prefix('-').unwrap_or(digits);
+    if magnitude.is_empty() || !magnitude.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(VerifyError::InvalidInput);
+    }
+    let is_safe = magnitude
+        .parse::<u64>()
+        .is_ok_and(|m| m <= MAX_SAFE_INTEGER);
+    match (is_safe, magnitude) {
+        // Negative zero is written as zero, as JCS does
+        (true, "0") => out.push('0'),
+        (true, _) => out.push_str(digits),
+        (false, _) => write_string(digits, out),
+    }
+    Ok(())
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_object(map: &Map<String, Value>, out: &mut String) -> Result<(), VerifyError> {
+    // Keys are sorted by their UTF-16 code units
+    let mut entries: Vec<_> = map
+        .iter()
+        .map(|(key, value)| (key.encode_utf16().collect::<Vec<_>>(), key, value))
+        .collect();
+    entries.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+    out.push('{');
+    for (i, (_, key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_string(key, out);
+        out.push(':');
+        write_value(value, out)?;
+    }
+    out.push('}');
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn canonical(value: Value) -> String {
+        String::from_utf8(to_canonical_json(&value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn objects_are_sorted_by_utf16_code_units() {
+        // From RFC 8785, section 3.2.3
+        let value = json!({
+            "\u{20ac}": "Euro Sign",
+            "\r": "Carriage Return",
+            "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\u{1f600}": "Emoji: Grinning Face",
+            "\u{80}": "Control",
+            "\u{f6}": "Latin Small Letter O With Diaeresis",
+        });
+        let expected = concat!(
+            "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",",
+            "\"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",",
+            "\"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+        );
+        assert_eq!(canonical(value), expected);
+    }
+
+    #[test]
+    fn strings_are_minimally_escaped() {
+        let value = json!(["\u{1}\u{1f}\"\\/\u{8}\u{c}\n\r\t\u{7f}"]);
+        assert_eq!(
+            canonical(value),
+            "[\"\\u0001\\u001f\\\"\\\\/\\b\\f\\n\\r\\t\u{7f}\"]"
+        );
+    }
+
+    #[test]
+    fn unsafe_integers_are_written_as_strings() {
+        let value = json!([
+            0,
+            -1,
+            MAX_SAFE_INTEGER,
+            MAX_SAFE_INTEGER + 1,
+            i64::MIN,
+            u128::MAX
+        ]);
+        assert_eq!(
+            canonical(value),
+            format!(
+                "[0,-1,9007199254740991,\"9007199254740992\",\"{}\",\"{}\"]",
+                i64::MIN,
+                u128::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn non_integers_are_rejected() {
+        let value: Value = serde_json::from_str("[1.5]").unwrap();
+        assert!(to_canonical_json(&value).is_err());
+    }
+}
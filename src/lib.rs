@@ -14,18 +14,157 @@
 // limitations under the License.
 
 #![cfg_attr(not(feature = "std"), no_std)]
+// Verification must be deterministic across platforms, so floating point is banned
+#![forbid(
+    clippy::float_arithmetic,
+    clippy::float_cmp,
+    clippy::cast_precision_loss,
+    clippy::lossy_float_literal
+)]
 
 extern crate alloc;
+// Unit tests use the standard library even when the crate doesn't
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
 
+#[cfg(feature = "audit")]
+mod audit;
+#[cfg(feature = "params")]
+mod backend;
+#[cfg(feature = "binding")]
+mod binding;
+#[cfg(feature = "std")]
+mod bundle;
+#[cfg(feature = "capabilities")]
+mod capabilities;
+mod commitments;
+mod config;
+#[cfg(feature = "toml")]
+mod config_file;
+#[cfg(feature = "context")]
+mod context;
+mod cost;
+mod debug;
+#[cfg(feature = "dedup")]
+mod dedup;
+#[cfg(feature = "diagnostic")]
+mod diagnostic;
+mod digest_tree;
+pub mod dory;
+#[cfg(feature = "equivalence")]
+mod equivalence;
 mod errors;
+#[cfg(feature = "json")]
+mod gateway;
+#[cfg(all(feature = "test", feature = "rand"))]
+mod generator;
+mod identifiers;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "light")]
+pub mod light;
+mod normalization;
+#[cfg(feature = "packed-vk")]
+mod packed_vk;
+mod paging;
+#[cfg(feature = "params")]
+mod params;
+mod plan;
+#[cfg(feature = "profile")]
+mod profile;
 mod proof;
+#[cfg(feature = "proof-context")]
+mod proof_context;
 mod pubs;
+#[cfg(feature = "registry")]
+mod registry;
+mod render;
+mod result;
+#[cfg(all(feature = "sandbox", unix))]
+mod sandbox;
 mod serde;
+#[cfg(all(feature = "std", feature = "registry"))]
+mod shared_registry;
+#[cfg(feature = "signed")]
+mod signed;
+#[cfg(feature = "skeleton")]
+mod skeleton;
+mod statement;
+#[cfg(feature = "std")]
+mod submission;
+mod telemetry;
 mod verification_key;
 mod verify;
+#[cfg(feature = "vk-bundle")]
+mod vk_bundle;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "wire-format")]
+mod wire_format;
 
+#[cfg(feature = "audit")]
+pub use audit::*;
+#[cfg(feature = "params")]
+pub use backend::*;
+#[cfg(feature = "binding")]
+pub use binding::*;
+#[cfg(feature = "std")]
+pub use bundle::*;
+#[cfg(feature = "capabilities")]
+pub use capabilities::*;
+pub use commitments::*;
+pub use config::*;
+#[cfg(feature = "context")]
+pub use context::*;
+pub use cost::*;
+#[cfg(feature = "dedup")]
+pub use dedup::*;
+#[cfg(feature = "diagnostic")]
+pub use diagnostic::*;
+pub use digest_tree::*;
+#[cfg(feature = "equivalence")]
+pub use equivalence::*;
 pub use errors::*;
+#[cfg(feature = "json")]
+pub use gateway::*;
+#[cfg(all(feature = "test", feature = "rand"))]
+pub use generator::*;
+pub use identifiers::*;
+pub use normalization::*;
+#[cfg(feature = "packed-vk")]
+pub use packed_vk::*;
+pub use paging::*;
+#[cfg(feature = "params")]
+pub use params::*;
+pub use plan::*;
+#[cfg(feature = "profile")]
+pub use profile::*;
 pub use proof::*;
+#[cfg(feature = "proof-context")]
+pub use proof_context::*;
 pub use pubs::*;
+#[cfg(feature = "registry")]
+pub use registry::*;
+pub use render::*;
+pub use result::*;
+#[cfg(all(feature = "sandbox", unix))]
+pub use sandbox::*;
+#[cfg(all(feature = "std", feature = "registry"))]
+pub use shared_registry::*;
+#[cfg(feature = "signed")]
+pub use signed::*;
+#[cfg(feature = "skeleton")]
+pub use skeleton::*;
+pub use statement::*;
+#[cfg(feature = "std")]
+pub use submission::*;
+pub use telemetry::*;
 pub use verification_key::*;
 pub use verify::*;
+#[cfg(feature = "vk-bundle")]
+pub use vk_bundle::*;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
+#[cfg(feature = "wire-format")]
+pub use wire_format::*;
@@ -0,0 +1,207 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities for resource-constrained clients that only need to check that a
+//! statement has been attested, without handling proofs or public inputs.
+//!
+//! Attestations commit to a binary Merkle tree of Keccak-256 leaves. Each leaf binds a
+//! statement hash (see [`PublicInput::hash`](crate::PublicInput::hash)) to the fingerprint
+//! of the verification key that verified it (see
+//! [`VerificationKey::fingerprint`](crate::VerificationKey::fingerprint)). Inner nodes
+//! hash the concatenation of their children; a node without a sibling is promoted to the
+//! next level unchanged.
+
+use alloc::vec::Vec;
+use sha3::{Digest, Keccak256};
+
+use crate::VerifyError;
+
+/// A Merkle inclusion path for an attestation leaf.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MerklePath {
+    /// The sibling hashes from the leaf level up to the root.
+    pub siblings: Vec<[u8; 32]>,
+    /// The position of the leaf in the tree.
+    pub leaf_index: u64,
+    /// The number of leaves in the tree.
+    pub num_leaves: u64,
+}
+
+/// Computes the attestation leaf binding a statement to a verification key.
+pub fn attestation_leaf(statement_hash: &[u8; 32], vk_fingerprint: &[u8; 32]) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update(vk_fingerprint)
+        .chain_update(statement_hash)
+        .finalize()
+        .into()
+}
+
+/// Computes the Merkle root implied by a leaf and its inclusion path.
+pub fn compute_root(leaf: [u8; 32], path: &MerklePath) -> Result<[u8; 32], VerifyError> {
+    if path.leaf_index >= path.num_leaves {
+        return Err(VerifyError::InvalidAttestation);
+    }
+    let mut siblings = path.siblings.iter();
+    let mut node = leaf;
+    let mut index = path.leaf_index;
+    let mut width = path.num_leaves;
+    while width > 1 {
+        let is_right = index % 2 == 1;
+        // The last node of an odd-sized level has no sibling and is promoted as is
+        if is_right || index + 1 < width {
+            let sibling = siblings.next().ok_or(VerifyError::InvalidAttestation)?;
+            let (left, right) = if is_right {
+                (sibling, &node)
+            } else {
+                (&node, sibling)
+            };
+            node = Keccak256::new()
+                .chain_update(left)
+                .chain_update(right)
+                .finalize()
+                .into();
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    if siblings.next().is_some() {
+        return Err(VerifyError::InvalidAttestation);
+    }
+    Ok(node)
+}
+
+/// Checks that a statement verified with the given key is part of an attestation.
+///
+/// # Arguments
+///
+/// * `statement_hash` - The hash of the statement public input.
+/// * `vk_fingerprint` - The fingerprint of the verification key.
+/// * `path` - The Merkle inclusion path of the statement leaf.
+/// * `root` - The attested Merkle root.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if the statement is attested, or an error otherwise.
+pub fn check_attested(
+    statement_hash: &[u8; 32],
+    vk_fingerprint: &[u8; 32],
+    path: &MerklePath,
+    root: &[u8; 32],
+) -> Result<(), VerifyError> {
+    let leaf = attestation_leaf(statement_hash, vk_fingerprint);
+    if &compute_root(leaf, path)? == root {
+        Ok(())
+    } else {
+        Err(VerifyError::InvalidAttestation)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::*;
+
+    use super::*;
+
+    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        Keccak256::new()
+            .chain_update(left)
+            .chain_update(right)
+            .finalize()
+            .into()
+    }
+
+    /// Builds the tree naively, returning the root and the path of every leaf.
+    fn build_tree(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<MerklePath>) {
+        let mut paths: Vec<_> = (0..leaves.len())
+            .map(|i| MerklePath {
+                siblings: Vec::new(),
+                leaf_index: i as u64,
+                num_leaves: leaves.len() as u64,
+            })
+            .collect();
+        let mut positions: Vec<usize> = (0..leaves.len()).collect();
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            for (path, position) in paths.iter_mut().zip(positions.iter_mut()) {
+                let sibling = *position ^ 1;
+                if sibling < level.len() {
+                    path.siblings.push(level[sibling]);
+                }
+                *position /= 2;
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        (level[0], paths)
+    }
+
+    fn statement(i: u8) -> [u8; 32] {
+        [i; 32]
+    }
+
+    #[rstest]
+    #[case::single(1)]
+    #[case::even(4)]
+    #[case::odd(5)]
+    #[case::large_odd(11)]
+    fn all_leaves_are_attested(#[case] num_leaves: u8) {
+        let vk = [0xaa; 32];
+        let leaves: Vec<_> = (0..num_leaves)
+            .map(|i| attestation_leaf(&statement(i), &vk))
+            .collect();
+        let (root, paths) = build_tree(&leaves);
+        for (i, path) in paths.iter().enumerate() {
+            assert!(check_attested(&statement(i as u8), &vk, path, &root).is_ok());
+        }
+    }
+
+    #[test]
+    fn wrong_statement_or_vk_is_rejected() {
+        let vk = [0xaa; 32];
+        let leaves: Vec<_> = (0..5)
+            .map(|i| attestation_leaf(&statement(i), &vk))
+            .collect();
+        let (root, paths) = build_tree(&leaves);
+        assert!(check_attested(&statement(1), &vk, &paths[0], &root).is_err());
+        assert!(check_attested(&statement(0), &[0xbb; 32], &paths[0], &root).is_err());
+    }
+
+    #[test]
+    fn malformed_paths_are_rejected() {
+        let vk = [0xaa; 32];
+        let leaves: Vec<_> = (0..5)
+            .map(|i| attestation_leaf(&statement(i), &vk))
+            .collect();
+        let (root, paths) = build_tree(&leaves);
+
+        let mut too_long = paths[0].clone();
+        too_long.siblings.push([0; 32]);
+        assert!(check_attested(&statement(0), &vk, &too_long, &root).is_err());
+
+        let mut too_short = paths[0].clone();
+        too_short.siblings.pop();
+        assert!(check_attested(&statement(0), &vk, &too_short, &root).is_err());
+
+        let mut out_of_range = paths[0].clone();
+        out_of_range.leaf_index = 5;
+        assert!(check_attested(&statement(0), &vk, &out_of_range, &root).is_err());
+    }
+}
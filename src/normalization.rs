@@ -0,0 +1,283 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use proof_of_sql::{
+    base::database::{ColumnType, OwnedColumn, OwnedTable},
+    proof_primitive::dory::DoryScalar,
+};
+use proof_of_sql_parser::posql_time::PoSQLTimeZone;
+#[cfg(feature = "nfc")]
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// How the values of varchar result columns are normalized before comparing the result
+/// attested by a proof with the claimed one.
+///
+/// Strings are proven as raw UTF-8 bytes, so a prover and a verifier that disagree on
+/// the Unicode normalization form of a value (e.g. `é` as one code point, or as `e`
+/// followed by a combining accent) see different results. Normalizing both sides makes
+/// such results match. Omitted column digests and paged claims are then computed over
+/// the normalized result, see [`TextNormalization::normalize_table`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextNormalization {
+    /// Compare strings byte for byte.
+    #[default]
+    None,
+    /// Compare strings in Unicode Normalization Form C, with the `nfc` feature.
+    #[cfg(feature = "nfc")]
+    Nfc,
+}
+
+impl TextNormalization {
+    /// Normalizes a string.
+    pub fn normalize<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        #[cfg(feature = "nfc")]
+        if *self == TextNormalization::Nfc && !is_nfc(value) {
+            return Cow::Owned(value.nfc().collect());
+        }
+        Cow::Borrowed(value)
+    }
+
+    /// Normalizes the values of the varchar columns of a table, leaving other columns
+    /// unchanged.
+    pub fn normalize_table<'a>(
+        &self,
+        table: &'a OwnedTable<DoryScalar>,
+    ) -> Cow<'a, OwnedTable<DoryScalar>> {
+        let is_normalized = |values: &[String]| {
+            values
+                .iter()
+                .all(|value| matches!(self.normalize(value), Cow::Borrowed(_)))
+        };
+        if table.inner_table().values().all(|column| match column {
+            OwnedColumn::VarChar(values) => is_normalized(values),
+            _ => true,
+        }) {
+            return Cow::Borrowed(table);
+        }
+
+        let columns = table.inner_table().iter().map(|(name, column)| {
+            let column = match column {
+                OwnedColumn::VarChar(values) => OwnedColumn::VarChar(
+                    values
+                        .iter()
+                        .map(|value| self.normalize(value).into_owned())
+                        .collect::<Vec<_>>(),
+                ),
+                column => column.clone(),
+            };
+            (*name, column)
+        });
+        Cow::Owned(OwnedTable::try_from_iter(columns).expect("normalization keeps the table shape"))
+    }
+}
+
+/// How the timezones of timestamp result columns are normalized before comparing the
+/// result attested by a proof with the claimed one.
+///
+/// Timestamps are stored as instants, and their timezone is only an annotation of the
+/// column type. Stacks that annotate the same instants with another timezone, e.g. the
+/// local one, therefore claim results that differ only by that annotation. Converting
+/// both sides to UTC makes such results match, while the public input keeps the
+/// original timezone for display.
+///
+/// The normalization only applies to that comparison. Public inputs are serialized and
+/// hashed with the timezones they were claimed with, so that their statement hash stays
+/// the one computed by the stack that made the claim: claims differing only by the
+/// timezone annotation verify alike, but have different statement hashes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimestampNormalization {
+    /// Compare timestamps with their timezone.
+    #[default]
+    None,
+    /// Compare timestamps converted to UTC.
+    Utc,
+}
+
+impl TimestampNormalization {
+    /// Normalizes the timezone of a timestamp column type, leaving other types unchanged.
+    pub fn normalize_type(&self, column_type: ColumnType) -> ColumnType {
+        match (self, column_type) {
+            (TimestampNormalization::Utc, ColumnType::TimestampTZ(unit, _)) => {
+                ColumnType::TimestampTZ(unit, PoSQLTimeZone::Utc)
+            }
+            _ => column_type,
+        }
+    }
+
+    /// Normalizes the timezones of the timestamp columns of a table, leaving other
+    /// columns unchanged.
+    pub fn normalize_table<'a>(
+        &self,
+        table: &'a OwnedTable<DoryScalar>,
+    ) -> Cow<'a, OwnedTable<DoryScalar>> {
+        let is_normalized = |column: &OwnedColumn<DoryScalar>| {
+            self.normalize_type(column.column_type()) == column.column_type()
+        };
+        if table.inner_table().values().all(is_normalized) {
+            return Cow::Borrowed(table);
+        }
+
+        let columns = table.inner_table().iter().map(|(name, column)| {
+            let column = match column {
+                // Values are instants, so only the annotation changes
+                OwnedColumn::TimestampTZ(unit, _, values) => {
+                    OwnedColumn::TimestampTZ(*unit, PoSQLTimeZone::Utc, values.clone())
+                }
+                column => column.clone(),
+            };
+            (*name, column)
+        });
+        Cow::Owned(OwnedTable::try_from_iter(columns).expect("normalization keeps the table shape"))
+    }
+}
+
+/// The normalizations applied to claimed and verified results before comparing them.
+///
+/// Converts from a [`TextNormalization`] alone, so that entry points taking a
+/// `ResultNormalization` also accept one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResultNormalization {
+    /// The normalization of varchar values.
+    pub text: TextNormalization,
+    /// The normalization of timestamp timezones.
+    pub timestamps: TimestampNormalization,
+}
+
+impl ResultNormalization {
+    /// Applies both normalizations to a table, see [`TextNormalization::normalize_table`]
+    /// and [`TimestampNormalization::normalize_table`].
+    pub fn normalize_table<'a>(
+        &self,
+        table: &'a OwnedTable<DoryScalar>,
+    ) -> Cow<'a, OwnedTable<DoryScalar>> {
+        match self.text.normalize_table(table) {
+            Cow::Borrowed(table) => self.timestamps.normalize_table(table),
+            Cow::Owned(table) => Cow::Owned(self.timestamps.normalize_table(&table).into_owned()),
+        }
+    }
+}
+
+impl From<TextNormalization> for ResultNormalization {
+    fn from(text: TextNormalization) -> Self {
+        Self {
+            text,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proof_of_sql::base::database::owned_table_utility::*;
+    use proof_of_sql_parser::posql_time::PoSQLTimeUnit;
+    #[cfg(feature = "nfc")]
+    use rstest::rstest;
+
+    use super::*;
+
+    #[cfg(feature = "nfc")]
+    #[rstest]
+    #[case::ascii("abc", "abc")]
+    #[case::decomposed("e\u{301}", "\u{e9}")]
+    #[case::composed("\u{e9}", "\u{e9}")]
+    #[case::reordered_marks("a\u{323}\u{302}", "\u{1ead}")]
+    #[case::hangul("\u{1100}\u{1161}", "\u{ac00}")]
+    #[case::astral("\u{1d518}\u{1f600}", "\u{1d518}\u{1f600}")]
+    #[case::astral_decomposed("\u{1d15e}", "\u{1d157}\u{1d165}")]
+    fn nfc_normalization(#[case] value: &str, #[case] expected: &str) {
+        assert_eq!(TextNormalization::Nfc.normalize(value), expected);
+        assert_eq!(TextNormalization::None.normalize(value), value);
+    }
+
+    #[cfg(feature = "nfc")]
+    #[test]
+    fn normalizes_varchar_columns_only() {
+        let table = owned_table([bigint("a", [1, 2]), varchar("b", ["e\u{301}", "x"])]);
+
+        let normalized = TextNormalization::Nfc.normalize_table(&table);
+        assert_eq!(
+            *normalized,
+            owned_table([bigint("a", [1, 2]), varchar("b", ["\u{e9}", "x"])])
+        );
+        assert!(matches!(
+            TextNormalization::Nfc.normalize_table(&normalized),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            TextNormalization::None.normalize_table(&table),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn normalizes_timezones_to_utc() {
+        let zoned = |offset| {
+            owned_table([
+                bigint("a", [1]),
+                timestamptz(
+                    "t",
+                    PoSQLTimeUnit::Second,
+                    PoSQLTimeZone::from_offset(offset),
+                    [1_700_000_000],
+                ),
+            ])
+        };
+        let utc = zoned(0);
+        let shifted = zoned(3600);
+        assert_eq!(*TimestampNormalization::Utc.normalize_table(&shifted), utc);
+        assert!(matches!(
+            TimestampNormalization::Utc.normalize_table(&utc),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(
+            *TimestampNormalization::None.normalize_table(&shifted),
+            shifted
+        );
+        assert_eq!(
+            TimestampNormalization::Utc.normalize_type(ColumnType::BigInt),
+            ColumnType::BigInt
+        );
+    }
+
+    #[cfg(feature = "nfc")]
+    #[test]
+    fn result_normalization_applies_both() {
+        let table = owned_table([
+            varchar("b", ["e\u{301}"]),
+            timestamptz(
+                "t",
+                PoSQLTimeUnit::Millisecond,
+                PoSQLTimeZone::FixedOffset(-18000),
+                [0],
+            ),
+        ]);
+        let normalization = ResultNormalization {
+            text: TextNormalization::Nfc,
+            timestamps: TimestampNormalization::Utc,
+        };
+        assert_eq!(
+            *normalization.normalize_table(&table),
+            owned_table([
+                varchar("b", ["\u{e9}"]),
+                timestamptz("t", PoSQLTimeUnit::Millisecond, PoSQLTimeZone::Utc, [0]),
+            ])
+        );
+        assert_eq!(
+            ResultNormalization::from(TextNormalization::Nfc).timestamps,
+            TimestampNormalization::None
+        );
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ciborium::Value;
+use proof_of_sql::proof_primitive::dory::VerifierSetup;
+
+use crate::{VerificationKey, VerifyError};
+
+/// Verification keys for several sigmas, storing the setup they share once.
+///
+/// Keys derived from the same public parameters only differ by their sigma, while their
+/// setup, dominated by the `Delta` and `chi` tables, is identical. Operators keeping a
+/// key per sigma, e.g. for sigmas 1 to 12, store them packed, in a twelfth of the space,
+/// and reconstruct standard [`VerificationKey`]s when loading them.
+///
+/// The container is serialized as a CBOR array of `[setup, sigmas]` pairs sorted by
+/// setup, each setup being a byte string in the format of [`VerificationKey::to_bytes`]
+/// without the sigma, and its sigmas a strictly increasing array, so that containers
+/// have a single valid encoding.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackedVks {
+    /// A key of each setup, and the sigmas of the packed keys of that setup, sorted.
+    groups: Vec<(VerificationKey, Vec<usize>)>,
+}
+
+impl PackedVks {
+    /// Creates an empty container.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key to the container, returning whether it was not already packed.
+    pub fn insert(&mut self, vk: &VerificationKey) -> bool {
+        let Some((_, sigmas)) = self
+            .groups
+            .iter_mut()
+            .find(|(key, _)| key.verifier_setup() == vk.verifier_setup())
+        else {
+            self.groups.push((vk.clone(), Vec::from([vk.sigma()])));
+            return true;
+        };
+        match sigmas.binary_search(&vk.sigma()) {
+            Ok(_) => false,
+            Err(index) => {
+                sigmas.insert(index, vk.sigma());
+                true
+            }
+        }
+    }
+
+    /// Reconstructs the packed keys, grouped by setup and sorted by sigma.
+    pub fn keys(&self) -> impl Iterator<Item = VerificationKey> + '_ {
+        self.groups.iter().flat_map(|(vk, sigmas)| {
+            sigmas.iter().map(|&sigma| {
+                vk.with_sigma(sigma)
+                    .expect("packed sigmas are supported by their setup")
+            })
+        })
+    }
+
+    /// Returns the number of packed keys.
+    pub fn len(&self) -> usize {
+        self.groups.iter().map(|(_, sigmas)| sigmas.len()).sum()
+    }
+
+    /// Returns whether the container holds no key.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Converts the container into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut groups: Vec<(Vec<u8>, &[usize])> = self
+            .groups
+            .iter()
+            .map(|(vk, sigmas)| (setup_bytes(vk.verifier_setup()), &sigmas[..]))
+            .collect();
+        groups.sort();
+        let entries = groups
+            .into_iter()
+            .map(|(setup, sigmas)| {
+                let sigmas = sigmas.iter().map(|&s| Value::Integer(s.into())).collect();
+                Value::Array(Vec::from([Value::Bytes(setup), Value::Array(sigmas)]))
+            })
+            .collect();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&Value::Array(entries), &mut bytes).unwrap();
+        bytes
+    }
+}
+
+impl FromIterator<VerificationKey> for PackedVks {
+    fn from_iter<I: IntoIterator<Item = VerificationKey>>(keys: I) -> Self {
+        let mut packed = Self::new();
+        for vk in keys {
+            packed.insert(&vk);
+        }
+        packed
+    }
+}
+
+impl TryFrom<&[u8]> for PackedVks {
+    type Error = VerifyError;
+
+    /// Decodes a container, validating each setup once, like a decoded
+    /// [`VerificationKey`], and each sigma against its setup.
+    ///
+    /// Containers that are not canonically encoded, or that hold a setup without
+    /// sigmas, are rejected with `InvalidVerificationKey`.
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let Ok(Value::Array(entries)) = ciborium::from_reader(value) else {
+            return Err(VerifyError::InvalidVerificationKey);
+        };
+        let mut groups = Vec::with_capacity(entries.len());
+        let mut previous: Option<Vec<u8>> = None;
+        for entry in entries {
+            let Value::Array(pair) = entry else {
+                return Err(VerifyError::InvalidVerificationKey);
+            };
+            let [Value::Bytes(setup), Value::Array(sigmas)] = &pair[..] else {
+                return Err(VerifyError::InvalidVerificationKey);
+            };
+            // Setups must be sorted, which also rules out duplicates
+            if previous.as_ref().is_some_and(|previous| previous >= setup) {
+                return Err(VerifyError::InvalidVerificationKey);
+            }
+            let sigmas = sigmas
+                .iter()
+                .map(|sigma| {
+                    sigma
+                        .as_integer()
+                        .and_then(|sigma| usize::try_from(sigma).ok())
+                        .ok_or(VerifyError::InvalidVerificationKey)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let (&first, _) = sigmas
+                .split_first()
+                .ok_or(VerifyError::InvalidVerificationKey)?;
+            if sigmas.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err(VerifyError::InvalidVerificationKey);
+            }
+            let mut reader = &setup[..];
+            let parsed = VerifierSetup::deserialize_compressed_unchecked(&mut reader)
+                .map_err(|_| VerifyError::InvalidVerificationKey)?;
+            if !reader.is_empty() {
+                return Err(VerifyError::InvalidVerificationKey);
+            }
+            let vk = VerificationKey::try_from((parsed, first))?;
+            // The largest sigma is the only one that may exceed the setup
+            vk.with_sigma(sigmas[sigmas.len() - 1])?;
+            groups.push((vk, sigmas));
+            previous = Some(setup.clone());
+        }
+        Ok(Self { groups })
+    }
+}
+
+/// Serializes a setup in the format of [`VerificationKey::to_bytes`], without the sigma.
+fn setup_bytes(setup: &VerifierSetup) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    setup.serialize_compressed(&mut bytes).unwrap();
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::proof_primitive::dory::PublicParameters;
+
+    use super::*;
+
+    fn encode(entries: Vec<Value>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&Value::Array(entries), &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let rng = &mut test_rng();
+        let vk = VerificationKey::new(&PublicParameters::test_rand(4, rng), 0);
+        let other = VerificationKey::new(&PublicParameters::test_rand(2, rng), 2);
+        let keys: Vec<_> = (0..=4)
+            .map(|sigma| vk.with_sigma(sigma).unwrap())
+            .chain([other])
+            .collect();
+
+        let mut packed: PackedVks = keys.iter().rev().cloned().collect();
+        assert!(!packed.insert(&keys[3]));
+        assert_eq!(packed.len(), 6);
+
+        // Each setup is stored once
+        let bytes = packed.to_bytes();
+        let separate: usize = keys.iter().map(|vk| vk.to_bytes().len()).sum();
+        assert!(bytes.len() * 3 < separate);
+
+        let decoded = PackedVks::try_from(&bytes[..]).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        let mut reconstructed: Vec<_> = decoded.keys().collect();
+        reconstructed.sort_by_key(|vk| (vk.max_nu(), vk.sigma()));
+        let mut expected = keys.clone();
+        expected.sort_by_key(|vk| (vk.max_nu(), vk.sigma()));
+        assert_eq!(reconstructed, expected);
+
+        let empty = PackedVks::try_from(&PackedVks::new().to_bytes()[..]).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.keys().count(), 0);
+    }
+
+    #[test]
+    fn invalid_containers_are_rejected() {
+        let vk = VerificationKey::new(&PublicParameters::test_rand(2, &mut test_rng()), 1);
+        let setup = setup_bytes(vk.verifier_setup());
+        let entry = |setup: &[u8], sigmas: &[u64]| {
+            let sigmas = sigmas.iter().map(|&s| Value::Integer(s.into())).collect();
+            Value::Array(Vec::from([
+                Value::Bytes(setup.to_vec()),
+                Value::Array(sigmas),
+            ]))
+        };
+        let mut corrupted = setup.clone();
+        corrupted[10] ^= 1;
+        let mut trailing = setup.clone();
+        trailing.push(0);
+
+        assert!(PackedVks::try_from(&encode(Vec::from([entry(&setup, &[1, 2])]))[..]).is_ok());
+        for bytes in [
+            Vec::from([0x01]),
+            encode(Vec::from([entry(&setup, &[])])),
+            encode(Vec::from([entry(&setup, &[2, 1])])),
+            encode(Vec::from([entry(&setup, &[1, 1])])),
+            encode(Vec::from([entry(&setup, &[3])])),
+            encode(Vec::from([entry(&setup, &[1]), entry(&setup, &[2])])),
+            encode(Vec::from([entry(&corrupted, &[1])])),
+            encode(Vec::from([entry(&trailing, &[1])])),
+        ] {
+            assert!(matches!(
+                PackedVks::try_from(&bytes[..]),
+                Err(VerifyError::InvalidVerificationKey)
+            ));
+        }
+    }
+}
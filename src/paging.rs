@@ -0,0 +1,472 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Paged claims of large query results.
+//!
+//! Instead of carrying every row of the result, a paged claim commits to it through a
+//! Merkle tree over pages of consecutive rows. Consumers can then check single pages
+//! against the root with a [`PageInclusionProof`], without the rest of the result, and
+//! spot-check single rows with a [`RowInclusionProof`].
+
+use alloc::vec::Vec;
+use proof_of_sql::{base::database::OwnedTable, proof_primitive::dory::DoryScalar};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    serde::{ColumnRef, NamedColumn, OwnedTableDef},
+    PublicInput, VerifyError,
+};
+
+/// Prefix of the hashed leaves of the page tree.
+const LEAF_PREFIX: u8 = 0;
+/// Prefix of the hashed inner nodes of the page tree.
+const NODE_PREFIX: u8 = 1;
+
+/// The claim of a paged result: the Merkle root over its pages, together with the shape
+/// of the pages.
+///
+/// Pages hold `rows_per_page` consecutive rows, except for the last one which holds the
+/// remaining ones. A result without rows has a single empty page. The tree is built
+/// bottom up by hashing pairs of nodes, an unpaired last node being moved up as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PagedResult {
+    rows_per_page: u64,
+    num_rows: u64,
+    root: [u8; 32],
+}
+
+/// The sibling nodes linking a page to the root of a [`PagedResult`], from the leaves up.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageInclusionProof {
+    siblings: Vec<[u8; 32]>,
+}
+
+impl PageInclusionProof {
+    /// Returns the sibling nodes, from the leaves up.
+    pub fn siblings(&self) -> &[[u8; 32]] {
+        &self.siblings
+    }
+}
+
+/// The proof that a row is part of a [`PagedResult`]: the page holding the row, and the
+/// proof linking that page to the root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowInclusionProof {
+    #[serde(with = "OwnedTableDef")]
+    page: OwnedTable<DoryScalar>,
+    page_proof: PageInclusionProof,
+}
+
+impl RowInclusionProof {
+    /// Returns the page holding the row.
+    pub fn page(&self) -> &OwnedTable<DoryScalar> {
+        &self.page
+    }
+
+    /// Returns the proof linking the page to the root.
+    pub fn page_proof(&self) -> &PageInclusionProof {
+        &self.page_proof
+    }
+}
+
+impl PagedResult {
+    /// Pages `table` into pages of `rows_per_page` rows and computes the root over them.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if `rows_per_page` is zero.
+    pub fn new(table: &OwnedTable<DoryScalar>, rows_per_page: usize) -> Result<Self, VerifyError> {
+        let leaves = page_leaves(table, rows_per_page)?;
+        Ok(Self {
+            rows_per_page: rows_per_page as u64,
+            num_rows: table.num_rows() as u64,
+            root: merkle_root(leaves),
+        })
+    }
+
+    /// Returns the number of rows of each page but the last.
+    pub fn rows_per_page(&self) -> u64 {
+        self.rows_per_page
+    }
+
+    /// Returns the number of rows of the result.
+    pub fn num_rows(&self) -> u64 {
+        self.num_rows
+    }
+
+    /// Returns the number of pages of the result.
+    pub fn num_pages(&self) -> u64 {
+        self.num_rows.div_ceil(self.rows_per_page.max(1)).max(1)
+    }
+
+    /// Returns the Merkle root over the pages.
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Checks that `page` is the page at `index` of the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidPageInclusion` if the page doesn't have the expected number of rows,
+    /// or is not linked to the root by `proof`.
+    pub fn verify_page(
+        &self,
+        index: u64,
+        page: &OwnedTable<DoryScalar>,
+        proof: &PageInclusionProof,
+    ) -> Result<(), VerifyError> {
+        if self.rows_per_page == 0 || index >= self.num_pages() {
+            return Err(VerifyError::InvalidPageInclusion);
+        }
+        let start = index * self.rows_per_page;
+        let expected_rows = self.num_rows.min(start.saturating_add(self.rows_per_page)) - start;
+        if page.num_rows() as u64 != expected_rows {
+            return Err(VerifyError::InvalidPageInclusion);
+        }
+
+        let mut node = leaf_hash(page)?;
+        let mut siblings = proof.siblings.iter();
+        let (mut index, mut width) = (index, self.num_pages());
+        while width > 1 {
+            if index % 2 == 1 {
+                let sibling = siblings.next().ok_or(VerifyError::InvalidPageInclusion)?;
+                node = node_hash(sibling, &node);
+            } else if index + 1 < width {
+                let sibling = siblings.next().ok_or(VerifyError::InvalidPageInclusion)?;
+                node = node_hash(&node, sibling);
+            }
+            index /= 2;
+            width = width.div_ceil(2);
+        }
+        if siblings.next().is_some() || node != self.root {
+            return Err(VerifyError::InvalidPageInclusion);
+        }
+        Ok(())
+    }
+
+    /// Checks that `row`, a table with a single row, is the row at `index` of the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidPageInclusion` if the page of `proof` is not the page holding the
+    /// row, or doesn't hold `row`, with the same columns in the same order, at `index`.
+    pub fn verify_row(
+        &self,
+        index: u64,
+        row: &OwnedTable<DoryScalar>,
+        proof: &RowInclusionProof,
+    ) -> Result<(), VerifyError> {
+        if self.rows_per_page == 0 || index >= self.num_rows {
+            return Err(VerifyError::InvalidPageInclusion);
+        }
+        self.verify_page(index / self.rows_per_page, &proof.page, &proof.page_proof)?;
+        let offset = (index % self.rows_per_page) as usize;
+        let expected = slice_rows(&proof.page, offset, offset + 1);
+        if row.num_rows() != 1 || !row.inner_table().iter().eq(expected.inner_table()) {
+            return Err(VerifyError::InvalidPageInclusion);
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `row`, a table with a single row, is the row at `index` of the paged
+/// result claimed by `pubs`, see [`PagedResult::verify_row`].
+///
+/// This lets light consumers spot-check rows of large results without the full table.
+/// It only shows that the row is part of the claim, so the proof must have been verified
+/// against `pubs` beforehand.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if the claim of `pubs` is not paged, and
+/// `InvalidPageInclusion` if the row is not part of the claimed result.
+pub fn verify_row_inclusion(
+    pubs: &PublicInput,
+    index: u64,
+    row: &OwnedTable<DoryScalar>,
+    proof: &RowInclusionProof,
+) -> Result<(), VerifyError> {
+    pubs.paged_result()
+        .ok_or(VerifyError::InvalidInput)?
+        .verify_row(index, row, proof)
+}
+
+/// Returns the page at `index` of `table` split into pages of `rows_per_page` rows, or
+/// `None` if there is no such page.
+pub fn result_page(
+    table: &OwnedTable<DoryScalar>,
+    rows_per_page: usize,
+    index: usize,
+) -> Option<OwnedTable<DoryScalar>> {
+    let num_pages = table.num_rows().div_ceil(rows_per_page.max(1)).max(1);
+    (rows_per_page > 0 && index < num_pages).then(|| {
+        let start = index * rows_per_page;
+        slice_rows(table, start, table.num_rows().min(start + rows_per_page))
+    })
+}
+
+/// Builds the proof linking the page at `index` of `table` to the root of its
+/// [`PagedResult`].
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if `rows_per_page` is zero or there is no page at `index`.
+pub fn page_inclusion_proof(
+    table: &OwnedTable<DoryScalar>,
+    rows_per_page: usize,
+    index: usize,
+) -> Result<PageInclusionProof, VerifyError> {
+    let levels = tree_levels(page_leaves(table, rows_per_page)?);
+    if index >= levels[0].len() {
+        return Err(VerifyError::InvalidInput);
+    }
+    let mut siblings = Vec::new();
+    let mut index = index;
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            siblings.push(*sibling);
+        }
+        index /= 2;
+    }
+    Ok(PageInclusionProof { siblings })
+}
+
+/// Builds the proof that the row at `index` of `table` is part of its [`PagedResult`]
+/// over pages of `rows_per_page` rows.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if `rows_per_page` is zero or there is no row at `index`.
+pub fn row_inclusion_proof(
+    table: &OwnedTable<DoryScalar>,
+    rows_per_page: usize,
+    index: usize,
+) -> Result<RowInclusionProof, VerifyError> {
+    if rows_per_page == 0 || index >= table.num_rows() {
+        return Err(VerifyError::InvalidInput);
+    }
+    let page_index = index / rows_per_page;
+    Ok(RowInclusionProof {
+        page: result_page(table, rows_per_page, page_index).ok_or(VerifyError::InvalidInput)?,
+        page_proof: page_inclusion_proof(table, rows_per_page, page_index)?,
+    })
+}
+
+/// Returns the rows of `table` in `start..end`.
+pub(crate) fn slice_rows(
+    table: &OwnedTable<DoryScalar>,
+    start: usize,
+    end: usize,
+) -> OwnedTable<DoryScalar> {
+    OwnedTable::try_from_iter(
+        table
+            .inner_table()
+            .iter()
+            .map(|(name, column)| (*name, column.slice(start, end))),
+    )
+    .expect("slices of a table have columns of equal length")
+}
+
+/// Hashes the pages of `table`.
+fn page_leaves(
+    table: &OwnedTable<DoryScalar>,
+    rows_per_page: usize,
+) -> Result<Vec<[u8; 32]>, VerifyError> {
+    if rows_per_page == 0 {
+        return Err(VerifyError::InvalidInput);
+    }
+    let num_pages = table.num_rows().div_ceil(rows_per_page).max(1);
+    (0..num_pages)
+        .map(|index| {
+            let start = index * rows_per_page;
+            leaf_hash(&slice_rows(
+                table,
+                start,
+                table.num_rows().min(start + rows_per_page),
+            ))
+        })
+        .collect()
+}
+
+/// Builds the levels of the tree over `leaves`, from the leaves up to the root.
+fn tree_levels(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = Vec::from([leaves]);
+    while levels[levels.len() - 1].len() > 1 {
+        let level = levels[levels.len() - 1]
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node_hash(left, right),
+                [node] => *node,
+                _ => unreachable!("chunks hold one or two nodes"),
+            })
+            .collect();
+        levels.push(level);
+    }
+    levels
+}
+
+/// Computes the root of the tree over `leaves`, or zero for a tree without leaves.
+pub(crate) fn merkle_root(leaves: Vec<[u8; 32]>) -> [u8; 32] {
+    tree_levels(leaves)
+        .last()
+        .and_then(|level| level.first().copied())
+        .unwrap_or([0; 32])
+}
+
+/// Hashes a page, i.e. the serialized list of its named columns.
+fn leaf_hash(page: &OwnedTable<DoryScalar>) -> Result<[u8; 32], VerifyError> {
+    let columns: Vec<_> = page
+        .inner_table()
+        .iter()
+        .map(|(name, column)| NamedColumn {
+            name,
+            column: ColumnRef(column),
+        })
+        .collect();
+    let mut bytes = Vec::from([LEAF_PREFIX]);
+    ciborium::into_writer(&columns, &mut bytes).map_err(|_| VerifyError::InvalidInput)?;
+    Ok(Keccak256::digest(bytes).into())
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+#[cfg(test)]
+mod test {
+    use proof_of_sql::base::database::owned_table_utility::*;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn table(num_rows: usize) -> OwnedTable<DoryScalar> {
+        let a: Vec<i64> = (0..num_rows as i64).collect();
+        let b: Vec<_> = a.iter().map(|i| alloc::format!("row {i}")).collect();
+        owned_table([bigint("a", a), varchar("b", b)])
+    }
+
+    #[rstest]
+    #[case::empty(0, 3)]
+    #[case::single_page(2, 3)]
+    #[case::full_pages(6, 3)]
+    #[case::partial_last_page(7, 3)]
+    #[case::odd_page_count(5, 1)]
+    fn every_page_is_included(#[case] num_rows: usize, #[case] rows_per_page: usize) {
+        let table = table(num_rows);
+        let paged = PagedResult::new(&table, rows_per_page).unwrap();
+        assert_eq!(paged.num_rows(), num_rows as u64);
+
+        for index in 0..paged.num_pages() as usize {
+            let page = result_page(&table, rows_per_page, index).unwrap();
+            let proof = page_inclusion_proof(&table, rows_per_page, index).unwrap();
+            assert!(paged.verify_page(index as u64, &page, &proof).is_ok());
+        }
+        let index = paged.num_pages() as usize;
+        assert!(result_page(&table, rows_per_page, index).is_none());
+        assert!(page_inclusion_proof(&table, rows_per_page, index).is_err());
+    }
+
+    #[test]
+    fn single_page_root_is_its_leaf() {
+        let table = table(2);
+        let paged = PagedResult::new(&table, 2).unwrap();
+        assert_eq!(paged.num_pages(), 1);
+        assert_eq!(paged.root(), leaf_hash(&table).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_pages() {
+        let table = table(7);
+        let paged = PagedResult::new(&table, 3).unwrap();
+        let page = result_page(&table, 3, 1).unwrap();
+        let proof = page_inclusion_proof(&table, 3, 1).unwrap();
+        let rejected = |index, page: &OwnedTable<DoryScalar>, proof: &PageInclusionProof| {
+            matches!(
+                paged.verify_page(index, page, proof),
+                Err(VerifyError::InvalidPageInclusion)
+            )
+        };
+
+        assert!(rejected(0, &page, &proof));
+        assert!(rejected(3, &page, &proof));
+        assert!(rejected(1, &result_page(&table, 3, 0).unwrap(), &proof));
+        assert!(rejected(1, &slice_rows(&page, 0, 2), &proof));
+
+        let mut tampered = proof.clone();
+        tampered.siblings[0][0] ^= 1;
+        assert!(rejected(1, &page, &tampered));
+        let mut extended = proof.clone();
+        extended.siblings.push([0; 32]);
+        assert!(rejected(1, &page, &extended));
+    }
+
+    #[test]
+    fn every_row_is_included() {
+        let table = table(7);
+        let paged = PagedResult::new(&table, 3).unwrap();
+
+        for index in 0..7 {
+            let row = slice_rows(&table, index, index + 1);
+            let proof = row_inclusion_proof(&table, 3, index).unwrap();
+            assert!(paged.verify_row(index as u64, &row, &proof).is_ok());
+        }
+        assert!(row_inclusion_proof(&table, 3, 7).is_err());
+        assert!(row_inclusion_proof(&table, 0, 0).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_rows() {
+        let table = table(7);
+        let paged = PagedResult::new(&table, 3).unwrap();
+        let row = slice_rows(&table, 4, 5);
+        let proof = row_inclusion_proof(&table, 3, 4).unwrap();
+        let rejected = |index, row: &OwnedTable<DoryScalar>, proof: &RowInclusionProof| {
+            matches!(
+                paged.verify_row(index, row, proof),
+                Err(VerifyError::InvalidPageInclusion)
+            )
+        };
+
+        assert!(rejected(3, &row, &proof));
+        assert!(rejected(7, &row, &proof));
+        assert!(rejected(1, &slice_rows(&table, 1, 2), &proof));
+        assert!(rejected(4, &slice_rows(&table, 4, 6), &proof));
+        let reordered = owned_table([varchar("b", ["row 4"]), bigint("a", [4])]);
+        assert!(rejected(4, &reordered, &proof));
+
+        let mut tampered = proof.clone();
+        tampered.page = owned_table([
+            bigint("a", [3, 4, 6]),
+            varchar("b", ["row 3", "row 4", "row 5"]),
+        ]);
+        assert!(rejected(4, &row, &tampered));
+    }
+
+    #[test]
+    fn rejects_empty_pages() {
+        assert!(matches!(
+            PagedResult::new(&table(1), 0),
+            Err(VerifyError::InvalidInput)
+        ));
+        assert!(result_page(&table(1), 0, 0).is_none());
+    }
+}
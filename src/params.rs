@@ -0,0 +1,346 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deep validation of Dory setups, to catch corrupted or tampered parameter files
+//! before keys are derived from them or proofs verified with them.
+//!
+//! There is no published ceremony transcript format for the Dory setups of
+//! proof-of-sql, so contribution chains can't be checked here yet. Once the final
+//! output of a ceremony is written the way `PublicParameters::save_to_file` writes
+//! parameters, [`validate_public_parameters`] and [`VerificationKey::new`] take it to
+//! a usable key.
+
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
+use ark_bls12_381::{G1Affine, G2Affine};
+use ark_ec::{AffineRepr, Group};
+use ark_serialize::{CanonicalDeserialize, Compress, Valid, Validate};
+use proof_of_sql::proof_primitive::dory::PublicParameters;
+use snafu::Snafu;
+
+use crate::{Gt, PairingBackend, VerificationKey};
+
+const G1_UNCOMPRESSED_SIZE: usize = 96;
+const G2_UNCOMPRESSED_SIZE: usize = 192;
+
+/// Setups for a larger `max_nu` can't be allocated anyway.
+pub(crate) const MAX_NU_LIMIT: usize = 32;
+
+/// The ways a setup can fail deep validation.
+#[derive(Debug, Snafu)]
+pub enum SetupError {
+    /// The bytes are not a serialized setup.
+    Malformed,
+    /// An element is not a point of the prime-order subgroup of its group.
+    #[snafu(display("{element} is not in the prime-order subgroup"))]
+    NotInSubgroup { element: String },
+    /// An element that must be random is the identity.
+    #[snafu(display("{element} is the identity"))]
+    Identity { element: String },
+    /// A generator repeats an earlier one.
+    #[snafu(display("{element} repeats an earlier generator"))]
+    Duplicate { element: String },
+    /// An element doesn't satisfy the relation binding it to the rest of the setup.
+    #[snafu(display("{element} is inconsistent with the rest of the setup"))]
+    Inconsistent { element: String },
+}
+
+/// Validates public parameters serialized the way `PublicParameters::save_to_file`
+/// writes them.
+///
+/// Every generator must be a point of the prime-order subgroup other than the identity,
+/// and the generators of `Gamma_1`, and of `Gamma_2`, must be pairwise distinct.
+///
+/// # Errors
+///
+/// Returns the first check that fails.
+pub fn validate_public_parameters(bytes: &[u8]) -> Result<PublicParameters, SetupError> {
+    let mut reader = bytes;
+    let max_nu = usize::try_from(read::<u64>(&mut reader, Compress::No)?)
+        .map_err(|_| SetupError::Malformed)?;
+    if max_nu > MAX_NU_LIMIT
+        || bytes.len()
+            != size_of::<u64>()
+                + (1 << max_nu) * (G1_UNCOMPRESSED_SIZE + G2_UNCOMPRESSED_SIZE)
+                + G1_UNCOMPRESSED_SIZE
+                + 2 * G2_UNCOMPRESSED_SIZE
+    {
+        return Err(SetupError::Malformed);
+    }
+
+    let gamma_1: Vec<G1Affine> = read_points(&mut reader, 1 << max_nu)?;
+    let gamma_2: Vec<G2Affine> = read_points(&mut reader, 1 << max_nu)?;
+    check_generators("Gamma_1", &gamma_1)?;
+    check_generators("Gamma_2", &gamma_2)?;
+    let h_1: G1Affine = read(&mut reader, Compress::No)?;
+    let h_2: G2Affine = read(&mut reader, Compress::No)?;
+    let gamma_2_fin: G2Affine = read(&mut reader, Compress::No)?;
+    check_point("H_1", &h_1)?;
+    check_point("H_2", &h_2)?;
+    check_point("Gamma_2_fin", &gamma_2_fin)?;
+
+    PublicParameters::deserialize_with_mode(bytes, Compress::No, Validate::No)
+        .map_err(|_| SetupError::Malformed)
+}
+
+/// Validates a serialized verification key, checking the relations between the
+/// elements of its setup that can be checked without the public parameters.
+///
+/// Beyond the subgroup checks done when decoding a [`VerificationKey`], this checks
+/// that:
+/// - `chi[0]` and `H_T` are the pairings of `Gamma_1_0` and `Gamma_2_0`, and of `H_1`
+///   and `H_2`, computed with the backend `B`,
+/// - `Delta_1L[k]` and `Delta_2L[k]` both equal `chi[k - 1]`, since they pair the same
+///   halves of the generators,
+/// - the elements for `k = 0`, which are unused, are the identity, and the others are
+///   not,
+/// - the key sigma doesn't exceed the setup `max_nu`.
+///
+/// # Errors
+///
+/// Returns the first check that fails.
+pub fn validate_verifier_setup<B: PairingBackend>(
+    bytes: &[u8],
+) -> Result<VerificationKey, SetupError> {
+    let mut reader = bytes;
+    let len = usize::try_from(read::<u64>(&mut &bytes[..], Compress::Yes)?)
+        .map_err(|_| SetupError::Malformed)?;
+    if len == 0
+        || len > MAX_NU_LIMIT + 1
+        || bytes.len() != VerificationKey::serialized_size(len - 1)
+    {
+        return Err(SetupError::Malformed);
+    }
+
+    let mut vectors: Vec<(&str, Vec<Gt>)> = Vec::new();
+    for name in ["Delta_1L", "Delta_1R", "Delta_2L", "Delta_2R", "chi"] {
+        let elements: Vec<Gt> = read(&mut reader, Compress::Yes)?;
+        if elements.len() != len {
+            return Err(SetupError::Malformed);
+        }
+        for (k, element) in elements.iter().enumerate() {
+            element.check().map_err(|_| SetupError::NotInSubgroup {
+                element: format!("{name}[{k}]"),
+            })?;
+            // chi[0] pairs actual generators, every other element for k = 0 is unused
+            if (k == 0 && name != "chi") != is_identity(element) {
+                return Err(if k == 0 {
+                    SetupError::Inconsistent {
+                        element: format!("{name}[0]"),
+                    }
+                } else {
+                    SetupError::Identity {
+                        element: format!("{name}[{k}]"),
+                    }
+                });
+            }
+        }
+        vectors.push((name, elements));
+    }
+    let gamma_1_0: G1Affine = read(&mut reader, Compress::Yes)?;
+    let gamma_2_0: G2Affine = read(&mut reader, Compress::Yes)?;
+    let h_1: G1Affine = read(&mut reader, Compress::Yes)?;
+    let h_2: G2Affine = read(&mut reader, Compress::Yes)?;
+    let h_t: Gt = read(&mut reader, Compress::Yes)?;
+    let gamma_2_fin: G2Affine = read(&mut reader, Compress::Yes)?;
+    let max_nu: u64 = read(&mut reader, Compress::Yes)?;
+    let sigma: u64 = read(&mut reader, Compress::Yes)?;
+    check_point("Gamma_1_0", &gamma_1_0)?;
+    check_point("Gamma_2_0", &gamma_2_0)?;
+    check_point("H_1", &h_1)?;
+    check_point("H_2", &h_2)?;
+    check_point("Gamma_2_fin", &gamma_2_fin)?;
+    h_t.check().map_err(|_| SetupError::NotInSubgroup {
+        element: "H_T".into(),
+    })?;
+
+    let inconsistent = |element: String| SetupError::Inconsistent { element };
+    if max_nu != len as u64 - 1 {
+        return Err(inconsistent("max_nu".into()));
+    }
+    if sigma > max_nu {
+        return Err(inconsistent("sigma".into()));
+    }
+    let (delta_1l, delta_2l, chi) = (&vectors[0].1, &vectors[2].1, &vectors[4].1);
+    for k in 1..len {
+        if delta_1l[k] != chi[k - 1] {
+            return Err(inconsistent(format!("Delta_1L[{k}]")));
+        }
+        if delta_2l[k] != chi[k - 1] {
+            return Err(inconsistent(format!("Delta_2L[{k}]")));
+        }
+    }
+    if chi[0] != B::pairing(gamma_1_0, gamma_2_0) {
+        return Err(inconsistent("chi[0]".into()));
+    }
+    if h_t != B::pairing(h_1, h_2) {
+        return Err(inconsistent("H_T".into()));
+    }
+
+    VerificationKey::try_from(bytes).map_err(|_| SetupError::Malformed)
+}
+
+/// Reads a value without validating it, so that failed checks can be attributed.
+fn read<T: CanonicalDeserialize>(reader: &mut &[u8], compress: Compress) -> Result<T, SetupError> {
+    T::deserialize_with_mode(reader, compress, Validate::No).map_err(|_| SetupError::Malformed)
+}
+
+fn read_points<T: CanonicalDeserialize>(
+    reader: &mut &[u8],
+    count: usize,
+) -> Result<Vec<T>, SetupError> {
+    (0..count).map(|_| read(reader, Compress::No)).collect()
+}
+
+/// Checks that a generator is a point of the prime-order subgroup other than the
+/// identity.
+fn check_point(name: &str, point: &impl AffineRepr) -> Result<(), SetupError> {
+    if point.check().is_err() {
+        return Err(SetupError::NotInSubgroup {
+            element: name.into(),
+        });
+    }
+    if point.is_zero() {
+        return Err(SetupError::Identity {
+            element: name.into(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks every generator of a vector, and that they are pairwise distinct.
+fn check_generators(name: &str, points: &[impl AffineRepr]) -> Result<(), SetupError> {
+    let mut seen = BTreeSet::new();
+    for (i, point) in points.iter().enumerate() {
+        let element = format!("{name}[{i}]");
+        check_point(&element, point)?;
+        let mut bytes = Vec::new();
+        point.serialize_compressed(&mut bytes).unwrap();
+        if !seen.insert(bytes) {
+            return Err(SetupError::Duplicate { element });
+        }
+    }
+    Ok(())
+}
+
+fn is_identity<G: Group>(element: &G) -> bool {
+    element.is_zero()
+}
+
+#[cfg(test)]
+mod test {
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::test_rng;
+
+    use super::*;
+    use crate::Arkworks;
+
+    const GT_SIZE: usize = 576;
+
+    fn params_bytes(max_nu: usize) -> (PublicParameters, Vec<u8>) {
+        let params = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let mut bytes = Vec::new();
+        params.serialize_uncompressed(&mut bytes).unwrap();
+        (params, bytes)
+    }
+
+    /// Returns the offset of the element `k` of the vector `vector` of a key setup.
+    fn gt_offset(max_nu: usize, vector: usize, k: usize) -> usize {
+        vector * (size_of::<u64>() + (max_nu + 1) * GT_SIZE) + size_of::<u64>() + k * GT_SIZE
+    }
+
+    fn assert_rejected<T>(result: Result<T, SetupError>, expected: &str) {
+        let error = result.err().expect("the setup should be rejected");
+        assert_eq!(alloc::string::ToString::to_string(&error), expected);
+    }
+
+    #[test]
+    fn valid_setups() {
+        let (params, bytes) = params_bytes(2);
+        let vk = VerificationKey::new(&params, 1);
+        let validated = validate_public_parameters(&bytes).unwrap();
+        assert_eq!(VerificationKey::new(&validated, 1), vk);
+
+        assert_eq!(
+            validate_verifier_setup::<Arkworks>(&vk.to_bytes()).unwrap(),
+            vk
+        );
+    }
+
+    #[test]
+    fn tampered_public_parameters() {
+        let (_, bytes) = params_bytes(2);
+        let gamma_1 = |i: usize| size_of::<u64>() + i * G1_UNCOMPRESSED_SIZE;
+        let h_1 = gamma_1(4) + 4 * G2_UNCOMPRESSED_SIZE;
+
+        let mut duplicate = bytes.clone();
+        duplicate.copy_within(gamma_1(0)..gamma_1(1), gamma_1(2));
+        assert_rejected(
+            validate_public_parameters(&duplicate),
+            "Gamma_1[2] repeats an earlier generator",
+        );
+
+        let mut identity = bytes.clone();
+        G1Affine::zero()
+            .serialize_uncompressed(&mut identity[h_1..h_1 + G1_UNCOMPRESSED_SIZE])
+            .unwrap();
+        assert_rejected(validate_public_parameters(&identity), "H_1 is the identity");
+
+        let mut off_curve = bytes.clone();
+        off_curve[gamma_1(1) + G1_UNCOMPRESSED_SIZE - 1] ^= 1;
+        assert_rejected(
+            validate_public_parameters(&off_curve),
+            "Gamma_1[1] is not in the prime-order subgroup",
+        );
+
+        assert_rejected(
+            validate_public_parameters(&bytes[..bytes.len() - 1]),
+            "The bytes are not a serialized setup.",
+        );
+    }
+
+    #[test]
+    fn tampered_verifier_setup() {
+        let (params, _) = params_bytes(2);
+        let bytes = VerificationKey::new(&params, 1).to_bytes();
+        let h_t = gt_offset(2, 5, 0) - size_of::<u64>() + 2 * (48 + 96);
+
+        let mut swapped = bytes.clone();
+        swapped.copy_within(gt_offset(2, 4, 2)..gt_offset(2, 4, 3), gt_offset(2, 4, 1));
+        assert_rejected(
+            validate_verifier_setup::<Arkworks>(&swapped),
+            "Delta_1L[2] is inconsistent with the rest of the setup",
+        );
+
+        let mut replaced = bytes.clone();
+        replaced.copy_within(gt_offset(2, 4, 1)..gt_offset(2, 4, 2), h_t);
+        assert_rejected(
+            validate_verifier_setup::<Arkworks>(&replaced),
+            "H_T is inconsistent with the rest of the setup",
+        );
+
+        let mut corrupted = bytes.clone();
+        corrupted[h_t] ^= 1;
+        assert_rejected(
+            validate_verifier_setup::<Arkworks>(&corrupted),
+            "H_T is not in the prime-order subgroup",
+        );
+
+        let sigma_too_large = VerificationKey::new(&params, 3).to_bytes();
+        assert_rejected(
+            validate_verifier_setup::<Arkworks>(&sigma_too_large),
+            "sigma is inconsistent with the rest of the setup",
+        );
+    }
+}
@@ -0,0 +1,646 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use ciborium::Value;
+use core::{cmp::Ordering, hash::BuildHasherDefault};
+use proof_of_sql::{
+    base::{
+        commitment::Commitment,
+        database::{ColumnRef, OwnedColumn, OwnedTable},
+        scalar::Scalar,
+    },
+    proof_primitive::dory::DoryCommitment,
+    sql::proof_plans::DynProofPlan,
+};
+use serde::Serialize;
+
+use crate::{ColumnId, PublicInput, TableId, VerifyError};
+
+/// The kinds of proof plans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlanKind {
+    /// A projection over a table.
+    Projection,
+    /// An aggregation grouped by some columns.
+    GroupBy,
+    /// A filtered projection over a table.
+    Filter,
+}
+
+/// How a verified result must be compared with an expected table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResultComparison {
+    /// Rows must appear in the same order.
+    Ordered,
+    /// Rows may appear in any order, but with the same multiplicity.
+    Unordered,
+}
+
+impl ResultComparison {
+    /// Returns whether two tables are equal under this comparison.
+    ///
+    /// Both comparisons require the same columns, in the same order and with the same types.
+    pub fn tables_equal<S: Scalar>(&self, left: &OwnedTable<S>, right: &OwnedTable<S>) -> bool {
+        match self {
+            ResultComparison::Ordered => left == right,
+            ResultComparison::Unordered => {
+                if left.num_rows() != right.num_rows()
+                    || !left.column_names().eq(right.column_names())
+                {
+                    return false;
+                }
+                match (sort_rows(left), sort_rows(right)) {
+                    (Some(left), Some(right)) => left == right,
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+/// Static information about a proof plan.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanInfo {
+    kind: PlanKind,
+    tables: Vec<TableId>,
+}
+
+impl PlanInfo {
+    /// Analyzes the given proof plan.
+    pub fn new<C: Commitment + Serialize>(plan: &DynProofPlan<C>) -> Self {
+        let kind = match plan {
+            DynProofPlan::Projection(_) => PlanKind::Projection,
+            DynProofPlan::GroupBy(_) => PlanKind::GroupBy,
+            DynProofPlan::Filter(_) => PlanKind::Filter,
+        };
+        let tables: IndexSet<_> = column_references(plan)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|column| TableId::from(column.table_ref()))
+            .collect();
+        Self {
+            kind,
+            tables: tables.into_iter().collect(),
+        }
+    }
+
+    /// Returns the kind of the plan.
+    pub fn kind(&self) -> PlanKind {
+        self.kind
+    }
+
+    /// Returns the tables the plan reads from.
+    pub fn tables(&self) -> &[TableId] {
+        &self.tables
+    }
+
+    /// Returns whether the plan guarantees the order of the result rows.
+    ///
+    /// Grouped results are proven to be sorted by the grouping columns. Projections and
+    /// filters return rows in the order they are stored, which SQL doesn't guarantee.
+    pub fn ordering_guaranteed(&self) -> bool {
+        self.kind == PlanKind::GroupBy
+    }
+
+    /// Returns how results of the plan must be compared with an expected table.
+    pub fn result_comparison(&self) -> ResultComparison {
+        if self.ordering_guaranteed() {
+            ResultComparison::Ordered
+        } else {
+            ResultComparison::Unordered
+        }
+    }
+}
+
+/// Restrictions on the plans a verifier accepts, checked before verification.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlanPolicy {
+    allowed_schemas: Option<Vec<String>>,
+    max_result_columns: Option<usize>,
+    max_varchar_length: Option<usize>,
+    max_decimal_precision: Option<u8>,
+}
+
+impl PlanPolicy {
+    /// Creates a policy accepting any plan.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a policy accepting only plans that read from tables in the given schemas.
+    ///
+    /// Schemas are matched exactly against the canonical, lowercase, schema of each table.
+    pub fn allowed_schemas<S: Into<String>>(schemas: impl IntoIterator<Item = S>) -> Self {
+        Self::new().with_allowed_schemas(schemas)
+    }
+
+    /// Restricts the schemas plans may read from.
+    pub fn with_allowed_schemas<S: Into<String>>(
+        mut self,
+        schemas: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.allowed_schemas = Some(schemas.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Limits the number of columns of claimed results, including the omitted ones.
+    pub fn with_max_result_columns(mut self, max_result_columns: usize) -> Self {
+        self.max_result_columns = Some(max_result_columns);
+        self
+    }
+
+    /// Limits the length, in bytes, of the strings of claimed results.
+    pub fn with_max_varchar_length(mut self, max_varchar_length: usize) -> Self {
+        self.max_varchar_length = Some(max_varchar_length);
+        self
+    }
+
+    /// Limits the precision of the decimal columns of claimed results.
+    pub fn with_max_decimal_precision(mut self, max_decimal_precision: u8) -> Self {
+        self.max_decimal_precision = Some(max_decimal_precision);
+        self
+    }
+
+    /// Returns the schemas plans may read from, if restricted.
+    pub fn schemas(&self) -> Option<&[String]> {
+        self.allowed_schemas.as_deref()
+    }
+
+    /// Returns the maximum number of columns of claimed results, if limited.
+    pub fn max_result_columns(&self) -> Option<usize> {
+        self.max_result_columns
+    }
+
+    /// Returns the maximum length, in bytes, of the strings of claimed results, if limited.
+    pub fn max_varchar_length(&self) -> Option<usize> {
+        self.max_varchar_length
+    }
+
+    /// Returns the maximum precision of the decimal columns of claimed results, if limited.
+    pub fn max_decimal_precision(&self) -> Option<u8> {
+        self.max_decimal_precision
+    }
+
+    /// Checks the result claimed by a public input against the policy, so that
+    /// statements can't be bloated by absurd result shapes.
+    pub fn check_query_data(&self, pubs: &PublicInput) -> Result<(), VerifyError> {
+        let table = pubs.query_data().table.inner_table();
+        let columns = table.len() + pubs.omitted_columns().len();
+        if let Some(max_columns) = self.max_result_columns.filter(|max| columns > *max) {
+            return Err(VerifyError::TooManyResultColumns {
+                columns,
+                max_columns,
+            });
+        }
+        for (name, column) in table {
+            match column {
+                OwnedColumn::VarChar(values) => {
+                    let Some(max_length) = self.max_varchar_length else {
+                        continue;
+                    };
+                    let length = values.iter().map(String::len).max().unwrap_or(0);
+                    if length > max_length {
+                        return Err(VerifyError::VarCharTooLong {
+                            column: ColumnId::from(*name),
+                            length,
+                            max_length,
+                        });
+                    }
+                }
+                OwnedColumn::Decimal75(precision, _, _) => {
+                    let precision = precision.value();
+                    if let Some(max_precision) =
+                        self.max_decimal_precision.filter(|max| precision > *max)
+                    {
+                        return Err(VerifyError::DecimalPrecisionTooLarge {
+                            column: ColumnId::from(*name),
+                            precision,
+                            max_precision,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a plan against the policy.
+    pub fn check(&self, info: &PlanInfo) -> Result<(), VerifyError> {
+        if let Some(allowed_schemas) = &self.allowed_schemas {
+            for table in info.tables() {
+                let schema = table.schema();
+                if !allowed_schemas.iter().any(|s| s == schema) {
+                    return Err(VerifyError::SchemaNotAllowed {
+                        table: table.clone(),
+                        schema: schema.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Limits on the size of the plans a verifier accepts.
+///
+/// Plans are recursive, and verifying them recurses over their structure, so a deeply
+/// nested plan could exhaust the stack. Limits are measured on the encoding of the
+/// plan, without recursion: every map, array and value counts as a node, and the depth
+/// is the nesting of maps and arrays. Each nested expression adds two levels, one for
+/// its kind and one for its fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PlanLimits {
+    max_depth: usize,
+    max_nodes: usize,
+}
+
+impl Default for PlanLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl PlanLimits {
+    /// The limits enforced unless configured otherwise, which leave room for dozens of
+    /// nested expressions.
+    pub const DEFAULT: Self = Self::new(128, 1 << 16);
+
+    /// Creates limits on the depth and the number of nodes of plans.
+    pub const fn new(max_depth: usize, max_nodes: usize) -> Self {
+        Self {
+            max_depth,
+            max_nodes,
+        }
+    }
+
+    /// Returns the maximum depth of a plan.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Returns the maximum number of nodes of a plan.
+    pub fn max_nodes(&self) -> usize {
+        self.max_nodes
+    }
+
+    /// Checks a plan against the limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PlanTooComplex` if the plan is deeper, or has more nodes, than allowed.
+    pub fn check(&self, plan: &DynProofPlan<DoryCommitment>) -> Result<(), VerifyError> {
+        let mut nodes = 0;
+        walk_plan(plan, |_, depth| {
+            nodes += 1;
+            if depth > self.max_depth || nodes > self.max_nodes {
+                return Err(VerifyError::PlanTooComplex);
+            }
+            Ok(())
+        })
+    }
+}
+
+type IndexSet<T> = indexmap::IndexSet<T, BuildHasherDefault<ahash::AHasher>>;
+
+/// Walks the encoding of a plan depth-first, in field order, calling `visit` on every
+/// map, array and value with its depth.
+///
+/// The crate analyzes plans through this walk, which uses an explicit stack, rather than
+/// through the recursive traversals of proof-of-sql, so that hostile plans can't
+/// overflow the stack. Encoding the plan recurses, but only as deep as decoding it did,
+/// which is bounded.
+pub(crate) fn walk_plan<C: Commitment + Serialize>(
+    plan: &DynProofPlan<C>,
+    mut visit: impl FnMut(&Value, usize) -> Result<(), VerifyError>,
+) -> Result<(), VerifyError> {
+    let encoding = Value::serialized(plan).map_err(|_| VerifyError::InvalidInput)?;
+    let mut pending = vec![(&encoding, 1)];
+    while let Some((value, depth)) = pending.pop() {
+        visit(value, depth)?;
+        // Children are pushed in reverse, so that they are popped in order
+        match value {
+            Value::Array(items) => {
+                pending.extend(items.iter().rev().map(|item| (item, depth + 1)));
+            }
+            Value::Map(entries) => pending.extend(
+                entries
+                    .iter()
+                    .rev()
+                    .flat_map(|(key, value)| [(value, depth + 1), (key, depth + 1)]),
+            ),
+            Value::Tag(_, value) => pending.push((value, depth + 1)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Returns the columns a plan references, in the order they first appear, like
+/// `ProofPlan::get_column_references` but without recursion.
+pub(crate) fn column_references<C: Commitment + Serialize>(
+    plan: &DynProofPlan<C>,
+) -> Result<Vec<ColumnRef>, VerifyError> {
+    let mut columns = IndexSet::default();
+    walk_plan(plan, |value, _| {
+        for (key, value) in value.as_map().into_iter().flatten() {
+            if key.as_text() == Some("column_ref") {
+                columns.insert(
+                    value
+                        .deserialized()
+                        .map_err(|_| VerifyError::InvalidInput)?,
+                );
+            }
+        }
+        Ok(())
+    })?;
+    Ok(columns.into_iter().collect())
+}
+
+/// Returns a copy of `table` with its rows sorted, or `None` if a column type is not supported.
+fn sort_rows<S: Scalar>(table: &OwnedTable<S>) -> Option<OwnedTable<S>> {
+    let columns: Vec<_> = table.inner_table().values().collect();
+    let mut indexes: Vec<usize> = (0..table.num_rows()).collect();
+    indexes.sort_by(|&i, &j| {
+        columns
+            .iter()
+            .map(|column| compare_values(column, i, j))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    let sorted = table
+        .inner_table()
+        .iter()
+        .map(|(name, column)| Some((*name, select_rows(column, &indexes)?)))
+        .collect::<Option<Vec<_>>>()?;
+    OwnedTable::try_from_iter(sorted).ok()
+}
+
+/// Compares the values at rows `i` and `j` of `column`.
+fn compare_values<S: Scalar>(column: &OwnedColumn<S>, i: usize, j: usize) -> Ordering {
+    match column {
+        OwnedColumn::Boolean(values) => values[i].cmp(&values[j]),
+        OwnedColumn::TinyInt(values) => values[i].cmp(&values[j]),
+        OwnedColumn::SmallInt(values) => values[i].cmp(&values[j]),
+        OwnedColumn::Int(values) => values[i].cmp(&values[j]),
+        OwnedColumn::BigInt(values) => values[i].cmp(&values[j]),
+        OwnedColumn::VarChar(values) => values[i].cmp(&values[j]),
+        OwnedColumn::Int128(values) => values[i].cmp(&values[j]),
+        OwnedColumn::Decimal75(_, _, values) | OwnedColumn::Scalar(values) => {
+            values[i].cmp(&values[j])
+        }
+        OwnedColumn::TimestampTZ(_, _, values) => values[i].cmp(&values[j]),
+        _ => Ordering::Equal,
+    }
+}
+
+/// Builds a column from the rows of `column` at the given indexes.
+fn select_rows<S: Scalar>(column: &OwnedColumn<S>, indexes: &[usize]) -> Option<OwnedColumn<S>> {
+    fn select<T: Clone>(values: &[T], indexes: &[usize]) -> Vec<T> {
+        indexes.iter().map(|&i| values[i].clone()).collect()
+    }
+    Some(match column {
+        OwnedColumn::Boolean(values) => OwnedColumn::Boolean(select(values, indexes)),
+        OwnedColumn::TinyInt(values) => OwnedColumn::TinyInt(select(values, indexes)),
+        OwnedColumn::SmallInt(values) => OwnedColumn::SmallInt(select(values, indexes)),
+        OwnedColumn::Int(values) => OwnedColumn::Int(select(values, indexes)),
+        OwnedColumn::BigInt(values) => OwnedColumn::BigInt(select(values, indexes)),
+        OwnedColumn::VarChar(values) => OwnedColumn::VarChar(select(values, indexes)),
+        OwnedColumn::Int128(values) => OwnedColumn::Int128(select(values, indexes)),
+        OwnedColumn::Decimal75(precision, scale, values) => {
+            OwnedColumn::Decimal75(*precision, *scale, select(values, indexes))
+        }
+        OwnedColumn::Scalar(values) => OwnedColumn::Scalar(select(values, indexes)),
+        OwnedColumn::TimestampTZ(unit, zone, values) => {
+            OwnedColumn::TimestampTZ(*unit, *zone, select(values, indexes))
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::{
+        base::database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+        proof_primitive::dory::{
+            DoryCommitment, DoryEvaluationProof, DoryProverPublicSetup, DoryScalar, ProverSetup,
+            PublicParameters,
+        },
+        sql::{parse::QueryExpr, proof::QueryData},
+    };
+    use rstest::*;
+
+    use super::*;
+
+    fn plan_info(query: &str) -> PlanInfo {
+        plan_info_for_table("sxt.table", query)
+    }
+
+    fn plan_info_for_table(table: &str, query: &str) -> PlanInfo {
+        PlanInfo::new(&plan_for_table(table, query))
+    }
+
+    fn plan_for_table(table: &str, query: &str) -> DynProofPlan<DoryCommitment> {
+        let public_parameters = PublicParameters::test_rand(2, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 2);
+        let mut accessor =
+            OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+        accessor.add_table(
+            table.parse().unwrap(),
+            owned_table([bigint("a", [1, 2, 3, 2]), bigint("b", [1, 1, 2, 2])]),
+            0,
+        );
+        let query = QueryExpr::<DoryCommitment>::try_new(
+            query.parse().unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        // Plans can't be cloned, but can be copied through their encoding
+        Value::serialized(query.proof_expr())
+            .unwrap()
+            .deserialized()
+            .unwrap()
+    }
+
+    /// Wraps the where clause of an encoded filter plan in `levels` negations.
+    fn nest_where_clause(plan: &mut Value, levels: usize) {
+        let filter = &mut plan.as_map_mut().unwrap()[0].1;
+        let (_, clause) = filter
+            .as_map_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some("where_clause"))
+            .unwrap();
+        for _ in 0..levels {
+            let expr = core::mem::replace(clause, Value::Null);
+            *clause = Value::Map(vec![(
+                Value::Text("Not".into()),
+                Value::Map(vec![(Value::Text("expr".into()), expr)]),
+            )]);
+        }
+    }
+
+    #[rstest]
+    #[case::no_where_clause("SELECT a FROM table", PlanKind::Filter, false)]
+    #[case::filter("SELECT a FROM table WHERE b = 1", PlanKind::Filter, false)]
+    #[case::group_by(
+        "SELECT b, SUM(a) AS s, COUNT(*) AS c FROM table GROUP BY b",
+        PlanKind::GroupBy,
+        true
+    )]
+    fn plan_ordering(#[case] query: &str, #[case] kind: PlanKind, #[case] ordered: bool) {
+        let info = plan_info(query);
+        assert_eq!(info.kind(), kind);
+        assert_eq!(info.tables(), &["sxt.table".parse::<TableId>().unwrap()]);
+        assert_eq!(info.ordering_guaranteed(), ordered);
+    }
+
+    #[test]
+    fn allowed_schemas() {
+        let policy = PlanPolicy::allowed_schemas(["sxt", "other"]);
+        assert!(policy.check(&plan_info("SELECT a FROM table")).is_ok());
+        assert!(PlanPolicy::new()
+            .check(&plan_info_for_table(
+                "evil.table",
+                "SELECT a FROM evil.table"
+            ))
+            .is_ok());
+
+        let result = policy.check(&plan_info_for_table(
+            "evil.table",
+            "SELECT a FROM evil.table",
+        ));
+        assert!(matches!(
+            result,
+            Err(VerifyError::SchemaNotAllowed { table, schema })
+                if table == "evil.table" && schema == "evil"
+        ));
+    }
+
+    #[test]
+    fn result_shape_limits() {
+        let pubs = PublicInput::new(
+            &plan_for_table("sxt.table", "SELECT a FROM table"),
+            Default::default(),
+            QueryData {
+                table: owned_table([
+                    bigint("a", [1, 2]),
+                    varchar("b", ["x", "long"]),
+                    decimal75("c", 12, 2, [1, 2]),
+                ]),
+                verification_hash: [0; 32],
+            },
+        );
+        let policy = PlanPolicy::new()
+            .with_max_result_columns(3)
+            .with_max_varchar_length(4)
+            .with_max_decimal_precision(12);
+        assert!(PlanPolicy::new().check_query_data(&pubs).is_ok());
+        assert!(policy.check_query_data(&pubs).is_ok());
+        assert_eq!(policy.max_result_columns(), Some(3));
+
+        assert!(matches!(
+            policy
+                .clone()
+                .with_max_result_columns(2)
+                .check_query_data(&pubs),
+            Err(VerifyError::TooManyResultColumns {
+                columns: 3,
+                max_columns: 2
+            })
+        ));
+        assert!(matches!(
+            policy
+                .clone()
+                .with_max_varchar_length(3)
+                .check_query_data(&pubs),
+            Err(VerifyError::VarCharTooLong { column, length: 4, max_length: 3 }) if column == "b"
+        ));
+        assert!(matches!(
+            policy
+                .clone()
+                .with_max_decimal_precision(11)
+                .check_query_data(&pubs),
+            Err(VerifyError::DecimalPrecisionTooLarge { column, precision: 12, max_precision: 11 })
+                if column == "c"
+        ));
+        // Omitted columns still count
+        let omitted = pubs
+            .omit_columns(["b".parse::<ColumnId>().unwrap()])
+            .unwrap();
+        assert!(matches!(
+            policy.with_max_result_columns(2).check_query_data(&omitted),
+            Err(VerifyError::TooManyResultColumns { columns: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn unordered_comparison_ignores_row_order() {
+        let left: OwnedTable<DoryScalar> =
+            owned_table([bigint("a", [1, 2, 2]), varchar("b", ["x", "y", "z"])]);
+        let shuffled = owned_table([bigint("a", [2, 1, 2]), varchar("b", ["z", "x", "y"])]);
+        let different = owned_table([bigint("a", [2, 1, 2]), varchar("b", ["y", "x", "y"])]);
+
+        assert!(ResultComparison::Unordered.tables_equal(&left, &shuffled));
+        assert!(!ResultComparison::Ordered.tables_equal(&left, &shuffled));
+        assert!(!ResultComparison::Unordered.tables_equal(&left, &different));
+    }
+
+    #[test]
+    fn comparisons_require_same_columns() {
+        let left: OwnedTable<DoryScalar> = owned_table([bigint("a", [1]), bigint("b", [2])]);
+        let swapped = owned_table([bigint("b", [2]), bigint("a", [1])]);
+
+        assert!(!ResultComparison::Unordered.tables_equal(&left, &swapped));
+        assert!(!ResultComparison::Ordered.tables_equal(&left, &swapped));
+    }
+
+    #[rstest]
+    #[case::sample(0, PlanLimits::DEFAULT, true)]
+    #[case::nested(20, PlanLimits::DEFAULT, true)]
+    #[case::too_deep(100, PlanLimits::DEFAULT, false)]
+    #[case::lower_max_depth(20, PlanLimits::new(32, 1 << 16), false)]
+    #[case::lower_max_nodes(0, PlanLimits::new(128, 16), false)]
+    fn plan_limits(#[case] levels: usize, #[case] limits: PlanLimits, #[case] accepted: bool) {
+        let plan = plan_for_table("sxt.table", "SELECT a FROM table WHERE b = 1");
+        let mut encoding = Value::serialized(&plan).unwrap();
+        nest_where_clause(&mut encoding, levels);
+        let plan: DynProofPlan<DoryCommitment> = encoding.deserialized().unwrap();
+
+        let result = limits.check(&plan);
+        assert_eq!(result.is_ok(), accepted);
+        assert!(result.is_ok() || matches!(result, Err(VerifyError::PlanTooComplex)));
+    }
+
+    #[rstest]
+    #[case::projection("SELECT b, a FROM table")]
+    #[case::filter("SELECT a FROM table WHERE b = 1 AND NOT a = b")]
+    #[case::group_by("SELECT b, SUM(a) AS s, COUNT(*) AS c FROM table WHERE a > 1 GROUP BY b")]
+    fn column_references_match_upstream(#[case] query: &str) {
+        use proof_of_sql::sql::proof::ProofPlan;
+
+        let plan = plan_for_table("sxt.table", query);
+        let expected: Vec<_> = plan.get_column_references().into_iter().collect();
+        assert_eq!(column_references(&plan).unwrap(), expected);
+    }
+}
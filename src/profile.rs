@@ -0,0 +1,405 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use blake2::{digest::consts::U32, Blake2b};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+
+use crate::{Proof, PublicInput, VerificationKey, VerifyError};
+
+/// The kinds of artifacts exchanged with a verifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArtifactKind {
+    /// A [`Proof`].
+    Proof,
+    /// A [`PublicInput`].
+    PublicInput,
+    /// A [`VerificationKey`].
+    VerificationKey,
+}
+
+impl ArtifactKind {
+    /// The error reported when an artifact of this kind cannot be decoded.
+    fn decode_error(&self) -> VerifyError {
+        match self {
+            ArtifactKind::Proof => VerifyError::InvalidProofData,
+            ArtifactKind::PublicInput => VerifyError::InvalidInput,
+            ArtifactKind::VerificationKey => VerifyError::InvalidVerificationKey,
+        }
+    }
+}
+
+/// The outer encoding applied to the canonical artifact bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// The canonical bytes as they are.
+    Raw,
+    /// The canonical bytes ABI-encoded as a Solidity `bytes` value.
+    AbiBytes,
+}
+
+impl Codec {
+    /// Wraps canonical artifact bytes.
+    fn wrap(&self, bytes: Vec<u8>) -> Vec<u8> {
+        match self {
+            Codec::Raw => bytes,
+            Codec::AbiBytes => {
+                let padding = (32 - bytes.len() % 32) % 32;
+                let mut out = Vec::with_capacity(64 + bytes.len() + padding);
+                out.extend_from_slice(&abi_word(32));
+                out.extend_from_slice(&abi_word(bytes.len()));
+                out.extend_from_slice(&bytes);
+                out.resize(out.len() + padding, 0);
+                out
+            }
+        }
+    }
+
+    /// Unwraps canonical artifact bytes, rejecting non-canonical encodings.
+    fn unwrap<'a>(&self, bytes: &'a [u8]) -> Option<&'a [u8]> {
+        match self {
+            Codec::Raw => Some(bytes),
+            Codec::AbiBytes => {
+                let (offset, rest) = bytes.split_first_chunk::<32>()?;
+                let (len, rest) = rest.split_first_chunk::<32>()?;
+                if offset != &abi_word(32) || len[..24].iter().any(|b| *b != 0) {
+                    return None;
+                }
+                let len = usize::try_from(u64::from_be_bytes(len[24..].try_into().ok()?)).ok()?;
+                let padding = (32 - len % 32) % 32;
+                if rest.len() != len.checked_add(padding)? {
+                    return None;
+                }
+                let (data, padding) = rest.split_at(len);
+                padding.iter().all(|b| *b == 0).then_some(data)
+            }
+        }
+    }
+}
+
+/// Encodes `value` as a 32 bytes big-endian ABI word.
+fn abi_word(value: usize) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+/// The hash functions used to compute artifact digests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HashFunction {
+    /// Keccak-256, as used by Ethereum and zkVerify.
+    Keccak256,
+    /// SHA-256.
+    Sha256,
+    /// BLAKE2b with a 256-bit output, as used by Substrate chains.
+    Blake2b256,
+}
+
+impl HashFunction {
+    /// Hashes the concatenation of `parts`.
+    pub fn hash(&self, parts: &[&[u8]]) -> [u8; 32] {
+        fn digest<D: Digest>(parts: &[&[u8]]) -> [u8; 32] {
+            let mut hasher = D::new();
+            for part in parts {
+                hasher.update(part);
+            }
+            hasher.finalize().as_slice().try_into().unwrap()
+        }
+        match self {
+            HashFunction::Keccak256 => digest::<Keccak256>(parts),
+            HashFunction::Sha256 => digest::<Sha256>(parts),
+            HashFunction::Blake2b256 => digest::<Blake2b<U32>>(parts),
+        }
+    }
+}
+
+/// Serialization profiles bundling the settings required by a target chain, so that
+/// integrators select a single profile instead of configuring each knob separately.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// Artifacts submitted to zkVerify.
+    ZkVerify,
+    /// Artifacts passed as calldata to EVM contracts.
+    EvmCalldata,
+    /// Artifacts exchanged off-chain, without size limits.
+    #[default]
+    Generic,
+}
+
+impl Profile {
+    /// Returns the outer encoding of the profile.
+    pub fn codec(&self) -> Codec {
+        match self {
+            Profile::ZkVerify | Profile::Generic => Codec::Raw,
+            Profile::EvmCalldata => Codec::AbiBytes,
+        }
+    }
+
+    /// Returns the hash function of the profile.
+    pub fn hash_function(&self) -> HashFunction {
+        match self {
+            Profile::ZkVerify | Profile::EvmCalldata => HashFunction::Keccak256,
+            Profile::Generic => HashFunction::Sha256,
+        }
+    }
+
+    /// Returns the domain separation tag prepended to hashed artifacts.
+    pub fn domain_tag(&self) -> &'static [u8] {
+        match self {
+            Profile::ZkVerify => b"proof-of-sql-verifier/zkverify",
+            Profile::EvmCalldata => b"proof-of-sql-verifier/evm-calldata",
+            Profile::Generic => b"proof-of-sql-verifier/generic",
+        }
+    }
+
+    /// Returns the maximum size in bytes of an encoded artifact.
+    pub fn max_size(&self, kind: ArtifactKind) -> usize {
+        match (self, kind) {
+            (Profile::ZkVerify, ArtifactKind::VerificationKey) => 64 * 1024,
+            (Profile::ZkVerify, _) => 1024 * 1024,
+            // Transactions larger than 128 KiB are not relayed by the reference client
+            (Profile::EvmCalldata, _) => 128 * 1024,
+            (Profile::Generic, _) => usize::MAX,
+        }
+    }
+
+    fn check_size(&self, kind: ArtifactKind, size: usize) -> Result<(), VerifyError> {
+        let max_size = self.max_size(kind);
+        if size > max_size {
+            return Err(VerifyError::ArtifactTooLarge { size, max_size });
+        }
+        Ok(())
+    }
+}
+
+/// An artifact that can be encoded, decoded and hashed according to a [`Profile`].
+pub trait Artifact: Sized {
+    /// The kind of the artifact.
+    const KIND: ArtifactKind;
+
+    /// Serializes the artifact in its canonical form.
+    fn to_canonical_bytes(&self) -> Result<Vec<u8>, VerifyError>;
+
+    /// Deserializes the artifact from its canonical form.
+    fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, VerifyError>;
+
+    /// Encodes the artifact for the given profile.
+    fn encode_for(&self, profile: Profile) -> Result<Vec<u8>, VerifyError> {
+        let bytes = profile.codec().wrap(self.to_canonical_bytes()?);
+        profile.check_size(Self::KIND, bytes.len())?;
+        Ok(bytes)
+    }
+
+    /// Decodes an artifact encoded for the given profile.
+    fn decode_for(bytes: &[u8], profile: Profile) -> Result<Self, VerifyError> {
+        profile.check_size(Self::KIND, bytes.len())?;
+        let bytes = profile
+            .codec()
+            .unwrap(bytes)
+            .ok_or(Self::KIND.decode_error())?;
+        Self::from_canonical_bytes(bytes)
+    }
+
+    /// Computes the domain-separated digest of the artifact for the given profile.
+    fn hash_for(&self, profile: Profile) -> Result<[u8; 32], VerifyError> {
+        self.hash_with(profile.hash_function(), profile.domain_tag())
+    }
+
+    /// Computes the digest of the artifact with an arbitrary hash function and domain
+    /// tag, e.g. to reproduce digests of chains without a dedicated [`Profile`].
+    ///
+    /// The hashed data is the length of the tag as a big endian `u32`, the tag, the
+    /// artifact kind and the canonical encoding, so that no tag can be confused with
+    /// another tag followed by an artifact kind.
+    fn hash_with(
+        &self,
+        function: HashFunction,
+        domain_tag: &[u8],
+    ) -> Result<[u8; 32], VerifyError> {
+        let bytes = self.to_canonical_bytes()?;
+        let tag_len = u32::try_from(domain_tag.len())
+            .map_err(|_| VerifyError::InvalidInput)?
+            .to_be_bytes();
+        Ok(function.hash(&[&tag_len, domain_tag, &[Self::KIND as u8], &bytes]))
+    }
+}
+
+impl Artifact for Proof {
+    const KIND: ArtifactKind = ArtifactKind::Proof;
+
+    fn to_canonical_bytes(&self) -> Result<Vec<u8>, VerifyError> {
+        Ok(self.to_bytes())
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, VerifyError> {
+        Self::try_from(bytes)
+    }
+}
+
+impl Artifact for PublicInput {
+    const KIND: ArtifactKind = ArtifactKind::PublicInput;
+
+    fn to_canonical_bytes(&self) -> Result<Vec<u8>, VerifyError> {
+        self.try_to_bytes()
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, VerifyError> {
+        Self::try_from(bytes)
+    }
+}
+
+impl Artifact for VerificationKey {
+    const KIND: ArtifactKind = ArtifactKind::VerificationKey;
+
+    fn to_canonical_bytes(&self) -> Result<Vec<u8>, VerifyError> {
+        Ok(self.to_bytes())
+    }
+
+    fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, VerifyError> {
+        Self::try_from(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::proof_primitive::dory::PublicParameters;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case::empty(0)]
+    #[case::partial_word(5)]
+    #[case::full_word(32)]
+    #[case::many_words(100)]
+    fn abi_bytes_roundtrip(#[case] len: usize) {
+        let data: Vec<u8> = (0..len as u8).collect();
+        let encoded = Codec::AbiBytes.wrap(data.clone());
+        assert_eq!(encoded.len() % 32, 0);
+        assert_eq!(Codec::AbiBytes.unwrap(&encoded), Some(&data[..]));
+    }
+
+    #[test]
+    fn abi_bytes_rejects_non_canonical_encodings() {
+        let encoded = Codec::AbiBytes.wrap(vec![1, 2, 3]);
+
+        let mut bad_offset = encoded.clone();
+        bad_offset[31] = 64;
+        assert_eq!(Codec::AbiBytes.unwrap(&bad_offset), None);
+
+        let mut dirty_padding = encoded.clone();
+        *dirty_padding.last_mut().unwrap() = 1;
+        assert_eq!(Codec::AbiBytes.unwrap(&dirty_padding), None);
+
+        assert_eq!(Codec::AbiBytes.unwrap(&encoded[..encoded.len() - 1]), None);
+        assert_eq!(Codec::AbiBytes.unwrap(&[]), None);
+    }
+
+    #[rstest]
+    fn verification_key_roundtrip(
+        #[values(Profile::ZkVerify, Profile::EvmCalldata, Profile::Generic)] profile: Profile,
+    ) {
+        let vk = VerificationKey::new(&PublicParameters::test_rand(1, &mut test_rng()), 1);
+        let bytes = vk.encode_for(profile).unwrap();
+        let decoded = VerificationKey::decode_for(&bytes, profile).unwrap();
+        assert_eq!(decoded.to_bytes(), vk.to_bytes());
+        assert_eq!(
+            decoded.hash_for(profile).unwrap(),
+            vk.hash_for(profile).unwrap()
+        );
+    }
+
+    #[test]
+    fn hashes_are_domain_separated() {
+        let vk = VerificationKey::new(&PublicParameters::test_rand(1, &mut test_rng()), 1);
+        assert_ne!(
+            vk.hash_for(Profile::ZkVerify).unwrap(),
+            vk.hash_for(Profile::EvmCalldata).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case::keccak(
+        HashFunction::Keccak256,
+        "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+    )]
+    #[case::sha256(
+        HashFunction::Sha256,
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    )]
+    #[case::blake2b(
+        HashFunction::Blake2b256,
+        "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+    )]
+    fn hash_functions_match_reference_digests(
+        #[case] function: HashFunction,
+        #[case] expected: &str,
+    ) {
+        let digest: String = function
+            .hash(&[b"a", b"bc"])
+            .iter()
+            .map(|byte| alloc::format!("{byte:02x}"))
+            .collect();
+        assert_eq!(digest, expected);
+    }
+
+    #[test]
+    fn hash_with_matches_profiles() {
+        let vk = VerificationKey::new(&PublicParameters::test_rand(1, &mut test_rng()), 1);
+        assert_eq!(
+            vk.hash_with(HashFunction::Keccak256, Profile::ZkVerify.domain_tag())
+                .unwrap(),
+            vk.hash_for(Profile::ZkVerify).unwrap()
+        );
+    }
+
+    #[test]
+    fn domain_tags_are_length_prefixed() {
+        // Without the length prefix, the longer tag would hash like the shorter one
+        // followed by the artifact kind
+        let vk = VerificationKey::new(&PublicParameters::test_rand(1, &mut test_rng()), 1);
+        let tag = b"tag";
+        let longer_tag = [&tag[..], &[ArtifactKind::VerificationKey as u8]].concat();
+        assert_ne!(
+            vk.hash_with(HashFunction::Keccak256, tag).unwrap(),
+            vk.hash_with(HashFunction::Keccak256, &longer_tag).unwrap()
+        );
+        let bytes = vk.to_canonical_bytes().unwrap();
+        assert_eq!(
+            vk.hash_with(HashFunction::Keccak256, tag).unwrap(),
+            HashFunction::Keccak256.hash(&[
+                &3_u32.to_be_bytes(),
+                tag,
+                &[ArtifactKind::VerificationKey as u8],
+                &bytes
+            ])
+        );
+    }
+
+    #[test]
+    fn size_limits_are_enforced() {
+        let max_size = Profile::EvmCalldata.max_size(ArtifactKind::Proof);
+        assert!(matches!(
+            Proof::decode_for(&vec![0; max_size + 1], Profile::EvmCalldata),
+            Err(VerifyError::ArtifactTooLarge { size, max_size: max }) if size == max_size + 1 && max == max_size
+        ));
+        assert!(matches!(
+            Proof::decode_for(&vec![0; max_size + 1], Profile::Generic),
+            Err(VerifyError::InvalidProofData)
+        ));
+    }
+}
@@ -14,10 +14,17 @@
 // limitations under the License.
 
 use alloc::vec::Vec;
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
 use proof_of_sql::proof_primitive::dory::DoryEvaluationProof;
 use proof_of_sql::sql::proof::VerifiableQueryResult;
 
-use crate::VerifyError;
+use serde::Serialize;
+use sha3::{Digest, Keccak256};
+
+use crate::{debug::ShortDigest, serde::cbor_serialized_size, VerifyError};
 
 /// Represents a Dory proof.
 ///
@@ -27,11 +34,41 @@ use crate::VerifyError;
 /// # Fields
 ///
 /// * `proof` - A `VerifiableQueryResult<DoryEvaluationProof>` containing the actual proof data.
+///
+/// Equality and hashing are defined over the serialized proof, and `Debug` only prints
+/// its size and a digest prefix.
+///
+/// Decoding only accepts the canonical encoding of a proof, so a proof has a single
+/// valid byte representation and can be deduplicated by the hash of its bytes.
 #[derive(Clone)]
 pub struct Proof {
     proof: VerifiableQueryResult<DoryEvaluationProof>,
 }
 
+impl PartialEq for Proof {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for Proof {}
+
+impl Hash for Proof {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state);
+    }
+}
+
+impl fmt::Debug for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.to_bytes();
+        f.debug_struct("Proof")
+            .field("size", &bytes.len())
+            .field("digest", &ShortDigest(Keccak256::digest(&bytes).into()))
+            .finish()
+    }
+}
+
 impl TryFrom<&[u8]> for Proof {
     type Error = VerifyError;
 
@@ -44,10 +81,18 @@ impl TryFrom<&[u8]> for Proof {
     /// # Returns
     ///
     /// * `Result<Self, Self::Error>` - A DoryProof if deserialization succeeds, or a VerifyError if it fails.
+    ///
+    /// Non-reduced scalars and points with invalid flags are rejected with
+    /// `InvalidProofData`. Encodings that decode to a valid proof but differ from its
+    /// canonical encoding, e.g. with trailing bytes after a group element or
+    /// non-minimal CBOR lengths, are rejected with `NonCanonicalProofEncoding`.
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let proof = ciborium::from_reader(value).map_err(|_| VerifyError::InvalidProofData)?;
-
-        Ok(Self::new(proof))
+        let proof =
+            Self::new(ciborium::from_reader(value).map_err(|_| VerifyError::InvalidProofData)?);
+        if proof.to_bytes() != value {
+            return Err(VerifyError::NonCanonicalProofEncoding);
+        }
+        Ok(proof)
     }
 }
 
@@ -76,6 +121,37 @@ impl Proof {
         result
     }
 
+    /// Computes the exact size of the serialized proof without allocating it.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The length of the byte vector that `to_bytes` would return.
+    pub fn serialized_size(&self) -> usize {
+        cbor_serialized_size(&self.proof).unwrap()
+    }
+
+    /// Computes the size of each component of the serialized proof, e.g. to find out
+    /// what dominates the size of a large proof.
+    ///
+    /// # Returns
+    ///
+    /// * `ProofSizeBreakdown` - The sizes, whose total is `serialized_size`.
+    pub fn size_breakdown(&self) -> ProofSizeBreakdown {
+        let mut breakdown = ProofSizeBreakdown::default();
+        if let Some(result) = &self.proof.provable_result {
+            breakdown.result = component_size(result);
+        }
+        if let Some(proof) = &self.proof.proof {
+            breakdown.bit_distributions = component_size(&proof.bit_distributions);
+            breakdown.commitments = component_size(&proof.commitments);
+            breakdown.sumcheck = component_size(&proof.sumcheck_proof);
+            breakdown.evaluations = component_size(&proof.pcs_proof_evaluations);
+            breakdown.opening = component_size(&proof.evaluation_proof);
+        }
+        breakdown.framing = self.serialized_size() - breakdown.components();
+        breakdown
+    }
+
     /// Converts the DoryProof into a VerifiableQueryResult<DoryEvaluationProof>.
     ///
     /// # Returns
@@ -85,3 +161,209 @@ impl Proof {
         &self.proof
     }
 }
+
+/// Computes the serialized size of a proof component, which serializes infallibly like
+/// the whole proof.
+fn component_size<T: Serialize>(value: &T) -> usize {
+    cbor_serialized_size(value).unwrap()
+}
+
+/// The size in bytes of each component of a serialized [`Proof`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ProofSizeBreakdown {
+    /// The provable result, i.e. the result payload embedded in the proof.
+    pub result: usize,
+    /// The bit distributions of the columns decomposed by the prover.
+    pub bit_distributions: usize,
+    /// The commitments to the intermediate columns computed by the prover.
+    pub commitments: usize,
+    /// The sumcheck messages.
+    pub sumcheck: usize,
+    /// The evaluations of the committed columns opened by the PCS.
+    pub evaluations: usize,
+    /// The Dory opening proof of the evaluations.
+    pub opening: usize,
+    /// The CBOR headers and field names around the components.
+    pub framing: usize,
+}
+
+impl ProofSizeBreakdown {
+    /// Returns the total size of the proof, i.e. its `serialized_size`.
+    pub fn total(&self) -> usize {
+        self.components() + self.framing
+    }
+
+    /// Returns the size of the components, without the framing.
+    fn components(&self) -> usize {
+        self.result
+            + self.bit_distributions
+            + self.commitments
+            + self.sumcheck
+            + self.evaluations
+            + self.opening
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use ciborium::Value;
+    use proof_of_sql::{
+        base::database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+        proof_primitive::dory::{DoryProverPublicSetup, ProverSetup, PublicParameters},
+        sql::parse::QueryExpr,
+    };
+    use rstest::rstest;
+
+    use super::*;
+
+    /// The BLS12-381 scalar field modulus, little-endian, i.e. a non-reduced encoding of 0.
+    const SCALAR_MODULUS: [u8; 32] = [
+        0x01, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0x02, 0xa4, 0xbd,
+        0x53, 0x05, 0xd8, 0xa1, 0x09, 0x08, 0xd8, 0x39, 0x33, 0x48, 0x7d, 0x9d, 0x29, 0x53, 0xa7,
+        0xed, 0x73,
+    ];
+
+    fn build_proof() -> Vec<u8> {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let mut accessor =
+            OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+        accessor.add_table(
+            "sxt.table".parse().unwrap(),
+            owned_table([bigint("a", [1, 2, 3, 2])]),
+            0,
+        );
+        let query = QueryExpr::try_new(
+            "SELECT a FROM table WHERE a = 2".parse().unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        Proof::new(VerifiableQueryResult::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        ))
+        .to_bytes()
+    }
+
+    /// Returns the value of `key` in a CBOR map.
+    fn field<'a>(value: &'a mut Value, key: &str) -> &'a mut Value {
+        let map = value.as_map_mut().unwrap();
+        &mut map
+            .iter_mut()
+            .find(|(k, _)| k.as_text() == Some(key))
+            .unwrap()
+            .1
+    }
+
+    /// Applies `mutate` to the inner query proof of a serialized proof.
+    fn mutate_proof(bytes: &[u8], mutate: impl FnOnce(&mut Value)) -> Vec<u8> {
+        let mut value: Value = ciborium::from_reader(bytes).unwrap();
+        mutate(field(&mut value, "proof"));
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+        bytes
+    }
+
+    /// Applies `mutate` to the bytes of a group element or scalar encoding, which
+    /// serde represents as an array of integers.
+    fn mutate_bytes(value: &mut Value, mutate: impl FnOnce(&mut Vec<u8>)) {
+        let mut bytes: Vec<u8> = value.deserialized().unwrap();
+        mutate(&mut bytes);
+        *value = Value::serialized(&bytes).unwrap();
+    }
+
+    /// Returns the encoding of the first scalar evaluation of the proof.
+    fn first_evaluation(proof: &mut Value) -> &mut Value {
+        &mut field(proof, "pcs_proof_evaluations")
+            .as_array_mut()
+            .unwrap()[0]
+    }
+
+    #[test]
+    fn canonical_proof_round_trips() {
+        let bytes = build_proof();
+        let proof = Proof::try_from(&bytes[..]).unwrap();
+
+        assert_eq!(proof.to_bytes(), bytes);
+        assert_eq!(mutate_proof(&bytes, |_| ()), bytes);
+    }
+
+    #[test]
+    fn size_breakdown_adds_up() {
+        let bytes = build_proof();
+        let breakdown = Proof::try_from(&bytes[..]).unwrap().size_breakdown();
+
+        assert_eq!(breakdown.total(), bytes.len());
+        assert!(breakdown.result > 0);
+        assert!(breakdown.sumcheck > 0);
+        assert!(breakdown.opening > breakdown.evaluations);
+        // Two map headers and seven field names, each with a one byte header
+        assert_eq!(breakdown.framing, 2 + 7 + 99);
+    }
+
+    #[test]
+    fn non_reduced_scalar_is_rejected() {
+        let bytes = mutate_proof(&build_proof(), |proof| {
+            mutate_bytes(first_evaluation(proof), |bytes| {
+                *bytes = SCALAR_MODULUS.to_vec();
+            });
+        });
+
+        assert!(matches!(
+            Proof::try_from(&bytes[..]),
+            Err(VerifyError::InvalidProofData)
+        ));
+    }
+
+    #[rstest]
+    #[case::compression_flag(0x80, false)]
+    // arkworks decodes any point with the infinity flag as the identity, ignoring the
+    // other bytes, so only the canonical encoding check catches this one
+    #[case::infinity_flag(0x40, true)]
+    fn point_with_invalid_flags_is_rejected(#[case] flag: u8, #[case] decodes: bool) {
+        let bytes = mutate_proof(&build_proof(), |proof| {
+            mutate_bytes(field(proof, "evaluation_proof"), |messages| {
+                // The scalar messages come first, each group prefixed by its count
+                let scalars = u64::from_le_bytes(messages[..8].try_into().unwrap());
+                let g1_start = 8 + 32 * usize::try_from(scalars).unwrap();
+                assert_ne!(messages[g1_start..g1_start + 8], [0; 8], "no G1 message");
+                messages[g1_start + 8] ^= flag;
+            });
+        });
+
+        match Proof::try_from(&bytes[..]) {
+            Err(VerifyError::NonCanonicalProofEncoding) => assert!(decodes),
+            Err(VerifyError::InvalidProofData) => assert!(!decodes),
+            result => panic!("unexpected result {result:?}"),
+        }
+    }
+
+    #[test]
+    fn trailing_bytes_after_scalar_are_rejected() {
+        let bytes = mutate_proof(&build_proof(), |proof| {
+            mutate_bytes(first_evaluation(proof), |bytes| bytes.push(0));
+        });
+
+        assert!(matches!(
+            Proof::try_from(&bytes[..]),
+            Err(VerifyError::NonCanonicalProofEncoding)
+        ));
+    }
+
+    #[test]
+    fn non_minimal_cbor_length_is_rejected() {
+        let bytes = build_proof();
+        // Encodes the length of the top-level map on an extra byte
+        assert_eq!(bytes[0], 0xa2);
+        let bytes = [&[0xb8, 0x02], &bytes[1..]].concat();
+
+        assert!(matches!(
+            Proof::try_from(&bytes[..]),
+            Err(VerifyError::NonCanonicalProofEncoding)
+        ));
+    }
+}
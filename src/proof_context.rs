@@ -0,0 +1,256 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{string::ToString, vec::Vec};
+use core::{fmt, str::FromStr};
+use proof_of_sql::{proof_primitive::dory::DoryEvaluationProof, sql::proof::VerifiableQueryResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{Proof, VerifyError};
+
+/// The proof version of the prover releases this verifier can verify proofs of.
+pub const SUPPORTED_PROVER_VERSION: SemVer = SemVer::new(0, 28, 0);
+
+/// The commitment schemes a proof can be produced with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ProofScheme {
+    /// The Dory polynomial commitment scheme over BLS12-381.
+    Dory,
+}
+
+/// A semantic version, e.g. of the prover release that produced a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct SemVer {
+    /// The major version.
+    pub major: u64,
+    /// The minor version.
+    pub minor: u64,
+    /// The patch version.
+    pub patch: u64,
+}
+
+impl SemVer {
+    /// Creates a new version.
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Returns whether proofs of both versions share the same format, i.e. whether they
+    /// have the same major version, and the same minor version before 1.0.
+    pub fn is_compatible_with(&self, other: &SemVer) -> bool {
+        self.major == other.major && (self.major != 0 || self.minor == other.minor)
+    }
+}
+
+impl fmt::Display for SemVer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for SemVer {
+    type Err = VerifyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.').map(|part| part.parse::<u64>());
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) => {
+                Ok(Self::new(major, minor, patch))
+            }
+            _ => Err(VerifyError::InvalidInput),
+        }
+    }
+}
+
+/// A proof bundled with the scheme and prover release that produced it, so that relayed
+/// proofs keep that context.
+///
+/// Decoding fails for proofs of a scheme or prover version this verifier doesn't support.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofWithContext {
+    scheme: ProofScheme,
+    prover_version: SemVer,
+    created_at: u64,
+    proof: Proof,
+}
+
+/// Wire representation of a [`ProofWithContext`].
+#[derive(Serialize, Deserialize)]
+struct ProofWithContextRepr<P> {
+    scheme: ProofScheme,
+    prover_version: SemVer,
+    created_at: u64,
+    proof: P,
+}
+
+impl TryFrom<&[u8]> for ProofWithContext {
+    type Error = VerifyError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let repr: ProofWithContextRepr<VerifiableQueryResult<DoryEvaluationProof>> =
+            ciborium::from_reader(value).map_err(|_| VerifyError::InvalidProofData)?;
+        if !repr
+            .prover_version
+            .is_compatible_with(&SUPPORTED_PROVER_VERSION)
+        {
+            return Err(VerifyError::UnsupportedProverVersion {
+                version: repr.prover_version.to_string(),
+            });
+        }
+        let context = Self {
+            scheme: repr.scheme,
+            prover_version: repr.prover_version,
+            created_at: repr.created_at,
+            proof: Proof::new(repr.proof),
+        };
+        // Like bare proofs, enveloped proofs only have a single valid encoding
+        if context.to_bytes() != value {
+            return Err(VerifyError::NonCanonicalProofEncoding);
+        }
+        Ok(context)
+    }
+}
+
+impl ProofWithContext {
+    /// Bundles a Dory proof with the version of the prover that produced it and its
+    /// creation time, in seconds since the Unix epoch.
+    pub fn new(proof: Proof, prover_version: SemVer, created_at: u64) -> Self {
+        Self {
+            scheme: ProofScheme::Dory,
+            prover_version,
+            created_at,
+            proof,
+        }
+    }
+
+    /// Returns the scheme the proof was produced with.
+    pub fn scheme(&self) -> ProofScheme {
+        self.scheme
+    }
+
+    /// Returns the version of the prover that produced the proof.
+    pub fn prover_version(&self) -> SemVer {
+        self.prover_version
+    }
+
+    /// Returns the creation time of the proof, in seconds since the Unix epoch.
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    /// Returns a reference to the proof.
+    pub fn proof(&self) -> &Proof {
+        &self.proof
+    }
+
+    /// Returns the proof, dropping its context.
+    pub fn into_proof(self) -> Proof {
+        self.proof
+    }
+
+    /// Converts the proof and its context into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let repr = ProofWithContextRepr {
+            scheme: self.scheme,
+            prover_version: self.prover_version,
+            created_at: self.created_at,
+            proof: self.proof.inner(),
+        };
+        let mut result = Vec::new();
+        ciborium::into_writer(&repr, &mut result).unwrap();
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn proof_with_version(prover_version: SemVer) -> ProofWithContext {
+        ProofWithContext::new(
+            Proof::new(Default::default()),
+            prover_version,
+            1_700_000_000,
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let proof = proof_with_version(SemVer::new(0, 28, 10));
+        let decoded = ProofWithContext::try_from(&proof.to_bytes()[..]).unwrap();
+
+        assert_eq!(decoded, proof);
+        assert_eq!(decoded.scheme(), ProofScheme::Dory);
+        assert_eq!(decoded.prover_version().to_string(), "0.28.10");
+        assert_eq!(decoded.created_at(), 1_700_000_000);
+    }
+
+    #[rstest]
+    #[case(SemVer::new(0, 27, 3))]
+    #[case(SemVer::new(0, 29, 0))]
+    #[case(SemVer::new(1, 28, 0))]
+    fn incompatible_prover_is_rejected(#[case] prover_version: SemVer) {
+        let bytes = proof_with_version(prover_version).to_bytes();
+
+        assert!(matches!(
+            ProofWithContext::try_from(&bytes[..]),
+            Err(VerifyError::UnsupportedProverVersion { version }) if version == prover_version.to_string()
+        ));
+    }
+
+    #[test]
+    fn unknown_scheme_is_rejected() {
+        let mut value: ciborium::Value =
+            ciborium::from_reader(&proof_with_version(SUPPORTED_PROVER_VERSION).to_bytes()[..])
+                .unwrap();
+        value.as_map_mut().unwrap()[0].1 = ciborium::Value::Text("Hyrax".into());
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+
+        assert!(matches!(
+            ProofWithContext::try_from(&bytes[..]),
+            Err(VerifyError::InvalidProofData)
+        ));
+    }
+
+    #[test]
+    fn non_canonical_envelope_is_rejected() {
+        let bytes = proof_with_version(SUPPORTED_PROVER_VERSION).to_bytes();
+        // Encodes the length of the top-level map on an extra byte
+        assert_eq!(bytes[0], 0xa4);
+        let bytes = [&[0xb8, 0x04], &bytes[1..]].concat();
+
+        assert!(matches!(
+            ProofWithContext::try_from(&bytes[..]),
+            Err(VerifyError::NonCanonicalProofEncoding)
+        ));
+    }
+
+    #[rstest]
+    #[case("0.28.10", Some(SemVer::new(0, 28, 10)))]
+    #[case("1.2", None)]
+    #[case("1.2.3.4", None)]
+    #[case("1.x.3", None)]
+    fn parse_semver(#[case] s: &str, #[case] expected: Option<SemVer>) {
+        assert_eq!(s.parse::<SemVer>().ok(), expected);
+    }
+}
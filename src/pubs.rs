@@ -13,34 +13,249 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use alloc::vec::Vec;
+use alloc::{format, string::ToString, vec::Vec};
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
 use proof_of_sql::{
-    base::commitment::QueryCommitments,
+    base::{
+        commitment::QueryCommitments,
+        database::{OwnedColumn, OwnedTable, TableRef},
+    },
     proof_primitive::dory::{DoryCommitment, DoryScalar},
     sql::{proof::QueryData, proof_plans::DynProofPlan},
 };
+use proof_of_sql_parser::Identifier;
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    commitments::{
+        collect_commitments, table_commitment_digest, validate_commitments,
+        UncheckedTableCommitment,
+    },
+    debug::ShortDigest,
+    paging::slice_rows,
+    plan::column_references,
+    serde::{ColumnRef, HashingWriter, NamedColumn, QueryDataDef},
+    ColumnId, CommitmentCache, IdentifierPolicy, PagedResult, PlanInfo, PlanLimits, RenderLimits,
+    Statement, TableId, VerifierConfig, VerifyError,
+};
+
+/// Versions of the statement hash format.
+///
+/// Older versions are kept so that statements can still be hashed the way contracts
+/// deployed against them expect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+pub enum HashVersion {
+    /// The Keccak-256 digest of the serialized public input.
+    V1,
+    /// The Keccak-256 digest of the serialized public input, prefixed by a domain tag.
+    V2,
+    /// The Keccak-256 digest of the serialized public input, prefixed by a domain tag,
+    /// the table commitments being serialized in canonical order.
+    V3,
+}
+
+impl HashVersion {
+    /// The version used for new deployments.
+    pub const LATEST: Self = HashVersion::V3;
+}
+
+/// The domain tag prepended to the serialized public input by [`HashVersion::V2`].
+const STATEMENT_HASH_V2_TAG: &[u8] = b"proof-of-sql-verifier/statement/v2";
+
+/// The domain tag prepended to the serialized public input by [`HashVersion::V3`].
+const STATEMENT_HASH_V3_TAG: &[u8] = b"proof-of-sql-verifier/statement/v3";
+
+/// The domain tag prepended to the serialized plan by [`PublicInput::plan_fingerprint`].
+const PLAN_FINGERPRINT_TAG: &[u8] = b"proof-of-sql-verifier/plan/v1";
+
+/// Computes the digest standing for an omitted result column, i.e. the Keccak-256
+/// digest of the serialized column together with its name.
+pub fn column_digest(
+    name: &Identifier,
+    column: &OwnedColumn<DoryScalar>,
+) -> Result<[u8; 32], VerifyError> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(
+        &NamedColumn {
+            name,
+            column: ColumnRef(column),
+        },
+        &mut bytes,
+    )
+    .map_err(|_| VerifyError::InvalidInput)?;
+    Ok(Keccak256::digest(bytes).into())
+}
+
+/// Lists the tables read by the plan of a serialized public input, in plan order.
+///
+/// Only the plan is decoded, and checked against the default [`PlanLimits`]: table
+/// commitments are skipped without being decompressed or validated, and the result is
+/// skipped without being decoded. Routing layers can thus dispatch submissions by table
+/// before paying for a full decode, which may still reject them.
+pub fn peek_referenced_tables(bytes: &[u8]) -> Result<Vec<TableId>, VerifyError> {
+    Ok(PlanInfo::new(&peek_plan(bytes)?).tables().to_vec())
+}
+
+/// Lists the columns read by the plan of a serialized public input, with their table, in
+/// plan order.
+///
+/// Like [`peek_referenced_tables`], only the plan is decoded.
+pub fn peek_referenced_columns(bytes: &[u8]) -> Result<Vec<(TableId, ColumnId)>, VerifyError> {
+    Ok(column_references(&peek_plan(bytes)?)?
+        .into_iter()
+        .map(|column| (column.table_ref().into(), column.column_id().into()))
+        .collect())
+}
 
-use crate::{serde::QueryDataDef, VerifyError};
+/// Decodes the plan of a serialized public input only.
+fn peek_plan(bytes: &[u8]) -> Result<DynProofPlan<DoryCommitment>, VerifyError> {
+    let PlanRepr { expr } = ciborium::from_reader(bytes).map_err(|_| VerifyError::InvalidInput)?;
+    PlanLimits::DEFAULT.check(&expr)?;
+    Ok(expr)
+}
 
 /// Represents the public input for a Dory proof.
 ///
 /// This structure encapsulates the necessary public information required
 /// for verifying a Dory proof, including the proof expression, commitments,
 /// and query data.
+///
+/// The query data may claim only a subset of the result columns, in which case the
+/// omitted ones are replaced by their [`column_digest`]. It may also claim no rows at
+/// all, the rows being committed to by a [`PagedResult`] instead.
+///
+/// Tables the plan doesn't read from may be bound by the [`table_commitment_digest`]
+/// of their commitment only, keeping the public input small, see
+/// [`PublicInput::digest_unreferenced_tables`]. Tables the plan reads from always need
+/// their full commitment.
+///
+/// Table commitments and digests are serialized in canonical order, sorted by table
+/// reference, so that the same logical statement always has the same encoding and
+/// [`HashVersion::V3`] hash. Older hash versions keep hashing the commitments in the order
+/// they were inserted in.
+///
+/// Equality and hashing are defined over the serialized public input, and `Debug`
+/// only prints the committed tables and the statement hash.
 #[derive(Serialize, Deserialize)]
+#[serde(try_from = "PublicInputRepr")]
 pub struct PublicInput {
     expr: DynProofPlan<DoryCommitment>,
+    #[serde(serialize_with = "crate::serde::serialize_sorted_commitments")]
     commitments: QueryCommitments<DoryCommitment>,
     #[serde(with = "QueryDataDef")]
     query_data: QueryData<DoryScalar>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    omitted_columns: Vec<(ColumnId, [u8; 32])>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    paged_result: Option<PagedResult>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    table_digests: Vec<(TableRef, [u8; 32])>,
+}
+
+/// Wire representation of a [`PublicInput`].
+///
+/// Table commitments are kept as a list of entries, so that a table appearing
+/// more than once is reported instead of silently overwriting the previous entry.
+/// They are also kept undecoded, so that their decompression, which dominates
+/// decoding time, can be spread across threads or skipped for cached commitments.
+#[derive(Deserialize)]
+struct PublicInputRepr {
+    expr: DynProofPlan<DoryCommitment>,
+    #[serde(deserialize_with = "crate::serde::deserialize_entries")]
+    commitments: Vec<(TableRef, UncheckedTableCommitment)>,
+    #[serde(with = "QueryDataDef")]
+    query_data: QueryData<DoryScalar>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    omitted_columns: Vec<(ColumnId, [u8; 32])>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    paged_result: Option<PagedResult>,
+    #[serde(default)]
+    table_digests: Vec<(TableRef, [u8; 32])>,
+}
+
+/// A [`PublicInput`] serialized with its table commitments in insertion order, as hashed
+/// by [`HashVersion::V1`] and [`HashVersion::V2`], which predate the canonical order.
+#[derive(Serialize)]
+struct InsertionOrdered<'a> {
+    expr: &'a DynProofPlan<DoryCommitment>,
+    commitments: &'a QueryCommitments<DoryCommitment>,
+    #[serde(with = "QueryDataDef")]
+    query_data: &'a QueryData<DoryScalar>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    omitted_columns: &'a Vec<(ColumnId, [u8; 32])>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paged_result: &'a Option<PagedResult>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    table_digests: &'a Vec<(TableRef, [u8; 32])>,
+}
+
+/// The plan of a [`PublicInputRepr`], the other fields being skipped when decoding.
+#[derive(Deserialize)]
+struct PlanRepr {
+    expr: DynProofPlan<DoryCommitment>,
+}
+
+impl TryFrom<PublicInputRepr> for PublicInput {
+    type Error = VerifyError;
+
+    fn try_from(repr: PublicInputRepr) -> Result<Self, VerifyError> {
+        PublicInput::from_repr(repr, None, PlanLimits::DEFAULT)
+    }
+}
+
+/// Public inputs are compared by their serialization, where table commitments are in
+/// canonical order. Equal public inputs may thus have different [`HashVersion::V1`] and
+/// [`HashVersion::V2`] statement hashes, which depend on the insertion order: maps and
+/// deduplication keyed on public inputs should use [`HashVersion::V3`].
+impl PartialEq for PublicInput {
+    fn eq(&self, other: &Self) -> bool {
+        self.try_to_bytes().ok() == other.try_to_bytes().ok()
+    }
+}
+
+impl Eq for PublicInput {}
+
+impl Hash for PublicInput {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.try_to_bytes().ok().hash(state);
+    }
+}
+
+impl fmt::Debug for PublicInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limits = RenderLimits::DEFAULT;
+        let mut tables: Vec<_> = self
+            .commitments
+            .keys()
+            .take(limits.max_cells)
+            .map(ToString::to_string)
+            .collect();
+        if self.commitments.len() > limits.max_cells {
+            tables.push(format!(
+                "...(+{} tables)",
+                self.commitments.len() - limits.max_cells
+            ));
+        }
+        f.debug_struct("PublicInput")
+            .field("tables", &tables)
+            .field(
+                "hash",
+                &self.hash(HashVersion::LATEST).ok().map(ShortDigest),
+            )
+            .finish()
+    }
 }
 
 impl TryFrom<&[u8]> for PublicInput {
     type Error = VerifyError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, VerifyError> {
-        PublicInput::try_from_bytes(bytes).map_err(|_| VerifyError::InvalidInput)
+        PublicInput::try_from_bytes(bytes)
     }
 }
 
@@ -69,7 +284,98 @@ impl PublicInput {
             expr,
             commitments,
             query_data,
+            omitted_columns: Vec::new(),
+            paged_result: None,
+            table_digests: Vec::new(),
+        }
+    }
+
+    /// Removes the named columns from the claimed result, keeping only their digests.
+    ///
+    /// The proof then only attests the remaining columns, plus the omitted ones
+    /// matching their digests.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if a name is not a claimed result column.
+    pub fn omit_columns(
+        mut self,
+        names: impl IntoIterator<Item = impl Into<ColumnId>>,
+    ) -> Result<Self, VerifyError> {
+        let mut table = self.query_data.table.into_inner();
+        for name in names {
+            let name = name.into();
+            let identifier = Identifier::from(name);
+            let column = table
+                .shift_remove(&identifier)
+                .ok_or(VerifyError::InvalidInput)?;
+            let digest = column_digest(&identifier, &column)?;
+            self.omitted_columns.push((name, digest));
+        }
+        self.query_data.table =
+            OwnedTable::try_new(table).map_err(|_| VerifyError::InvalidInput)?;
+        Ok(self)
+    }
+
+    /// Returns the names and digests of the result columns omitted from the claim.
+    pub fn omitted_columns(&self) -> &[(ColumnId, [u8; 32])] {
+        &self.omitted_columns
+    }
+
+    /// Replaces the claimed rows with a [`PagedResult`] over pages of `rows_per_page`
+    /// rows, keeping only the columns of the claimed result.
+    ///
+    /// This keeps the public input small for large results. Pages can then be checked
+    /// individually with [`PagedResult::verify_page`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if `rows_per_page` is zero, or the claim is already paged
+    /// or omits columns.
+    pub fn page_result(mut self, rows_per_page: usize) -> Result<Self, VerifyError> {
+        if self.paged_result.is_some() || !self.omitted_columns.is_empty() {
+            return Err(VerifyError::InvalidInput);
         }
+        let table = &self.query_data.table;
+        self.paged_result = Some(PagedResult::new(table, rows_per_page)?);
+        self.query_data.table = slice_rows(table, 0, 0);
+        Ok(self)
+    }
+
+    /// Returns the paged claim of the result rows, if the claim is paged.
+    pub fn paged_result(&self) -> Option<&PagedResult> {
+        self.paged_result.as_ref()
+    }
+
+    /// Replaces the commitments of the tables the plan doesn't read from by their
+    /// [`table_commitment_digest`], keeping the public input small while still binding
+    /// those tables.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if a commitment can't be serialized.
+    pub fn digest_unreferenced_tables(mut self) -> Result<Self, VerifyError> {
+        let referenced = PlanInfo::new(&self.expr);
+        let unreferenced: Vec<_> = self
+            .commitments
+            .keys()
+            .filter(|table_ref| !referenced.tables().contains(&TableId::from(**table_ref)))
+            .copied()
+            .collect();
+        for table_ref in unreferenced {
+            let commitment = self.commitments.shift_remove(&table_ref).unwrap();
+            self.table_digests
+                .push((table_ref, table_commitment_digest(&commitment)?));
+        }
+        self.table_digests
+            .sort_by_key(|(table_ref, _)| table_key(table_ref));
+        Ok(self)
+    }
+
+    /// Returns the tables bound by the digest of their commitment only, with those
+    /// digests, sorted by table reference.
+    pub fn table_digests(&self) -> &[(TableRef, [u8; 32])] {
+        &self.table_digests
     }
 
     /// Returns a reference to the proof expression.
@@ -87,6 +393,37 @@ impl PublicInput {
         &self.query_data
     }
 
+    /// Converts the public input into a [`Statement`], dropping the claimed result.
+    pub fn into_statement(self) -> Statement {
+        Statement::from_parts(self.expr, self.commitments)
+    }
+
+    /// Analyzes the proof plan of the public input.
+    pub fn plan_info(&self) -> PlanInfo {
+        PlanInfo::new(&self.expr)
+    }
+
+    /// Computes the Keccak-256 digest of the tag `proof-of-sql-verifier/plan/v1` followed
+    /// by the serialized proof plan.
+    ///
+    /// The fingerprint identifies the query shape only: statements running the same plan
+    /// over other table contents, or claiming other results, share it.
+    pub fn plan_fingerprint(&self) -> Result<[u8; 32], VerifyError> {
+        let mut hasher = Keccak256::new();
+        hasher.update(PLAN_FINGERPRINT_TAG);
+        ciborium::into_writer(&self.expr, HashingWriter(&mut hasher))
+            .map_err(|_| VerifyError::InvalidInput)?;
+        Ok(hasher.finalize().into())
+    }
+
+    /// Returns whether the claimed result matches `expected`, comparing rows in order
+    /// only when the plan guarantees the result ordering.
+    pub fn result_matches(&self, expected: &OwnedTable<DoryScalar>) -> bool {
+        self.plan_info()
+            .result_comparison()
+            .tables_equal(&self.query_data.table, expected)
+    }
+
     /// Converts the public input into a byte array.
     pub fn try_to_bytes(&self) -> Result<Vec<u8>, VerifyError> {
         let mut result = Vec::new();
@@ -94,12 +431,209 @@ impl PublicInput {
         Ok(result)
     }
 
+    /// Computes the statement hash in the given format version.
+    pub fn hash(&self, version: HashVersion) -> Result<[u8; 32], VerifyError> {
+        match version {
+            HashVersion::V1 => self.hash_v1(),
+            HashVersion::V2 => self.hash_v2(),
+            HashVersion::V3 => self.hash_v3(),
+        }
+    }
+
+    /// Computes the [`HashVersion::V1`] statement hash.
+    ///
+    /// The table commitments are hashed in the order they were inserted in, so the hash
+    /// of a statement depends on how it was built or decoded, and may differ between
+    /// public inputs that are equal.
+    pub fn hash_v1(&self) -> Result<[u8; 32], VerifyError> {
+        hash_with_prefix(&[], &self.insertion_ordered())
+    }
+
+    /// Computes the [`HashVersion::V2`] statement hash.
+    ///
+    /// The table commitments are hashed in the order they were inserted in, so the hash
+    /// of a statement depends on how it was built or decoded, and may differ between
+    /// public inputs that are equal.
+    pub fn hash_v2(&self) -> Result<[u8; 32], VerifyError> {
+        hash_with_prefix(STATEMENT_HASH_V2_TAG, &self.insertion_ordered())
+    }
+
+    /// Computes the [`HashVersion::V3`] statement hash.
+    pub fn hash_v3(&self) -> Result<[u8; 32], VerifyError> {
+        hash_with_prefix(STATEMENT_HASH_V3_TAG, self)
+    }
+
+    /// Returns the public input serialized the way [`HashVersion::V1`] and
+    /// [`HashVersion::V2`] hash it.
+    fn insertion_ordered(&self) -> InsertionOrdered<'_> {
+        InsertionOrdered {
+            expr: &self.expr,
+            commitments: &self.commitments,
+            query_data: &self.query_data,
+            omitted_columns: &self.omitted_columns,
+            paged_result: &self.paged_result,
+            table_digests: &self.table_digests,
+        }
+    }
+
+    /// Serializes the public input as canonical JSON, see [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785).
+    ///
+    /// Integers outside the range exactly representable by IEEE 754 doubles are written
+    /// as strings of decimal digits instead of numbers.
+    #[cfg(feature = "json")]
+    pub fn to_canonical_json(&self) -> Result<Vec<u8>, VerifyError> {
+        crate::json::to_canonical_json(self)
+    }
+
+    /// Computes the statement hash for JSON profiles, i.e. the Keccak-256 digest of the
+    /// canonical JSON serialization of the public input.
+    #[cfg(feature = "json")]
+    pub fn json_hash(&self) -> Result<[u8; 32], VerifyError> {
+        Ok(Keccak256::digest(self.to_canonical_json()?).into())
+    }
+
+    /// Computes the exact size of the serialized public input without allocating it.
+    pub fn serialized_size(&self) -> Result<usize, VerifyError> {
+        crate::serde::cbor_serialized_size(self).map_err(|_| VerifyError::InvalidInput)
+    }
+
+    /// Converts a byte array into a `DoryPublicInput` instance, enforcing the given
+    /// identifier policy.
+    ///
+    /// With [`IdentifierPolicy::Strict`], inputs containing identifiers that are not in
+    /// canonical lowercase form are rejected with `NonCanonicalIdentifier`.
+    pub fn try_from_bytes_with_policy(
+        bytes: &[u8],
+        policy: IdentifierPolicy,
+    ) -> Result<Self, VerifyError> {
+        Self::try_from_bytes_with(bytes, policy, PlanLimits::DEFAULT)
+    }
+
+    /// Converts a byte array into a `DoryPublicInput` instance, enforcing the identifier
+    /// policy and the plan limits of `config`.
+    pub fn try_from_bytes_with_config(
+        bytes: &[u8],
+        config: &VerifierConfig,
+    ) -> Result<Self, VerifyError> {
+        Self::try_from_bytes_with(bytes, config.identifier_policy(), config.plan_limits())
+    }
+
+    fn try_from_bytes_with(
+        bytes: &[u8],
+        policy: IdentifierPolicy,
+        limits: PlanLimits,
+    ) -> Result<Self, VerifyError> {
+        let repr: PublicInputRepr =
+            ciborium::from_reader(bytes).map_err(|_| VerifyError::InvalidInput)?;
+        let pubs = Self::from_repr(repr, None, limits)?;
+        if policy == IdentifierPolicy::Strict {
+            let raw: ciborium::Value =
+                ciborium::from_reader(bytes).map_err(|_| VerifyError::InvalidInput)?;
+            let canonical: ciborium::Value = ciborium::from_reader(&pubs.try_to_bytes()?[..])
+                .map_err(|_| VerifyError::InvalidInput)?;
+            policy.check_encoding(&raw, &canonical)?;
+        }
+        Ok(pubs)
+    }
+
+    /// Converts a byte array into a `DoryPublicInput` instance, taking already validated
+    /// table commitments from `cache` and adding the newly validated ones to it.
+    pub fn try_from_bytes_with_cache(
+        bytes: &[u8],
+        cache: &mut CommitmentCache,
+    ) -> Result<Self, VerifyError> {
+        let repr: PublicInputRepr =
+            ciborium::from_reader(bytes).map_err(|_| VerifyError::InvalidInput)?;
+        Self::from_repr(repr, Some(cache), PlanLimits::DEFAULT)
+    }
+
     /// Converts a byte array into a `DoryPublicInput` instance.
     fn try_from_bytes(bytes: &[u8]) -> Result<Self, VerifyError> {
-        ciborium::from_reader(bytes).map_err(|_| VerifyError::InvalidInput)
+        let repr: PublicInputRepr =
+            ciborium::from_reader(bytes).map_err(|_| VerifyError::InvalidInput)?;
+        Self::try_from(repr)
+    }
+
+    /// Builds a public input from its wire representation, validating the table
+    /// commitments through `cache` if given.
+    ///
+    /// The plan is checked against `limits` first, before anything recurses over it.
+    fn from_repr(
+        repr: PublicInputRepr,
+        cache: Option<&mut CommitmentCache>,
+        limits: PlanLimits,
+    ) -> Result<Self, VerifyError> {
+        limits.check(&repr.expr)?;
+        let commitments = collect_commitments(validate_commitments(repr.commitments, cache)?)?;
+        for (index, (name, _)) in repr.omitted_columns.iter().enumerate() {
+            if repr
+                .query_data
+                .table
+                .inner_table()
+                .contains_key(&Identifier::from(*name))
+                || repr.omitted_columns[..index].iter().any(|(n, _)| n == name)
+            {
+                return Err(VerifyError::InvalidInput);
+            }
+        }
+        if let Some(paged_result) = &repr.paged_result {
+            if paged_result.rows_per_page() == 0
+                || !repr.omitted_columns.is_empty()
+                || repr.query_data.table.num_rows() != 0
+            {
+                return Err(VerifyError::InvalidInput);
+            }
+        }
+        check_table_digests(&repr.expr, &commitments, &repr.table_digests)?;
+        Ok(Self {
+            expr: repr.expr,
+            commitments,
+            query_data: repr.query_data,
+            omitted_columns: repr.omitted_columns,
+            paged_result: repr.paged_result,
+            table_digests: repr.table_digests,
+        })
     }
 }
 
+/// Computes the Keccak-256 digest of `prefix` followed by the serialized `value`,
+/// without allocating the serialization.
+fn hash_with_prefix<T: Serialize>(prefix: &[u8], value: &T) -> Result<[u8; 32], VerifyError> {
+    let mut hasher = Keccak256::new();
+    hasher.update(prefix);
+    ciborium::into_writer(value, HashingWriter(&mut hasher))
+        .map_err(|_| VerifyError::InvalidInput)?;
+    Ok(hasher.finalize().into())
+}
+
+/// The key ordering tables in the canonical encoding.
+fn table_key(table_ref: &TableRef) -> (Identifier, Identifier) {
+    (table_ref.schema_id(), table_ref.table_id())
+}
+
+/// Checks that tables given as digests are not read by the plan, have no full
+/// commitment, and are listed once in canonical order.
+fn check_table_digests(
+    expr: &DynProofPlan<DoryCommitment>,
+    commitments: &QueryCommitments<DoryCommitment>,
+    table_digests: &[(TableRef, [u8; 32])],
+) -> Result<(), VerifyError> {
+    let referenced = PlanInfo::new(expr);
+    for (index, (table_ref, _)) in table_digests.iter().enumerate() {
+        let table = TableId::from(*table_ref);
+        if referenced.tables().contains(&table) {
+            return Err(VerifyError::DigestOnlyCommitment { table });
+        }
+        if commitments.contains_key(table_ref) {
+            return Err(VerifyError::DuplicateTableCommitment { table });
+        }
+        if index > 0 && table_key(&table_digests[index - 1].0) >= table_key(table_ref) {
+            return Err(VerifyError::InvalidInput);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
 
@@ -121,7 +655,7 @@ mod test {
         },
     };
 
-    use crate::{Proof, VerificationKey};
+    use crate::{CommitmentCache, Proof, VerificationKey};
 
     use super::*;
 
@@ -137,7 +671,7 @@ mod test {
     /// Builds a test accessor with sample data.
     fn build_accessor<T: CommitmentEvaluationProof>(
         setup: <T as CommitmentEvaluationProof>::ProverPublicSetup<'_>,
-    ) -> OwnedTableTestAccessor<T> {
+    ) -> OwnedTableTestAccessor<'_, T> {
         let mut accessor = OwnedTableTestAccessor::<T>::new_empty_with_setup(setup);
         accessor.add_table(
             "sxt.table".parse().unwrap(),
@@ -189,11 +723,630 @@ mod test {
         let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
 
         let bytes = pubs.try_to_bytes().unwrap();
+        assert_eq!(pubs.serialized_size().unwrap(), bytes.len());
 
+        let hash = pubs.hash(HashVersion::LATEST).unwrap();
         let pubs = PublicInput::try_from(&bytes[..]).unwrap();
+        assert_eq!(pubs.hash(HashVersion::LATEST).unwrap(), hash);
         let proof = Proof::new(proof);
+        assert_eq!(proof.serialized_size(), proof.to_bytes().len());
         let result = crate::verify_proof(&proof, &pubs, &vk);
 
         assert!(result.is_ok());
     }
+
+    /// Builds a public input for the sample query.
+    fn build_public_input() -> PublicInput {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let vk = VerificationKey::new(&public_parameters, 4);
+
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &vk.to_dory())
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        PublicInput::new(query.proof_expr(), query_commitments, query_data)
+    }
+
+    #[test]
+    fn plan_limits_are_enforced_when_decoding() {
+        let bytes = build_public_input().try_to_bytes().unwrap();
+        let mut encoding: ciborium::Value = ciborium::from_reader(&bytes[..]).unwrap();
+        let (_, expr) = encoding
+            .as_map_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some("expr"))
+            .unwrap();
+        let (_, clause) = expr.as_map_mut().unwrap()[0]
+            .1
+            .as_map_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some("where_clause"))
+            .unwrap();
+        for _ in 0..70 {
+            let inner = core::mem::replace(clause, ciborium::Value::Null);
+            *clause = ciborium::Value::Map(vec![(
+                "Not".into(),
+                ciborium::Value::Map(vec![("expr".into(), inner)]),
+            )]);
+        }
+        let mut deep = Vec::new();
+        ciborium::into_writer(&encoding, &mut deep).unwrap();
+
+        assert!(matches!(
+            PublicInput::try_from(&deep[..]),
+            Err(VerifyError::PlanTooComplex)
+        ));
+        let config = VerifierConfig::new().with_plan_limits(PlanLimits::new(256, 1 << 16));
+        assert!(PublicInput::try_from_bytes_with_config(&deep, &config).is_ok());
+    }
+
+    #[test]
+    fn public_input_eq_hash_debug() {
+        use std::collections::HashSet;
+
+        let pubs = build_public_input();
+        let same = PublicInput::try_from(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        assert_eq!(pubs, same);
+        assert_eq!(HashSet::from([pubs, same]).len(), 1);
+
+        let pubs = build_public_input();
+        let debug = format!("{pubs:?}");
+        assert!(debug.starts_with("PublicInput { tables: [\"sxt.table\"], hash: Some(0x"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn canonical_json_hash() {
+        let pubs = build_public_input();
+        let json = pubs.to_canonical_json().unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert!(value.get("commitments").is_some());
+
+        let decoded = PublicInput::try_from(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        assert_eq!(decoded.to_canonical_json().unwrap(), json);
+        assert_eq!(decoded.json_hash().unwrap(), pubs.json_hash().unwrap());
+        assert_ne!(
+            pubs.json_hash().unwrap(),
+            pubs.hash(HashVersion::LATEST).unwrap()
+        );
+    }
+
+    #[test]
+    fn query_cost() {
+        let cost = crate::QueryCost::of(&build_public_input());
+        // Columns a and b of the four rows table are read, and two rows of b are returned
+        assert_eq!(cost.scanned_cells, 8);
+        assert_eq!(cost.result_cells, 2);
+    }
+
+    #[test]
+    fn hash_versions() {
+        let pubs = build_public_input();
+        let bytes = pubs.try_to_bytes().unwrap();
+
+        let v1: [u8; 32] = Keccak256::digest(&bytes).into();
+        assert_eq!(pubs.hash(HashVersion::V1).unwrap(), v1);
+        assert_eq!(pubs.hash_v1().unwrap(), v1);
+
+        let v2: [u8; 32] = Keccak256::digest([STATEMENT_HASH_V2_TAG, &bytes].concat()).into();
+        assert_eq!(pubs.hash(HashVersion::V2).unwrap(), v2);
+        assert_eq!(pubs.hash_v2().unwrap(), v2);
+        assert_ne!(v1, v2);
+
+        let v3: [u8; 32] = Keccak256::digest([STATEMENT_HASH_V3_TAG, &bytes].concat()).into();
+        assert_eq!(pubs.hash(HashVersion::V3).unwrap(), v3);
+        assert_eq!(pubs.hash_v3().unwrap(), v3);
+        assert_ne!(v2, v3);
+    }
+
+    #[test]
+    fn plan_fingerprint_ignores_data() {
+        let pubs = build_public_input();
+        let fingerprint = pubs.plan_fingerprint().unwrap();
+        let mut expr = Vec::new();
+        ciborium::into_writer(pubs.expr(), &mut expr).unwrap();
+        let expected: [u8; 32] = Keccak256::digest([PLAN_FINGERPRINT_TAG, &expr].concat()).into();
+        assert_eq!(fingerprint, expected);
+
+        let other_result = PublicInput::new(
+            pubs.expr(),
+            pubs.commitments().clone(),
+            QueryData {
+                table: owned_table([varchar("b", ["other"])]),
+                verification_hash: [0; 32],
+            },
+        );
+        assert_eq!(other_result.plan_fingerprint().unwrap(), fingerprint);
+
+        let (_, other_plan, _) = build_two_column_statement();
+        assert_ne!(other_plan.plan_fingerprint().unwrap(), fingerprint);
+    }
+
+    #[test]
+    fn peek_referenced_tables_and_columns() {
+        let pubs = build_public_input();
+        let bytes = pubs.try_to_bytes().unwrap();
+        let table = TableId::new("sxt", "table").unwrap();
+        let columns: Vec<_> = pubs
+            .expr()
+            .get_column_references()
+            .into_iter()
+            .map(|column| (table.clone(), ColumnId::from(column.column_id())))
+            .collect();
+        assert_eq!(
+            peek_referenced_tables(&bytes).unwrap(),
+            core::slice::from_ref(&table)
+        );
+        assert_eq!(peek_referenced_columns(&bytes).unwrap(), columns);
+
+        // Commitments are not decoded
+        let mut encoding: ciborium::Value = ciborium::from_reader(&bytes[..]).unwrap();
+        let (_, commitments) = encoding
+            .as_map_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some("commitments"))
+            .unwrap();
+        *commitments = ciborium::Value::Text("not commitments".into());
+        let mut corrupted = Vec::new();
+        ciborium::into_writer(&encoding, &mut corrupted).unwrap();
+        assert!(PublicInput::try_from(&corrupted[..]).is_err());
+        assert_eq!(peek_referenced_tables(&corrupted).unwrap(), [table]);
+
+        assert!(matches!(
+            peek_referenced_tables(&bytes[..bytes.len() / 2]),
+            Err(VerifyError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn decode_with_commitment_cache() {
+        let pubs = build_public_input();
+        let bytes = pubs.try_to_bytes().unwrap();
+        let mut cache = CommitmentCache::new();
+
+        for _ in 0..2 {
+            let decoded = PublicInput::try_from_bytes_with_cache(&bytes, &mut cache).unwrap();
+            assert_eq!(decoded, pubs);
+            assert_eq!(cache.len(), 1);
+        }
+    }
+
+    /// Builds a proof and a public input for a query returning both sample columns.
+    fn build_two_column_statement() -> (Proof, PublicInput, VerificationKey) {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let vk = VerificationKey::new(&public_parameters, 4);
+
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = QueryExpr::try_new(
+            "SELECT a, b FROM table WHERE a = 2".parse().unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &vk.to_dory())
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        (Proof::new(proof), pubs, vk)
+    }
+
+    /// Re-encodes `pubs` after applying `f` to the entries of its omitted columns.
+    fn tamper_omitted_columns(
+        pubs: &PublicInput,
+        f: impl FnOnce(&mut Vec<ciborium::Value>),
+    ) -> Vec<u8> {
+        let mut value: ciborium::Value =
+            ciborium::from_reader(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        let (_, omitted) = value
+            .as_map_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some("omitted_columns"))
+            .unwrap();
+        f(omitted.as_array_mut().unwrap());
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn partial_result_claim() {
+        let (proof, pubs, vk) = build_two_column_statement();
+        let full_bytes = pubs.try_to_bytes().unwrap();
+        let a: Identifier = "a".parse().unwrap();
+
+        let pubs = pubs.omit_columns([a]).unwrap();
+        assert_eq!(pubs.query_data().table.num_columns(), 1);
+        assert_eq!(pubs.omitted_columns().len(), 1);
+        let pubs = PublicInput::try_from(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        assert!(crate::verify_proof(&proof, &pubs, &vk).is_ok());
+
+        // A full claim is encoded exactly as before partial claims were supported
+        let full = PublicInput::try_from(&full_bytes[..]).unwrap();
+        assert!(full.omitted_columns().is_empty());
+        assert_eq!(full.try_to_bytes().unwrap(), full_bytes);
+    }
+
+    #[test]
+    fn partial_result_claim_with_wrong_digest() {
+        let (proof, pubs, vk) = build_two_column_statement();
+        let pubs = pubs
+            .omit_columns(["a".parse::<ColumnId>().unwrap()])
+            .unwrap();
+        let bytes = tamper_omitted_columns(&pubs, |entries| {
+            let digest = &mut entries[0].as_array_mut().unwrap()[1];
+            digest.as_array_mut().unwrap()[0] = ciborium::Value::Integer(0xff.into());
+        });
+        let pubs = PublicInput::try_from(&bytes[..]).unwrap();
+
+        assert!(matches!(
+            crate::verify_proof(&proof, &pubs, &vk),
+            Err(VerifyError::VerificationFailed)
+        ));
+    }
+
+    #[test]
+    fn invalid_omitted_columns() {
+        let (_, pubs, _) = build_two_column_statement();
+        assert!(matches!(
+            pubs.omit_columns(["c".parse::<ColumnId>().unwrap()]),
+            Err(VerifyError::InvalidInput)
+        ));
+
+        let (_, pubs, _) = build_two_column_statement();
+        let pubs = pubs
+            .omit_columns(["a".parse::<ColumnId>().unwrap()])
+            .unwrap();
+        let duplicated = tamper_omitted_columns(&pubs, |entries| entries.push(entries[0].clone()));
+        assert!(matches!(
+            PublicInput::try_from(&duplicated[..]),
+            Err(VerifyError::InvalidInput)
+        ));
+        let claimed = tamper_omitted_columns(&pubs, |entries| {
+            entries[0].as_array_mut().unwrap()[0] = ciborium::Value::Text("b".into());
+        });
+        assert!(matches!(
+            PublicInput::try_from(&claimed[..]),
+            Err(VerifyError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn paged_result_claim() {
+        let (proof, pubs, vk) = build_two_column_statement();
+        let result = pubs.query_data().table.clone();
+
+        let pubs = pubs.page_result(1).unwrap();
+        assert_eq!(pubs.query_data().table.num_rows(), 0);
+        let paged_result = *pubs.paged_result().unwrap();
+        assert_eq!(paged_result.num_pages(), result.num_rows() as u64);
+        let pubs = PublicInput::try_from(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        assert!(crate::verify_proof(&proof, &pubs, &vk).is_ok());
+
+        let page = crate::result_page(&result, 1, 1).unwrap();
+        let page_proof = crate::page_inclusion_proof(&result, 1, 1).unwrap();
+        assert!(paged_result.verify_page(1, &page, &page_proof).is_ok());
+
+        let row_proof = crate::row_inclusion_proof(&result, 1, 1).unwrap();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&row_proof, &mut bytes).unwrap();
+        let row_proof: crate::RowInclusionProof = ciborium::from_reader(&bytes[..]).unwrap();
+        assert!(crate::verify_row_inclusion(&pubs, 1, &page, &row_proof).is_ok());
+
+        let (_, unpaged, _) = build_two_column_statement();
+        assert!(matches!(
+            crate::verify_row_inclusion(&unpaged, 1, &page, &row_proof),
+            Err(VerifyError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn paged_result_claim_with_wrong_shape() {
+        let (proof, pubs, vk) = build_two_column_statement();
+        let paged = pubs.page_result(1).unwrap();
+        let mut value: ciborium::Value =
+            ciborium::from_reader(&paged.try_to_bytes().unwrap()[..]).unwrap();
+        let (_, paged_result) = value
+            .as_map_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some("paged_result"))
+            .unwrap();
+        let (_, rows_per_page) = &mut paged_result.as_map_mut().unwrap()[0];
+        *rows_per_page = ciborium::Value::Integer(2.into());
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+        let pubs = PublicInput::try_from(&bytes[..]).unwrap();
+
+        assert!(matches!(
+            crate::verify_proof(&proof, &pubs, &vk),
+            Err(VerifyError::VerificationFailed)
+        ));
+    }
+
+    #[test]
+    fn invalid_paged_result_claims() {
+        let (_, pubs, _) = build_two_column_statement();
+        assert!(matches!(
+            pubs.page_result(0),
+            Err(VerifyError::InvalidInput)
+        ));
+
+        let (_, pubs, _) = build_two_column_statement();
+        let paged = pubs.page_result(1).unwrap();
+        assert!(matches!(
+            paged.page_result(1),
+            Err(VerifyError::InvalidInput)
+        ));
+
+        let (_, pubs, _) = build_two_column_statement();
+        let omitted = pubs
+            .omit_columns(["a".parse::<ColumnId>().unwrap()])
+            .unwrap();
+        assert!(matches!(
+            omitted.page_result(1),
+            Err(VerifyError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn canonical_commitment_order() {
+        let pubs = build_public_input();
+        let commitment = pubs.commitments().values().next().unwrap();
+        let with_tables = |tables: [&str; 3]| {
+            let commitments = tables
+                .into_iter()
+                .map(|table| (table.parse().unwrap(), commitment.clone()))
+                .collect();
+            let query_data = QueryData {
+                table: pubs.query_data().table.clone(),
+                verification_hash: pubs.query_data().verification_hash,
+            };
+            PublicInput::new(pubs.expr(), commitments, query_data)
+        };
+
+        let sorted = with_tables(["sxt.a", "sxt.b", "tbl.a"]);
+        let unsorted = with_tables(["tbl.a", "sxt.b", "sxt.a"]);
+        assert_eq!(
+            sorted.try_to_bytes().unwrap(),
+            unsorted.try_to_bytes().unwrap()
+        );
+        assert_eq!(
+            sorted.hash(HashVersion::V3).unwrap(),
+            unsorted.hash(HashVersion::V3).unwrap()
+        );
+        // Older versions hash the commitments in insertion order, as they always did, even
+        // though the public inputs are equal
+        assert_eq!(sorted, unsorted);
+        for version in [HashVersion::V1, HashVersion::V2] {
+            assert_ne!(
+                sorted.hash(version).unwrap(),
+                unsorted.hash(version).unwrap()
+            );
+        }
+
+        let decoded = PublicInput::try_from(&unsorted.try_to_bytes().unwrap()[..]).unwrap();
+        let tables: Vec<_> = decoded
+            .commitments()
+            .keys()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(tables, ["sxt.a", "sxt.b", "tbl.a"]);
+    }
+
+    /// Re-encodes `pubs` after applying `f` to the entries of its commitments map.
+    fn tamper_commitments(
+        pubs: &PublicInput,
+        f: impl FnOnce(&mut Vec<(ciborium::Value, ciborium::Value)>),
+    ) -> Vec<u8> {
+        let mut value: ciborium::Value =
+            ciborium::from_reader(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        let ciborium::Value::Map(fields) = &mut value else {
+            panic!("public input is not encoded as a map")
+        };
+        let (_, commitments) = fields
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some("commitments"))
+            .unwrap();
+        f(commitments.as_map_mut().unwrap());
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn duplicate_table_commitment() {
+        let pubs = build_public_input();
+        let bytes = tamper_commitments(&pubs, |entries| entries.push(entries[0].clone()));
+
+        assert!(matches!(
+            PublicInput::try_from(&bytes[..]),
+            Err(VerifyError::DuplicateTableCommitment { table }) if table == "sxt.table"
+        ));
+    }
+
+    #[test]
+    fn conflicting_table_commitment() {
+        let pubs = build_public_input();
+        let bytes = tamper_commitments(&pubs, |entries| {
+            let (key, mut commitment) = entries[0].clone();
+            let (_, range) = commitment
+                .as_map_mut()
+                .unwrap()
+                .iter_mut()
+                .find(|(key, _)| key.as_text() == Some("range"))
+                .unwrap();
+            let (_, end) = range
+                .as_map_mut()
+                .unwrap()
+                .iter_mut()
+                .find(|(key, _)| key.as_text() == Some("end"))
+                .unwrap();
+            *end = ciborium::Value::Integer(5.into());
+            entries.push((key, commitment));
+        });
+
+        assert!(matches!(
+            PublicInput::try_from(&bytes[..]),
+            Err(VerifyError::ConflictingTableCommitment { table }) if table == "sxt.table"
+        ));
+    }
+
+    /// Re-encodes `pubs` after applying `f` to its table digests.
+    fn tamper_table_digests(
+        pubs: &PublicInput,
+        f: impl FnOnce(&mut Vec<ciborium::Value>),
+    ) -> Vec<u8> {
+        let mut value: ciborium::Value =
+            ciborium::from_reader(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        let (_, digests) = value
+            .as_map_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some("table_digests"))
+            .unwrap();
+        f(digests.as_array_mut().unwrap());
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn digested_unreferenced_tables() {
+        let (proof, pubs, vk) = build_two_column_statement();
+        let commitment = pubs.commitments().values().next().unwrap();
+        let mut commitments = pubs.commitments().clone();
+        for table in ["tbl.other", "sxt.other"] {
+            commitments.insert(table.parse().unwrap(), commitment.clone());
+        }
+        let query_data = QueryData {
+            table: pubs.query_data().table.clone(),
+            verification_hash: pubs.query_data().verification_hash,
+        };
+        let full = PublicInput::new(pubs.expr(), commitments, query_data);
+        let sparse = PublicInput::try_from(&full.try_to_bytes().unwrap()[..])
+            .unwrap()
+            .digest_unreferenced_tables()
+            .unwrap();
+
+        let tables: Vec<_> = sparse
+            .commitments()
+            .keys()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(tables, ["sxt.table"]);
+        let digests: Vec<_> = sparse
+            .table_digests()
+            .iter()
+            .map(|(table_ref, digest)| (table_ref.to_string(), *digest))
+            .collect();
+        let digest = table_commitment_digest(commitment).unwrap();
+        assert_eq!(
+            digests,
+            [
+                ("sxt.other".to_string(), digest),
+                ("tbl.other".to_string(), digest)
+            ]
+        );
+        assert!(sparse.serialized_size().unwrap() < full.serialized_size().unwrap());
+        assert_ne!(
+            sparse.hash(HashVersion::LATEST).unwrap(),
+            full.hash(HashVersion::LATEST).unwrap()
+        );
+        let decoded = PublicInput::try_from(&sparse.try_to_bytes().unwrap()[..]).unwrap();
+        assert_eq!(decoded, sparse);
+        assert!(crate::verify_proof(&proof, &decoded, &vk).is_ok());
+
+        let bytes = tamper_table_digests(&sparse, |digests| digests.swap(0, 1));
+        assert!(matches!(
+            PublicInput::try_from(&bytes[..]),
+            Err(VerifyError::InvalidInput)
+        ));
+        let bytes = tamper_table_digests(&sparse, |digests| {
+            let mut referenced = digests[0].clone();
+            referenced.as_array_mut().unwrap()[0] =
+                ciborium::Value::serialized(&"sxt.table".parse::<TableRef>().unwrap()).unwrap();
+            digests.insert(0, referenced);
+        });
+        assert!(matches!(
+            PublicInput::try_from(&bytes[..]),
+            Err(VerifyError::DigestOnlyCommitment { table }) if table == "sxt.table"
+        ));
+        let bytes = tamper_table_digests(&sparse, |digests| digests.push(digests[1].clone()));
+        assert!(PublicInput::try_from(&bytes[..]).is_err());
+    }
+
+    #[test]
+    fn strict_identifier_policy() {
+        let pubs = build_public_input();
+        let bytes = tamper_commitments(&pubs, |entries| {
+            entries[0].0 = ciborium::Value::Text("SXT.Table".into());
+        });
+
+        let pubs =
+            PublicInput::try_from_bytes_with_policy(&bytes, IdentifierPolicy::CaseInsensitive)
+                .unwrap();
+        assert!(pubs
+            .commitments()
+            .contains_key(&"sxt.table".parse::<TableRef>().unwrap()));
+        assert!(matches!(
+            PublicInput::try_from_bytes_with_policy(&bytes, IdentifierPolicy::Strict),
+            Err(VerifyError::NonCanonicalIdentifier { identifier }) if identifier == "SXT.Table"
+        ));
+        assert!(PublicInput::try_from_bytes_with_policy(
+            &pubs.try_to_bytes().unwrap(),
+            IdentifierPolicy::Strict
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn strict_identifier_policy_accepts_unsorted_tables() {
+        let pubs = build_public_input();
+        let commitment = pubs.commitments().values().next().unwrap();
+        let commitments = ["sxt.table", "sxt.a", "tbl.a"]
+            .into_iter()
+            .map(|table| (table.parse().unwrap(), commitment.clone()))
+            .collect();
+        let query_data = QueryData {
+            table: pubs.query_data().table.clone(),
+            verification_hash: pubs.query_data().verification_hash,
+        };
+        let pubs = PublicInput::new(pubs.expr(), commitments, query_data);
+
+        let unsorted = tamper_commitments(&pubs, |entries| entries.reverse());
+        assert_ne!(unsorted, pubs.try_to_bytes().unwrap());
+        let decoded =
+            PublicInput::try_from_bytes_with_policy(&unsorted, IdentifierPolicy::Strict).unwrap();
+        assert_eq!(decoded, pubs);
+
+        let uppercase = tamper_commitments(&pubs, |entries| {
+            entries.reverse();
+            entries[1].0 = ciborium::Value::Text("SXT.TABLE".into());
+        });
+        assert!(matches!(
+            PublicInput::try_from_bytes_with_policy(&uppercase, IdentifierPolicy::Strict),
+            Err(VerifyError::NonCanonicalIdentifier { identifier }) if identifier == "SXT.TABLE"
+        ));
+    }
 }
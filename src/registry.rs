@@ -0,0 +1,222 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::collections::BTreeMap;
+
+use crate::{VerificationKey, VerifyError};
+
+/// The period during which a registered verification key may be used.
+///
+/// Bounds are expressed on a caller-chosen monotonic clock, such as block heights or
+/// UNIX timestamps, which must be used consistently for a given registry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyValidity {
+    /// The first point in time at which the key is usable, if any.
+    pub activation: Option<u64>,
+    /// The first point in time at which the key is no longer usable, if any.
+    pub expiry: Option<u64>,
+}
+
+impl KeyValidity {
+    /// A validity window without bounds.
+    pub const ALWAYS: Self = Self {
+        activation: None,
+        expiry: None,
+    };
+
+    /// Creates a validity window starting at `activation` and ending right before `expiry`.
+    pub fn new(activation: Option<u64>, expiry: Option<u64>) -> Self {
+        Self { activation, expiry }
+    }
+
+    /// Checks whether the window contains `now`.
+    pub fn check(&self, now: u64) -> Result<(), VerifyError> {
+        if self.activation.is_some_and(|activation| now < activation) {
+            return Err(VerifyError::VkNotYetActive);
+        }
+        if self.expiry.is_some_and(|expiry| now >= expiry) {
+            return Err(VerifyError::VkExpired);
+        }
+        Ok(())
+    }
+}
+
+/// A registered verification key along with its rotation metadata.
+#[derive(Clone, Debug)]
+pub struct VkEntry {
+    vk: VerificationKey,
+    validity: KeyValidity,
+}
+
+impl VkEntry {
+    pub(crate) fn new(vk: VerificationKey, validity: KeyValidity) -> Self {
+        Self { vk, validity }
+    }
+
+    /// Returns the verification key.
+    pub fn vk(&self) -> &VerificationKey {
+        &self.vk
+    }
+
+    /// Returns the validity window of the key.
+    pub fn validity(&self) -> KeyValidity {
+        self.validity
+    }
+}
+
+/// A collection of verification keys indexed by their fingerprint.
+///
+/// Each key carries a [`KeyValidity`] window, so that key rotation schedules are
+/// enforced by the registry rather than by every caller.
+#[derive(Clone, Debug, Default)]
+pub struct VkRegistry {
+    entries: BTreeMap<[u8; 32], VkEntry>,
+}
+
+impl VkRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a verification key, replacing any previous entry for the same key.
+    ///
+    /// # Returns
+    ///
+    /// The fingerprint under which the key has been registered.
+    pub fn register(&mut self, vk: VerificationKey, validity: KeyValidity) -> [u8; 32] {
+        let fingerprint = vk.fingerprint();
+        self.entries.insert(fingerprint, VkEntry::new(vk, validity));
+        fingerprint
+    }
+
+    /// Updates the validity window of a registered key.
+    pub fn set_validity(
+        &mut self,
+        fingerprint: &[u8; 32],
+        validity: KeyValidity,
+    ) -> Result<(), VerifyError> {
+        let entry = self
+            .entries
+            .get_mut(fingerprint)
+            .ok_or(VerifyError::InvalidVerificationKey)?;
+        entry.validity = validity;
+        Ok(())
+    }
+
+    /// Removes a key from the registry.
+    pub fn remove(&mut self, fingerprint: &[u8; 32]) -> Option<VkEntry> {
+        self.entries.remove(fingerprint)
+    }
+
+    /// Returns the entry for a key regardless of its validity.
+    pub fn get(&self, fingerprint: &[u8; 32]) -> Option<&VkEntry> {
+        self.entries.get(fingerprint)
+    }
+
+    /// Returns a key if it is registered and valid at `now`.
+    pub fn get_active(
+        &self,
+        fingerprint: &[u8; 32],
+        now: u64,
+    ) -> Result<&VerificationKey, VerifyError> {
+        let entry = self
+            .entries
+            .get(fingerprint)
+            .ok_or(VerifyError::InvalidVerificationKey)?;
+        entry.validity.check(now)?;
+        Ok(&entry.vk)
+    }
+
+    /// Iterates over the keys valid at `now`, ordered by fingerprint.
+    pub fn active_keys(&self, now: u64) -> impl Iterator<Item = (&[u8; 32], &VerificationKey)> {
+        self.entries
+            .iter()
+            .filter(move |(_, entry)| entry.validity.check(now).is_ok())
+            .map(|(fingerprint, entry)| (fingerprint, &entry.vk))
+    }
+
+    /// Returns the number of registered keys.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no key is registered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over all entries, ordered by fingerprint.
+    pub fn entries(&self) -> impl Iterator<Item = (&[u8; 32], &VkEntry)> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::proof_primitive::dory::PublicParameters;
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case::unbounded(KeyValidity::ALWAYS, 10, true)]
+    #[case::before_activation(KeyValidity::new(Some(10), None), 9, false)]
+    #[case::at_activation(KeyValidity::new(Some(10), None), 10, true)]
+    #[case::before_expiry(KeyValidity::new(None, Some(20)), 19, true)]
+    #[case::at_expiry(KeyValidity::new(None, Some(20)), 20, false)]
+    fn validity_window(#[case] validity: KeyValidity, #[case] now: u64, #[case] valid: bool) {
+        assert_eq!(validity.check(now).is_ok(), valid);
+    }
+
+    #[test]
+    fn rotation() {
+        let rng = &mut test_rng();
+        let old_vk = VerificationKey::new(&PublicParameters::test_rand(1, rng), 1);
+        let new_vk = VerificationKey::new(&PublicParameters::test_rand(1, rng), 1);
+
+        let mut registry = VkRegistry::new();
+        let old_id = registry.register(old_vk, KeyValidity::new(None, Some(100)));
+        let new_id = registry.register(new_vk, KeyValidity::new(Some(90), None));
+        assert_eq!(registry.len(), 2);
+
+        assert!(registry.get_active(&old_id, 50).is_ok());
+        assert!(matches!(
+            registry.get_active(&new_id, 50),
+            Err(VerifyError::VkNotYetActive)
+        ));
+        assert_eq!(registry.active_keys(95).count(), 2);
+        assert!(matches!(
+            registry.get_active(&old_id, 100),
+            Err(VerifyError::VkExpired)
+        ));
+        assert_eq!(
+            registry
+                .active_keys(100)
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>(),
+            [new_id]
+        );
+
+        registry.set_validity(&old_id, KeyValidity::ALWAYS).unwrap();
+        assert!(registry.get_active(&old_id, 100).is_ok());
+        assert!(registry.remove(&old_id).is_some());
+        assert!(matches!(
+            registry.get_active(&old_id, 100),
+            Err(VerifyError::InvalidVerificationKey)
+        ));
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bounded rendering of untrusted data for humans, e.g. in logs.
+//!
+//! Results come from untrusted public inputs and proofs, so rendering them must neither
+//! allocate in proportion to their size nor let their contents forge log lines.
+
+use core::fmt::{self, Display, Formatter, Write};
+use proof_of_sql::{
+    base::database::{OwnedColumn, OwnedTable},
+    proof_primitive::dory::DoryScalar,
+};
+
+/// Limits on the amount of data rendered by human-readable representations.
+///
+/// Whatever exceeds the limits is elided, and replaced by a marker telling how much was
+/// left out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderLimits {
+    /// The maximum number of table cells, or list items, rendered.
+    pub max_cells: usize,
+    /// The maximum number of bytes rendered from each string.
+    pub max_string_len: usize,
+}
+
+impl RenderLimits {
+    /// Limits suitable for log lines.
+    pub const DEFAULT: Self = Self {
+        max_cells: 64,
+        max_string_len: 256,
+    };
+
+    /// Creates limits rendering at most `max_cells` cells and `max_string_len` bytes of
+    /// each string.
+    pub fn new(max_cells: usize, max_string_len: usize) -> Self {
+        Self {
+            max_cells,
+            max_string_len,
+        }
+    }
+}
+
+impl Default for RenderLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A string rendered with at most `max_len` of its bytes, with control and other
+/// non-printable characters escaped.
+pub(crate) struct Sanitized<'a> {
+    pub(crate) value: &'a str,
+    pub(crate) max_len: usize,
+}
+
+impl Display for Sanitized<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut len = 0;
+        for c in self.value.chars() {
+            if len + c.len_utf8() > self.max_len {
+                return write!(f, "...(+{} bytes)", self.value.len() - len);
+            }
+            len += c.len_utf8();
+            for escaped in c.escape_debug() {
+                f.write_char(escaped)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A table rendered as text, one row per line, within [`RenderLimits`].
+///
+/// Cells are separated by ` | ` and strings are quoted. Rows are rendered in full as
+/// long as the number of cells stays within the limit.
+pub struct TableDisplay<'a> {
+    table: &'a OwnedTable<DoryScalar>,
+    limits: RenderLimits,
+}
+
+impl<'a> TableDisplay<'a> {
+    /// Renders `table` within `limits`.
+    pub fn new(table: &'a OwnedTable<DoryScalar>, limits: RenderLimits) -> Self {
+        Self { table, limits }
+    }
+}
+
+impl Display for TableDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let columns = self.table.inner_table();
+        let shown_columns = columns.len().min(self.limits.max_cells.max(1));
+        for (index, name) in columns.keys().take(shown_columns).enumerate() {
+            if index > 0 {
+                f.write_str(" | ")?;
+            }
+            write!(f, "{name}")?;
+        }
+        if shown_columns < columns.len() {
+            write!(f, " | ...(+{} columns)", columns.len() - shown_columns)?;
+        }
+
+        let num_rows = self.table.num_rows();
+        let shown_rows = num_rows.min(self.limits.max_cells / shown_columns.max(1));
+        for row in 0..shown_rows {
+            f.write_char('\n')?;
+            for (index, column) in columns.values().take(shown_columns).enumerate() {
+                if index > 0 {
+                    f.write_str(" | ")?;
+                }
+                write_cell(f, column, row, self.limits.max_string_len)?;
+            }
+        }
+        if shown_rows < num_rows {
+            write!(f, "\n...(+{} rows)", num_rows - shown_rows)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the value at `row` of `column`.
+fn write_cell(
+    f: &mut Formatter<'_>,
+    column: &OwnedColumn<DoryScalar>,
+    row: usize,
+    max_string_len: usize,
+) -> fmt::Result {
+    match column {
+        OwnedColumn::Boolean(values) => write!(f, "{}", values[row]),
+        OwnedColumn::TinyInt(values) => write!(f, "{}", values[row]),
+        OwnedColumn::SmallInt(values) => write!(f, "{}", values[row]),
+        OwnedColumn::Int(values) => write!(f, "{}", values[row]),
+        OwnedColumn::BigInt(values) | OwnedColumn::TimestampTZ(_, _, values) => {
+            write!(f, "{}", values[row])
+        }
+        OwnedColumn::Int128(values) => write!(f, "{}", values[row]),
+        OwnedColumn::VarChar(values) => write!(
+            f,
+            "\"{}\"",
+            Sanitized {
+                value: &values[row],
+                max_len: max_string_len,
+            }
+        ),
+        OwnedColumn::Decimal75(_, scale, values) => match i128::try_from(values[row]) {
+            Ok(value) => write_decimal(f, value, *scale),
+            Err(_) => write!(f, "{:+}e{}", values[row], -i16::from(*scale)),
+        },
+        OwnedColumn::Scalar(values) => write!(f, "{}", values[row]),
+        _ => f.write_str("?"),
+    }
+}
+
+/// Writes `value * 10^-scale` in decimal notation.
+fn write_decimal(f: &mut Formatter<'_>, value: i128, scale: i8) -> fmt::Result {
+    if scale <= 0 {
+        write!(f, "{value}")?;
+        return (0..-i16::from(scale)).try_for_each(|_| f.write_char('0'));
+    }
+    if scale > 38 {
+        return write!(f, "{value}e-{scale}");
+    }
+    let scale = u32::from(scale.unsigned_abs());
+    let divisor = 10_u128.pow(scale);
+    let sign = if value < 0 { "-" } else { "" };
+    let magnitude = value.unsigned_abs();
+    write!(
+        f,
+        "{sign}{}.{:0width$}",
+        magnitude / divisor,
+        magnitude % divisor,
+        width = scale as usize
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{format, string::ToString, vec::Vec};
+    use proof_of_sql::base::database::owned_table_utility::*;
+
+    use super::*;
+
+    #[test]
+    fn renders_small_table_in_full() {
+        let table = owned_table([
+            bigint("a", [1, -2]),
+            varchar("b", ["x", "line\nbreak"]),
+            decimal75("c", 10, 2, [1250, -1]),
+        ]);
+
+        assert_eq!(
+            TableDisplay::new(&table, RenderLimits::DEFAULT).to_string(),
+            "a | b | c\n1 | \"x\" | 12.50\n-2 | \"line\\nbreak\" | -0.01"
+        );
+    }
+
+    #[test]
+    fn elides_rows_and_columns() {
+        let table = owned_table([bigint("a", [1, 2, 3]), bigint("b", [4, 5, 6])]);
+
+        assert_eq!(
+            TableDisplay::new(&table, RenderLimits::new(4, 8)).to_string(),
+            "a | b\n1 | 4\n2 | 5\n...(+1 rows)"
+        );
+        assert_eq!(
+            TableDisplay::new(&table, RenderLimits::new(1, 8)).to_string(),
+            "a | ...(+1 columns)\n1\n...(+2 rows)"
+        );
+    }
+
+    #[test]
+    fn bounds_large_strings() {
+        let huge = "\u{e9}".repeat(1 << 20);
+        let table = owned_table([varchar("b", [huge.as_str()])]);
+        let rendered = TableDisplay::new(&table, RenderLimits::new(8, 5)).to_string();
+
+        assert_eq!(
+            rendered,
+            format!("b\n\"\u{e9}\u{e9}...(+{} bytes)\"", huge.len() - 4)
+        );
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        let value: Vec<_> = ["a\u{1b}[31m", "\u{202e}b"]
+            .iter()
+            .map(|value| Sanitized { value, max_len: 64 }.to_string())
+            .collect();
+
+        assert_eq!(value, ["a\\u{1b}[31m", "\\u{202e}b"]);
+    }
+}
@@ -0,0 +1,197 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use core::fmt;
+use proof_of_sql::{
+    base::database::{LiteralValue, OwnedColumn, OwnedTable},
+    proof_primitive::dory::DoryScalar,
+};
+use proof_of_sql_parser::Identifier;
+
+use crate::{ColumnId, RenderLimits, ResultDigestTree, TableDisplay, VerifyError};
+
+/// The result of a query, as attested by a verified proof.
+///
+/// Results without rows are regular tables: their columns are present, but empty, and
+/// [`VerifiedTable::rows`] yields nothing.
+///
+/// `Debug` only prints the shape of the result, see [`VerifiedTable::display`] to render
+/// its values.
+#[derive(Clone, PartialEq, Eq)]
+pub struct VerifiedTable {
+    table: OwnedTable<DoryScalar>,
+}
+
+impl fmt::Debug for VerifiedTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let columns: Vec<_> = self
+            .table
+            .column_names()
+            .take(RenderLimits::DEFAULT.max_cells)
+            .map(Identifier::as_str)
+            .collect();
+        f.debug_struct("VerifiedTable")
+            .field("columns", &columns)
+            .field("num_columns", &self.num_columns())
+            .field("num_rows", &self.num_rows())
+            .finish()
+    }
+}
+
+impl VerifiedTable {
+    /// Wraps a verified result, rejecting columns of types rows can't be built from.
+    pub(crate) fn try_new(table: OwnedTable<DoryScalar>) -> Result<Self, VerifyError> {
+        if table.inner_table().values().all(is_supported) {
+            Ok(Self { table })
+        } else {
+            Err(VerifyError::InvalidInput)
+        }
+    }
+
+    /// Returns the result as a table.
+    pub fn table(&self) -> &OwnedTable<DoryScalar> {
+        &self.table
+    }
+
+    /// Returns the result as a table, consuming it.
+    pub fn into_table(self) -> OwnedTable<DoryScalar> {
+        self.table
+    }
+
+    /// Returns the names of the result columns, in order.
+    pub fn column_names(&self) -> impl Iterator<Item = ColumnId> + '_ {
+        self.table.column_names().copied().map(ColumnId::from)
+    }
+
+    /// Returns the number of result columns.
+    pub fn num_columns(&self) -> usize {
+        self.table.num_columns()
+    }
+
+    /// Returns the number of result rows.
+    pub fn num_rows(&self) -> usize {
+        self.table.num_rows()
+    }
+
+    /// Returns whether the result has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.num_rows() == 0
+    }
+
+    /// Computes the digest tree of the result over chunks of `rows_per_chunk` rows, to
+    /// store it on untrusted storage and let consumers check it piece by piece.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if `rows_per_chunk` is zero.
+    pub fn digest_tree(&self, rows_per_chunk: usize) -> Result<ResultDigestTree, VerifyError> {
+        ResultDigestTree::new(&self.table, rows_per_chunk)
+    }
+
+    /// Renders the result as text within `limits`, e.g. for logging.
+    pub fn display(&self, limits: RenderLimits) -> TableDisplay<'_> {
+        TableDisplay::new(&self.table, limits)
+    }
+
+    /// Iterates over the result rows, each holding one value per column.
+    pub fn rows(&self) -> impl Iterator<Item = Vec<LiteralValue<DoryScalar>>> + '_ {
+        (0..self.num_rows()).map(|i| {
+            self.table
+                .inner_table()
+                .values()
+                .map(|column| value_at(column, i))
+                .collect()
+        })
+    }
+}
+
+/// Returns whether values of `column` can be read by [`value_at`].
+fn is_supported(column: &OwnedColumn<DoryScalar>) -> bool {
+    matches!(
+        column,
+        OwnedColumn::Boolean(_)
+            | OwnedColumn::TinyInt(_)
+            | OwnedColumn::SmallInt(_)
+            | OwnedColumn::Int(_)
+            | OwnedColumn::BigInt(_)
+            | OwnedColumn::VarChar(_)
+            | OwnedColumn::Int128(_)
+            | OwnedColumn::Decimal75(..)
+            | OwnedColumn::Scalar(_)
+            | OwnedColumn::TimestampTZ(..)
+    )
+}
+
+/// Returns the value at row `i` of `column`.
+fn value_at(column: &OwnedColumn<DoryScalar>, i: usize) -> LiteralValue<DoryScalar> {
+    match column {
+        OwnedColumn::Boolean(values) => LiteralValue::Boolean(values[i]),
+        OwnedColumn::TinyInt(values) => LiteralValue::TinyInt(values[i]),
+        OwnedColumn::SmallInt(values) => LiteralValue::SmallInt(values[i]),
+        OwnedColumn::Int(values) => LiteralValue::Int(values[i]),
+        OwnedColumn::BigInt(values) => LiteralValue::BigInt(values[i]),
+        OwnedColumn::VarChar(values) => {
+            LiteralValue::VarChar((values[i].clone(), values[i].as_str().into()))
+        }
+        OwnedColumn::Int128(values) => LiteralValue::Int128(values[i]),
+        OwnedColumn::Decimal75(precision, scale, values) => {
+            LiteralValue::Decimal75(*precision, *scale, values[i])
+        }
+        OwnedColumn::Scalar(values) => LiteralValue::Scalar(values[i]),
+        OwnedColumn::TimestampTZ(unit, zone, values) => {
+            LiteralValue::TimeStampTZ(*unit, *zone, values[i])
+        }
+        _ => unreachable!("unsupported columns are rejected by VerifiedTable::try_new"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proof_of_sql::base::database::owned_table_utility::*;
+
+    use super::*;
+
+    #[test]
+    fn rows_of_empty_table() {
+        let table =
+            VerifiedTable::try_new(owned_table([bigint("a", [0; 0]), varchar("b", [""; 0])]))
+                .unwrap();
+
+        assert!(table.is_empty());
+        assert_eq!(table.num_columns(), 2);
+        assert!(table.column_names().eq(["a", "b"]));
+        assert_eq!(
+            alloc::format!("{table:?}"),
+            "VerifiedTable { columns: [\"a\", \"b\"], num_columns: 2, num_rows: 0 }"
+        );
+        assert_eq!(table.rows().count(), 0);
+    }
+
+    #[test]
+    fn rows_hold_one_value_per_column() {
+        let table =
+            VerifiedTable::try_new(owned_table([bigint("a", [1, 2]), varchar("b", ["x", "y"])]))
+                .unwrap();
+        let rows: Vec<_> = table.rows().collect();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][0], LiteralValue::BigInt(2));
+        assert_eq!(
+            rows[1][1],
+            LiteralValue::VarChar(("y".into(), DoryScalar::from("y")))
+        );
+    }
+}
@@ -0,0 +1,335 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of untrusted artifacts in a subprocess with resource limits.
+//!
+//! The artifacts are decoded and verified by a child process, so that a crash or a
+//! runaway allocation in a decoder ends the child rather than the service. The parent
+//! sends the serialized proof, public input and verification key on the child's stdin,
+//! each as a little endian `u64` length followed by its bytes, and the child answers on
+//! its stdout with either `verified ROWS COLUMNS`, or `rejected REASON` followed by the
+//! error message on the next line. A child still running after its wall-clock limit,
+//! e.g. blocked on I/O, is killed along with the processes it started.
+
+use std::{
+    io::{self, Read, Write},
+    os::unix::process::CommandExt,
+    process::{Child, Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use snafu::Snafu;
+
+use crate::{rejection_reason, verify_proof_with_result, Proof, PublicInput, VerificationKey};
+
+/// The resource limits of a verification subprocess.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SandboxLimits {
+    memory_bytes: u64,
+    cpu_secs: u64,
+    wall_secs: u64,
+}
+
+impl SandboxLimits {
+    /// 4 GiB of address space, 60 seconds of CPU time and 120 seconds of wall-clock time.
+    pub const DEFAULT: Self = Self::new(4 << 30, 60, 120);
+
+    /// Creates limits on the address space, in bytes, the CPU time, in seconds, and the
+    /// wall-clock time, in seconds, of the subprocess.
+    pub const fn new(memory_bytes: u64, cpu_secs: u64, wall_secs: u64) -> Self {
+        Self {
+            memory_bytes,
+            cpu_secs,
+            wall_secs,
+        }
+    }
+
+    /// Returns the limit on the address space of the subprocess, in bytes.
+    pub fn memory_bytes(&self) -> u64 {
+        self.memory_bytes
+    }
+
+    /// Returns the limit on the CPU time of the subprocess, in seconds.
+    pub fn cpu_secs(&self) -> u64 {
+        self.cpu_secs
+    }
+
+    /// Returns the limit on the wall-clock time of the subprocess, in seconds.
+    pub fn wall_secs(&self) -> u64 {
+        self.wall_secs
+    }
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Errors raised by a sandboxed verification.
+#[derive(Debug, Snafu)]
+pub enum SandboxError {
+    /// The subprocess can't be started, or can't be communicated with.
+    #[snafu(display("Cannot run the verification subprocess: {source}"))]
+    Spawn { source: io::Error },
+    /// The subprocess ended without reporting an outcome, e.g. after crashing or
+    /// exceeding its limits.
+    #[snafu(display("The verification subprocess terminated abnormally ({status})"))]
+    Terminated { status: ExitStatus },
+    /// The subprocess was still running after its wall-clock limit, and was killed.
+    #[snafu(display("The verification subprocess exceeded its limit of {wall_secs} seconds"))]
+    TimedOut { wall_secs: u64 },
+    /// The artifacts were rejected, `reason` being the [`rejection_reason`] of the error.
+    #[snafu(display("{message}"))]
+    Rejected { reason: String, message: String },
+}
+
+/// Decodes and verifies the artifacts in the subprocess run by `command`, within
+/// `limits`, returning the number of rows and columns of the verified result.
+///
+/// `command` must run a program calling [`serve_sandboxed_verification`], usually the
+/// current executable with arguments telling it to do so. Its stdin and stdout are
+/// replaced by pipes, and the memory and CPU limits are applied with `setrlimit` between
+/// the fork and the exec. The subprocess runs in its own process group, which is killed
+/// once the wall-clock limit is exceeded.
+pub fn verify_sandboxed(
+    mut command: Command,
+    limits: SandboxLimits,
+    proof: &[u8],
+    pubs: &[u8],
+    vk: &[u8],
+) -> Result<(usize, usize), SandboxError> {
+    // SAFETY: `setrlimit` is async-signal-safe, and only limits the child
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setrlimit(libc::RLIMIT_AS, &rlimit(limits.memory_bytes)) != 0
+                || libc::setrlimit(libc::RLIMIT_CPU, &rlimit(limits.cpu_secs)) != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut child = command
+        .process_group(0)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|source| SandboxError::Spawn { source })?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    // The pipes are served by threads, so that a child that doesn't read its input or
+    // write its output can't block the deadline
+    let (status, sent, received) = thread::scope(|scope| {
+        let sender = scope.spawn(move || {
+            [proof, pubs, vk].iter().try_for_each(|artifact| {
+                stdin.write_all(&(artifact.len() as u64).to_le_bytes())?;
+                stdin.write_all(artifact)
+            })
+        });
+        let receiver = scope.spawn(move || {
+            let mut answer = Vec::new();
+            stdout.read_to_end(&mut answer).map(|_| answer)
+        });
+        let status = wait_until(&mut child, Duration::from_secs(limits.wall_secs));
+        let sent = sender.join().expect("the sender doesn't panic");
+        let received = receiver.join().expect("the receiver doesn't panic");
+        (status, sent, received)
+    });
+    let status = status
+        .map_err(|source| SandboxError::Spawn { source })?
+        .ok_or(SandboxError::TimedOut {
+            wall_secs: limits.wall_secs,
+        })?;
+    // A child dying while reading breaks the pipe, which its status explains better
+    match sent {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            return Err(SandboxError::Spawn { source: e })
+        }
+        _ => (),
+    }
+    let answer = received.map_err(|source| SandboxError::Spawn { source })?;
+
+    let terminated = || SandboxError::Terminated { status };
+    if !status.success() {
+        return Err(terminated());
+    }
+    let answer = String::from_utf8(answer).map_err(|_| terminated())?;
+    let (status, message) = answer.split_once('\n').ok_or_else(terminated)?;
+    let mut words = status.split(' ');
+    match (words.next(), words.next(), words.next(), words.next()) {
+        (Some("verified"), Some(rows), Some(columns), None) => rows
+            .parse()
+            .ok()
+            .zip(columns.parse().ok())
+            .ok_or_else(terminated),
+        (Some("rejected"), Some(reason), None, None) => Err(SandboxError::Rejected {
+            reason: reason.to_string(),
+            message: message.trim_end_matches('\n').to_string(),
+        }),
+        _ => Err(terminated()),
+    }
+}
+
+/// Reads artifacts sent by [`verify_sandboxed`] on stdin, decodes and verifies them, and
+/// writes the outcome to stdout.
+///
+/// # Errors
+///
+/// Returns an error if stdin can't be read or stdout written, in which case the caller
+/// should exit with a failure status.
+pub fn serve_sandboxed_verification() -> io::Result<()> {
+    let mut stdin = io::stdin().lock();
+    let mut artifacts = [Vec::new(), Vec::new(), Vec::new()];
+    for artifact in &mut artifacts {
+        let mut len = [0; 8];
+        stdin.read_exact(&mut len)?;
+        let len = usize::try_from(u64::from_le_bytes(len))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        *artifact = vec![0; len];
+        stdin.read_exact(artifact)?;
+    }
+    let [proof, pubs, vk] = artifacts;
+
+    let outcome = Proof::try_from(&proof[..]).and_then(|proof| {
+        let pubs = PublicInput::try_from(&pubs[..])?;
+        let vk = VerificationKey::try_from(&vk[..])?;
+        verify_proof_with_result(&proof, &pubs, &vk)
+    });
+    let mut stdout = io::stdout().lock();
+    match outcome {
+        Ok(result) => writeln!(
+            stdout,
+            "verified {} {}",
+            result.num_rows(),
+            result.num_columns()
+        )?,
+        Err(e) => writeln!(stdout, "rejected {}\n{e}", rejection_reason(&e))?,
+    }
+    stdout.flush()
+}
+
+/// Waits for `child` to exit for at most `timeout`, returning `None` if it didn't, in
+/// which case its process group is killed.
+fn wait_until(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            // The pipes are only closed once every process of the group is gone
+            let group = libc::pid_t::try_from(child.id())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            // SAFETY: `kill` has no memory safety requirements
+            if unsafe { libc::kill(-group, libc::SIGKILL) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// A limit setting both the soft and the hard limit of a resource to `value`.
+fn rlimit(value: u64) -> libc::rlimit {
+    libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]);
+        command
+    }
+
+    fn run(script: &str) -> Result<(usize, usize), SandboxError> {
+        verify_sandboxed(
+            shell(script),
+            SandboxLimits::DEFAULT,
+            b"proof",
+            b"pubs",
+            b"vk",
+        )
+    }
+
+    #[test]
+    fn outcomes_are_parsed() {
+        assert_eq!(run("cat > /dev/null; echo verified 3 2").unwrap(), (3, 2));
+        assert!(matches!(
+            run("cat > /dev/null; printf 'rejected invalid_input\\nInvalid input\\n'"),
+            Err(SandboxError::Rejected { reason, message })
+                if reason == "invalid_input" && message == "Invalid input"
+        ));
+    }
+
+    #[test]
+    fn abnormal_terminations_are_reported() {
+        for script in [
+            "kill -9 $$",
+            "exit 3",
+            "cat > /dev/null",
+            "cat > /dev/null; echo verified",
+            "cat > /dev/null; echo accepted 3 2",
+        ] {
+            assert!(
+                matches!(run(script), Err(SandboxError::Terminated { .. })),
+                "{script}"
+            );
+        }
+        // The child exceeds its CPU time
+        let limits = SandboxLimits::new(
+            SandboxLimits::DEFAULT.memory_bytes(),
+            1,
+            SandboxLimits::DEFAULT.wall_secs(),
+        );
+        let result = verify_sandboxed(shell("while :; do :; done"), limits, b"", b"", b"");
+        assert!(matches!(result, Err(SandboxError::Terminated { .. })));
+    }
+
+    #[test]
+    fn hanging_children_are_killed() {
+        let limits = SandboxLimits::new(
+            SandboxLimits::DEFAULT.memory_bytes(),
+            SandboxLimits::DEFAULT.cpu_secs(),
+            1,
+        );
+        // The first child never reads its input, the second one leaves a grandchild
+        // holding its stdout
+        let proof = vec![0; 1 << 20];
+        for script in [
+            "sleep 1000",
+            "cat > /dev/null; sleep 1000; echo verified 3 2",
+        ] {
+            let start = Instant::now();
+            let result = verify_sandboxed(shell(script), limits, &proof, b"pubs", b"vk");
+            assert!(
+                matches!(result, Err(SandboxError::TimedOut { wall_secs: 1 })),
+                "{script}"
+            );
+            assert!(start.elapsed() < Duration::from_secs(10), "{script}");
+        }
+    }
+}
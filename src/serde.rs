@@ -15,20 +15,77 @@
 
 use crate::errors::VerifyError;
 use alloc::{string::String, vec::Vec};
+use core::{fmt, marker::PhantomData};
 use proof_of_sql::{
     base::{
+        commitment::QueryCommitments,
         database::{OwnedColumn, OwnedTable},
         math::decimal::Precision,
     },
-    proof_primitive::dory::DoryScalar,
+    proof_primitive::dory::{DoryCommitment, DoryScalar},
     sql::proof::QueryData,
 };
 use proof_of_sql_parser::{
     posql_time::{PoSQLTimeUnit, PoSQLTimeZone},
     Identifier,
 };
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use serde_with::{serde_as, DeserializeAs, MapPreventDuplicates, SerializeAs};
+use serde::{
+    de::{Error as _, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// A sink that discards CBOR output while keeping track of how many bytes were written.
+struct ByteCounter<'a>(&'a mut usize);
+
+impl ciborium_io::Write for ByteCounter<'_> {
+    type Error = core::convert::Infallible;
+
+    fn write_all(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        *self.0 += data.len();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A sink feeding CBOR output to a hasher, so that large values can be hashed without
+/// allocating their encoding.
+pub(crate) struct HashingWriter<'a, D>(pub(crate) &'a mut D);
+
+impl<D: sha3::Digest> ciborium_io::Write for HashingWriter<'_, D> {
+    type Error = core::convert::Infallible;
+
+    fn write_all(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.0.update(data);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Computes the size in bytes of the CBOR encoding of `value` without allocating it.
+pub(crate) fn cbor_serialized_size<T: Serialize + ?Sized>(
+    value: &T,
+) -> Result<usize, ciborium::ser::Error<core::convert::Infallible>> {
+    let mut size = 0;
+    ciborium::into_writer(value, ByteCounter(&mut size))?;
+    Ok(size)
+}
+
+/// Serializes query commitments ordered by table reference, so that the encoding doesn't
+/// depend on the order the commitments were inserted in.
+pub(crate) fn serialize_sorted_commitments<S: Serializer>(
+    commitments: &QueryCommitments<DoryCommitment>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut entries: Vec<_> = commitments.iter().collect();
+    entries.sort_by_key(|(table_ref, _)| (table_ref.schema_id(), table_ref.table_id()));
+    serializer.collect_map(entries)
+}
 
 type IndexMap = indexmap::IndexMap<
     Identifier,
@@ -44,30 +101,114 @@ pub(crate) struct QueryDataDef {
     verification_hash: [u8; 32],
 }
 
-#[serde_as]
-#[derive(Serialize, Deserialize)]
-struct RaggedTable {
-    #[serde_as(as = "MapPreventDuplicates<_, OwnedColumnDef>")]
-    table: IndexMap,
+/// Serializes a table as `{"table": {name: column, ...}}`.
+///
+/// Deserialization rejects tables with duplicate column names or columns of different
+/// lengths.
+pub(crate) struct OwnedTableDef;
+
+impl OwnedTableDef {
+    pub(crate) fn serialize<S: Serializer>(
+        table: &OwnedTable<DoryScalar>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        TableRepr {
+            table: ColumnsRef(table.inner_table()),
+        }
+        .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<OwnedTable<DoryScalar>, D::Error> {
+        let TableRepr {
+            table: Columns(table),
+        } = TableRepr::deserialize(deserializer)?;
+        OwnedTable::try_new(table).map_err(|_| D::Error::custom(VerifyError::InvalidInput))
+    }
 }
 
-#[serde_as]
 #[derive(Serialize, Deserialize)]
-#[serde(remote = "OwnedTable<DoryScalar>", try_from = "RaggedTable")]
-struct OwnedTableDef {
-    #[serde_as(as = "MapPreventDuplicates<_, OwnedColumnDef>")]
-    #[serde(getter = "OwnedTable::inner_table")]
-    table: IndexMap,
+struct TableRepr<T> {
+    table: T,
+}
+
+/// Borrowed table columns, serialized as a map from names to columns.
+struct ColumnsRef<'a>(&'a IndexMap);
+
+impl Serialize for ColumnsRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.0
+                .iter()
+                .map(|(name, column)| (name, ColumnRef(column))),
+        )
+    }
+}
+
+/// Table columns, deserialized from a map without duplicate names.
+struct Columns(IndexMap);
+
+impl<'de> Deserialize<'de> for Columns {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ColumnsVisitor;
+
+        impl<'de> Visitor<'de> for ColumnsVisitor {
+            type Value = Columns;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of columns")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Columns, A::Error> {
+                let mut columns = IndexMap::default();
+                while let Some((name, ColumnValue(column))) = map.next_entry()? {
+                    if columns.insert(name, column).is_some() {
+                        return Err(A::Error::custom(format_args!("duplicate column {name}")));
+                    }
+                }
+                Ok(Columns(columns))
+            }
+        }
+
+        deserializer.deserialize_map(ColumnsVisitor)
+    }
 }
 
-impl TryFrom<RaggedTable> for OwnedTable<DoryScalar> {
-    type Error = VerifyError;
+/// Deserializes a map into the list of its entries, keeping entries with duplicate keys.
+pub(crate) fn deserialize_entries<'de, D, K, V>(deserializer: D) -> Result<Vec<(K, V)>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    struct EntriesVisitor<K, V>(PhantomData<(K, V)>);
+
+    impl<'de, K: Deserialize<'de>, V: Deserialize<'de>> Visitor<'de> for EntriesVisitor<K, V> {
+        type Value = Vec<(K, V)>;
 
-    fn try_from(value: RaggedTable) -> Result<Self, Self::Error> {
-        Self::try_new(value.table).map_err(|_| VerifyError::InvalidInput)
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut entries = Vec::new();
+            while let Some(entry) = map.next_entry()? {
+                entries.push(entry);
+            }
+            Ok(entries)
+        }
     }
+
+    deserializer.deserialize_map(EntriesVisitor(PhantomData))
 }
 
+/// Mirrors the variants of [`OwnedColumn`].
+///
+/// Columns can't hold nulls in proof-of-sql 0.28: once upstream adds presence slices
+/// for nullable columns, they must be encoded here, and taken into account when hashing
+/// and comparing results, so that two results differing only in their nulls never
+/// compare equal.
 #[derive(Serialize, Deserialize)]
 #[serde(remote = "OwnedColumn<DoryScalar>")]
 #[non_exhaustive]
@@ -83,21 +224,131 @@ enum OwnedColumnDef {
     TimestampTZ(PoSQLTimeUnit, PoSQLTimeZone, Vec<i64>),
 }
 
-impl SerializeAs<OwnedColumn<DoryScalar>> for OwnedColumnDef {
-    fn serialize_as<S>(source: &OwnedColumn<DoryScalar>, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        OwnedColumnDef::serialize(source, serializer)
+/// A borrowed column, serialized through [`OwnedColumnDef`].
+pub(crate) struct ColumnRef<'a>(pub(crate) &'a OwnedColumn<DoryScalar>);
+
+impl Serialize for ColumnRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        OwnedColumnDef::serialize(self.0, serializer)
     }
 }
 
-impl<'de> DeserializeAs<'de, OwnedColumn<DoryScalar>> for OwnedColumnDef {
-    fn deserialize_as<D>(deserializer: D) -> Result<OwnedColumn<DoryScalar>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        OwnedColumnDef::deserialize(deserializer)
+/// A column, deserialized through [`OwnedColumnDef`].
+struct ColumnValue(OwnedColumn<DoryScalar>);
+
+impl<'de> Deserialize<'de> for ColumnValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        OwnedColumnDef::deserialize(deserializer).map(ColumnValue)
+    }
+}
+
+/// A borrowed result column together with its name, serialized as `{name, column}`.
+#[derive(Serialize)]
+pub(crate) struct NamedColumn<'a> {
+    pub(crate) name: &'a Identifier,
+    pub(crate) column: ColumnRef<'a>,
+}
+
+/// Serialization of bytes as standard, padded, base64 strings.
+#[cfg(feature = "audit")]
+pub(crate) mod base64 {
+    use super::*;
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    /// Encodes `bytes` as base64.
+    pub(crate) fn encode(bytes: &[u8]) -> String {
+        let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let mut group = [0; 4];
+            group[1..=chunk.len()].copy_from_slice(chunk);
+            let group = u32::from_be_bytes(group);
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    text.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+                } else {
+                    text.push('=');
+                }
+            }
+        }
+        text
+    }
+
+    /// Decodes base64 text, or returns `None` if it isn't padded base64.
+    pub(crate) fn decode(text: &str) -> Option<Vec<u8>> {
+        let value = |c: u8| ALPHABET.iter().position(|&a| a == c).map(|v| v as u32);
+        let text = text.as_bytes();
+        if !text.len().is_multiple_of(4) {
+            return None;
+        }
+        let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+        for (index, chunk) in text.chunks(4).enumerate() {
+            let last = index == text.len() / 4 - 1;
+            let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+            if padding > 2 || (padding > 0 && !last) {
+                return None;
+            }
+            let mut group = 0;
+            for &c in &chunk[..4 - padding] {
+                group = group << 6 | value(c)?;
+            }
+            group <<= 6 * padding;
+            bytes.extend_from_slice(&group.to_be_bytes()[1..4 - padding]);
+        }
+        Some(bytes)
+    }
+
+    pub(crate) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&encode(bytes))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        decode(&text).ok_or_else(|| D::Error::custom("invalid base64"))
+    }
+
+    pub(crate) fn serialize_optional<S: Serializer>(
+        bytes: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        bytes.as_deref().map(encode).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize_optional<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| decode(&text).ok_or_else(|| D::Error::custom("invalid base64")))
+            .transpose()
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn round_trips() {
+            for (bytes, text) in [
+                (&b""[..], ""),
+                (b"f", "Zg=="),
+                (b"fo", "Zm8="),
+                (b"foo", "Zm9v"),
+                (b"foob", "Zm9vYg=="),
+                (b"\xff\xfe\xfd", "//79"),
+            ] {
+                assert_eq!(encode(bytes), text);
+                assert_eq!(decode(text).unwrap(), bytes);
+            }
+        }
+
+        #[test]
+        fn malformed_text_is_rejected() {
+            for text in ["Zg", "Zg=", "Z===", "Zg==Zm9v", "Zm9*"] {
+                assert_eq!(decode(text), None, "{text}");
+            }
+        }
     }
 }
 
@@ -133,7 +384,20 @@ mod owned_table {
                 }
             }
         "#;
-        assert!(serde_json::from_str::<Wrapper>(&invalid_table_toml).is_err())
+        assert!(serde_json::from_str::<Wrapper>(invalid_table_toml).is_err())
+    }
+
+    #[test]
+    fn deserialization_should_fail_with_duplicate_columns() {
+        let duplicate_columns_json = r#"
+            {
+                "table": {
+                    "column": { "Boolean": [true] },
+                    "column": { "Boolean": [false] }
+                }
+            }
+        "#;
+        assert!(serde_json::from_str::<Wrapper>(duplicate_columns_json).is_err())
     }
 
     #[test]
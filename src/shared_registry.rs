@@ -0,0 +1,387 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A verification key registry shared between the threads of a verifier service.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    num::NonZeroUsize,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{KeyValidity, VerificationKey, VerifyError, VkEntry, VkRegistry};
+
+/// Counters of the lookups served by a [`SharedVkRegistry`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RegistryMetrics {
+    /// Lookups of a registered key.
+    pub hits: u64,
+    /// Lookups of a key that is not registered, or was evicted.
+    pub misses: u64,
+    /// Keys evicted to stay within the capacity.
+    pub evictions: u64,
+}
+
+impl RegistryMetrics {
+    /// Returns the percentage of lookups that found their key, if there were any.
+    pub fn hit_rate_percent(&self) -> Option<u64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits * 100 / lookups)
+    }
+}
+
+/// A registered key, along with the last time it was used.
+#[derive(Debug)]
+struct Slot {
+    entry: Arc<VkEntry>,
+    last_used: AtomicU64,
+}
+
+/// A [`VkRegistry`] that can be shared between threads, with an optional bound on the
+/// number of keys.
+///
+/// Lookups only take a read lock, so they proceed concurrently. When registering a key
+/// would exceed the capacity, the least recently used key is evicted, and is reported
+/// as missing by later lookups until registered again. Entries are handed out as
+/// [`Arc`]s, so an evicted key stays usable by the verifications already holding it.
+#[derive(Debug, Default)]
+pub struct SharedVkRegistry {
+    slots: RwLock<HashMap<[u8; 32], Slot>>,
+    capacity: Option<NonZeroUsize>,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// A registered key, as persisted by [`SharedVkRegistry::save`].
+#[derive(Serialize, Deserialize)]
+struct PersistedEntry {
+    vk: Vec<u8>,
+    activation: Option<u64>,
+    expiry: Option<u64>,
+}
+
+impl SharedVkRegistry {
+    /// Creates an empty registry without capacity bound.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty registry holding at most `capacity` keys.
+    pub fn with_capacity(capacity: NonZeroUsize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the maximum number of keys, if bounded.
+    pub fn capacity(&self) -> Option<NonZeroUsize> {
+        self.capacity
+    }
+
+    /// Registers a verification key, replacing any previous entry for the same key and
+    /// evicting the least recently used key if the registry is full.
+    ///
+    /// # Returns
+    ///
+    /// The fingerprint under which the key has been registered.
+    pub fn register(&self, vk: VerificationKey, validity: KeyValidity) -> [u8; 32] {
+        let fingerprint = vk.fingerprint();
+        let mut slots = self.slots.write().unwrap_or_else(PoisonError::into_inner);
+        slots.insert(
+            fingerprint,
+            Slot {
+                entry: Arc::new(VkEntry::new(vk, validity)),
+                last_used: AtomicU64::new(self.tick()),
+            },
+        );
+        if let Some(capacity) = self.capacity {
+            while slots.len() > capacity.get() {
+                let evicted = slots
+                    .iter()
+                    .min_by_key(|(_, slot)| slot.last_used.load(Ordering::Relaxed))
+                    .map(|(fingerprint, _)| *fingerprint)
+                    .expect("a full registry has entries");
+                slots.remove(&evicted);
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        fingerprint
+    }
+
+    /// Updates the validity window of a registered key.
+    pub fn set_validity(
+        &self,
+        fingerprint: &[u8; 32],
+        validity: KeyValidity,
+    ) -> Result<(), VerifyError> {
+        let mut slots = self.slots.write().unwrap_or_else(PoisonError::into_inner);
+        let slot = slots
+            .get_mut(fingerprint)
+            .ok_or(VerifyError::InvalidVerificationKey)?;
+        slot.entry = Arc::new(VkEntry::new(slot.entry.vk().clone(), validity));
+        Ok(())
+    }
+
+    /// Removes a key from the registry.
+    pub fn remove(&self, fingerprint: &[u8; 32]) -> Option<Arc<VkEntry>> {
+        let mut slots = self.slots.write().unwrap_or_else(PoisonError::into_inner);
+        slots.remove(fingerprint).map(|slot| slot.entry)
+    }
+
+    /// Returns the entry for a key regardless of its validity.
+    pub fn get(&self, fingerprint: &[u8; 32]) -> Option<Arc<VkEntry>> {
+        let slots = self.slots.read().unwrap_or_else(PoisonError::into_inner);
+        match slots.get(fingerprint) {
+            Some(slot) => {
+                slot.last_used.store(self.tick(), Ordering::Relaxed);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(slot.entry.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Returns the entry for a key if it is registered and valid at `now`.
+    pub fn get_active(
+        &self,
+        fingerprint: &[u8; 32],
+        now: u64,
+    ) -> Result<Arc<VkEntry>, VerifyError> {
+        let entry = self
+            .get(fingerprint)
+            .ok_or(VerifyError::InvalidVerificationKey)?;
+        entry.validity().check(now)?;
+        Ok(entry)
+    }
+
+    /// Returns the number of registered keys.
+    pub fn len(&self) -> usize {
+        self.slots
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no key is registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the lookup counters accumulated since the registry was created.
+    pub fn metrics(&self) -> RegistryMetrics {
+        RegistryMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Copies the registered keys into a [`VkRegistry`].
+    pub fn snapshot(&self) -> VkRegistry {
+        let slots = self.slots.read().unwrap_or_else(PoisonError::into_inner);
+        let mut registry = VkRegistry::new();
+        for slot in slots.values() {
+            registry.register(slot.entry.vk().clone(), slot.entry.validity());
+        }
+        registry
+    }
+
+    /// Saves the registered keys and their validity windows to `path`.
+    ///
+    /// The file is written next to `path` first and then renamed, so a registry being
+    /// saved is never observed half written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let entries: Vec<_> = self
+            .snapshot()
+            .entries()
+            .map(|(_, entry)| PersistedEntry {
+                vk: entry.vk().to_bytes(),
+                activation: entry.validity().activation,
+                expiry: entry.validity().expiry,
+            })
+            .collect();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&entries, &mut bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Loads a registry saved with [`SharedVkRegistry::save`], bounded by `capacity`.
+    ///
+    /// Keys are registered in fingerprint order, so when the saved registry exceeds the
+    /// capacity, the keys with the largest fingerprints are kept.
+    pub fn load(path: impl AsRef<Path>, capacity: Option<NonZeroUsize>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let entries: Vec<PersistedEntry> = ciborium::from_reader(&bytes[..])
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let registry = Self {
+            capacity,
+            ..Self::default()
+        };
+        for entry in entries {
+            let vk = VerificationKey::try_from(&entry.vk[..])
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            registry.register(vk, KeyValidity::new(entry.activation, entry.expiry));
+        }
+        Ok(registry)
+    }
+
+    /// Returns the next value of the recency clock.
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+impl From<VkRegistry> for SharedVkRegistry {
+    fn from(registry: VkRegistry) -> Self {
+        let shared = Self::new();
+        for (_, entry) in registry.entries() {
+            shared.register(entry.vk().clone(), entry.validity());
+        }
+        shared
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use ark_std::test_rng;
+    use proof_of_sql::proof_primitive::dory::PublicParameters;
+
+    use super::*;
+
+    fn keys(count: usize) -> Vec<VerificationKey> {
+        let rng = &mut test_rng();
+        (0..count)
+            .map(|_| VerificationKey::new(&PublicParameters::test_rand(1, rng), 1))
+            .collect()
+    }
+
+    #[test]
+    fn evicts_least_recently_used_key() {
+        let registry = SharedVkRegistry::with_capacity(NonZeroUsize::new(2).unwrap());
+        let mut keys = keys(3).into_iter();
+        let first = registry.register(keys.next().unwrap(), KeyValidity::ALWAYS);
+        let second = registry.register(keys.next().unwrap(), KeyValidity::ALWAYS);
+
+        assert!(registry.get(&first).is_some());
+        let third = registry.register(keys.next().unwrap(), KeyValidity::ALWAYS);
+
+        assert_eq!(registry.len(), 2);
+        assert!(registry.get(&second).is_none());
+        assert!(registry.get(&first).is_some());
+        assert!(registry.get(&third).is_some());
+        assert_eq!(
+            registry.metrics(),
+            RegistryMetrics {
+                hits: 3,
+                misses: 1,
+                evictions: 1,
+            }
+        );
+        assert_eq!(registry.metrics().hit_rate_percent(), Some(75));
+    }
+
+    #[test]
+    fn enforces_validity() {
+        let registry = SharedVkRegistry::new();
+        let fingerprint =
+            registry.register(keys(1).remove(0), KeyValidity::new(Some(10), Some(20)));
+
+        assert!(matches!(
+            registry.get_active(&fingerprint, 5),
+            Err(VerifyError::VkNotYetActive)
+        ));
+        assert!(registry.get_active(&fingerprint, 15).is_ok());
+        registry
+            .set_validity(&fingerprint, KeyValidity::new(None, Some(15)))
+            .unwrap();
+        assert!(matches!(
+            registry.get_active(&fingerprint, 15),
+            Err(VerifyError::VkExpired)
+        ));
+        assert!(registry.remove(&fingerprint).is_some());
+        assert!(matches!(
+            registry.get_active(&fingerprint, 15),
+            Err(VerifyError::InvalidVerificationKey)
+        ));
+    }
+
+    #[test]
+    fn concurrent_lookups() {
+        let registry = SharedVkRegistry::new();
+        let fingerprints: Vec<_> = keys(4)
+            .into_iter()
+            .map(|vk| registry.register(vk, KeyValidity::ALWAYS))
+            .collect();
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for fingerprint in fingerprints.iter().cycle().take(100) {
+                        let entry = registry.get(fingerprint).unwrap();
+                        assert_eq!(entry.vk().fingerprint(), *fingerprint);
+                    }
+                });
+            }
+        });
+        assert_eq!(registry.metrics().hits, 400);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("posql-registry-{}", std::process::id()));
+        let registry = SharedVkRegistry::new();
+        let mut keys = keys(2).into_iter();
+        let always = registry.register(keys.next().unwrap(), KeyValidity::ALWAYS);
+        let bounded = registry.register(keys.next().unwrap(), KeyValidity::new(Some(1), Some(2)));
+        registry.save(&path).unwrap();
+
+        let loaded = SharedVkRegistry::load(&path, None).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get(&always).unwrap().validity(), KeyValidity::ALWAYS);
+        assert_eq!(
+            loaded.get(&bounded).unwrap().validity(),
+            KeyValidity::new(Some(1), Some(2))
+        );
+        let bounded_load = SharedVkRegistry::load(&path, NonZeroUsize::new(1)).unwrap();
+        assert_eq!(bounded_load.len(), 1);
+
+        fs::write(&path, b"garbage").unwrap();
+        assert_eq!(
+            SharedVkRegistry::load(&path, None).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
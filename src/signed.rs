@@ -0,0 +1,243 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use ciborium::Value;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+use crate::{VerifierConfig, VerifyError};
+
+/// The domain tag prepended to the payload before signing it.
+const SIGNED_ARTIFACT_TAG: &[u8] = b"proof-of-sql-verifier/signed-artifact/v1";
+
+/// An encoded artifact, e.g. a proof or a public input, signed by the prover that
+/// produced it.
+///
+/// Decoding checks the ed25519 signature, so a decoded `SignedArtifact` always carries
+/// a payload signed by its `signer_pubkey`. Deployments only accepting artifacts from
+/// registered provers decode them with
+/// [`SignedArtifact::try_from_bytes_with_config`], which also checks the signer against
+/// the [trusted provers](VerifierConfig::with_trusted_prover).
+///
+/// The signature covers the tag `proof-of-sql-verifier/signed-artifact/v1` followed by
+/// the payload, so that it can't be confused with signatures made for other purposes.
+/// Signed artifacts are serialized as a CBOR array of the payload, the public key and
+/// the signature, each as a byte string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedArtifact {
+    payload: Vec<u8>,
+    signer_pubkey: [u8; 32],
+    signature: [u8; 64],
+}
+
+impl TryFrom<&[u8]> for SignedArtifact {
+    type Error = VerifyError;
+
+    /// Decodes a signed artifact, checking its signature.
+    ///
+    /// Returns `InvalidInput` if the bytes are not a signed artifact, or are followed by
+    /// trailing bytes, and `InvalidSignature` if the signature doesn't match the payload.
+    fn try_from(bytes: &[u8]) -> Result<Self, VerifyError> {
+        let mut remaining = bytes;
+        let Ok(Value::Array(fields)) = ciborium::from_reader(&mut remaining) else {
+            return Err(VerifyError::InvalidInput);
+        };
+        if !remaining.is_empty() {
+            return Err(VerifyError::InvalidInput);
+        }
+        let [Value::Bytes(payload), Value::Bytes(signer_pubkey), Value::Bytes(signature)] =
+            <[Value; 3]>::try_from(fields).map_err(|_| VerifyError::InvalidInput)?
+        else {
+            return Err(VerifyError::InvalidInput);
+        };
+        let artifact = Self {
+            payload,
+            signer_pubkey: signer_pubkey
+                .try_into()
+                .map_err(|_| VerifyError::InvalidInput)?,
+            signature: signature
+                .try_into()
+                .map_err(|_| VerifyError::InvalidInput)?,
+        };
+        artifact.check_signature()?;
+        Ok(artifact)
+    }
+}
+
+impl SignedArtifact {
+    /// Signs an encoded artifact with the ed25519 secret key of the prover.
+    pub fn sign(payload: Vec<u8>, secret_key: &[u8; 32]) -> Self {
+        let signing_key = SigningKey::from_bytes(secret_key);
+        let signature = signing_key.sign(&signed_message(&payload)).to_bytes();
+        Self {
+            payload,
+            signer_pubkey: signing_key.verifying_key().to_bytes(),
+            signature,
+        }
+    }
+
+    /// Decodes a signed artifact, checking its signature and that it was signed by one
+    /// of the trusted provers of `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidSignature` if the signature doesn't match the payload, and
+    /// `UntrustedSigner` if the signer is not a trusted prover, which is always the case
+    /// when the configuration has none.
+    pub fn try_from_bytes_with_config(
+        bytes: &[u8],
+        config: &VerifierConfig,
+    ) -> Result<Self, VerifyError> {
+        let artifact = Self::try_from(bytes)?;
+        if !config.trusted_provers().contains(&artifact.signer_pubkey) {
+            return Err(VerifyError::UntrustedSigner);
+        }
+        Ok(artifact)
+    }
+
+    /// Returns the signed, encoded artifact.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Returns the signed, encoded artifact, dropping the signature.
+    pub fn into_payload(self) -> Vec<u8> {
+        self.payload
+    }
+
+    /// Returns the ed25519 public key of the signer.
+    pub fn signer_pubkey(&self) -> &[u8; 32] {
+        &self.signer_pubkey
+    }
+
+    /// Returns the ed25519 signature.
+    pub fn signature(&self) -> &[u8; 64] {
+        &self.signature
+    }
+
+    /// Converts the signed artifact into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let fields = Value::Array(Vec::from([
+            Value::Bytes(self.payload.clone()),
+            Value::Bytes(self.signer_pubkey.to_vec()),
+            Value::Bytes(self.signature.to_vec()),
+        ]));
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&fields, &mut bytes).unwrap();
+        bytes
+    }
+
+    /// Checks the signature with the strict rules, which reject small order keys and
+    /// malleable signatures.
+    fn check_signature(&self) -> Result<(), VerifyError> {
+        let key = VerifyingKey::from_bytes(&self.signer_pubkey)
+            .map_err(|_| VerifyError::InvalidSignature)?;
+        key.verify_strict(
+            &signed_message(&self.payload),
+            &Signature::from_bytes(&self.signature),
+        )
+        .map_err(|_| VerifyError::InvalidSignature)
+    }
+}
+
+/// Builds the message signed for `payload`.
+fn signed_message(payload: &[u8]) -> Vec<u8> {
+    [SIGNED_ARTIFACT_TAG, payload].concat()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SECRET_KEY: [u8; 32] = [7; 32];
+
+    fn trusting(secret_key: &[u8; 32]) -> VerifierConfig {
+        let pubkey = SigningKey::from_bytes(secret_key)
+            .verifying_key()
+            .to_bytes();
+        VerifierConfig::new().with_trusted_prover(pubkey)
+    }
+
+    #[test]
+    fn round_trip() {
+        let artifact = SignedArtifact::sign(b"proof".to_vec(), &SECRET_KEY);
+        let decoded = SignedArtifact::try_from(&artifact.to_bytes()[..]).unwrap();
+        assert_eq!(decoded, artifact);
+        assert_eq!(decoded.payload(), b"proof");
+        assert_eq!(
+            decoded.signer_pubkey(),
+            &SigningKey::from_bytes(&SECRET_KEY)
+                .verifying_key()
+                .to_bytes()
+        );
+        assert_eq!(decoded.into_payload(), b"proof");
+    }
+
+    #[test]
+    fn tampered_artifacts_are_rejected() {
+        let artifact = SignedArtifact::sign(b"proof".to_vec(), &SECRET_KEY);
+
+        let mut payload = artifact.clone();
+        payload.payload[0] ^= 1;
+        assert!(matches!(
+            SignedArtifact::try_from(&payload.to_bytes()[..]),
+            Err(VerifyError::InvalidSignature)
+        ));
+
+        let mut signature = artifact.clone();
+        signature.signature[0] ^= 1;
+        assert!(matches!(
+            SignedArtifact::try_from(&signature.to_bytes()[..]),
+            Err(VerifyError::InvalidSignature)
+        ));
+
+        let mut signer = artifact.clone();
+        signer.signer_pubkey = *SignedArtifact::sign(Vec::new(), &[8; 32]).signer_pubkey();
+        assert!(matches!(
+            SignedArtifact::try_from(&signer.to_bytes()[..]),
+            Err(VerifyError::InvalidSignature)
+        ));
+
+        let bytes = artifact.to_bytes();
+        assert!(matches!(
+            SignedArtifact::try_from(&bytes[..bytes.len() - 1]),
+            Err(VerifyError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let mut bytes = SignedArtifact::sign(b"proof".to_vec(), &SECRET_KEY).to_bytes();
+        bytes.push(0);
+        assert!(matches!(
+            SignedArtifact::try_from(&bytes[..]),
+            Err(VerifyError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn only_trusted_signers_are_accepted() {
+        let bytes = SignedArtifact::sign(b"proof".to_vec(), &SECRET_KEY).to_bytes();
+        assert!(SignedArtifact::try_from_bytes_with_config(&bytes, &trusting(&SECRET_KEY)).is_ok());
+        assert!(matches!(
+            SignedArtifact::try_from_bytes_with_config(&bytes, &trusting(&[8; 32])),
+            Err(VerifyError::UntrustedSigner)
+        ));
+        assert!(matches!(
+            SignedArtifact::try_from_bytes_with_config(&bytes, &VerifierConfig::new()),
+            Err(VerifyError::UntrustedSigner)
+        ));
+    }
+}
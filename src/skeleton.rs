@@ -0,0 +1,367 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+use proof_of_sql::{
+    base::{commitment::ColumnCommitmentMetadataMap, database::TableRef},
+    proof_primitive::dory::DoryCommitment,
+    sql::proof_plans::DynProofPlan,
+};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    cost::scanned_cells,
+    verify::{check_columns_metadata, check_offsets, check_table_sizes},
+    PlanInfo, PlanLimits, QueryCost, TableId, VerificationKey, VerifierConfig, VerifyError,
+};
+
+/// The domain tag prepended to the encoded skeleton by [`StatementSkeleton::fingerprint`].
+const SKELETON_FINGERPRINT_TAG: &[u8] = b"proof-of-sql-verifier/statement-skeleton/v1";
+
+/// The shape of a committed table: its row range and the metadata of its columns.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSkeleton {
+    column_commitments: ColumnMetadata,
+    range: Range<usize>,
+}
+
+/// The column metadata of a table commitment, without the commitments.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ColumnMetadata {
+    column_metadata: ColumnCommitmentMetadataMap,
+}
+
+impl TableSkeleton {
+    /// Returns the range of rows the table commitment covers.
+    pub fn range(&self) -> &Range<usize> {
+        &self.range
+    }
+
+    /// Returns the number of rows of the table.
+    pub fn num_rows(&self) -> usize {
+        self.range.len()
+    }
+
+    /// Returns the metadata of the committed columns.
+    pub fn column_metadata(&self) -> &ColumnCommitmentMetadataMap {
+        &self.column_commitments.column_metadata
+    }
+}
+
+/// A statement whose table commitments only hold column metadata, without the
+/// commitment points.
+///
+/// Metadata-only payloads are encoded like a [`Statement`](crate::Statement), each table
+/// commitment lacking its `commitments`. They can't be verified against, and decoding
+/// them as a [`PublicInput`](crate::PublicInput) fails with `MetadataOnlyCommitment`, but
+/// tooling can still run the schema and policy checks, fingerprint the statement and
+/// estimate its cost from a skeleton.
+///
+/// Full public inputs and statements also decode as skeletons, the commitment points
+/// being ignored, so they have the same fingerprint as their metadata-only payload.
+#[derive(Serialize)]
+pub struct StatementSkeleton {
+    expr: DynProofPlan<DoryCommitment>,
+    #[serde(serialize_with = "serialize_tables")]
+    commitments: Vec<(TableRef, TableSkeleton)>,
+}
+
+/// Serializes the tables as a map, like table commitments.
+fn serialize_tables<S: serde::Serializer>(
+    tables: &[(TableRef, TableSkeleton)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(tables.iter().map(|(table_ref, table)| (table_ref, table)))
+}
+
+/// Wire representation of a [`StatementSkeleton`].
+#[derive(Deserialize)]
+struct StatementSkeletonRepr {
+    expr: DynProofPlan<DoryCommitment>,
+    #[serde(deserialize_with = "crate::serde::deserialize_entries")]
+    commitments: Vec<(TableRef, TableSkeleton)>,
+}
+
+impl TryFrom<&[u8]> for StatementSkeleton {
+    type Error = VerifyError;
+
+    /// Decodes a skeleton, from a metadata-only payload or a full statement or public
+    /// input.
+    ///
+    /// Returns `DuplicateTableCommitment` if a table appears more than once, and
+    /// `InvalidInput` if the bytes are malformed.
+    fn try_from(bytes: &[u8]) -> Result<Self, VerifyError> {
+        let repr: StatementSkeletonRepr =
+            ciborium::from_reader(bytes).map_err(|_| VerifyError::InvalidInput)?;
+        PlanLimits::DEFAULT.check(&repr.expr)?;
+        let mut commitments = repr.commitments;
+        if commitments
+            .iter()
+            .any(|(_, table)| table.range.start > table.range.end)
+        {
+            return Err(VerifyError::InvalidInput);
+        }
+        // Sort like committed tables are serialized, so the encoding is canonical
+        commitments.sort_by_key(|(table_ref, _)| (table_ref.schema_id(), table_ref.table_id()));
+        if let Some(pair) = commitments.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(VerifyError::DuplicateTableCommitment {
+                table: TableId::from(pair[0].0),
+            });
+        }
+        Ok(Self {
+            expr: repr.expr,
+            commitments,
+        })
+    }
+}
+
+impl StatementSkeleton {
+    /// Returns a reference to the proof expression.
+    pub fn expr(&self) -> &DynProofPlan<DoryCommitment> {
+        &self.expr
+    }
+
+    /// Analyzes the proof plan.
+    pub fn plan_info(&self) -> PlanInfo {
+        PlanInfo::new(&self.expr)
+    }
+
+    /// Returns the committed tables, sorted by table reference.
+    pub fn tables(&self) -> impl Iterator<Item = (&TableRef, &TableSkeleton)> {
+        self.commitments
+            .iter()
+            .map(|(table_ref, table)| (table_ref, table))
+    }
+
+    /// Returns the shape of a committed table, if any.
+    pub fn table(&self, table_ref: &TableRef) -> Option<&TableSkeleton> {
+        self.commitments
+            .iter()
+            .find(|(t, _)| t == table_ref)
+            .map(|(_, table)| table)
+    }
+
+    /// Runs the checks of [`precheck`](crate::precheck) that only need column metadata:
+    /// the plan columns must be bound to the tables, and the verification key must be
+    /// large enough for them.
+    pub fn precheck(&self, vk: &VerificationKey) -> Result<(), VerifyError> {
+        check_columns_metadata(&self.expr, |table_ref| {
+            self.table(table_ref).map(TableSkeleton::column_metadata)
+        })?;
+        check_table_sizes(&self.expr, vk, |table_ref| {
+            self.table(table_ref).map(TableSkeleton::num_rows)
+        })
+    }
+
+    /// Runs the checks of [`StatementSkeleton::precheck`], additionally checking the plan
+    /// against the configured limits and policy, and the tables against the configured
+    /// offsets.
+    pub fn precheck_with_config(
+        &self,
+        vk: &VerificationKey,
+        config: &VerifierConfig,
+    ) -> Result<(), VerifyError> {
+        config.plan_limits().check(&self.expr)?;
+        config.plan_policy().check(&self.plan_info())?;
+        check_offsets(config, |table_ref| {
+            self.table(table_ref).map(|table| table.range.start)
+        })?;
+        self.precheck(vk)
+    }
+
+    /// Estimates the cost of the query. The result is unknown, so only the scanned cells
+    /// are counted.
+    pub fn cost(&self) -> QueryCost {
+        QueryCost {
+            scanned_cells: scanned_cells(&self.expr, |table_ref| {
+                self.table(table_ref).map(TableSkeleton::num_rows)
+            }),
+            result_cells: 0,
+            proof_size: None,
+        }
+    }
+
+    /// Converts the skeleton into its metadata-only payload.
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>, VerifyError> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(self, &mut bytes).map_err(|_| VerifyError::InvalidInput)?;
+        Ok(bytes)
+    }
+
+    /// Computes the Keccak-256 digest of the tag
+    /// `proof-of-sql-verifier/statement-skeleton/v1` followed by the metadata-only
+    /// payload.
+    ///
+    /// The fingerprint identifies the plan and the shape of the tables, not their
+    /// content: statements over tables of the same shape share it.
+    pub fn fingerprint(&self) -> Result<[u8; 32], VerifyError> {
+        let mut hasher = Keccak256::new();
+        hasher.update(SKELETON_FINGERPRINT_TAG);
+        hasher.update(self.try_to_bytes()?);
+        Ok(hasher.finalize().into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use ciborium::Value;
+    use proof_of_sql::{
+        base::{
+            commitment::{QueryCommitments, QueryCommitmentsExt},
+            database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+        },
+        proof_primitive::dory::{
+            DoryEvaluationProof, DoryProverPublicSetup, ProverSetup, PublicParameters,
+        },
+        sql::{
+            parse::QueryExpr,
+            proof::{ProofPlan, VerifiableQueryResult},
+        },
+    };
+
+    use super::*;
+    use crate::PublicInput;
+
+    fn build_public_input() -> (PublicInput, VerificationKey) {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let vk = VerificationKey::new(&public_parameters, 4);
+
+        let mut accessor =
+            OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+        accessor.add_table(
+            "sxt.table".parse().unwrap(),
+            owned_table([
+                bigint("a", [1, 2, 3, 2]),
+                varchar("b", ["x", "y", "z", "w"]),
+            ]),
+            0,
+        );
+        let query = QueryExpr::try_new(
+            "SELECT b FROM table WHERE a = 2".parse().unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &vk.to_dory())
+            .unwrap();
+        let columns = query.proof_expr().get_column_references();
+        let commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+        let pubs = PublicInput::new(query.proof_expr(), commitments, query_data);
+        (pubs, vk)
+    }
+
+    fn field_mut<'a>(value: &'a mut Value, name: &str) -> &'a mut Value {
+        value
+            .as_map_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some(name))
+            .map(|(_, field)| field)
+            .unwrap()
+    }
+
+    /// Removes the commitment points from the table commitments of an encoded public
+    /// input.
+    fn strip_commitments(bytes: &[u8]) -> Vec<u8> {
+        let mut value: Value = ciborium::from_reader(bytes).unwrap();
+        for (_, commitment) in field_mut(&mut value, "commitments").as_map_mut().unwrap() {
+            field_mut(commitment, "column_commitments")
+                .as_map_mut()
+                .unwrap()
+                .retain(|(key, _)| key.as_text() != Some("commitments"));
+        }
+        let mut stripped = Vec::new();
+        ciborium::into_writer(&value, &mut stripped).unwrap();
+        stripped
+    }
+
+    #[test]
+    fn metadata_only_payloads_decode_as_skeletons() {
+        let (pubs, _) = build_public_input();
+        let bytes = pubs.try_to_bytes().unwrap();
+        let metadata_only = strip_commitments(&bytes);
+        assert!(matches!(
+            PublicInput::try_from(&metadata_only[..]),
+            Err(VerifyError::MetadataOnlyCommitment { .. })
+        ));
+
+        let skeleton = StatementSkeleton::try_from(&metadata_only[..]).unwrap();
+        let full = StatementSkeleton::try_from(&bytes[..]).unwrap();
+        assert_eq!(skeleton.fingerprint().unwrap(), full.fingerprint().unwrap());
+        let table_ref: TableRef = "sxt.table".parse().unwrap();
+        let table = skeleton.table(&table_ref).unwrap();
+        assert_eq!(table.range(), &(0..4));
+        assert_eq!(table.column_metadata().len(), 2);
+        assert_eq!(skeleton.tables().count(), 1);
+
+        let decoded = StatementSkeleton::try_from(&skeleton.try_to_bytes().unwrap()[..]).unwrap();
+        assert_eq!(
+            decoded.fingerprint().unwrap(),
+            skeleton.fingerprint().unwrap()
+        );
+        assert_eq!(
+            skeleton.cost().scanned_cells,
+            QueryCost::of(&pubs).scanned_cells
+        );
+        assert_eq!(skeleton.plan_info(), pubs.plan_info());
+    }
+
+    #[test]
+    fn skeletons_are_prechecked() {
+        let (pubs, vk) = build_public_input();
+        let skeleton = StatementSkeleton::try_from(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        assert!(skeleton.precheck(&vk).is_ok());
+        assert!(skeleton
+            .precheck_with_config(&vk, &VerifierConfig::new())
+            .is_ok());
+
+        let small_vk = VerificationKey::new(&PublicParameters::test_rand(1, &mut test_rng()), 0);
+        assert!(matches!(
+            skeleton.precheck(&small_vk),
+            Err(VerifyError::TableTooLarge { rows: 4, .. })
+        ));
+        let config =
+            VerifierConfig::new().with_table_offset(TableRef::new("sxt.table".parse().unwrap()), 2);
+        assert!(matches!(
+            skeleton.precheck_with_config(&vk, &config),
+            Err(VerifyError::OffsetMismatch { actual: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn duplicate_tables_are_rejected() {
+        let (pubs, _) = build_public_input();
+        let mut value: Value = ciborium::from_reader(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        let commitments = field_mut(&mut value, "commitments").as_map_mut().unwrap();
+        commitments.push(commitments[0].clone());
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&value, &mut bytes).unwrap();
+        assert!(matches!(
+            StatementSkeleton::try_from(&bytes[..]),
+            Err(VerifyError::DuplicateTableCommitment { .. })
+        ));
+    }
+}
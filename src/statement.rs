@@ -0,0 +1,127 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use proof_of_sql::{
+    base::{commitment::QueryCommitments, database::TableRef},
+    proof_primitive::dory::DoryCommitment,
+    sql::proof_plans::DynProofPlan,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commitments::{collect_commitments, validate_commitments, UncheckedTableCommitment},
+    VerifyError,
+};
+
+/// The statement a proof is verified against without claiming its result, i.e. the
+/// proof plan and the commitments of the tables it queries.
+///
+/// See [`verify_and_extract`](crate::verify_and_extract). Table commitments are
+/// serialized in canonical order, like in [`PublicInput`](crate::PublicInput).
+#[derive(Serialize, Deserialize)]
+#[serde(try_from = "StatementRepr")]
+pub struct Statement {
+    expr: DynProofPlan<DoryCommitment>,
+    #[serde(serialize_with = "crate::serde::serialize_sorted_commitments")]
+    commitments: QueryCommitments<DoryCommitment>,
+}
+
+/// A borrowed [`Statement`], serialized the same way, e.g. to hash the statement of a
+/// public input without copying its plan.
+#[cfg(feature = "binding")]
+#[derive(Serialize)]
+pub(crate) struct StatementRef<'a> {
+    pub(crate) expr: &'a DynProofPlan<DoryCommitment>,
+    #[serde(serialize_with = "serialize_commitments_ref")]
+    pub(crate) commitments: &'a QueryCommitments<DoryCommitment>,
+}
+
+#[cfg(feature = "binding")]
+fn serialize_commitments_ref<S: serde::Serializer>(
+    commitments: &&QueryCommitments<DoryCommitment>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    crate::serde::serialize_sorted_commitments(commitments, serializer)
+}
+
+/// Wire representation of a [`Statement`], see `PublicInputRepr`.
+#[derive(Deserialize)]
+struct StatementRepr {
+    expr: DynProofPlan<DoryCommitment>,
+    #[serde(deserialize_with = "crate::serde::deserialize_entries")]
+    commitments: Vec<(TableRef, UncheckedTableCommitment)>,
+}
+
+impl TryFrom<StatementRepr> for Statement {
+    type Error = VerifyError;
+
+    fn try_from(repr: StatementRepr) -> Result<Self, VerifyError> {
+        let commitments = collect_commitments(validate_commitments(repr.commitments, None)?)?;
+        Ok(Self::from_parts(repr.expr, commitments))
+    }
+}
+
+impl TryFrom<&[u8]> for Statement {
+    type Error = VerifyError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, VerifyError> {
+        ciborium::from_reader(bytes).map_err(|_| VerifyError::InvalidInput)
+    }
+}
+
+impl Statement {
+    /// Creates a new statement.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The query plan for proving a query.
+    /// * `commitments` - The query commitments.
+    pub fn new(
+        expr: &DynProofPlan<DoryCommitment>,
+        commitments: QueryCommitments<DoryCommitment>,
+    ) -> Self {
+        // Copy trait is not implemented for ProofPlan, so we serialize and deserialize
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&expr, &mut bytes).unwrap();
+        let expr: DynProofPlan<DoryCommitment> = ciborium::from_reader(&bytes[..]).unwrap();
+        Self::from_parts(expr, commitments)
+    }
+
+    /// Creates a statement from owned parts.
+    pub(crate) fn from_parts(
+        expr: DynProofPlan<DoryCommitment>,
+        commitments: QueryCommitments<DoryCommitment>,
+    ) -> Self {
+        Self { expr, commitments }
+    }
+
+    /// Returns a reference to the proof expression.
+    pub fn expr(&self) -> &DynProofPlan<DoryCommitment> {
+        &self.expr
+    }
+
+    /// Returns a reference to the query commitments.
+    pub fn commitments(&self) -> &QueryCommitments<DoryCommitment> {
+        &self.commitments
+    }
+
+    /// Converts the statement into a byte array.
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>, VerifyError> {
+        let mut result = Vec::new();
+        ciborium::into_writer(self, &mut result).map_err(|_| VerifyError::InvalidInput)?;
+        Ok(result)
+    }
+}
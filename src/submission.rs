@@ -0,0 +1,159 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loading and saving submissions laid out the way zkVerify tooling expects them.
+//!
+//! A submission is a directory holding the serialized proof in `proof.bin`, the public
+//! input in `pubs.bin`, and either the verification key in `vk.bin` or, for keys
+//! registered beforehand, the 32 bytes of its fingerprint in `vk_hash.bin`.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use sha3::{Digest, Keccak256};
+use snafu::Snafu;
+
+use crate::{Proof, PublicInput, VerificationKey, VerifyError};
+
+/// The file holding the proof of a submission.
+pub const PROOF_FILE: &str = "proof.bin";
+/// The file holding the public input of a submission.
+pub const PUBS_FILE: &str = "pubs.bin";
+/// The file holding the verification key of a submission.
+pub const VK_FILE: &str = "vk.bin";
+/// The file holding the fingerprint of the verification key of a submission.
+pub const VK_HASH_FILE: &str = "vk_hash.bin";
+
+/// The verification key of a submission, either referenced or included.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VkRefOrBytes {
+    /// The fingerprint of a registered key, see [`VerificationKey::fingerprint`].
+    Ref([u8; 32]),
+    /// The serialized key.
+    Bytes(Vec<u8>),
+}
+
+impl VkRefOrBytes {
+    /// Returns the fingerprint of the key.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        match self {
+            VkRefOrBytes::Ref(fingerprint) => *fingerprint,
+            VkRefOrBytes::Bytes(bytes) => Keccak256::digest(bytes).into(),
+        }
+    }
+}
+
+impl From<&VerificationKey> for VkRefOrBytes {
+    fn from(vk: &VerificationKey) -> Self {
+        VkRefOrBytes::Bytes(vk.to_bytes())
+    }
+}
+
+/// Errors raised while loading or saving a submission.
+#[derive(Debug, Snafu)]
+pub enum SubmissionError {
+    /// A file of the submission can't be read or written.
+    #[snafu(display("Cannot access {}: {source}", path.display()))]
+    Io { path: PathBuf, source: io::Error },
+    /// An artifact of the submission can't be encoded or decoded.
+    #[snafu(display("Invalid {file}: {source}"))]
+    InvalidArtifact {
+        file: &'static str,
+        source: VerifyError,
+    },
+    /// The submission has either both or none of the verification key files.
+    #[snafu(display("Expected exactly one of {VK_FILE} and {VK_HASH_FILE}"))]
+    AmbiguousVk,
+}
+
+/// Loads the submission stored in `dir`.
+pub fn load_submission(
+    dir: impl AsRef<Path>,
+) -> Result<(Proof, PublicInput, VkRefOrBytes), SubmissionError> {
+    let dir = dir.as_ref();
+    let proof = Proof::try_from(&read(dir, PROOF_FILE)?[..]).map_err(|source| {
+        SubmissionError::InvalidArtifact {
+            file: PROOF_FILE,
+            source,
+        }
+    })?;
+    let pubs = PublicInput::try_from(&read(dir, PUBS_FILE)?[..]).map_err(|source| {
+        SubmissionError::InvalidArtifact {
+            file: PUBS_FILE,
+            source,
+        }
+    })?;
+    let vk = match (dir.join(VK_FILE).exists(), dir.join(VK_HASH_FILE).exists()) {
+        (true, false) => VkRefOrBytes::Bytes(read(dir, VK_FILE)?),
+        (false, true) => {
+            let fingerprint = read(dir, VK_HASH_FILE)?.try_into().map_err(|_| {
+                SubmissionError::InvalidArtifact {
+                    file: VK_HASH_FILE,
+                    source: VerifyError::InvalidVerificationKey,
+                }
+            })?;
+            VkRefOrBytes::Ref(fingerprint)
+        }
+        _ => return Err(SubmissionError::AmbiguousVk),
+    };
+    Ok((proof, pubs, vk))
+}
+
+/// Saves a submission to `dir`, creating the directory if needed.
+pub fn save_submission(
+    dir: impl AsRef<Path>,
+    proof: &Proof,
+    pubs: &PublicInput,
+    vk: &VkRefOrBytes,
+) -> Result<(), SubmissionError> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir).map_err(|source| SubmissionError::Io {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    let pubs = pubs
+        .try_to_bytes()
+        .map_err(|source| SubmissionError::InvalidArtifact {
+            file: PUBS_FILE,
+            source,
+        })?;
+    write(dir, PROOF_FILE, &proof.to_bytes())?;
+    write(dir, PUBS_FILE, &pubs)?;
+    let (file, bytes, stale) = match vk {
+        VkRefOrBytes::Ref(fingerprint) => (VK_HASH_FILE, &fingerprint[..], VK_FILE),
+        VkRefOrBytes::Bytes(bytes) => (VK_FILE, &bytes[..], VK_HASH_FILE),
+    };
+    write(dir, file, bytes)?;
+    // A key file left over from a previous submission would make the directory ambiguous
+    let path = dir.join(stale);
+    match fs::remove_file(&path) {
+        Err(source) if source.kind() != io::ErrorKind::NotFound => {
+            Err(SubmissionError::Io { path, source })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn read(dir: &Path, file: &str) -> Result<Vec<u8>, SubmissionError> {
+    let path = dir.join(file);
+    fs::read(&path).map_err(|source| SubmissionError::Io { path, source })
+}
+
+fn write(dir: &Path, file: &str, bytes: &[u8]) -> Result<(), SubmissionError> {
+    let path = dir.join(file);
+    fs::write(&path, bytes).map_err(|source| SubmissionError::Io { path, source })
+}
@@ -0,0 +1,215 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Opt-in counters of verification outcomes.
+//!
+//! Nothing is recorded unless the caller passes an [`OutcomeSink`], which services
+//! implement on top of their own metrics system. Labels only describe the verifier and
+//! the outcome, never the statement, so that counters stay anonymous. Slow
+//! verifications are the exception: they are reported with the fingerprint of their
+//! plan, see [`SlowVerification`].
+
+use core::time::Duration;
+
+use crate::{ProofStage, PublicInput, VerificationKey, VerifyError};
+
+/// The only proof scheme supported by the verifier.
+pub const DORY_SCHEME: &str = "dory";
+
+/// The labels of a verification outcome.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OutcomeLabels {
+    /// The proof scheme, always [`DORY_SCHEME`].
+    pub scheme: &'static str,
+    /// The maximum nu of the verification key.
+    pub nu: usize,
+    /// `"verified"` or `"rejected"`.
+    pub outcome: &'static str,
+    /// The reason of a rejection, see [`rejection_reason`].
+    pub reason: Option<&'static str>,
+}
+
+impl OutcomeLabels {
+    /// Builds the labels of the outcome of a verification with `vk`.
+    pub fn new(vk: &VerificationKey, result: &Result<(), VerifyError>) -> Self {
+        Self {
+            scheme: DORY_SCHEME,
+            nu: vk.max_nu(),
+            outcome: if result.is_ok() {
+                "verified"
+            } else {
+                "rejected"
+            },
+            reason: result.as_ref().err().map(rejection_reason),
+        }
+    }
+}
+
+/// A verification that took longer than the
+/// [slow verification threshold](crate::VerifierConfig::with_slow_verification_threshold).
+///
+/// Records identify the query shape through the plan fingerprint, so that operators can
+/// find the pathological plans among their traffic without logging whole statements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SlowVerification {
+    /// The time the verification took.
+    pub duration: Duration,
+    /// The fingerprint of the plan, see [`PublicInput::plan_fingerprint`], or `None` if
+    /// the plan can't be serialized.
+    pub plan_fingerprint: Option<[u8; 32]>,
+    /// The maximum nu of the verification key.
+    pub nu: usize,
+    /// The number of rows of the claimed result.
+    pub result_rows: usize,
+    /// The number of columns of the claimed result.
+    pub result_columns: usize,
+}
+
+impl SlowVerification {
+    /// Builds the record of a verification of `pubs` with `vk` that took `duration`.
+    pub fn new(pubs: &PublicInput, vk: &VerificationKey, duration: Duration) -> Self {
+        let result = &pubs.query_data().table;
+        Self {
+            duration,
+            plan_fingerprint: pubs.plan_fingerprint().ok(),
+            nu: vk.max_nu(),
+            result_rows: result.num_rows(),
+            result_columns: result.num_columns(),
+        }
+    }
+}
+
+/// A receiver of verification outcomes, e.g. counters exported to a metrics system.
+pub trait OutcomeSink {
+    /// Records one verification outcome.
+    fn record(&self, labels: &OutcomeLabels);
+
+    /// Records a verification that exceeded the slow verification threshold, before its
+    /// outcome is recorded. Slow verifications are ignored by default.
+    fn record_slow(&self, _record: &SlowVerification) {}
+}
+
+/// Records the outcome of a verification with `vk` to `sink`, returning the result
+/// unchanged.
+pub fn record_outcome(
+    sink: &dyn OutcomeSink,
+    vk: &VerificationKey,
+    result: Result<(), VerifyError>,
+) -> Result<(), VerifyError> {
+    sink.record(&OutcomeLabels::new(vk, &result));
+    result
+}
+
+/// Returns a stable label naming the kind of `error`, free of any data it carries.
+pub fn rejection_reason(error: &VerifyError) -> &'static str {
+    // No wildcard, so that new errors can't be added without a label
+    match error {
+        VerifyError::InvalidInput => "invalid_input",
+        VerifyError::InvalidProofData => "invalid_proof_data",
+        VerifyError::VerificationFailed => "verification_failed",
+        VerifyError::ProofRejected { stage } => match stage {
+            ProofStage::Shape => "proof_rejected_shape",
+            ProofStage::Sumcheck => "proof_rejected_sumcheck",
+            ProofStage::ResultEvaluation => "proof_rejected_result_evaluation",
+            ProofStage::Opening => "proof_rejected_opening",
+            ProofStage::Other => "proof_rejected",
+        },
+        VerifyError::NonCanonicalProofEncoding => "non_canonical_proof_encoding",
+        VerifyError::InvalidVerificationKey => "invalid_verification_key",
+        VerifyError::InvalidVerificationKeyChunk { .. } => "invalid_verification_key_chunk",
+        VerifyError::VkNotYetActive => "vk_not_yet_active",
+        VerifyError::VkExpired => "vk_expired",
+        VerifyError::InvalidAttestation => "invalid_attestation",
+        VerifyError::MissingTableCommitment { .. } => "missing_table_commitment",
+        VerifyError::MissingColumnMetadata { .. } => "missing_column_metadata",
+        VerifyError::ColumnTypeMismatch { .. } => "column_type_mismatch",
+        VerifyError::DuplicateTableCommitment { .. } => "duplicate_table_commitment",
+        VerifyError::ConflictingTableCommitment { .. } => "conflicting_table_commitment",
+        VerifyError::NonCanonicalIdentifier { .. } => "non_canonical_identifier",
+        VerifyError::TableTooLarge { .. } => "table_too_large",
+        VerifyError::ArtifactTooLarge { .. } => "artifact_too_large",
+        VerifyError::SchemaNotAllowed { .. } => "schema_not_allowed",
+        VerifyError::PlanTooComplex => "plan_too_complex",
+        VerifyError::DegenerateCommitment { .. } => "degenerate_commitment",
+        VerifyError::OffsetMismatch { .. } => "offset_mismatch",
+        VerifyError::UnsupportedProverVersion { .. } => "unsupported_prover_version",
+        VerifyError::UnverifiableEmptyResult => "unverifiable_empty_result",
+        VerifyError::InvalidPageInclusion => "invalid_page_inclusion",
+        VerifyError::InvalidResultChunk => "invalid_result_chunk",
+        VerifyError::InvalidSignature => "invalid_signature",
+        VerifyError::UntrustedSigner => "untrusted_signer",
+        VerifyError::DigestOnlyCommitment { .. } => "digest_only_commitment",
+        VerifyError::MetadataOnlyCommitment { .. } => "metadata_only_commitment",
+        VerifyError::ColumnCommitmentMismatch => "column_commitment_mismatch",
+        VerifyError::ResultPayloadMismatch => "result_payload_mismatch",
+        VerifyError::DuplicateSubmission => "duplicate_submission",
+        VerifyError::TooManyResultColumns { .. } => "too_many_result_columns",
+        VerifyError::VarCharTooLong { .. } => "varchar_too_long",
+        VerifyError::DecimalPrecisionTooLarge { .. } => "decimal_precision_too_large",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+
+    use ark_std::test_rng;
+    use proof_of_sql::proof_primitive::dory::PublicParameters;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<OutcomeLabels>>);
+
+    impl OutcomeSink for Recorder {
+        fn record(&self, labels: &OutcomeLabels) {
+            self.0.borrow_mut().push(*labels);
+        }
+    }
+
+    #[test]
+    fn records_outcomes_with_labels() {
+        let vk = VerificationKey::new(&PublicParameters::test_rand(3, &mut test_rng()), 2);
+        let sink = Recorder::default();
+
+        assert!(record_outcome(&sink, &vk, Ok(())).is_ok());
+        assert!(matches!(
+            record_outcome(&sink, &vk, Err(VerifyError::VerificationFailed)),
+            Err(VerifyError::VerificationFailed)
+        ));
+
+        let verified = OutcomeLabels {
+            scheme: "dory",
+            nu: 3,
+            outcome: "verified",
+            reason: None,
+        };
+        let rejected = OutcomeLabels {
+            outcome: "rejected",
+            reason: Some("verification_failed"),
+            ..verified
+        };
+        assert_eq!(*sink.0.borrow(), [verified, rejected]);
+    }
+
+    #[test]
+    fn reasons_omit_error_data() {
+        let error = VerifyError::NonCanonicalIdentifier {
+            identifier: "Secret".into(),
+        };
+        assert_eq!(rejection_reason(&error), "non_canonical_identifier");
+    }
+}
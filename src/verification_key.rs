@@ -14,27 +14,61 @@
 // limitations under the License.
 
 use alloc::vec::Vec;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Valid};
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
 use proof_of_sql::proof_primitive::dory::{
     DoryVerifierPublicSetup, PublicParameters, VerifierSetup,
 };
+use sha3::{Digest, Keccak256};
 
-use crate::VerifyError;
+use crate::{debug::ShortDigest, VerifyError};
 
 const GT_SERIALIZED_SIZE: usize = 576;
 const G1_AFFINE_SERIALIZED_SIZE: usize = 48;
 const G2_AFFINE_SERIALIZED_SIZE: usize = 96;
 
+/// The size of the header of a key chunk: its index and the number of chunks, as
+/// little-endian `u32`s, the key fingerprint and the Keccak-256 digest of the payload.
+const CHUNK_HEADER_SIZE: usize = 4 + 4 + 32 + 32;
+
 /// Represents a verification key for Dory proofs.
 ///
 /// This structure wraps a `VerifierSetup` and provides methods for
 /// creating, deserializing, and converting the verification key.
-#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+///
+/// Decoding a `VerificationKey`, or building one from an upstream setup, checks that its
+/// group elements lie in the right subgroups and that its sigma is supported by its
+/// setup. Keys decoded without these checks are [`UnvalidatedVk`]s, which must be
+/// validated before they can verify anything. [`VerificationKey::new`] trusts its
+/// arguments instead: a sigma its parameters don't support is only rejected when
+/// verifying, with `InvalidVerificationKey`.
+///
+/// `Debug` prints the key parameters and fingerprint instead of the group elements.
+#[derive(Clone, PartialEq, Eq, CanonicalSerialize)]
 pub struct VerificationKey {
     setup: VerifierSetup,
     sigma: usize,
 }
 
+impl Hash for VerificationKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state);
+    }
+}
+
+impl fmt::Debug for VerificationKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerificationKey")
+            .field("sigma", &self.sigma)
+            .field("max_nu", &self.max_nu())
+            .field("fingerprint", &ShortDigest(self.fingerprint()))
+            .finish()
+    }
+}
+
 impl TryFrom<&[u8]> for VerificationKey {
     type Error = VerifyError;
 
@@ -46,16 +80,95 @@ impl TryFrom<&[u8]> for VerificationKey {
     ///
     /// # Returns
     ///
-    /// * `Result<Self, Self::Error>` - A VerificationKey if deserialization succeeds, or a VerifyError if it fails.
+    /// * `Result<Self, Self::Error>` - A VerificationKey if deserialization and validation succeed, or a VerifyError if either fails.
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        VerificationKey::deserialize_compressed(value)
-            .map_err(|_| VerifyError::InvalidVerificationKey)
+        UnvalidatedVk::try_from(value)?.validate()
+    }
+}
+
+impl TryFrom<(VerifierSetup, usize)> for VerificationKey {
+    type Error = VerifyError;
+
+    /// Creates a VerificationKey from an upstream setup and the sigma to verify proofs
+    /// for, validating them like a decoded key, see [`UnvalidatedVk::validate`].
+    fn try_from((setup, sigma): (VerifierSetup, usize)) -> Result<Self, Self::Error> {
+        UnvalidatedVk { setup, sigma }.validate()
+    }
+}
+
+/// A verification key decoded without validation, see [`VerificationKey`].
+///
+/// Checking that the group elements of a key lie in the right subgroups is costly, so
+/// callers decoding keys they have already validated, e.g. when loading them from a
+/// trusted store, may defer it. An unvalidated key can't be used for verification:
+///
+/// ```compile_fail
+/// use proof_of_sql_verifier::{verify_proof, Proof, PublicInput, UnvalidatedVk};
+///
+/// fn verify(proof: &Proof, pubs: &PublicInput, vk: &UnvalidatedVk) {
+///     let _ = verify_proof(proof, pubs, vk);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct UnvalidatedVk {
+    setup: VerifierSetup,
+    sigma: usize,
+}
+
+/// A validated verification key: every [`VerificationKey`] is.
+pub type ValidatedVk = VerificationKey;
+
+impl TryFrom<&[u8]> for UnvalidatedVk {
+    type Error = VerifyError;
+
+    /// Decodes a serialized verification key, only checking that it is well-formed.
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut reader = value;
+        let setup = VerifierSetup::deserialize_compressed_unchecked(&mut reader)
+            .map_err(|_| VerifyError::InvalidVerificationKey)?;
+        let sigma = usize::deserialize_compressed(&mut reader)
+            .map_err(|_| VerifyError::InvalidVerificationKey)?;
+        Ok(Self { setup, sigma })
+    }
+}
+
+impl UnvalidatedVk {
+    /// Returns the sigma parameter of the key.
+    pub fn sigma(&self) -> usize {
+        self.sigma
+    }
+
+    /// Returns the maximum nu supported by the underlying setup.
+    pub fn max_nu(&self) -> usize {
+        setup_max_nu(&self.setup)
+    }
+
+    /// Checks the key, turning it into one that can verify proofs.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidVerificationKey` if a group element of the key is not in its
+    /// prime-order subgroup, or if its sigma exceeds the maximum nu of its setup.
+    pub fn validate(self) -> Result<ValidatedVk, VerifyError> {
+        self.setup
+            .check()
+            .map_err(|_| VerifyError::InvalidVerificationKey)?;
+        if self.sigma > self.max_nu() {
+            return Err(VerifyError::InvalidVerificationKey);
+        }
+        Ok(VerificationKey {
+            setup: self.setup,
+            sigma: self.sigma,
+        })
     }
 }
 
 impl VerificationKey {
     /// Creates a new VerificationKey from PublicParameters.
     ///
+    /// The sigma is not checked against the maximum nu of the parameters, see
+    /// [`VerificationKey`].
+    ///
     /// # Arguments
     ///
     /// * `params` - A reference to PublicParameters.
@@ -77,6 +190,85 @@ impl VerificationKey {
         buf
     }
 
+    /// Computes the Keccak-256 fingerprint of the serialized verification key.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        Keccak256::digest(self.to_bytes()).into()
+    }
+
+    /// Splits the serialized key into chunks of at most `chunk_size` bytes of payload,
+    /// e.g. to store it across several transactions.
+    ///
+    /// Each chunk starts with a header holding its index, the number of chunks, the
+    /// key fingerprint and a checksum of its payload, so that [`Self::from_chunks`] can
+    /// detect missing, foreign or corrupted chunks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero, or if the key needs more than `u32::MAX` chunks.
+    pub fn to_chunks(&self, chunk_size: usize) -> Vec<Vec<u8>> {
+        let bytes = self.to_bytes();
+        let fingerprint: [u8; 32] = Keccak256::digest(&bytes).into();
+        let count = u32::try_from(bytes.len().div_ceil(chunk_size)).unwrap();
+        bytes
+            .chunks(chunk_size)
+            .zip(0u32..)
+            .map(|(payload, index)| {
+                let mut chunk = Vec::with_capacity(CHUNK_HEADER_SIZE + payload.len());
+                chunk.extend_from_slice(&index.to_le_bytes());
+                chunk.extend_from_slice(&count.to_le_bytes());
+                chunk.extend_from_slice(&fingerprint);
+                chunk.extend_from_slice(&Keccak256::digest(payload));
+                chunk.extend_from_slice(payload);
+                chunk
+            })
+            .collect()
+    }
+
+    /// Reassembles a key from the chunks produced by [`Self::to_chunks`], given in any
+    /// order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidVerificationKeyChunk` for the first chunk that is malformed,
+    /// corrupted, duplicated, missing, or belongs to another key, and
+    /// `InvalidVerificationKey` if there are no chunks.
+    pub fn from_chunks(chunks: &[Vec<u8>]) -> Result<Self, VerifyError> {
+        let mut parsed = chunks
+            .iter()
+            .enumerate()
+            .map(|(position, chunk)| parse_chunk(chunk, position))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (_, expected) = parsed.first().ok_or(VerifyError::InvalidVerificationKey)?;
+        let (count, fingerprint) = (expected.count, expected.fingerprint);
+        if let Some((_, header)) = parsed
+            .iter()
+            .find(|(_, header)| header.count != count || header.fingerprint != fingerprint)
+        {
+            return Err(VerifyError::InvalidVerificationKeyChunk {
+                index: header.index,
+            });
+        }
+
+        // After sorting, the first index out of place is either duplicated or missing
+        parsed.sort_by_key(|(_, header)| header.index);
+        let mut bytes = Vec::new();
+        for (index, (payload, header)) in parsed.iter().enumerate() {
+            if header.index != index {
+                return Err(VerifyError::InvalidVerificationKeyChunk { index });
+            }
+            bytes.extend_from_slice(payload);
+        }
+        if parsed.len() != count {
+            return Err(VerifyError::InvalidVerificationKeyChunk {
+                index: parsed.len(),
+            });
+        }
+        if <[u8; 32]>::from(Keccak256::digest(&bytes)) != fingerprint {
+            return Err(VerifyError::InvalidVerificationKey);
+        }
+        Self::try_from(&bytes[..])
+    }
+
     /// Converts the VerificationKey into a DoryVerifierPublicSetup.
     ///
     /// # Returns
@@ -86,8 +278,59 @@ impl VerificationKey {
         DoryVerifierPublicSetup::new(&self.setup, self.sigma)
     }
 
+    /// Returns the upstream setup of the key, e.g. to call proof-of-sql APIs directly.
+    pub fn verifier_setup(&self) -> &VerifierSetup {
+        &self.setup
+    }
+
+    /// Splits the key into its upstream setup and sigma, the inverse of the conversion
+    /// from `(VerifierSetup, usize)`.
+    pub fn into_parts(self) -> (VerifierSetup, usize) {
+        (self.setup, self.sigma)
+    }
+
+    /// Returns the key of the same setup verifying proofs for `sigma`, without checking
+    /// the setup again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidVerificationKey` if `sigma` exceeds the maximum nu of the setup.
+    pub fn with_sigma(&self, sigma: usize) -> Result<Self, VerifyError> {
+        if sigma > self.max_nu() {
+            return Err(VerifyError::InvalidVerificationKey);
+        }
+        Ok(Self {
+            setup: self.setup.clone(),
+            sigma,
+        })
+    }
+
+    /// Returns the sigma parameter this key verifies proofs for.
+    pub fn sigma(&self) -> usize {
+        self.sigma
+    }
+
+    /// Returns the maximum nu supported by the underlying setup.
+    ///
+    /// The setup does not expose it directly, so it is recovered from the
+    /// size of its serialization, which is linear in `max_nu`.
+    pub fn max_nu(&self) -> usize {
+        setup_max_nu(&self.setup)
+    }
+
+    /// Returns the maximum number of rows a table may have to be verified with this key,
+    /// see [`max_rows_supported`].
+    pub fn max_rows(&self) -> u128 {
+        max_rows_supported(self.max_nu(), self.sigma)
+    }
+
     /// Computes the serialized size of a VerificationKey.
     ///
+    /// The size is linear in `max_nu`, e.g. under 50 KB for a `max_nu` of 16: only the
+    /// public parameters grow exponentially with it. Keys are therefore read in full,
+    /// and the time spent loading one is dominated by the subgroup checks, which
+    /// services loading keys from a trusted store can defer with [`UnvalidatedVk`].
+    ///
     /// # Arguments
     ///
     /// * `max_nu`
@@ -96,12 +339,71 @@ impl VerificationKey {
     ///
     /// The size in bytes of the serialized VerificationKey.
     pub fn serialized_size(max_nu: usize) -> usize {
-        5 * (size_of::<usize>() + (max_nu + 1) * GT_SERIALIZED_SIZE) // Delta_1L, Delta_1R, Delta_2L, Delta_2R, chi
+        // Lengths and other usize values are serialized as u64 on every target
+        5 * (size_of::<u64>() + (max_nu + 1) * GT_SERIALIZED_SIZE) // Delta_1L, Delta_1R, Delta_2L, Delta_2R, chi
         + 2 * G1_AFFINE_SERIALIZED_SIZE// Gamma_1_0, H_1
         + 3 * G2_AFFINE_SERIALIZED_SIZE // Gamma_2_0, H_2, Gamma_2_fin
         + GT_SERIALIZED_SIZE // H_T
-        + 2 * size_of::<usize>() // max_nu, sigma
+        + 2 * size_of::<u64>() // max_nu, sigma
+    }
+}
+
+/// Recovers the maximum nu of a setup from the size of its serialization, which is
+/// linear in `max_nu`.
+fn setup_max_nu(setup: &VerifierSetup) -> usize {
+    let setup_size = setup.serialized_size(Compress::Yes);
+    let fixed_size = VerificationKey::serialized_size(0) - size_of::<u64>(); // sigma is not part of the setup
+    (setup_size - fixed_size) / (5 * GT_SERIALIZED_SIZE)
+}
+
+/// The header of a verification key chunk.
+struct ChunkHeader {
+    index: usize,
+    count: usize,
+    fingerprint: [u8; 32],
+}
+
+/// Splits a chunk into its header and checked payload, reporting a chunk too short to
+/// hold a header against its `position`.
+fn parse_chunk(chunk: &[u8], position: usize) -> Result<(&[u8], ChunkHeader), VerifyError> {
+    if chunk.len() < CHUNK_HEADER_SIZE {
+        return Err(VerifyError::InvalidVerificationKeyChunk { index: position });
+    }
+    let (header, payload) = chunk.split_at(CHUNK_HEADER_SIZE);
+    let read_u32 = |offset: usize| {
+        let bytes = header[offset..offset + 4].try_into().unwrap();
+        u32::from_le_bytes(bytes) as usize
+    };
+    let header = ChunkHeader {
+        index: read_u32(0),
+        count: read_u32(4),
+        fingerprint: header[8..40].try_into().unwrap(),
+    };
+    let checksum: [u8; 32] = Keccak256::digest(payload).into();
+    if checksum[..] != chunk[40..CHUNK_HEADER_SIZE] || header.index >= header.count {
+        return Err(VerifyError::InvalidVerificationKeyChunk {
+            index: header.index,
+        });
     }
+    Ok((payload, header))
+}
+
+/// Computes the maximum number of table rows that a Dory setup supports.
+///
+/// Dory arranges a table of `2^num_vars` rows in a matrix with `2^sigma` columns,
+/// which requires `nu = max(sigma, num_vars - sigma) <= max_nu`. Tables of up to
+/// `2^(max_nu + sigma)` rows can therefore be verified, and none if `sigma > max_nu`.
+/// Operators can use it to pick the `max_nu` of public parameters and the sigma of keys
+/// before generating them; [`VerificationKey::max_rows`] applies it to a key.
+///
+/// Returns `u128::MAX` when the bound doesn't fit in a `u128`.
+pub fn max_rows_supported(max_nu: usize, sigma: usize) -> u128 {
+    if sigma > max_nu {
+        return 0;
+    }
+    1u128
+        .checked_shl((max_nu + sigma) as u32)
+        .unwrap_or(u128::MAX)
 }
 
 #[cfg(test)]
@@ -124,6 +426,181 @@ mod test {
         assert_eq!(dory_key.verifier_setup(), &vk.setup);
     }
 
+    #[test]
+    fn verification_key_parts_round_trip() {
+        let public_parameters = PublicParameters::test_rand(2, &mut test_rng());
+        let vk = VerificationKey::new(&public_parameters, 1);
+        assert_eq!(
+            vk.verifier_setup(),
+            &VerifierSetup::from(&public_parameters)
+        );
+
+        let (setup, sigma) = vk.clone().into_parts();
+        assert_eq!(sigma, 1);
+        assert_eq!(
+            VerificationKey::try_from((setup.clone(), sigma)).unwrap(),
+            vk
+        );
+        assert!(matches!(
+            VerificationKey::try_from((setup, 3)),
+            Err(VerifyError::InvalidVerificationKey)
+        ));
+    }
+
+    #[test]
+    fn verification_key_eq_hash_debug() {
+        use std::collections::HashSet;
+
+        let vk = VerificationKey::new(&PublicParameters::test_rand(2, &mut test_rng()), 1);
+        let same = VerificationKey::try_from(vk.to_bytes().as_slice()).unwrap();
+        let other = VerificationKey::new(&PublicParameters::test_rand(2, &mut test_rng()), 2);
+        assert_eq!(vk, same);
+        assert_ne!(vk, other);
+        assert_eq!(HashSet::from([vk.clone(), same, other]).len(), 2);
+
+        let debug = format!("{vk:?}");
+        assert!(debug.starts_with("VerificationKey { sigma: 1, max_nu: 2, fingerprint: 0x"));
+    }
+
+    #[rstest]
+    #[case::small(100)]
+    #[case::whole_key(VerificationKey::serialized_size(2))]
+    #[case::larger_than_key(1 << 20)]
+    fn verification_key_chunks_round_trip(#[case] chunk_size: usize) {
+        let vk = VerificationKey::new(&PublicParameters::test_rand(2, &mut test_rng()), 1);
+        let mut chunks = vk.to_chunks(chunk_size);
+        assert_eq!(
+            chunks.len(),
+            VerificationKey::serialized_size(2).div_ceil(chunk_size)
+        );
+        assert!(chunks
+            .iter()
+            .all(|c| c.len() <= CHUNK_HEADER_SIZE + chunk_size));
+
+        chunks.reverse();
+        assert_eq!(VerificationKey::from_chunks(&chunks).unwrap(), vk);
+    }
+
+    #[test]
+    fn verification_key_chunks_are_checked() {
+        let vk = VerificationKey::new(&PublicParameters::test_rand(2, &mut test_rng()), 1);
+        let other = VerificationKey::new(&PublicParameters::test_rand(2, &mut test_rng()), 2);
+        let chunks = vk.to_chunks(1000);
+        let invalid_chunk = |chunks: &[Vec<u8>], index| {
+            matches!(
+                VerificationKey::from_chunks(chunks),
+                Err(VerifyError::InvalidVerificationKeyChunk { index: i }) if i == index
+            )
+        };
+
+        let mut corrupted = chunks.clone();
+        *corrupted[2].last_mut().unwrap() ^= 1;
+        assert!(invalid_chunk(&corrupted, 2));
+
+        let mut missing = chunks.clone();
+        missing.remove(1);
+        assert!(invalid_chunk(&missing, 1));
+        assert!(invalid_chunk(&chunks[..chunks.len() - 1], chunks.len() - 1));
+
+        let mut duplicated = chunks.clone();
+        duplicated[3] = chunks[2].clone();
+        assert!(invalid_chunk(&duplicated, 3));
+
+        let mut foreign = chunks.clone();
+        foreign[1] = other.to_chunks(1000)[1].clone();
+        assert!(invalid_chunk(&foreign, 1));
+
+        let mut truncated = chunks.clone();
+        truncated[4].truncate(CHUNK_HEADER_SIZE - 1);
+        assert!(invalid_chunk(&truncated, 4));
+
+        assert!(matches!(
+            VerificationKey::from_chunks(&[]),
+            Err(VerifyError::InvalidVerificationKey)
+        ));
+    }
+
+    #[rstest]
+    #[case::max_nu_0(0)]
+    #[case::max_nu_3(3)]
+    fn verification_key_max_nu(#[case] max_nu: usize) {
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let vk = VerificationKey::new(&public_parameters, 2);
+        assert_eq!(vk.max_nu(), max_nu);
+        assert_eq!(vk.sigma(), 2);
+    }
+
+    #[rstest]
+    #[case::sigma_too_large(1, 2, 0)]
+    #[case::square(2, 2, 16)]
+    #[case::wide(4, 1, 32)]
+    #[case::overflow(100, 100, u128::MAX)]
+    fn max_rows(#[case] max_nu: usize, #[case] sigma: usize, #[case] expected: u128) {
+        assert_eq!(max_rows_supported(max_nu, sigma), expected);
+    }
+
+    /// The nu of the Dory matrix holding a table of `2^num_vars` rows, with `2^sigma`
+    /// columns.
+    fn matrix_nu(num_vars: usize, sigma: usize) -> usize {
+        sigma.max(num_vars.saturating_sub(sigma))
+    }
+
+    #[rstest]
+    fn max_rows_fill_the_dory_matrix(
+        #[values(0, 1, 3, 6)] max_nu: usize,
+        #[values(0, 1, 3)] sigma: usize,
+    ) {
+        let max_rows = max_rows_supported(max_nu, sigma);
+        if sigma > max_nu {
+            assert_eq!(max_rows, 0);
+            return;
+        }
+        let num_vars = max_rows.trailing_zeros() as usize;
+        assert_eq!(max_rows, 1 << num_vars);
+        assert_eq!(matrix_nu(num_vars, sigma), max_nu);
+        assert!(matrix_nu(num_vars + 1, sigma) > max_nu);
+    }
+
+    #[test]
+    fn verification_key_max_rows() {
+        let vk = VerificationKey::new(&PublicParameters::test_rand(3, &mut test_rng()), 2);
+        assert_eq!(vk.max_rows(), max_rows_supported(3, 2));
+        assert_eq!(vk.max_rows(), 32);
+    }
+
+    #[test]
+    fn verification_key_with_sigma() {
+        let params = PublicParameters::test_rand(3, &mut test_rng());
+        let vk = VerificationKey::new(&params, 2);
+        assert_eq!(vk.with_sigma(3).unwrap(), VerificationKey::new(&params, 3));
+        assert!(matches!(
+            vk.with_sigma(4),
+            Err(VerifyError::InvalidVerificationKey)
+        ));
+    }
+
+    #[test]
+    fn unvalidated_verification_key() {
+        let vk = VerificationKey::new(&PublicParameters::test_rand(2, &mut test_rng()), 1);
+        let unvalidated = UnvalidatedVk::try_from(&vk.to_bytes()[..]).unwrap();
+        assert_eq!((unvalidated.sigma(), unvalidated.max_nu()), (1, 2));
+        assert_eq!(unvalidated.validate().unwrap(), vk);
+
+        // A sigma larger than the setup supports decodes, but doesn't validate
+        let inconsistent =
+            VerificationKey::new(&PublicParameters::test_rand(1, &mut test_rng()), 2);
+        let bytes = inconsistent.to_bytes();
+        let unvalidated = UnvalidatedVk::try_from(&bytes[..]).unwrap();
+        assert!(matches!(
+            unvalidated.validate(),
+            Err(VerifyError::InvalidVerificationKey)
+        ));
+        assert!(matches!(
+            VerificationKey::try_from(&bytes[..]),
+            Err(VerifyError::InvalidVerificationKey)
+        ));
+    }
+
     #[test]
     fn verification_key_short_buffer() {
         let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
@@ -174,4 +651,9 @@ mod test {
             VerificationKey::serialized_size(max_nu)
         )
     }
+
+    #[test]
+    fn large_keys_stay_small() {
+        assert!(VerificationKey::serialized_size(16) < 50_000);
+    }
 }
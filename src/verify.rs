@@ -13,17 +13,305 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use proof_of_sql::base::commitment::CommitmentEvaluationProof;
-use proof_of_sql::sql::proof::ProofPlan;
+use alloc::{boxed::Box, string::ToString};
+use proof_of_sql::base::commitment::{
+    ColumnCommitmentMetadataMap, Commitment, CommitmentEvaluationProof,
+};
+use proof_of_sql::base::database::{OwnedTable, TableRef};
+use proof_of_sql::proof_primitive::dory::{DoryCommitment, DoryEvaluationProof, DoryScalar};
 use proof_of_sql::sql::proof_plans::DynProofPlan;
 use proof_of_sql::{
     base::commitment::QueryCommitments,
-    sql::proof::{QueryData, VerifiableQueryResult},
+    sql::proof::{ProofPlan, QueryData, QueryError, VerifiableQueryResult},
+};
+use serde::Serialize;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    column_digest, paging::slice_rows, plan::column_references, ColumnId, ColumnMismatch,
+    DegenerateShape, PagedResult, PlanInfo, Proof, ProofStage, PublicInput, ResultNormalization,
+    Statement, TableId, TextNormalization, TimestampNormalization, VerificationKey, VerifiedTable,
+    VerifierConfig, VerifyError,
 };
 
-use crate::{Proof, PublicInput, VerificationKey, VerifyError};
+/// Checks that every column referenced by the plan is backed by a table commitment
+/// holding the column, with the type the plan expects.
+///
+/// This is one of the checks run by [`precheck`] and the verification entry points,
+/// exposed for callers composing their own checks.
+///
+/// # Arguments
+///
+/// * `expr` - The proof plan expression.
+/// * `commitments` - The query commitments.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if all references are bound, or an error otherwise.
+pub fn check_column_bindings<C: Commitment + Serialize>(
+    expr: &DynProofPlan<C>,
+    commitments: &QueryCommitments<C>,
+) -> Result<(), VerifyError> {
+    check_columns_metadata(expr, |table_ref| {
+        commitments
+            .get(table_ref)
+            .map(|commitment| commitment.column_commitments().column_metadata())
+    })
+}
+
+/// Checks that every column referenced by the plan belongs to a table whose column
+/// metadata is returned by `metadata`, and has the type the plan expects there.
+pub(crate) fn check_columns_metadata<'a, C: Commitment + Serialize>(
+    expr: &DynProofPlan<C>,
+    metadata: impl Fn(&TableRef) -> Option<&'a ColumnCommitmentMetadataMap>,
+) -> Result<(), VerifyError> {
+    for column in column_references(expr)? {
+        let table = TableId::from(column.table_ref());
+        let Some(table_metadata) = metadata(&column.table_ref()) else {
+            return Err(VerifyError::MissingTableCommitment { table });
+        };
+        let Some(metadata) = table_metadata.get(&column.column_id()) else {
+            return Err(VerifyError::MissingColumnMetadata {
+                table,
+                column: Box::new(column.column_id().into()),
+            });
+        };
+        if metadata.column_type() != column.column_type() {
+            return Err(VerifyError::ColumnTypeMismatch {
+                mismatch: Box::new(ColumnMismatch {
+                    table,
+                    column: column.column_id().into(),
+                    expected: *column.column_type(),
+                    actual: *metadata.column_type(),
+                }),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the commitments of the tables read by the plan have a shape an honestly
+/// computed commitment has: a range that doesn't end before it starts, one commitment
+/// per column, and identity commitments when the range is empty.
+///
+/// Commitments are decoded without any of these checks, and a reversed range would make
+/// the table look empty to upstream verification, which then doesn't check the proof
+/// against its columns. Tables without a commitment are left to
+/// [`check_column_bindings`].
+///
+/// # Arguments
+///
+/// * `expr` - The proof plan expression.
+/// * `commitments` - The query commitments.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if all commitments are well formed, or
+///   `DegenerateCommitment` otherwise.
+pub fn check_commitment_shapes<C: Commitment + Serialize>(
+    expr: &DynProofPlan<C>,
+    commitments: &QueryCommitments<C>,
+) -> Result<(), VerifyError> {
+    for table in PlanInfo::new(expr).tables() {
+        let Some(commitment) = commitments.get(&TableRef::from(table)) else {
+            continue;
+        };
+        let degenerate = |shape| VerifyError::DegenerateCommitment {
+            table: table.clone(),
+            shape,
+        };
+        let range = commitment.range();
+        if range.start > range.end {
+            return Err(degenerate(DegenerateShape::ReversedRange {
+                start: range.start,
+                end: range.end,
+            }));
+        }
+        let column_commitments = commitment.column_commitments();
+        let (num_commitments, num_columns) = (
+            column_commitments.commitments().len(),
+            column_commitments.column_metadata().len(),
+        );
+        if num_commitments != num_columns {
+            return Err(degenerate(DegenerateShape::ColumnCountMismatch {
+                commitments: num_commitments,
+                columns: num_columns,
+            }));
+        }
+        if range.is_empty() {
+            let identity = C::default();
+            if let Some(column) = column_commitments
+                .commitments()
+                .iter()
+                .position(|commitment| *commitment != identity)
+            {
+                return Err(degenerate(DegenerateShape::NonEmptyColumnCommitment {
+                    column,
+                }));
+            }
+        }
+    }
+    Ok(())
+}
 
-/// Verifies a generic proof against the provided expression, commitments, and query data.
+/// Checks the commitment shapes like [`check_commitment_shapes`], and that the claimed
+/// result has no more rows than the tables read by the plan.
+///
+/// The plans supported upstream read from a single table and never add rows, so a larger
+/// claim can't verify.
+pub(crate) fn check_public_input_shapes(pubs: &PublicInput) -> Result<(), VerifyError> {
+    check_commitment_shapes(pubs.expr(), pubs.commitments())?;
+    let result_rows = pubs.query_data().table.num_rows();
+    for table in PlanInfo::new(pubs.expr()).tables() {
+        if let Some(commitment) = pubs.commitments().get(&TableRef::from(table)) {
+            let table_rows = commitment.num_rows();
+            if result_rows > table_rows {
+                return Err(VerifyError::DegenerateCommitment {
+                    table: table.clone(),
+                    shape: DegenerateShape::ResultExceedsTable {
+                        result_rows,
+                        table_rows,
+                    },
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the verification key can possibly verify proofs for the given statement.
+///
+/// # Arguments
+///
+/// * `expr` - The proof plan expression.
+/// * `commitments` - The query commitments.
+/// * `vk` - The verification key.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if the key is usable, or an error otherwise.
+pub(crate) fn check_verification_key(
+    expr: &DynProofPlan<DoryCommitment>,
+    commitments: &QueryCommitments<DoryCommitment>,
+    vk: &VerificationKey,
+) -> Result<(), VerifyError> {
+    check_table_sizes(expr, vk, |table_ref| {
+        commitments
+            .get(table_ref)
+            .map(|commitment| commitment.num_rows())
+    })
+}
+
+/// Checks that the verification key can possibly verify proofs for the plan, given the
+/// number of rows of its tables returned by `num_rows`.
+pub(crate) fn check_table_sizes(
+    expr: &DynProofPlan<DoryCommitment>,
+    vk: &VerificationKey,
+    num_rows: impl Fn(&TableRef) -> Option<usize>,
+) -> Result<(), VerifyError> {
+    // Dory always needs nu >= sigma, so a sigma above the setup capacity can never verify
+    if vk.sigma() > vk.max_nu() {
+        return Err(VerifyError::InvalidVerificationKey);
+    }
+
+    let max_supported = vk.max_rows();
+    for table in PlanInfo::new(expr).tables() {
+        if let Some(rows) = num_rows(&TableRef::from(table)) {
+            if rows as u128 > max_supported {
+                return Err(VerifyError::TableTooLarge {
+                    rows,
+                    max_supported,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the verified query result matches the result claimed by the public input.
+///
+/// Without omitted columns the whole result must equal the claimed table. Otherwise each
+/// result column must either match its digest, or equal the next claimed column. For
+/// paged claims the claimed table only holds the result columns, and the rows of the
+/// result must have the claimed [`PagedResult`].
+///
+/// This is the last check of [`verify_proof`], exposed for callers that obtain the
+/// verified result by other means, e.g. from [`verify_and_extract`].
+///
+/// # Arguments
+///
+/// * `pubs` - The public input for the proof.
+/// * `result` - The verified query result.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if the claim matches, or `VerificationFailed` otherwise.
+pub fn check_result_claim(
+    pubs: &PublicInput,
+    result: &OwnedTable<DoryScalar>,
+) -> Result<(), VerifyError> {
+    check_normalized_result_claim(pubs, result, TextNormalization::None)
+}
+
+/// Checks the result claim like [`check_result_claim`], after normalizing the varchar
+/// values, and the timestamp timezones, of both the claimed and the verified results.
+///
+/// # Arguments
+///
+/// * `pubs` - The public input for the proof.
+/// * `result` - The verified query result.
+/// * `normalization` - The normalization applied to the results, or only to their
+///   varchar values when given a [`TextNormalization`].
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if the claim matches, or `VerificationFailed` otherwise.
+pub fn check_normalized_result_claim(
+    pubs: &PublicInput,
+    result: &OwnedTable<DoryScalar>,
+    normalization: impl Into<ResultNormalization>,
+) -> Result<(), VerifyError> {
+    let normalization = normalization.into();
+    let claimed = &*normalization.normalize_table(&pubs.query_data().table);
+    let result = &*normalization.normalize_table(result);
+    if let Some(paged_result) = pubs.paged_result() {
+        let rows_per_page =
+            usize::try_from(paged_result.rows_per_page()).map_err(|_| VerifyError::InvalidInput)?;
+        return if slice_rows(result, 0, 0) == *claimed
+            && PagedResult::new(result, rows_per_page)? == *paged_result
+        {
+            Ok(())
+        } else {
+            Err(VerifyError::VerificationFailed)
+        };
+    }
+    let omitted = pubs.omitted_columns();
+    if omitted.is_empty() {
+        return if result == claimed {
+            Ok(())
+        } else {
+            Err(VerifyError::VerificationFailed)
+        };
+    }
+
+    if result.num_columns() != claimed.num_columns() + omitted.len() {
+        return Err(VerifyError::VerificationFailed);
+    }
+    let mut claimed_columns = claimed.inner_table().iter();
+    for (name, column) in result.inner_table() {
+        let matches = match omitted.iter().find(|(n, _)| *n == ColumnId::from(*name)) {
+            Some((_, digest)) => column_digest(name, column)? == *digest,
+            None => claimed_columns.next() == Some((name, column)),
+        };
+        if !matches {
+            return Err(VerifyError::VerificationFailed);
+        }
+    }
+    Ok(())
+}
+
+/// Verifies a generic proof against the provided expression and commitments, assuming
+/// the column bindings have already been checked, and returns the verified query data.
 ///
 /// # Type Parameters
 ///
@@ -34,49 +322,249 @@ use crate::{Proof, PublicInput, VerificationKey, VerifyError};
 /// * `proof` - The proof to be verified, wrapped in a `VerifiableQueryResult`.
 /// * `expr` - The proof plan expression.
 /// * `commitments` - The query commitments.
-/// * `query_data` - The query data.
 /// * `setup` - The verifier's public setup.
 ///
 /// # Returns
 ///
-/// * `Result<(), VerifyError>` - Ok(()) if the proof is valid, or an error if verification fails.
-fn verify_proof_internal<CP: CommitmentEvaluationProof>(
+/// * `Result<QueryData<CP::Scalar>, VerifyError>` - The query data computed from the proof
+///   if it is valid, or an error if verification fails.
+fn verify_unclaimed_proof<CP: CommitmentEvaluationProof>(
     proof: &VerifiableQueryResult<CP>,
     expr: &DynProofPlan<CP::Commitment>,
     commitments: &QueryCommitments<CP::Commitment>,
-    query_data: &QueryData<CP::Scalar>,
     setup: &CP::VerifierPublicSetup<'_>,
-) -> Result<(), VerifyError> {
-    // Check that the columns in the proof match the columns in the commitments
-    for column in expr.get_column_references() {
-        if let Some(commitment) = commitments.get(&column.table_ref()) {
-            if let Some(metadata) = commitment
-                .column_commitments()
-                .get_metadata(&column.column_id())
-            {
-                if metadata.column_type() != column.column_type() {
-                    return Err(VerifyError::InvalidInput);
-                }
+) -> Result<QueryData<CP::Scalar>, VerifyError> {
+    // Upstream panics checking the ordering of a grouped result without rows
+    let empty_result = proof
+        .provable_result
+        .as_ref()
+        .is_some_and(|result| result.indexes().is_empty());
+    if empty_result && matches!(expr, DynProofPlan::GroupBy(_)) {
+        return Err(VerifyError::UnverifiableEmptyResult);
+    }
+
+    proof
+        .verify(expr, commitments, setup)
+        .map_err(|error| upstream_error(&error))
+}
+
+/// Maps an upstream verification error to the stage it was raised at, or to
+/// `ResultPayloadMismatch` if the embedded result can't be decoded.
+fn upstream_error(error: &QueryError) -> VerifyError {
+    match error {
+        QueryError::ProofError { .. } => {
+            // Upstream doesn't export its proof error type, only its message
+            let message = error.to_string();
+            let message = message
+                .strip_prefix("Verification error: ")
+                .unwrap_or(&message);
+            VerifyError::ProofRejected {
+                stage: ProofStage::from_message(message),
             }
-        } else {
-            return Err(VerifyError::InvalidInput);
         }
+        QueryError::MiscellaneousEvaluationError => VerifyError::ProofRejected {
+            stage: ProofStage::ResultEvaluation,
+        },
+        _ => VerifyError::ResultPayloadMismatch,
     }
+}
 
-    let result = proof
-        .verify(expr, commitments, setup)
-        .map_err(|_| VerifyError::VerificationFailed)?;
+/// Verifies a generic proof against the provided expression, commitments, and query data,
+/// assuming the column bindings have already been checked.
+///
+/// Only the verification hash of `query_data` is checked, the verified result is returned
+/// to be checked against the claimed one.
+///
+/// # Type Parameters
+///
+/// * `CP` - A type that implements `CommitmentEvaluationProof`.
+///
+/// # Arguments
+///
+/// * `proof` - The proof to be verified, wrapped in a `VerifiableQueryResult`.
+/// * `expr` - The proof plan expression.
+/// * `commitments` - The query commitments.
+/// * `query_data` - The query data.
+/// * `setup` - The verifier's public setup.
+///
+/// # Returns
+///
+/// * `Result<QueryData<CP::Scalar>, VerifyError>` - The verified query data if the proof is
+///   valid, or an error if verification fails.
+pub(crate) fn verify_bound_proof<CP: CommitmentEvaluationProof>(
+    proof: &VerifiableQueryResult<CP>,
+    expr: &DynProofPlan<CP::Commitment>,
+    commitments: &QueryCommitments<CP::Commitment>,
+    query_data: &QueryData<CP::Scalar>,
+    setup: &CP::VerifierPublicSetup<'_>,
+) -> Result<QueryData<CP::Scalar>, VerifyError> {
+    let result = verify_unclaimed_proof(proof, expr, commitments, setup)?;
 
-    if result.table != query_data.table || result.verification_hash != query_data.verification_hash
-    {
-        Err(VerifyError::VerificationFailed)
+    // The hash comparison is constant-time to avoid leaking how much of it matches
+    let hash_matches: bool = result
+        .verification_hash
+        .ct_eq(&query_data.verification_hash)
+        .into();
+    if hash_matches {
+        Ok(result)
     } else {
+        Err(VerifyError::VerificationFailed)
+    }
+}
+
+/// Checks that the result embedded in a proof has the shape of the claimed result,
+/// assuming the column bindings have already been checked.
+///
+/// The embedded result is the one the proof attests, so a proof whose result has other
+/// columns or another number of rows than the claim can only fail verification. This
+/// check rejects it without verifying the proof. Values are left to the result claim
+/// check.
+///
+/// Whether a proof embeds a result is not up to the prover: upstream embeds one exactly
+/// when the queried tables are not empty, so any other proof is rejected too.
+///
+/// # Arguments
+///
+/// * `proof` - The proof to be verified, wrapped in a `VerifiableQueryResult`.
+/// * `pubs` - The public input for the proof.
+/// * `timestamps` - The normalization applied to the timezones of both results.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if the embedded result has the claimed shape, or
+///   `ResultPayloadMismatch` otherwise.
+pub(crate) fn check_result_payload(
+    proof: &VerifiableQueryResult<DoryEvaluationProof>,
+    pubs: &PublicInput,
+    timestamps: TimestampNormalization,
+) -> Result<(), VerifyError> {
+    let expected = !pubs.expr().is_empty(pubs.commitments());
+    let payload = match &proof.provable_result {
+        Some(payload) if expected => payload,
+        None if !expected => return Ok(()),
+        _ => return Err(VerifyError::ResultPayloadMismatch),
+    };
+    let result: OwnedTable<DoryScalar> = payload
+        .to_owned_table(&pubs.expr().get_column_result_fields())
+        .map_err(|_| VerifyError::ResultPayloadMismatch)?;
+
+    let claimed = &pubs.query_data().table;
+    let omitted = pubs.omitted_columns();
+    let claimed_columns = claimed
+        .inner_table()
+        .iter()
+        .map(|(name, column)| (name, timestamps.normalize_type(column.column_type())));
+    let result_columns = result
+        .inner_table()
+        .iter()
+        .filter(|(name, _)| !omitted.iter().any(|(n, _)| *n == ColumnId::from(**name)))
+        .map(|(name, column)| (name, timestamps.normalize_type(column.column_type())));
+    // Paged claims hold no rows, and claims of omitted columns only may not tell how many
+    let rows_match = pubs.paged_result().is_some()
+        || claimed.num_columns() == 0
+        || claimed.num_rows() == result.num_rows();
+    if result.num_columns() == claimed.num_columns() + omitted.len()
+        && result_columns.eq(claimed_columns)
+        && rows_match
+    {
         Ok(())
+    } else {
+        Err(VerifyError::ResultPayloadMismatch)
+    }
+}
+
+/// Checks that the commitments of the tables with an expected offset in `config` start
+/// at that offset.
+///
+/// Proofs don't record the offsets the prover placed the tables at, so a mismatch would
+/// otherwise only surface as a failed verification.
+///
+/// # Arguments
+///
+/// * `pubs` - The public input for the proof.
+/// * `config` - The verifier configuration.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if all offsets match, or `OffsetMismatch` otherwise.
+pub(crate) fn check_table_offsets(
+    pubs: &PublicInput,
+    config: &VerifierConfig,
+) -> Result<(), VerifyError> {
+    check_offsets(config, |table_ref| {
+        pubs.commitments()
+            .get(table_ref)
+            .map(|commitment| commitment.range().start)
+    })
+}
+
+/// Checks that the tables start at the offsets set in the configuration, given the
+/// offsets returned by `offset`.
+pub(crate) fn check_offsets(
+    config: &VerifierConfig,
+    offset: impl Fn(&TableRef) -> Option<usize>,
+) -> Result<(), VerifyError> {
+    for (table_id, expected) in config.table_offsets() {
+        if let Some(actual) = offset(&TableRef::from(table_id)) {
+            if actual != *expected {
+                return Err(VerifyError::OffsetMismatch {
+                    table: table_id.clone(),
+                    expected: *expected,
+                    actual,
+                });
+            }
+        }
     }
+    Ok(())
+}
+
+/// Runs the cheap checks that don't involve the proof: the plan columns must be bound
+/// to well formed commitments, see [`check_commitment_shapes`], and the verification key
+/// must be usable for the public input.
+///
+/// A successful precheck doesn't imply that verification succeeds, but a failing
+/// one implies that it fails.
+///
+/// # Arguments
+///
+/// * `pubs` - The public input for the proof.
+/// * `vk` - The verification key used to verify the proof.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if all checks pass, or the first failing check.
+pub fn precheck(pubs: &PublicInput, vk: &VerificationKey) -> Result<(), VerifyError> {
+    check_column_bindings(pubs.expr(), pubs.commitments())?;
+    check_public_input_shapes(pubs)?;
+    check_verification_key(pubs.expr(), pubs.commitments(), vk)
+}
+
+/// Runs the checks of [`precheck`], additionally checking the plan against the configured
+/// limits and that the table commitments start at the offsets set in the configuration.
+///
+/// # Arguments
+///
+/// * `pubs` - The public input for the proof.
+/// * `vk` - The verification key used to verify the proof.
+/// * `config` - The verifier configuration.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if all checks pass, or the first failing check.
+pub fn precheck_with_config(
+    pubs: &PublicInput,
+    vk: &VerificationKey,
+    config: &VerifierConfig,
+) -> Result<(), VerifyError> {
+    config.plan_limits().check(pubs.expr())?;
+    check_table_offsets(pubs, config)?;
+    precheck(pubs, vk)
 }
 
 /// Verifies a Dory proof against the provided public input and verification key.
 ///
+/// The proof is verified by reference, so no copy of it is made.
+///
 /// # Arguments
 ///
 /// * `proof` - The Dory proof to be verified.
@@ -95,11 +583,415 @@ pub fn verify_proof(
     pubs: &PublicInput,
     vk: &VerificationKey,
 ) -> Result<(), VerifyError> {
-    verify_proof_internal(
+    verify_proof_with_result(proof, pubs, vk).map(|_| ())
+}
+
+/// Verifies a Dory proof like [`verify_proof`], returning the verified query result.
+///
+/// When the public input claims only some of the result columns, the returned table
+/// still holds all of them.
+///
+/// # Arguments
+///
+/// * `proof` - The Dory proof to be verified.
+/// * `pubs` - The public input for the proof.
+/// * `vk` - The verification key used to verify the proof.
+///
+/// # Returns
+///
+/// * `Result<VerifiedTable, VerifyError>` - The verified result if the proof is valid, or an
+///   error if verification fails.
+pub fn verify_proof_with_result(
+    proof: &Proof,
+    pubs: &PublicInput,
+    vk: &VerificationKey,
+) -> Result<VerifiedTable, VerifyError> {
+    verify_normalized_proof(proof, pubs, vk, ResultNormalization::default())
+}
+
+/// Verifies a Dory proof like [`verify_proof_with_result`], normalizing the results
+/// before checking the result claim.
+pub(crate) fn verify_normalized_proof(
+    proof: &Proof,
+    pubs: &PublicInput,
+    vk: &VerificationKey,
+    normalization: ResultNormalization,
+) -> Result<VerifiedTable, VerifyError> {
+    check_verification_key(pubs.expr(), pubs.commitments(), vk)?;
+    check_column_bindings(pubs.expr(), pubs.commitments())?;
+    check_public_input_shapes(pubs)?;
+    check_result_payload(proof.inner(), pubs, normalization.timestamps)?;
+    let result = verify_bound_proof(
         proof.inner(),
         pubs.expr(),
         pubs.commitments(),
         pubs.query_data(),
         &vk.to_dory(),
-    )
+    )?;
+    check_normalized_result_claim(pubs, &result.table, normalization)?;
+    VerifiedTable::try_new(result.table)
+}
+
+/// Verifies a Dory proof against a statement, returning the query result computed from
+/// the proof.
+///
+/// Unlike [`verify_proof_with_result`], no result is claimed up front, so the statement
+/// only holds the plan and the table commitments. The result is trusted because the
+/// proof attests it, which makes this suitable for consumers that just want the verified
+/// result.
+///
+/// # Arguments
+///
+/// * `proof` - The Dory proof to be verified.
+/// * `statement` - The plan and commitments the proof is verified against.
+/// * `vk` - The verification key used to verify the proof.
+///
+/// # Returns
+///
+/// * `Result<VerifiedTable, VerifyError>` - The verified result if the proof is valid, or an
+///   error if verification fails.
+pub fn verify_and_extract(
+    proof: &Proof,
+    statement: &Statement,
+    vk: &VerificationKey,
+) -> Result<VerifiedTable, VerifyError> {
+    check_column_bindings(statement.expr(), statement.commitments())?;
+    check_commitment_shapes(statement.expr(), statement.commitments())?;
+    check_verification_key(statement.expr(), statement.commitments(), vk)?;
+    let result = verify_unclaimed_proof(
+        proof.inner(),
+        statement.expr(),
+        statement.commitments(),
+        &vk.to_dory(),
+    )?;
+    VerifiedTable::try_new(result.table)
+}
+
+/// Verifies a Dory proof like [`verify_proof`], applying the given configuration.
+///
+/// The plan is checked against the configured [`PlanLimits`](crate::PlanLimits) and
+/// [`PlanPolicy`](crate::PlanPolicy), and the table commitments against the configured
+/// offsets first. The result claim is checked against the shape limits of the policy,
+/// and with the configured [`TextNormalization`] and [`TimestampNormalization`].
+///
+/// # Arguments
+///
+/// * `proof` - The Dory proof to be verified.
+/// * `pubs` - The public input for the proof.
+/// * `vk` - The verification key used to verify the proof.
+/// * `config` - The verifier configuration.
+///
+/// # Returns
+///
+/// * `Result<(), VerifyError>` - Ok(()) if the proof is valid, or an error if verification fails.
+pub fn verify_proof_with_config(
+    proof: &Proof,
+    pubs: &PublicInput,
+    vk: &VerificationKey,
+    config: &VerifierConfig,
+) -> Result<(), VerifyError> {
+    config.plan_limits().check(pubs.expr())?;
+    config.plan_policy().check(&pubs.plan_info())?;
+    config.plan_policy().check_query_data(pubs)?;
+    check_table_offsets(pubs, config)?;
+    config
+        .parallelism()
+        .install(|| verify_normalized_proof(proof, pubs, vk, config.result_normalization()))
+        .map(|_| ())
+}
+
+/// Verifies a Dory proof against several candidate verification keys, e.g. during a
+/// key rotation window.
+///
+/// The plan is checked against the default [`PlanLimits`](crate::PlanLimits) and
+/// [`PlanPolicy`](crate::PlanPolicy) first. Keys failing the precheck are skipped and
+/// verification stops at the first key that verifies the proof.
+///
+/// # Arguments
+///
+/// * `proof` - The Dory proof to be verified.
+/// * `pubs` - The public input for the proof.
+/// * `vks` - The candidate verification keys.
+///
+/// # Returns
+///
+/// * `Result<usize, VerifyError>` - The index of the first key that verifies the proof, or
+///   the last error encountered if none does.
+pub fn verify_with_any(
+    proof: &Proof,
+    pubs: &PublicInput,
+    vks: &[VerificationKey],
+) -> Result<usize, VerifyError> {
+    let config = VerifierConfig::default();
+    config.plan_limits().check(pubs.expr())?;
+    config.plan_policy().check(&pubs.plan_info())?;
+    config.plan_policy().check_query_data(pubs)?;
+
+    let mut error = VerifyError::InvalidVerificationKey;
+    for (index, vk) in vks.iter().enumerate() {
+        match verify_normalized_proof(proof, pubs, vk, config.result_normalization()) {
+            Ok(_) => return Ok(index),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::{
+        base::{
+            commitment::QueryCommitmentsExt,
+            database::{owned_table_utility::*, ColumnType, OwnedTableTestAccessor, TestAccessor},
+        },
+        proof_primitive::dory::{
+            DoryEvaluationProof, DoryProverPublicSetup, ProverSetup, PublicParameters,
+        },
+        sql::{parse::QueryExpr, proof::ProofPlan},
+    };
+    use proof_of_sql_parser::Identifier;
+    use rstest::rstest;
+
+    use super::*;
+
+    /// Builds the plan of a query over `table`, with the commitments of that table.
+    fn build_statement(
+        table: OwnedTable<DoryScalar>,
+    ) -> (QueryExpr<DoryCommitment>, QueryCommitments<DoryCommitment>) {
+        build_query_statement("SELECT a, b FROM table WHERE a = 2", table)
+    }
+
+    /// Builds the plan of `sql` and the commitments to the columns it references.
+    fn build_query_statement(
+        sql: &str,
+        table: OwnedTable<DoryScalar>,
+    ) -> (QueryExpr<DoryCommitment>, QueryCommitments<DoryCommitment>) {
+        let public_parameters = PublicParameters::test_rand(2, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let mut accessor = OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(
+            DoryProverPublicSetup::new(&ps, 2),
+        );
+        accessor.add_table("sxt.table".parse().unwrap(), table, 0);
+        let query =
+            QueryExpr::try_new(sql.parse().unwrap(), "sxt".parse().unwrap(), &accessor).unwrap();
+        let columns = query.proof_expr().get_column_references();
+        let commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+        (query, commitments)
+    }
+
+    #[test]
+    fn column_bindings() {
+        let (query, commitments) =
+            build_statement(owned_table([bigint("a", [1, 2]), varchar("b", ["x", "y"])]));
+        let expr = query.proof_expr();
+        assert!(check_column_bindings(expr, &commitments).is_ok());
+
+        assert!(matches!(
+            check_column_bindings(expr, &QueryCommitments::default()),
+            Err(VerifyError::MissingTableCommitment { table }) if table == "sxt.table"
+        ));
+
+        let (_, retyped) = build_statement(owned_table([bigint("a", [1, 2]), bigint("b", [3, 4])]));
+        assert!(matches!(
+            check_column_bindings(expr, &retyped),
+            Err(VerifyError::ColumnTypeMismatch { mismatch }) if *mismatch == ColumnMismatch {
+                table: "sxt.table".parse().unwrap(),
+                column: "b".parse().unwrap(),
+                expected: ColumnType::VarChar,
+                actual: ColumnType::BigInt,
+            }
+        ));
+
+        let (_, partial) = build_query_statement(
+            "SELECT a FROM table WHERE a = 2",
+            owned_table([bigint("a", [1, 2]), varchar("b", ["x", "y"])]),
+        );
+        assert!(matches!(
+            check_column_bindings(expr, &partial),
+            Err(VerifyError::MissingColumnMetadata { table, column })
+                if table == "sxt.table" && *column == "b"
+        ));
+    }
+
+    /// Rewrites the commitment of `sxt.table` through its CBOR representation.
+    fn tamper_commitment(
+        commitments: &QueryCommitments<DoryCommitment>,
+        edit: impl FnOnce(&mut ciborium::Value),
+    ) -> QueryCommitments<DoryCommitment> {
+        let table_ref = TableRef::from(&"sxt.table".parse::<TableId>().unwrap());
+        let mut value = ciborium::Value::serialized(&commitments[&table_ref]).unwrap();
+        edit(&mut value);
+        let mut tampered = commitments.clone();
+        tampered.insert(table_ref, value.deserialized().unwrap());
+        tampered
+    }
+
+    /// Returns the field `name` of a CBOR map.
+    fn field<'a>(value: &'a mut ciborium::Value, name: &str) -> &'a mut ciborium::Value {
+        let ciborium::Value::Map(entries) = value else {
+            panic!("not a map")
+        };
+        let (_, field) = entries
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some(name))
+            .unwrap();
+        field
+    }
+
+    #[test]
+    fn commitment_shapes() {
+        let (query, commitments) =
+            build_statement(owned_table([bigint("a", [1, 2]), varchar("b", ["x", "y"])]));
+        let expr = query.proof_expr();
+        assert!(check_commitment_shapes(expr, &commitments).is_ok());
+        assert!(check_commitment_shapes(expr, &QueryCommitments::default()).is_ok());
+
+        let set_range = |start: u64, end: u64| {
+            move |value: &mut ciborium::Value| {
+                let range = field(value, "range");
+                *field(range, "start") = start.into();
+                *field(range, "end") = end.into();
+            }
+        };
+        let reversed = tamper_commitment(&commitments, set_range(2, 0));
+        assert!(matches!(
+            check_commitment_shapes(expr, &reversed),
+            Err(VerifyError::DegenerateCommitment { table, shape })
+                if table == "sxt.table"
+                    && shape == DegenerateShape::ReversedRange { start: 2, end: 0 }
+        ));
+
+        let missing = tamper_commitment(&commitments, |value| {
+            let column_commitments = field(value, "column_commitments");
+            let ciborium::Value::Array(column_commitments) =
+                field(column_commitments, "commitments")
+            else {
+                panic!("not an array")
+            };
+            column_commitments.pop();
+        });
+        assert!(matches!(
+            check_commitment_shapes(expr, &missing),
+            Err(VerifyError::DegenerateCommitment { shape, .. })
+                if shape == DegenerateShape::ColumnCountMismatch { commitments: 1, columns: 2 }
+        ));
+
+        let emptied = tamper_commitment(&commitments, set_range(2, 2));
+        assert!(matches!(
+            check_commitment_shapes(expr, &emptied),
+            Err(VerifyError::DegenerateCommitment { shape, .. })
+                if shape == DegenerateShape::NonEmptyColumnCommitment { column: 0 }
+        ));
+
+        // Commitments to tables that are empty at any offset are well formed
+        let (_, empty) = build_statement(owned_table([bigint("a", [0; 0]), varchar("b", [""; 0])]));
+        assert!(check_commitment_shapes(expr, &empty).is_ok());
+        let moved = tamper_commitment(&empty, set_range(5, 5));
+        assert!(check_commitment_shapes(expr, &moved).is_ok());
+    }
+
+    #[test]
+    fn result_larger_than_table() {
+        let table = owned_table([bigint("a", [1, 2]), varchar("b", ["x", "y"])]);
+        let (query, commitments) = build_statement(table.clone());
+        let pubs = |table| {
+            PublicInput::new(
+                query.proof_expr(),
+                commitments.clone(),
+                QueryData {
+                    table,
+                    verification_hash: [0; 32],
+                },
+            )
+        };
+        assert!(check_public_input_shapes(&pubs(table)).is_ok());
+
+        let claimed = owned_table([bigint("a", [2, 2, 2]), varchar("b", ["y", "y", "y"])]);
+        assert!(matches!(
+            check_public_input_shapes(&pubs(claimed)),
+            Err(VerifyError::DegenerateCommitment { shape, .. })
+                if shape == DegenerateShape::ResultExceedsTable { result_rows: 3, table_rows: 2 }
+        ));
+    }
+
+    #[rstest]
+    #[case::full_claim(&[], owned_table([bigint("a", [2]), varchar("b", ["y"])]), true)]
+    #[case::wrong_value(&[], owned_table([bigint("a", [2]), varchar("b", ["z"])]), false)]
+    #[case::missing_row(&[], owned_table([bigint("a", [0; 0]), varchar("b", [""; 0])]), false)]
+    #[case::omitted_column(&["b"], owned_table([bigint("a", [2]), varchar("b", ["y"])]), true)]
+    #[case::wrong_omitted_value(&["b"], owned_table([bigint("a", [2]), varchar("b", ["z"])]), false)]
+    #[case::wrong_claimed_value(&["b"], owned_table([bigint("a", [3]), varchar("b", ["y"])]), false)]
+    fn result_claim(
+        #[case] omitted: &[&str],
+        #[case] result: OwnedTable<DoryScalar>,
+        #[case] matches: bool,
+    ) {
+        let claimed = owned_table([bigint("a", [2]), varchar("b", ["y"])]);
+        let (query, commitments) = build_statement(claimed.clone());
+        let query_data = QueryData {
+            table: claimed,
+            verification_hash: [0; 32],
+        };
+        let pubs = PublicInput::new(query.proof_expr(), commitments, query_data)
+            .omit_columns(
+                omitted
+                    .iter()
+                    .map(|name| name.parse::<Identifier>().unwrap()),
+            )
+            .unwrap();
+
+        let checked = check_result_claim(&pubs, &result);
+        if matches {
+            assert!(checked.is_ok());
+        } else {
+            assert!(matches!(checked, Err(VerifyError::VerificationFailed)));
+        }
+    }
+
+    #[cfg(feature = "nfc")]
+    #[rstest]
+    #[case::same_form("\u{e9}", "\u{e9}", TextNormalization::None, true)]
+    #[case::other_form("e\u{301}", "\u{e9}", TextNormalization::None, false)]
+    #[case::other_form_normalized("e\u{301}", "\u{e9}", TextNormalization::Nfc, true)]
+    #[case::other_value_normalized("e\u{300}", "\u{e9}", TextNormalization::Nfc, false)]
+    #[case::astral(
+        "\u{1d518}\u{1f600}",
+        "\u{1d518}\u{1f600}",
+        TextNormalization::Nfc,
+        true
+    )]
+    fn normalized_result_claim(
+        #[case] claimed: &str,
+        #[case] computed: &str,
+        #[case] normalization: TextNormalization,
+        #[case] matches: bool,
+    ) {
+        let claimed = owned_table([bigint("a", [2]), varchar("b", [claimed])]);
+        let computed = owned_table([bigint("a", [2]), varchar("b", [computed])]);
+        let (query, commitments) = build_statement(claimed.clone());
+        let query_data = QueryData {
+            table: claimed,
+            verification_hash: [0; 32],
+        };
+        let pubs = PublicInput::new(query.proof_expr(), commitments, query_data);
+        let omitted = normalization
+            .normalize_table(&pubs.query_data().table)
+            .into_owned();
+        let omitted = PublicInput::new(
+            pubs.expr(),
+            pubs.commitments().clone(),
+            QueryData {
+                table: omitted,
+                verification_hash: [0; 32],
+            },
+        )
+        .omit_columns(["b".parse::<ColumnId>().unwrap()])
+        .unwrap();
+
+        for pubs in [pubs, omitted] {
+            let checked = check_normalized_result_claim(&pubs, &computed, normalization);
+            assert_eq!(checked.is_ok(), matches);
+        }
+    }
 }
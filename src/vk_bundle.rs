@@ -0,0 +1,247 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use ciborium::Value;
+
+use crate::{ProofScheme, VerificationKey, VerifyError};
+
+/// The verification key of a single proof scheme.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SchemeVk {
+    /// A key verifying [`ProofScheme::Dory`] proofs.
+    Dory(VerificationKey),
+}
+
+impl SchemeVk {
+    /// Returns the scheme the key verifies proofs of.
+    pub fn scheme(&self) -> ProofScheme {
+        match self {
+            SchemeVk::Dory(_) => ProofScheme::Dory,
+        }
+    }
+
+    /// Serializes the key in the format of its scheme.
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            SchemeVk::Dory(vk) => vk.to_bytes(),
+        }
+    }
+
+    /// Deserializes a key of `scheme`.
+    fn from_bytes(scheme: ProofScheme, bytes: &[u8]) -> Result<Self, VerifyError> {
+        match scheme {
+            ProofScheme::Dory => VerificationKey::try_from(bytes).map(SchemeVk::Dory),
+        }
+    }
+}
+
+impl From<VerificationKey> for SchemeVk {
+    fn from(vk: VerificationKey) -> Self {
+        SchemeVk::Dory(vk)
+    }
+}
+
+/// Verification keys for several proof schemes, with at most one key per scheme.
+///
+/// Operators verifying proofs of several schemes side by side distribute their keys as a
+/// single bundle. The bundle is serialized as a CBOR array of `[scheme, key]` pairs in
+/// the order of [`VkBundle::schemes`], each key being a byte string in the format of its
+/// scheme, so that bundles have a single valid encoding.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VkBundle {
+    keys: Vec<SchemeVk>,
+}
+
+impl VkBundle {
+    /// Creates an empty bundle.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key to the bundle, returning the key it replaces for the same scheme.
+    pub fn insert(&mut self, vk: impl Into<SchemeVk>) -> Option<SchemeVk> {
+        let vk = vk.into();
+        match self.position(vk.scheme()) {
+            Ok(index) => Some(core::mem::replace(&mut self.keys[index], vk)),
+            Err(index) => {
+                self.keys.insert(index, vk);
+                None
+            }
+        }
+    }
+
+    /// Returns the key of `scheme`, if any.
+    pub fn get(&self, scheme: ProofScheme) -> Option<&SchemeVk> {
+        self.position(scheme).ok().map(|index| &self.keys[index])
+    }
+
+    /// Returns the key verifying Dory proofs, if any.
+    pub fn dory(&self) -> Option<&VerificationKey> {
+        match self.get(ProofScheme::Dory)? {
+            SchemeVk::Dory(vk) => Some(vk),
+        }
+    }
+
+    /// Returns the schemes the bundle holds keys for.
+    pub fn schemes(&self) -> impl Iterator<Item = ProofScheme> + '_ {
+        self.keys.iter().map(SchemeVk::scheme)
+    }
+
+    /// Returns the number of keys in the bundle.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether the bundle holds no key.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Converts the bundle into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let entries = self
+            .keys
+            .iter()
+            .map(|vk| {
+                let scheme = Value::serialized(&vk.scheme()).unwrap();
+                Value::Array(Vec::from([scheme, Value::Bytes(vk.to_bytes())]))
+            })
+            .collect();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&Value::Array(entries), &mut bytes).unwrap();
+        bytes
+    }
+
+    /// Finds the index of the key of `scheme`, or where it would be inserted.
+    fn position(&self, scheme: ProofScheme) -> Result<usize, usize> {
+        self.keys.binary_search_by_key(&scheme, SchemeVk::scheme)
+    }
+}
+
+impl TryFrom<&[u8]> for VkBundle {
+    type Error = VerifyError;
+
+    /// Decodes a bundle, validating every key.
+    ///
+    /// Bundles holding keys of unknown schemes, or several keys of a scheme, are rejected
+    /// with `InvalidVerificationKey`.
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let Ok(Value::Array(entries)) = ciborium::from_reader(value) else {
+            return Err(VerifyError::InvalidVerificationKey);
+        };
+        let mut keys: Vec<SchemeVk> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let Value::Array(pair) = entry else {
+                return Err(VerifyError::InvalidVerificationKey);
+            };
+            let [scheme, Value::Bytes(bytes)] = &pair[..] else {
+                return Err(VerifyError::InvalidVerificationKey);
+            };
+            let scheme: ProofScheme = scheme
+                .deserialized()
+                .map_err(|_| VerifyError::InvalidVerificationKey)?;
+            // Keys must be sorted by scheme, which also rules out duplicates
+            if keys.last().is_some_and(|last| last.scheme() >= scheme) {
+                return Err(VerifyError::InvalidVerificationKey);
+            }
+            keys.push(SchemeVk::from_bytes(scheme, bytes)?);
+        }
+        Ok(Self { keys })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_std::test_rng;
+    use proof_of_sql::proof_primitive::dory::PublicParameters;
+
+    use super::*;
+
+    fn vk(max_nu: usize) -> VerificationKey {
+        VerificationKey::new(&PublicParameters::test_rand(max_nu, &mut test_rng()), 1)
+    }
+
+    fn encode(entries: Vec<Value>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&Value::Array(entries), &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bundle = VkBundle::new();
+        assert!(bundle.insert(vk(1)).is_none());
+        assert_eq!(bundle.insert(vk(2)), Some(SchemeVk::Dory(vk(1))));
+
+        let decoded = VkBundle::try_from(&bundle.to_bytes()[..]).unwrap();
+        assert_eq!(decoded, bundle);
+        assert_eq!(decoded.len(), 1);
+        assert!(decoded.schemes().eq([ProofScheme::Dory]));
+        assert_eq!(decoded.dory(), Some(&vk(2)));
+        assert_eq!(
+            decoded.get(ProofScheme::Dory).map(SchemeVk::scheme),
+            Some(ProofScheme::Dory)
+        );
+
+        let empty = VkBundle::try_from(&VkBundle::new().to_bytes()[..]).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.dory(), None);
+    }
+
+    #[test]
+    fn context_registers_bundle_keys() {
+        let mut context = crate::VerifierContext::from(vk(1));
+        assert!(matches!(
+            context.register_bundle(&VkBundle::new()),
+            Err(VerifyError::InvalidVerificationKey)
+        ));
+        assert_eq!(context.vk(), &vk(1));
+
+        let mut bundle = VkBundle::new();
+        bundle.insert(vk(2));
+        context.register_bundle(&bundle).unwrap();
+        assert_eq!(context.vk(), &vk(2));
+    }
+
+    #[test]
+    fn invalid_bundles_are_rejected() {
+        let entry = |scheme: &str, bytes: Vec<u8>| {
+            Value::Array(Vec::from([Value::Text(scheme.into()), Value::Bytes(bytes)]))
+        };
+        let key = vk(1).to_bytes();
+        let mut corrupted = key.clone();
+        corrupted[10] ^= 1;
+
+        for bytes in [
+            Vec::from([0x01]),
+            encode(Vec::from([
+                entry("Dory", key.clone()),
+                entry("Dory", key.clone()),
+            ])),
+            encode(Vec::from([entry("InnerProduct", key.clone())])),
+            encode(Vec::from([entry("Dory", corrupted)])),
+            encode(Vec::from([Value::Array(Vec::from([Value::Text(
+                "Dory".into(),
+            )]))])),
+        ] {
+            assert!(matches!(
+                VkBundle::try_from(&bytes[..]),
+                Err(VerifyError::InvalidVerificationKey)
+            ));
+        }
+    }
+}
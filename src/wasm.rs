@@ -0,0 +1,66 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JavaScript bindings, e.g. to verify query results in a browser before showing them.
+
+use js_sys::{Error, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::{dory, rejection_reason, VerifyError};
+
+/// Verifies a Dory proof given its serialized proof, public input and verification key,
+/// exported to JavaScript as `verify(proof, pubs, vk)` taking `Uint8Array`s.
+///
+/// # Errors
+///
+/// Throws an `Error` whose `message` is the error message, whose `reason` is the
+/// [`rejection_reason`] of the error, and whose `artifact` is `"proof"`, `"pubs"` or
+/// `"vk"` when that artifact can't be decoded, and `null` otherwise.
+#[wasm_bindgen(js_name = verify)]
+pub fn verify_wasm(proof: &[u8], pubs: &[u8], vk: &[u8]) -> Result<(), JsValue> {
+    verify_artifacts(proof, pubs, vk).map_err(|(artifact, e)| {
+        let error = Error::new(&e.to_string());
+        let artifact = artifact.map_or(JsValue::NULL, JsValue::from_str);
+        // Setting properties of a fresh `Error` can't fail
+        let _ = Reflect::set(&error, &"reason".into(), &rejection_reason(&e).into());
+        let _ = Reflect::set(&error, &"artifact".into(), &artifact);
+        error.into()
+    })
+}
+
+/// Decodes and verifies the artifacts, naming the artifact that can't be decoded if any.
+fn verify_artifacts(
+    proof: &[u8],
+    pubs: &[u8],
+    vk: &[u8],
+) -> Result<(), (Option<&'static str>, VerifyError)> {
+    let proof = dory::Proof::try_from(proof).map_err(|e| (Some("proof"), e))?;
+    let pubs = dory::PublicInput::try_from(pubs).map_err(|e| (Some("pubs"), e))?;
+    let vk = dory::VerificationKey::try_from(vk).map_err(|e| (Some("vk"), e))?;
+    dory::verify(&proof, &pubs, &vk).map_err(|e| (None, e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decoding_errors_name_the_artifact() {
+        assert!(matches!(
+            verify_artifacts(&[0xff], &[], &[]),
+            Err((Some("proof"), VerifyError::InvalidProofData))
+        ));
+    }
+}
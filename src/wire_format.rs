@@ -0,0 +1,335 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Versioning of the wire formats of the artifacts exchanged with the verifier.
+//!
+//! Every change to the bytes of an artifact bumps [`WIRE_FORMAT_VERSION`] and is recorded
+//! in [`WIRE_FORMAT_CHANGELOG`], so that runtimes can gate artifacts by the format they
+//! were encoded in. The tests of this module pin the encoding of a fixture of every
+//! artifact to the version it was last changed in, so that no change to the bytes goes
+//! unnoticed.
+
+use serde::Serialize;
+
+/// The current version of the wire formats, i.e. the version of the last entry of
+/// [`WIRE_FORMAT_CHANGELOG`].
+pub const WIRE_FORMAT_VERSION: u32 = 1;
+
+/// The artifacts whose encoding is versioned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[non_exhaustive]
+pub enum WireArtifact {
+    /// A [`Proof`](crate::Proof).
+    Proof,
+    /// A [`ProofWithContext`](crate::ProofWithContext).
+    ProofWithContext,
+    /// A [`PublicInput`](crate::PublicInput).
+    PublicInput,
+    /// A [`Statement`](crate::Statement).
+    Statement,
+    /// A [`StatementSkeleton`](crate::StatementSkeleton).
+    StatementSkeleton,
+    /// A [`VerificationKey`](crate::VerificationKey).
+    VerificationKey,
+    /// A [`VkBundle`](crate::VkBundle).
+    VkBundle,
+    /// A [`SignedArtifact`](crate::SignedArtifact).
+    SignedArtifact,
+}
+
+impl WireArtifact {
+    /// Every versioned artifact.
+    pub const ALL: [WireArtifact; 8] = [
+        WireArtifact::Proof,
+        WireArtifact::ProofWithContext,
+        WireArtifact::PublicInput,
+        WireArtifact::Statement,
+        WireArtifact::StatementSkeleton,
+        WireArtifact::VerificationKey,
+        WireArtifact::VkBundle,
+        WireArtifact::SignedArtifact,
+    ];
+
+    /// Returns the wire format version the encoding of the artifact last changed in.
+    pub const fn format_version(self) -> u32 {
+        let mut index = WIRE_FORMAT_CHANGELOG.len();
+        while index > 0 {
+            index -= 1;
+            let change = &WIRE_FORMAT_CHANGELOG[index];
+            let mut artifact = 0;
+            while artifact < change.artifacts.len() {
+                if change.artifacts[artifact] as u8 == self as u8 {
+                    return change.version;
+                }
+                artifact += 1;
+            }
+        }
+        // Every artifact is introduced by the first version, see below
+        unreachable!()
+    }
+}
+
+/// A change to the wire formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatChange {
+    /// The version introducing the change.
+    pub version: u32,
+    /// The artifacts whose encoding changed.
+    pub artifacts: &'static [WireArtifact],
+    /// A description of the change.
+    pub summary: &'static str,
+}
+
+/// Every change to the wire formats, oldest first.
+///
+/// Entries are never changed once released: a change to the encoding of any artifact
+/// appends an entry with the next version, and bumps [`WIRE_FORMAT_VERSION`].
+pub const WIRE_FORMAT_CHANGELOG: &[FormatChange] = &[FormatChange {
+    version: 1,
+    artifacts: &WireArtifact::ALL,
+    summary: "Initial formats",
+}];
+
+// Checks the changelog at compile time: versions are consecutive, starting from 1 and
+// ending with the current version, and the first one introduces every artifact.
+const _: () = {
+    let mut index = 0;
+    while index < WIRE_FORMAT_CHANGELOG.len() {
+        let change = &WIRE_FORMAT_CHANGELOG[index];
+        assert!(change.version == index as u32 + 1);
+        assert!(!change.artifacts.is_empty());
+        index += 1;
+    }
+    assert!(WIRE_FORMAT_CHANGELOG[index - 1].version == WIRE_FORMAT_VERSION);
+    assert!(WIRE_FORMAT_CHANGELOG[0].artifacts.len() == WireArtifact::ALL.len());
+};
+
+// The fixtures cover every artifact, signed ones included
+#[cfg(all(test, feature = "signed"))]
+mod test {
+    use alloc::{string::String, vec::Vec};
+    use ark_std::test_rng;
+    use ciborium::Value;
+    use proof_of_sql::{
+        base::{
+            commitment::{QueryCommitments, QueryCommitmentsExt},
+            database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+        },
+        proof_primitive::dory::{
+            DoryEvaluationProof, DoryProverPublicSetup, ProverSetup, PublicParameters,
+        },
+        sql::{
+            parse::QueryExpr,
+            proof::{ProofPlan, VerifiableQueryResult},
+        },
+    };
+    use sha3::{Digest, Keccak256};
+
+    use super::*;
+    use crate::{
+        Proof, ProofWithContext, PublicInput, SemVer, SignedArtifact, StatementSkeleton,
+        VerificationKey, VkBundle,
+    };
+
+    /// The Keccak-256 digests of the fixture of each artifact, by the format version
+    /// they were recorded in, oldest first.
+    ///
+    /// When a test below reports that the encoding of an artifact changed, the change
+    /// must be deliberate: append an entry to [`WIRE_FORMAT_CHANGELOG`], bump
+    /// [`WIRE_FORMAT_VERSION`], and append the new digest with the new version here,
+    /// leaving the previous digests untouched.
+    const FIXTURE_DIGESTS: &[(WireArtifact, u32, &str)] = &[
+        (
+            WireArtifact::Proof,
+            1,
+            "e5bdbac62ed5e2a57bbb35c9ecd9df10d59e595f80fc650c55ac6fe6a19466f9",
+        ),
+        (
+            WireArtifact::ProofWithContext,
+            1,
+            "2e1d2d5919542f5d6a8f5c25087a58fddbe037c4ef303689c25d94605db0740f",
+        ),
+        (
+            WireArtifact::PublicInput,
+            1,
+            "5dbdbcd6f1fe99655d4be0831233d7095e44ed5e1f63f926252902325b604d13",
+        ),
+        (
+            WireArtifact::Statement,
+            1,
+            "b1ba31c77260a4f112266e70166cedddebd27b3557e774a15edc74720a5f219e",
+        ),
+        (
+            WireArtifact::StatementSkeleton,
+            1,
+            "1403b1fb9f129569c8f061fedac83f9ee79f48d4e1535c25fb2f30f2a352cac0",
+        ),
+        (
+            WireArtifact::VerificationKey,
+            1,
+            "8bdba36cc69fc5c0965f72d374ff36642a71f6c364b1f27677b6ad0dbb329b17",
+        ),
+        (
+            WireArtifact::VkBundle,
+            1,
+            "ac3092a6ad8b9e612369aaf525a6f0d6d2c097c1828b8f5ee859a1c4205c7162",
+        ),
+        (
+            WireArtifact::SignedArtifact,
+            1,
+            "df62e0e70cd55aa5fc6d6c3799e5c9835c6f94a9286e12e5fcf88aaea3a859c9",
+        ),
+    ];
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes
+            .iter()
+            .map(|byte| alloc::format!("{byte:02x}"))
+            .collect()
+    }
+
+    fn field_mut<'a>(value: &'a mut Value, name: &str) -> &'a mut Value {
+        value
+            .as_map_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|(key, _)| key.as_text() == Some(name))
+            .map(|(_, field)| field)
+            .unwrap()
+    }
+
+    /// Builds the fixture of every artifact, which must be deterministic.
+    fn build_fixtures() -> Vec<(WireArtifact, Vec<u8>)> {
+        let public_parameters = PublicParameters::test_rand(2, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 2);
+        let vk = VerificationKey::new(&public_parameters, 2);
+
+        let mut accessor =
+            OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+        accessor.add_table(
+            "sxt.table".parse().unwrap(),
+            owned_table([
+                bigint("a", [1, 2, 3, 2]),
+                varchar("b", ["x", "y", "z", "w"]),
+            ]),
+            0,
+        );
+        let query = QueryExpr::try_new(
+            "SELECT b FROM table WHERE a = 2".parse().unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &vk.to_dory())
+            .unwrap();
+        let columns = query.proof_expr().get_column_references();
+        let commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+        let pubs = PublicInput::new(query.proof_expr(), commitments, query_data);
+        let proof = Proof::new(proof);
+        let pubs_bytes = pubs.try_to_bytes().unwrap();
+
+        let statement = pubs.into_statement().try_to_bytes().unwrap();
+        let mut skeleton: Value = ciborium::from_reader(&statement[..]).unwrap();
+        for (_, commitment) in field_mut(&mut skeleton, "commitments")
+            .as_map_mut()
+            .unwrap()
+        {
+            field_mut(commitment, "column_commitments")
+                .as_map_mut()
+                .unwrap()
+                .retain(|(key, _)| key.as_text() != Some("commitments"));
+        }
+        let mut skeleton_bytes = Vec::new();
+        ciborium::into_writer(&skeleton, &mut skeleton_bytes).unwrap();
+        let skeleton = StatementSkeleton::try_from(&skeleton_bytes[..]).unwrap();
+
+        let mut bundle = VkBundle::new();
+        bundle.insert(vk.clone());
+        let context = ProofWithContext::new(proof.clone(), SemVer::new(0, 28, 10), 1_700_000_000);
+        let signed = SignedArtifact::sign(pubs_bytes.clone(), &[7; 32]);
+
+        Vec::from([
+            (WireArtifact::Proof, proof.to_bytes()),
+            (WireArtifact::ProofWithContext, context.to_bytes()),
+            (WireArtifact::PublicInput, pubs_bytes),
+            (WireArtifact::Statement, statement),
+            (
+                WireArtifact::StatementSkeleton,
+                skeleton.try_to_bytes().unwrap(),
+            ),
+            (WireArtifact::VerificationKey, vk.to_bytes()),
+            (WireArtifact::VkBundle, bundle.to_bytes()),
+            (WireArtifact::SignedArtifact, signed.to_bytes()),
+        ])
+    }
+
+    #[test]
+    fn fixtures_match_their_format_version() {
+        let fixtures = build_fixtures();
+        assert!(fixtures
+            .iter()
+            .map(|(artifact, _)| *artifact)
+            .eq(WireArtifact::ALL));
+        for (artifact, bytes) in fixtures {
+            let digest = hex(&Keccak256::digest(&bytes));
+            let recorded = FIXTURE_DIGESTS
+                .iter()
+                .rev()
+                .find(|(recorded, _, _)| *recorded == artifact);
+            assert_eq!(
+                recorded.map(|(_, version, digest)| (*version, *digest)),
+                Some((artifact.format_version(), &digest[..])),
+                "the encoding of {artifact:?} changed, bump the wire format version"
+            );
+        }
+    }
+
+    #[test]
+    fn fixture_digests_are_bumped_monotonically() {
+        for artifact in WireArtifact::ALL {
+            let recorded: Vec<_> = FIXTURE_DIGESTS
+                .iter()
+                .filter(|(recorded, _, _)| *recorded == artifact)
+                .collect();
+            for pair in recorded.windows(2) {
+                let ((_, old_version, old_digest), (_, new_version, new_digest)) =
+                    (pair[0], pair[1]);
+                assert!(old_version < new_version, "{artifact:?}");
+                assert_ne!(old_digest, new_digest, "{artifact:?}");
+            }
+            // Every recorded version must change the artifact in the changelog
+            for (_, version, _) in recorded {
+                assert!(WIRE_FORMAT_CHANGELOG.iter().any(
+                    |change| change.version == *version && change.artifacts.contains(&artifact)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn format_versions_follow_the_changelog() {
+        for artifact in WireArtifact::ALL {
+            assert!(artifact.format_version() <= WIRE_FORMAT_VERSION);
+        }
+        assert_eq!(WireArtifact::Proof.format_version(), 1);
+    }
+}
@@ -0,0 +1,772 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "json")]
+
+use std::{fs, path::PathBuf, process::Command};
+
+use ark_std::test_rng;
+use proof_of_sql::{
+    base::{
+        commitment::{QueryCommitments, QueryCommitmentsExt},
+        database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+    },
+    proof_primitive::dory::{
+        DoryEvaluationProof, DoryProverPublicSetup, DoryVerifierPublicSetup, ProverSetup,
+        PublicParameters, VerifierSetup,
+    },
+    sql::{
+        parse::QueryExpr,
+        proof::{ProofPlan, VerifiableQueryResult},
+    },
+};
+use proof_of_sql_verifier::{
+    Artifact, HashFunction, HashVersion, Profile, Proof, PublicInput, ReplayEntry, VerificationKey,
+};
+
+/// Creates an empty scratch directory for the test.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("posql-verifier-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Generates serialized artifacts for a sample query.
+fn build_artifacts() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+    let ps = ProverSetup::from(&public_parameters);
+    let vs = VerifierSetup::from(&public_parameters);
+    let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+
+    let mut accessor =
+        OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+    accessor.add_table(
+        "sxt.table".parse().unwrap(),
+        owned_table([bigint("a", [1, 2, 3, 2])]),
+        0,
+    );
+    let query = QueryExpr::try_new(
+        "SELECT a FROM table WHERE a = 2".parse().unwrap(),
+        "sxt".parse().unwrap(),
+        &accessor,
+    )
+    .unwrap();
+    let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+        query.proof_expr(),
+        &accessor,
+        &prover_setup,
+    );
+    let query_data = proof
+        .verify(
+            query.proof_expr(),
+            &accessor,
+            &DoryVerifierPublicSetup::new(&vs, 4),
+        )
+        .unwrap();
+    let columns = query.proof_expr().get_column_references();
+    let query_commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+    let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+    (
+        Proof::new(proof).to_bytes(),
+        pubs.try_to_bytes().unwrap(),
+        VerificationKey::new(&public_parameters, 4).to_bytes(),
+    )
+}
+
+/// Tests that `watch --once` records every complete submission exactly once.
+#[test]
+fn watch_records_submissions_once() {
+    let dir = scratch_dir("watch");
+    let incoming = dir.join("incoming");
+    fs::create_dir(&incoming).unwrap();
+    let (proof, pubs, vk) = build_artifacts();
+    fs::write(dir.join("vk.bin"), vk).unwrap();
+    fs::write(incoming.join("a.proof.bin"), &proof).unwrap();
+    fs::write(incoming.join("a.pubs.bin"), &pubs).unwrap();
+    fs::write(incoming.join("b.proof.bin"), [0xff]).unwrap();
+    fs::write(incoming.join("b.pubs.bin"), &pubs).unwrap();
+    // Incomplete submissions are left for later
+    fs::write(incoming.join("c.proof.bin"), &proof).unwrap();
+
+    let out = dir.join("results.jsonl");
+    let watch = || {
+        let status = Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+            .args(["watch", "--once", "--dir"])
+            .arg(&incoming)
+            .arg("--vk")
+            .arg(dir.join("vk.bin"))
+            .arg("--out")
+            .arg(&out)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        fs::read_to_string(&out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let outcomes = watch();
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(outcomes[0]["id"], "a");
+    assert_eq!(outcomes[0]["verified"], true);
+    assert_eq!(outcomes[1]["id"], "b");
+    assert_eq!(outcomes[1]["verified"], false);
+    assert!(outcomes[1]["error"].is_string());
+
+    fs::write(incoming.join("c.pubs.bin"), &pubs).unwrap();
+    let outcomes = watch();
+    assert_eq!(outcomes.len(), 3);
+    assert_eq!(outcomes[2]["id"], "c");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests that `replay` reports the logged submissions whose outcome changed.
+#[test]
+fn replay_reports_divergences() {
+    let dir = scratch_dir("replay");
+    let (proof, pubs, vk) = build_artifacts();
+    fs::write(dir.join("vk.bin"), &vk).unwrap();
+    let entry = |id: &str, proof: &[u8], vk: Option<&[u8]>, verified: bool| {
+        let entry = ReplayEntry {
+            id: id.to_string(),
+            proof: proof.to_vec(),
+            pubs: pubs.clone(),
+            vk: vk.map(<[u8]>::to_vec),
+            verified,
+        };
+        serde_json::to_string(&entry).unwrap()
+    };
+    let replay = |log: &[String]| {
+        fs::write(dir.join("log.jsonl"), log.join("\n")).unwrap();
+        Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+            .args(["replay", "--log"])
+            .arg(dir.join("log.jsonl"))
+            .arg("--vk")
+            .arg(dir.join("vk.bin"))
+            .output()
+            .unwrap()
+    };
+
+    let consistent = [
+        entry("a", &proof, Some(&vk), true),
+        entry("b", &proof, None, true),
+        entry("c", &proof[1..], None, false),
+    ];
+    let output = replay(&consistent);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let diverging = [
+        entry("a", &proof, None, false),
+        entry("b", &proof[1..], None, true),
+    ];
+    let output = replay(&diverging);
+    assert!(!output.status.success());
+    let divergences: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(divergences.len(), 2);
+    assert_eq!(divergences[0]["id"], "a");
+    assert_eq!(divergences[0]["replayed"], true);
+    assert_eq!(divergences[1]["id"], "b");
+    assert_eq!(divergences[1]["logged"], true);
+    assert!(divergences[1]["error"].is_string());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests that `params validate` accepts consistent setups and rejects mismatched ones.
+#[test]
+fn params_validate_checks_setups() {
+    let dir = scratch_dir("params");
+    let public_parameters = PublicParameters::test_rand(2, &mut test_rng());
+    public_parameters
+        .save_to_file(&dir.join("params.bin"))
+        .unwrap();
+    let vk = VerificationKey::new(&public_parameters, 1);
+    fs::write(dir.join("vk.bin"), vk.to_bytes()).unwrap();
+    let other = VerificationKey::new(&PublicParameters::test_rand(3, &mut test_rng()), 1);
+    fs::write(dir.join("other.bin"), other.to_bytes()).unwrap();
+    let validate = |vk: &str| {
+        Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+            .args(["params", "validate", "--params"])
+            .arg(dir.join("params.bin"))
+            .arg("--vk")
+            .arg(dir.join(vk))
+            .output()
+            .unwrap()
+    };
+
+    let output = validate("vk.bin");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("valid verification key, max_nu 2, sigma 1"));
+
+    let output = validate("other.bin");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("not derived from the public parameters"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests that `vk inspect` prints the parameters and capacity of keys and setups.
+#[test]
+fn vk_inspect_prints_capacity() {
+    let dir = scratch_dir("vk");
+    let vk = VerificationKey::new(&PublicParameters::test_rand(3, &mut test_rng()), 2);
+    fs::write(dir.join("vk.bin"), vk.to_bytes()).unwrap();
+    let inspect = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+            .args(["vk", "inspect"])
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+
+    let output = inspect(&["vk.bin"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let fingerprint: String = vk
+        .fingerprint()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    assert_eq!(
+        stdout,
+        format!("fingerprint {fingerprint}\nmax_nu 3\nsigma 2\nmax_rows 32\n")
+    );
+
+    let output = inspect(&["--max-nu", "16", "--sigma", "8"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .ends_with("max_rows 16777216\n"));
+
+    let output = inspect(&["--max-nu", "1", "--sigma", "2"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(inspect(&["--max-nu", "1"]).status.code(), Some(2));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests that `hash` prints the digests computed by the library.
+#[test]
+fn hash_prints_artifact_digests() {
+    let dir = scratch_dir("hash");
+    let (proof, pubs, vk) = build_artifacts();
+    fs::write(dir.join("proof.bin"), &proof).unwrap();
+    fs::write(dir.join("pubs.bin"), &pubs).unwrap();
+    fs::write(dir.join("vk.bin"), &vk).unwrap();
+    let hash = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+            .arg("hash")
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "hash {args:?} failed");
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+    let hex = |digest: [u8; 32]| -> String { digest.iter().map(|b| format!("{b:02x}")).collect() };
+
+    let vk = VerificationKey::try_from(&vk[..]).unwrap();
+    assert_eq!(hash(&["vk", "vk.bin"]), hex(vk.fingerprint()));
+    let pubs = PublicInput::try_from(&pubs[..]).unwrap();
+    assert_eq!(
+        hash(&["pubs", "pubs.bin", "--algo", "sha256", "--domain", "tag"]),
+        hex(pubs.hash_with(HashFunction::Sha256, b"tag").unwrap())
+    );
+    for (name, version) in [
+        ("v1", HashVersion::V1),
+        ("v2", HashVersion::V2),
+        ("v3", HashVersion::V3),
+    ] {
+        assert_eq!(
+            hash(&["pubs", "pubs.bin", "--statement-hash", name]),
+            hex(pubs.hash(version).unwrap())
+        );
+    }
+    let proof = Proof::try_from(&proof[..]).unwrap();
+    let domain = String::from_utf8(Profile::ZkVerify.domain_tag().to_vec()).unwrap();
+    assert_eq!(
+        hash(&["proof", "proof.bin", "--domain", &domain]),
+        hex(proof.hash_for(Profile::ZkVerify).unwrap())
+    );
+    assert_eq!(
+        hash(&["proof", "proof.bin", "--algo", "blake2"]),
+        hex(HashFunction::Blake2b256.hash(&[&proof.to_bytes()]))
+    );
+
+    for args in [
+        &["vk", "proof.bin"][..],
+        &["vk", "vk.bin", "--statement-hash", "v3"],
+        &[
+            "pubs",
+            "pubs.bin",
+            "--statement-hash",
+            "v3",
+            "--domain",
+            "tag",
+        ],
+        &["pubs", "pubs.bin", "--statement-hash", "v4"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+            .arg("hash")
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "hash {args:?} succeeded");
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Runs `verify` on the artifacts written to `dir` by [`build_artifacts`].
+fn run_verify(dir: &PathBuf, sources: [&str; 3], args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+        .args(["verify", "--proof", sources[0], "--pubs", sources[1]])
+        .args(["--vk", sources[2]])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+/// Tests that `verify` checks pinned digests and size limits before verifying.
+#[test]
+fn verify_checks_pinned_artifacts() {
+    let dir = scratch_dir("verify");
+    let (proof, pubs, vk) = build_artifacts();
+    fs::write(dir.join("proof.bin"), &proof).unwrap();
+    fs::write(dir.join("pubs.bin"), &pubs).unwrap();
+    fs::write(dir.join("vk.bin"), &vk).unwrap();
+    let files = ["proof.bin", "pubs.bin", "vk.bin"];
+    let hex: String = HashFunction::Keccak256
+        .hash(&[&proof])
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+
+    let output = run_verify(&dir, files, &["--expect-hash", &format!("proof={hex}")]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "verified, 2 rows and 1 columns\n"
+    );
+
+    let output = run_verify(&dir, files, &["--expect-hash", &format!("pubs={hex}")]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("pubs.bin doesn't match the expected pubs hash"));
+
+    let output = run_verify(&dir, files, &["--max-size", "100"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("larger than 100 bytes"));
+
+    let output = run_verify(&dir, files, &["--expect-hash", "proof=00"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    #[cfg(not(feature = "remote-artifacts"))]
+    {
+        let output = run_verify(
+            &dir,
+            ["https://localhost/proof.bin", files[1], files[2]],
+            &[],
+        );
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("require the remote-artifacts feature"));
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests that `verify --sandbox` verifies in a subprocess, and survives its failures.
+#[cfg(all(feature = "sandbox", unix))]
+#[test]
+fn verify_in_sandbox() {
+    let dir = scratch_dir("sandbox");
+    let (proof, pubs, vk) = build_artifacts();
+    fs::write(dir.join("proof.bin"), &proof).unwrap();
+    fs::write(dir.join("pubs.bin"), &pubs).unwrap();
+    fs::write(dir.join("vk.bin"), &vk).unwrap();
+    fs::write(dir.join("invalid.bin"), [0xff]).unwrap();
+    let files = ["proof.bin", "pubs.bin", "vk.bin"];
+
+    let output = run_verify(&dir, files, &["--sandbox"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "verified, 2 rows and 1 columns\n"
+    );
+
+    let output = run_verify(
+        &dir,
+        ["invalid.bin", files[1], files[2]],
+        &["--sandbox", "--output", "json"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["verified"], false);
+    assert_eq!(report["reason"], "invalid_proof_data");
+
+    // The subprocess can't even load with 1 MiB of address space
+    let output = run_verify(&dir, files, &["--sandbox", "--sandbox-memory", "1048576"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("verification subprocess"));
+
+    let output = run_verify(&dir, files, &["--sandbox-cpu", "1"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests that `verify` fetches `s3://` artifacts from the configured endpoint.
+#[cfg(feature = "remote-artifacts")]
+#[test]
+fn verify_fetches_remote_artifacts() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    let dir = scratch_dir("remote");
+    let (proof, pubs, vk) = build_artifacts();
+    fs::write(dir.join("vk.bin"), &vk).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request_line = String::new();
+            BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .unwrap();
+            let body = match request_line.split(' ').nth(1) {
+                Some("/bucket/q.proof.bin") => &proof,
+                Some("/bucket/q.pubs.bin") => &pubs,
+                _ => {
+                    stream
+                        .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                        .unwrap();
+                    continue;
+                }
+            };
+            let header = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len());
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+    let verify = |proof: &str, args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+            .args([
+                "verify",
+                "--proof",
+                proof,
+                "--pubs",
+                "s3://bucket/q.pubs.bin",
+            ])
+            .args(["--vk", "vk.bin"])
+            .args(args)
+            .env("AWS_ENDPOINT_URL_S3", &endpoint)
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+
+    assert!(verify("s3://bucket/q.proof.bin", &[]).status.success());
+    let output = verify("s3://bucket/q.proof.bin", &["--max-size", "1000"]);
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("s3://bucket/q.proof.bin is larger than 1000 bytes"));
+    let output = verify("s3://bucket/missing.bin", &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("cannot fetch s3://bucket/missing.bin"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests that `capabilities` prints the library capabilities as JSON.
+#[test]
+fn capabilities_prints_json() {
+    let output = Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+        .arg("capabilities")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json,
+        serde_json::from_slice::<serde_json::Value>(
+            &proof_of_sql_verifier::capabilities().to_json()
+        )
+        .unwrap()
+    );
+}
+
+/// Tests the JSON output of every command against snapshots, so that changes to the
+/// schema are deliberate.
+#[test]
+fn json_output_snapshots() {
+    let dir = scratch_dir("json");
+    let (proof, pubs, vk) = build_artifacts();
+    fs::write(dir.join("proof.bin"), &proof).unwrap();
+    fs::write(dir.join("pubs.bin"), &pubs).unwrap();
+    fs::write(dir.join("vk.bin"), &vk).unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+            .args(args)
+            .args(["--output", "json"])
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let hex = |digest: [u8; 32]| -> String { digest.iter().map(|b| format!("{b:02x}")).collect() };
+    let fingerprint = hex(VerificationKey::try_from(&vk[..]).unwrap().fingerprint());
+
+    assert_eq!(
+        run(&[
+            "verify",
+            "--proof",
+            "proof.bin",
+            "--pubs",
+            "pubs.bin",
+            "--vk",
+            "vk.bin"
+        ]),
+        r#"{"columns":1,"command":"verify","error":null,"reason":null,"rows":2,"schema_version":1,"verified":true}
+"#
+    );
+    // The rng is seeded, so the parameters of the artifacts are drawn first
+    let rng = &mut test_rng();
+    PublicParameters::test_rand(4, rng);
+    let other_vk = VerificationKey::new(&PublicParameters::test_rand(4, rng), 4);
+    fs::write(dir.join("other_vk.bin"), other_vk.to_bytes()).unwrap();
+    assert_eq!(
+        run(&[
+            "verify",
+            "--proof",
+            "proof.bin",
+            "--pubs",
+            "pubs.bin",
+            "--vk",
+            "other_vk.bin"
+        ]),
+        r#"{"columns":null,"command":"verify","error":"Proof rejected at the opening stage","reason":"proof_rejected_opening","rows":null,"schema_version":1,"verified":false}
+"#
+    );
+
+    assert_eq!(
+        run(&["hash", "vk", "vk.bin", "--algo", "sha256", "--domain", "tag"]),
+        format!(
+            r#"{{"algorithm":"sha256","artifact":"vk","command":"hash","digest":"{}","domain":"tag","schema_version":1,"statement_hash":null}}
+"#,
+            hex(VerificationKey::try_from(&vk[..])
+                .unwrap()
+                .hash_with(HashFunction::Sha256, b"tag")
+                .unwrap())
+        )
+    );
+
+    assert_eq!(
+        run(&["vk", "inspect", "vk.bin"]),
+        format!(
+            r#"{{"command":"vk inspect","fingerprint":"{fingerprint}","max_nu":4,"max_rows":256,"schema_version":1,"sigma":4}}
+"#
+        )
+    );
+
+    assert_eq!(
+        run(&["params", "validate", "--vk", "vk.bin"]),
+        r#"{"command":"params validate","derived":null,"params":null,"schema_version":1,"vk":{"max_nu":4,"path":"vk.bin","sigma":4}}
+"#
+    );
+
+    fs::write(
+        dir.join("log.jsonl"),
+        serde_json::to_string(&ReplayEntry {
+            id: "a".to_string(),
+            proof: proof.clone(),
+            pubs: pubs.clone(),
+            vk: None,
+            verified: false,
+        })
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        run(&["replay", "--log", "log.jsonl", "--vk", "vk.bin"]),
+        r#"{"command":"replay","divergences":[{"error":null,"id":"a","logged":false,"replayed":true}],"replayed":1,"schema_version":1}
+"#
+    );
+
+    let incoming = dir.join("incoming");
+    fs::create_dir(&incoming).unwrap();
+    fs::write(incoming.join("a.proof.bin"), &proof).unwrap();
+    fs::write(incoming.join("a.pubs.bin"), &pubs).unwrap();
+    let watched = run(&[
+        "watch",
+        "--once",
+        "--dir",
+        "incoming",
+        "--vk",
+        "vk.bin",
+        "--out",
+        "results.jsonl",
+    ]);
+    let mut outcome: serde_json::Value = serde_json::from_str(&watched).unwrap();
+    assert!(outcome["timestamp"].as_u64().unwrap() > 0);
+    outcome["timestamp"] = 0.into();
+    assert_eq!(
+        outcome.to_string(),
+        r#"{"command":"watch","error":null,"id":"a","schema_version":1,"timestamp":0,"verified":true}"#
+    );
+
+    let capabilities: serde_json::Value = serde_json::from_str(&run(&["capabilities"])).unwrap();
+    assert_eq!(capabilities["schema_version"], 1);
+    assert_eq!(capabilities["command"], "capabilities");
+    assert_eq!(
+        capabilities["capabilities"],
+        serde_json::from_slice::<serde_json::Value>(
+            &proof_of_sql_verifier::capabilities().to_json()
+        )
+        .unwrap()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests that `soak` samples the memory usage between passes over the corpus.
+#[test]
+fn soak_samples_memory_usage() {
+    let dir = scratch_dir("soak");
+    let corpus = dir.join("corpus");
+    fs::create_dir(&corpus).unwrap();
+    let (proof, pubs, vk) = build_artifacts();
+    fs::write(dir.join("vk.bin"), vk).unwrap();
+    fs::write(corpus.join("a.proof.bin"), &proof).unwrap();
+    fs::write(corpus.join("a.pubs.bin"), &pubs).unwrap();
+    fs::write(corpus.join("b.proof.bin"), [0xff]).unwrap();
+    fs::write(corpus.join("b.pubs.bin"), &pubs).unwrap();
+    let soak = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_soak"))
+            .arg("--dir")
+            .arg(&corpus)
+            .arg("--vk")
+            .arg(dir.join("vk.bin"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = soak(&["--duration", "1", "--report-secs", "0", "--output", "json"]);
+    assert!(output.status.success());
+    let samples: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(!samples.is_empty());
+    for (index, sample) in samples.iter().enumerate() {
+        assert_eq!(sample["command"], "soak");
+        assert_eq!(sample["passes"], index + 1);
+        assert_eq!(sample["verifications"], 2 * (index + 1));
+        assert!(sample["heap_bytes"].as_u64().unwrap() > 0);
+    }
+
+    assert_eq!(soak(&["--windows", "0"]).status.code(), Some(2));
+    fs::remove_dir_all(&corpus).unwrap();
+    fs::create_dir(&corpus).unwrap();
+    assert_eq!(soak(&[]).status.code(), Some(1));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests that invalid arguments exit with the usage error code and print the usage.
+#[test]
+fn invalid_arguments_print_usage() {
+    for args in [
+        &[][..],
+        &["verify"],
+        &["watch", "--dir"],
+        &["capabilities", "x"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_posql-verifier"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.starts_with("error: "), "{args:?}");
+        assert!(stderr.contains("Usage: posql-verifier"), "{args:?}");
+    }
+}
+
+/// Tests that `generate-sample-proof` reports invalid arguments and unwritable outputs
+/// instead of panicking.
+#[cfg(all(feature = "rand", feature = "test"))]
+#[test]
+fn generate_sample_proof_reports_errors() {
+    let dir = scratch_dir("generate");
+    let generate = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_generate-sample-proof"))
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+
+    let output = generate(&["--max-nu", "40"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("invalid value 40 for --max-nu"));
+    let output = generate(&["--out", "missing"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    fs::create_dir(dir.join("out")).unwrap();
+    fs::create_dir(dir.join("out").join("vk.bin")).unwrap();
+    let output = generate(&["--max-nu", "2", "--out", "out"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("cannot write out/vk.bin"));
+
+    fs::remove_dir(dir.join("out").join("vk.bin")).unwrap();
+    assert!(generate(&["--max-nu", "2", "--out", "out"])
+        .status
+        .success());
+    let vk = fs::read(dir.join("out").join("vk.bin")).unwrap();
+    assert_eq!(VerificationKey::try_from(&vk[..]).unwrap().max_nu(), 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
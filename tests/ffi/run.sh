@@ -0,0 +1,21 @@
+#!/usr/bin/env bash
+# Builds the static library, generates a sample proof and runs the C smoke test against them.
+set -euo pipefail
+
+ROOT="$(cd "$(dirname "${BASH_SOURCE[0]}")/../.." && pwd)"
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "${WORK_DIR}"' EXIT
+
+cd "${ROOT}"
+cargo build --release -p proof-of-sql-verifier-ffi
+cargo build --release --bin generate-sample-proof --features "rand test"
+
+cd "${WORK_DIR}"
+"${ROOT}/target/release/generate-sample-proof"
+${CC:-cc} -std=c99 -Wall -Wextra -Werror \
+  -I "${ROOT}/ffi/include" \
+  "${ROOT}/tests/ffi/smoke.c" \
+  "${ROOT}/target/release/libproof_of_sql_verifier_ffi.a" \
+  -lpthread -ldl -lm \
+  -o smoke
+./smoke proof.bin pubs.bin vk.bin
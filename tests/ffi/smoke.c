@@ -0,0 +1,65 @@
+/*
+ * Copyright 2024, Horizen Labs, Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * Smoke test of the C bindings: verifies the sample proof written by
+ * `generate-sample-proof`, then checks that a tampered proof is rejected.
+ *
+ * Usage: smoke <proof.bin> <pubs.bin> <vk.bin>
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "proof_of_sql_verifier.h"
+
+static uint8_t *read_file(const char *path, size_t *len) {
+  FILE *file = fopen(path, "rb");
+  if (file == NULL) {
+    perror(path);
+    exit(EXIT_FAILURE);
+  }
+  fseek(file, 0, SEEK_END);
+  *len = (size_t)ftell(file);
+  rewind(file);
+  uint8_t *data = malloc(*len);
+  if (data == NULL || fread(data, 1, *len, file) != *len) {
+    fprintf(stderr, "failed to read %s\n", path);
+    exit(EXIT_FAILURE);
+  }
+  fclose(file);
+  return data;
+}
+
+int main(int argc, char **argv) {
+  if (argc != 4) {
+    fprintf(stderr, "usage: %s <proof.bin> <pubs.bin> <vk.bin>\n", argv[0]);
+    return EXIT_FAILURE;
+  }
+
+  size_t proof_len, pubs_len, vk_len;
+  uint8_t *proof = read_file(argv[1], &proof_len);
+  uint8_t *pubs = read_file(argv[2], &pubs_len);
+  uint8_t *vk = read_file(argv[3], &vk_len);
+
+  printf("proof-of-sql-verifier %s\n", posql_version());
+
+  PosqlStatus status = posql_verify(proof, proof_len, pubs, pubs_len, vk, vk_len);
+  if (status != POSQL_STATUS_OK) {
+    fprintf(stderr, "valid proof rejected with status %d\n", status);
+    return EXIT_FAILURE;
+  }
+
+  proof[proof_len / 2] ^= 0x01;
+  status = posql_verify(proof, proof_len, pubs, pubs_len, vk, vk_len);
+  if (status == POSQL_STATUS_OK) {
+    fprintf(stderr, "tampered proof accepted\n");
+    return EXIT_FAILURE;
+  }
+
+  free(proof);
+  free(pubs);
+  free(vk);
+  printf("ok\n");
+  return EXIT_SUCCESS;
+}
@@ -13,6 +13,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "context")]
+use std::{cell::RefCell, time::Duration};
+
 use ark_std::test_rng;
 use proof_of_sql::{
     base::commitment::QueryCommitments,
@@ -26,11 +29,17 @@ use proof_of_sql::{
     },
     sql::{
         parse::QueryExpr,
-        proof::{ProofPlan, VerifiableQueryResult},
+        proof::{ProofPlan, QueryData, VerifiableQueryResult},
     },
 };
 
-use proof_of_sql_verifier::{Proof, PublicInput, VerificationKey};
+#[cfg(feature = "context")]
+use proof_of_sql_verifier::{
+    OutcomeLabels, OutcomeSink, SlowVerification, SubmissionDeduper, TableId, VerifierContext,
+};
+use proof_of_sql_verifier::{
+    Parallelism, Proof, PublicInput, VerificationKey, VerifierConfig, VerifyError,
+};
 
 // Helper functions for setting up test data and queries
 
@@ -46,7 +55,7 @@ fn compute_query_commitments<C: Commitment>(
 /// Builds a test accessor with sample data.
 fn build_accessor<T: CommitmentEvaluationProof>(
     setup: <T as CommitmentEvaluationProof>::ProverPublicSetup<'_>,
-) -> OwnedTableTestAccessor<T> {
+) -> OwnedTableTestAccessor<'_, T> {
     let mut accessor = OwnedTableTestAccessor::<T>::new_empty_with_setup(setup);
     accessor.add_table(
         "sxt.table".parse().unwrap(),
@@ -62,7 +71,7 @@ fn build_accessor<T: CommitmentEvaluationProof>(
 /// Builds a test accessor with altered sample data.
 fn build_altered_accessor<T: CommitmentEvaluationProof>(
     setup: <T as CommitmentEvaluationProof>::ProverPublicSetup<'_>,
-) -> OwnedTableTestAccessor<T> {
+) -> OwnedTableTestAccessor<'_, T> {
     let mut accessor = OwnedTableTestAccessor::<T>::new_empty_with_setup(setup);
     accessor.add_table(
         "sxt.table".parse().unwrap(),
@@ -78,7 +87,7 @@ fn build_altered_accessor<T: CommitmentEvaluationProof>(
 /// Builds a test accessor with different table and column names.
 fn build_alien_accessor<T: CommitmentEvaluationProof>(
     setup: <T as CommitmentEvaluationProof>::ProverPublicSetup<'_>,
-) -> OwnedTableTestAccessor<T> {
+) -> OwnedTableTestAccessor<'_, T> {
     let mut accessor = OwnedTableTestAccessor::<T>::new_empty_with_setup(setup);
     accessor.add_table(
         "sxt.table2".parse().unwrap(),
@@ -164,6 +173,266 @@ mod generate_and_verify_proof {
         assert!(result.is_ok());
     }
 
+    /// Tests verification of a Dory proof through the configurable entry point.
+    #[test]
+    fn with_sequential_config() {
+        // Initialize setup
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        // Build table accessor and query
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+
+        // Generate proof
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+
+        // Get query data and commitments
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+
+        // Verify proof
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+        let config = VerifierConfig::default().with_parallelism(Parallelism::Sequential);
+        let result = proof_of_sql_verifier::verify_proof_with_config(&proof, &pubs, &vk, &config);
+
+        assert!(result.is_ok());
+    }
+
+    /// Tests verification of a Dory proof through a reusable verifier context.
+    #[cfg(feature = "context")]
+    #[test]
+    fn with_verifier_context() {
+        // Initialize setup
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        // Build table accessor and query
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+
+        // Generate proof
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+
+        // Get query data and commitments
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+
+        // Verify proof
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+        let context = VerifierContext::new(
+            vk,
+            VerifierConfig::new().with_parallelism(Parallelism::Sequential),
+        );
+        let pubs = context
+            .decode_public_input(&pubs.try_to_bytes().unwrap())
+            .unwrap();
+
+        assert!(context.precheck(&pubs).is_ok());
+        assert!(context.verify(&proof, &pubs).is_ok());
+    }
+
+    /// Tests that verifications above the configured threshold are reported as slow.
+    #[cfg(feature = "context")]
+    #[test]
+    fn with_slow_verification_threshold() {
+        #[derive(Default)]
+        struct Recorder {
+            outcomes: RefCell<Vec<OutcomeLabels>>,
+            slow: RefCell<Vec<SlowVerification>>,
+        }
+
+        impl OutcomeSink for Recorder {
+            fn record(&self, labels: &OutcomeLabels) {
+                self.outcomes.borrow_mut().push(*labels);
+            }
+
+            fn record_slow(&self, record: &SlowVerification) {
+                self.slow.borrow_mut().push(*record);
+            }
+        }
+
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+
+        let sink = Recorder::default();
+        let context = VerifierContext::new(vk.clone(), VerifierConfig::new());
+        assert!(context.verify_recorded(&proof, &pubs, &sink).is_ok());
+        let context = VerifierContext::new(
+            vk.clone(),
+            VerifierConfig::new().with_slow_verification_threshold(Duration::from_secs(3600)),
+        );
+        assert!(context.verify_recorded(&proof, &pubs, &sink).is_ok());
+        assert!(sink.slow.borrow().is_empty());
+
+        let context = VerifierContext::new(
+            vk,
+            VerifierConfig::new().with_slow_verification_threshold(Duration::ZERO),
+        );
+        assert!(context.verify_recorded(&proof, &pubs, &sink).is_ok());
+        assert_eq!(sink.outcomes.borrow().len(), 3);
+        let slow = sink.slow.borrow();
+        assert_eq!(slow.len(), 1);
+        assert!(slow[0].duration > Duration::ZERO);
+        assert_eq!(
+            slow[0].plan_fingerprint,
+            Some(pubs.plan_fingerprint().unwrap())
+        );
+        assert_eq!(slow[0].nu, max_nu);
+        assert_eq!(slow[0].result_rows, 2);
+        assert_eq!(slow[0].result_columns, 1);
+    }
+
+    /// Tests that statements verified recently are dropped by a deduplicating context.
+    #[cfg(feature = "context")]
+    #[test]
+    fn with_submission_deduper() {
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let context = VerifierContext::from(VerificationKey::new(&public_parameters, sigma));
+        let mut deduper = SubmissionDeduper::new(16, 60);
+
+        // Invalid proofs don't prevent the statement from being verified later
+        let invalid = Proof::new(Default::default());
+        assert!(context
+            .verify_deduplicated(&invalid, &pubs, &mut deduper, 1000)
+            .is_err());
+        assert!(deduper.is_empty());
+
+        context
+            .verify_deduplicated(&proof, &pubs, &mut deduper, 1000)
+            .unwrap();
+        assert!(matches!(
+            deduper.check(&pubs, 1059),
+            Err(VerifyError::DuplicateSubmission)
+        ));
+        assert!(matches!(
+            context.verify_deduplicated(&proof, &pubs, &mut deduper, 1059),
+            Err(VerifyError::DuplicateSubmission)
+        ));
+        context
+            .verify_deduplicated(&proof, &pubs, &mut deduper, 1060)
+            .unwrap();
+    }
+
+    /// Tests that commitments not starting at the expected table offset are reported.
+    #[cfg(feature = "context")]
+    #[test]
+    fn with_mismatched_offset() {
+        // Initialize setup
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        // Build table accessor and query
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+
+        // Generate proof
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+
+        // Get query data and commitments
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+
+        // Verify proof expecting the table at another offset
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+        let table = TableId::new("sxt", "table").unwrap();
+        let context = VerifierContext::new(
+            vk.clone(),
+            VerifierConfig::new().with_table_offset(table.clone(), 1000),
+        );
+
+        assert!(matches!(
+            context.precheck(&pubs),
+            Err(VerifyError::OffsetMismatch { table, expected: 1000, actual: 0 }) if table == "sxt.table"
+        ));
+        assert!(matches!(
+            context.verify(&proof, &pubs),
+            Err(VerifyError::OffsetMismatch { .. })
+        ));
+
+        let context = VerifierContext::new(vk, VerifierConfig::new().with_table_offset(table, 0));
+        assert!(context.verify(&proof, &pubs).is_ok());
+    }
+
     /// Tests the generation and verification of a Dory proof for a non-existent record.
     #[test]
     fn for_non_existant_record() {
@@ -241,6 +510,54 @@ mod generate_and_verify_proof {
         assert!(result.is_err());
     }
 
+    /// Tests that tables exceeding the verification key capacity are reported as such.
+    #[test]
+    fn table_too_large() {
+        // Initialize setup
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        // Build table accessor and query
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+
+        // Generate proof
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+
+        // Get query data and commitments
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+
+        // Verify proof with a key supporting a single row
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let small_parameters = PublicParameters::test_rand(0, &mut test_rng());
+        let vk = VerificationKey::new(&small_parameters, 0);
+
+        assert!(matches!(
+            proof_of_sql_verifier::precheck(&pubs, &vk),
+            Err(VerifyError::TableTooLarge {
+                rows: 4,
+                max_supported: 1
+            })
+        ));
+        assert!(matches!(
+            proof_of_sql_verifier::verify_proof(&proof, &pubs, &vk),
+            Err(VerifyError::TableTooLarge { .. })
+        ));
+    }
+
     /// Tests that verification fails when the underlying data has been altered.
     #[test]
     fn for_altered_data() {
@@ -322,4 +639,1294 @@ mod generate_and_verify_proof {
 
         assert!(result.is_err());
     }
+
+    /// Tests that proofs whose embedded result doesn't have the shape of the claimed
+    /// one, or that don't embed one, are rejected before verification.
+    #[test]
+    fn with_mismatched_result_payload() {
+        // Initialize setup
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        // Build table accessor and query
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+
+        // Generate proof
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let verification_hash = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap()
+            .verification_hash;
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+
+        // Claim a single row out of the two the proof attests
+        let query_data = QueryData {
+            table: owned_table([varchar("b", ["hello"])]),
+            verification_hash,
+        };
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments.clone(), query_data);
+        assert!(matches!(
+            proof_of_sql_verifier::verify_proof(&Proof::new(proof.clone()), &pubs, &vk),
+            Err(VerifyError::ResultPayloadMismatch)
+        ));
+
+        // Strip the result from the proof
+        let query_data = QueryData {
+            table: owned_table([varchar("b", ["hello", "world"])]),
+            verification_hash,
+        };
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let stripped = VerifiableQueryResult {
+            provable_result: None,
+            ..proof
+        };
+        assert!(matches!(
+            proof_of_sql_verifier::verify_proof(&Proof::new(stripped), &pubs, &vk),
+            Err(VerifyError::ResultPayloadMismatch)
+        ));
+    }
+}
+
+mod error_injection {
+    use proof_of_sql::proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup};
+    use proof_of_sql::sql::proof::{ProvableQueryResult, QueryProof};
+    use proof_of_sql_verifier::ProofStage;
+
+    use super::*;
+
+    /// A valid proof of the sample query with its public input, and a proof of the same
+    /// query over altered data to take tampered parts from.
+    fn build_proofs() -> (
+        VerifiableQueryResult<DoryEvaluationProof>,
+        VerifiableQueryResult<DoryEvaluationProof>,
+        PublicInput,
+        VerificationKey,
+    ) {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let altered_accessor = build_altered_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::new(query.proof_expr(), &accessor, &prover_setup);
+        let altered =
+            VerifiableQueryResult::new(query.proof_expr(), &altered_accessor, &prover_setup);
+        let query_data = proof
+            .verify(
+                query.proof_expr(),
+                &accessor,
+                &DoryVerifierPublicSetup::new(&vs, 4),
+            )
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        (
+            proof,
+            altered,
+            pubs,
+            VerificationKey::new(&public_parameters, 4),
+        )
+    }
+
+    /// Verifies `proof` after applying `inject` to its query proof.
+    fn verify_injected(
+        proof: &VerifiableQueryResult<DoryEvaluationProof>,
+        pubs: &PublicInput,
+        vk: &VerificationKey,
+        inject: impl FnOnce(&mut QueryProof<DoryEvaluationProof>),
+    ) -> Result<(), VerifyError> {
+        let mut proof = proof.clone();
+        inject(proof.proof.as_mut().unwrap());
+        proof_of_sql_verifier::verify_proof(&Proof::new(proof), pubs, vk)
+    }
+
+    /// Tests that failures injected at each stage of upstream verification are reported
+    /// with that stage, and that a result not decoding into the claimed one is told apart.
+    #[test]
+    fn failures_are_reported_by_stage() {
+        let (proof, altered, pubs, vk) = build_proofs();
+        let altered = altered.proof.unwrap();
+        let rejected_at = |result, expected| matches!(result, Err(VerifyError::ProofRejected { stage }) if stage == expected);
+
+        let shape = verify_injected(&proof, &pubs, &vk, |proof| {
+            proof.pcs_proof_evaluations.pop();
+        });
+        assert!(rejected_at(shape, ProofStage::Shape));
+
+        let sumcheck = verify_injected(&proof, &pubs, &vk, |proof| {
+            proof.sumcheck_proof = altered.sumcheck_proof.clone();
+        });
+        assert!(rejected_at(sumcheck, ProofStage::Sumcheck));
+
+        let result_evaluation = verify_injected(&proof, &pubs, &vk, |proof| {
+            proof
+                .pcs_proof_evaluations
+                .clone_from(&altered.pcs_proof_evaluations);
+        });
+        assert!(rejected_at(result_evaluation, ProofStage::ResultEvaluation));
+
+        let opening = verify_injected(&proof, &pubs, &vk, |proof| {
+            proof.evaluation_proof = altered.evaluation_proof.clone();
+        });
+        assert!(rejected_at(opening, ProofStage::Opening));
+
+        let undecodable = VerifiableQueryResult {
+            provable_result: Some(ProvableQueryResult::default()),
+            ..proof
+        };
+        assert!(matches!(
+            proof_of_sql_verifier::verify_proof(&Proof::new(undecodable), &pubs, &vk),
+            Err(VerifyError::ResultPayloadMismatch)
+        ));
+    }
+}
+
+mod verify_with_any {
+    use proof_of_sql::proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup};
+    use proof_of_sql_verifier::{verify_with_any, ProofStage};
+
+    use super::*;
+
+    /// Generates a proof and public input for the sample query with the given parameters.
+    fn build_proof_and_pubs(
+        public_parameters: &PublicParameters,
+        sigma: usize,
+    ) -> (Proof, PublicInput) {
+        let ps = ProverSetup::from(public_parameters);
+        let vs = VerifierSetup::from(public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        (Proof::new(proof), pubs)
+    }
+
+    /// Tests that the index of the matching key is reported.
+    #[test]
+    fn reports_matching_key() {
+        let rng = &mut test_rng();
+        let old_parameters = PublicParameters::test_rand(4, rng);
+        let new_parameters = PublicParameters::test_rand(4, rng);
+        let (proof, pubs) = build_proof_and_pubs(&new_parameters, 4);
+
+        let vks = [
+            VerificationKey::new(&old_parameters, 4),
+            VerificationKey::new(&new_parameters, 4),
+        ];
+
+        assert_eq!(verify_with_any(&proof, &pubs, &vks).unwrap(), 1);
+    }
+
+    /// Tests that keys failing the precheck are skipped.
+    #[test]
+    fn skips_unusable_keys() {
+        let parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let (proof, pubs) = build_proof_and_pubs(&parameters, 4);
+
+        let vks = [
+            VerificationKey::new(&parameters, 5),
+            VerificationKey::new(&parameters, 4),
+        ];
+
+        assert_eq!(verify_with_any(&proof, &pubs, &vks).unwrap(), 1);
+    }
+
+    /// Tests that the result embedded in the proof is checked against the claimed one.
+    #[test]
+    fn checks_result_payload() {
+        let parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let (proof, pubs) = build_proof_and_pubs(&parameters, 4);
+        let stripped = VerifiableQueryResult {
+            provable_result: None,
+            ..proof.inner().clone()
+        };
+
+        let vks = [VerificationKey::new(&parameters, 4)];
+
+        assert!(matches!(
+            verify_with_any(&Proof::new(stripped), &pubs, &vks),
+            Err(VerifyError::ResultPayloadMismatch)
+        ));
+    }
+
+    /// Tests that verification fails when no key matches.
+    #[test]
+    fn fails_without_matching_key() {
+        let rng = &mut test_rng();
+        let parameters = PublicParameters::test_rand(4, rng);
+        let other_parameters = PublicParameters::test_rand(4, rng);
+        let (proof, pubs) = build_proof_and_pubs(&parameters, 4);
+
+        let vks = [VerificationKey::new(&other_parameters, 4)];
+
+        assert!(matches!(
+            verify_with_any(&proof, &pubs, &vks),
+            Err(VerifyError::ProofRejected {
+                stage: ProofStage::Opening
+            })
+        ));
+        assert!(matches!(
+            verify_with_any(&proof, &pubs, &[]),
+            Err(VerifyError::InvalidVerificationKey)
+        ));
+    }
+}
+
+#[cfg(feature = "diagnostic")]
+mod verify_diagnostic {
+    use proof_of_sql::proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup};
+    use proof_of_sql_verifier::{verify_diagnostic, Check, CheckStatus};
+
+    use super::*;
+
+    /// Generates serialized artifacts for the sample query.
+    fn build_artifacts(sigma: usize) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, 4);
+
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+        (
+            Proof::new(proof).to_bytes(),
+            pubs.try_to_bytes().unwrap(),
+            vk.to_bytes(),
+        )
+    }
+
+    /// Tests that every check passes for a valid proof.
+    #[test]
+    fn valid_proof() {
+        let (proof, pubs, vk) = build_artifacts(4);
+        let report = verify_diagnostic(&proof, &pubs, &vk);
+
+        assert!(report.is_ok());
+        assert_eq!(report.checks().len(), 7);
+    }
+
+    /// Tests that independent failures are all reported.
+    #[test]
+    fn reports_independent_failures() {
+        let (_, pubs, vk) = build_artifacts(5);
+        let report = verify_diagnostic(&[0xff], &pubs, &vk);
+
+        let failures: Vec<_> = report.failures().map(|(check, _)| check).collect();
+        assert_eq!(failures, [Check::DecodeProof, Check::DecodeVerificationKey]);
+        assert!(matches!(
+            report.status(Check::ColumnBindings),
+            Some(CheckStatus::Passed)
+        ));
+        assert!(matches!(
+            report.status(Check::Proof),
+            Some(CheckStatus::Skipped)
+        ));
+    }
+}
+
+#[cfg(feature = "audit")]
+mod audit_trail {
+    use proof_of_sql::proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup};
+    use proof_of_sql_verifier::{
+        verify_audited, AuditOutcome, Check, ReplayEntry, SoftwareVersions,
+    };
+
+    use super::*;
+
+    /// Generates a proof and public input for the sample query.
+    fn build_artifacts() -> (Proof, PublicInput, PublicParameters) {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(
+                query.proof_expr(),
+                &accessor,
+                &DoryVerifierPublicSetup::new(&vs, 4),
+            )
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        (Proof::new(proof), pubs, public_parameters)
+    }
+
+    /// Tests that a successful verification records every check with the artifact
+    /// digests and verifier versions.
+    #[cfg(feature = "context")]
+    #[test]
+    fn records_valid_proof() {
+        let (proof, pubs, public_parameters) = build_artifacts();
+        let vk = VerificationKey::new(&public_parameters, 4);
+        let context = VerifierContext::from(vk.clone());
+
+        let trail = context.verify_audited(&proof, &pubs).unwrap();
+        assert!(trail.is_verified());
+        assert_eq!(trail.versions, SoftwareVersions::current());
+        assert_eq!(trail.digests.verification_key, vk.fingerprint());
+        assert_eq!(
+            trail.digests.statement,
+            pubs.hash(proof_of_sql_verifier::HashVersion::LATEST)
+                .unwrap()
+        );
+        assert_eq!(trail.checks.len(), 4);
+        assert!(trail.elapsed_micros.is_some());
+
+        // Digests only depend on the artifacts
+        let again = context.verify_audited(&proof, &pubs).unwrap();
+        assert_eq!(again.digests, trail.digests);
+    }
+
+    /// Tests that checks following a rejection are recorded as skipped.
+    #[test]
+    fn records_rejection() {
+        let (proof, pubs, public_parameters) = build_artifacts();
+        let vk = VerificationKey::new(&public_parameters, 5);
+
+        let trail = verify_audited(&proof, &pubs, &vk, &VerifierConfig::default()).unwrap();
+        assert!(!trail.is_verified());
+        assert_eq!(
+            trail.checks,
+            [
+                (Check::PlanPolicy, AuditOutcome::Passed),
+                (Check::ColumnBindings, AuditOutcome::Passed),
+                (
+                    Check::VerificationKey,
+                    AuditOutcome::Failed("invalid_verification_key")
+                ),
+                (Check::Proof, AuditOutcome::Skipped),
+            ]
+        );
+    }
+
+    /// Tests that replay entries hold the artifacts and the outcome of the trail, and
+    /// survive a JSON round trip.
+    #[test]
+    fn replay_entry_round_trips() {
+        let (proof, pubs, public_parameters) = build_artifacts();
+        let vk = VerificationKey::new(&public_parameters, 4);
+        let trail = verify_audited(&proof, &pubs, &vk, &VerifierConfig::default()).unwrap();
+
+        let entry = ReplayEntry::new("a", &proof, &pubs, &trail).unwrap();
+        assert!(entry.verified);
+        assert_eq!(entry.vk, None);
+        let json = serde_json::to_value(&entry).unwrap();
+        assert!(json["proof"].is_string());
+        assert!(json.get("vk").is_none());
+        assert_eq!(serde_json::from_value::<ReplayEntry>(json).unwrap(), entry);
+
+        let entry = entry.with_vk(&vk);
+        let decoded: ReplayEntry =
+            serde_json::from_str(&serde_json::to_string(&entry).unwrap()).unwrap();
+        assert_eq!(decoded, entry);
+        assert_eq!(Proof::try_from(&decoded.proof[..]).unwrap(), proof);
+        assert_eq!(
+            VerificationKey::try_from(&decoded.vk.unwrap()[..]).unwrap(),
+            vk
+        );
+    }
+}
+
+#[cfg(all(feature = "test", feature = "rand"))]
+mod generated_tables {
+    use proof_of_sql::proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup};
+    use proof_of_sql_verifier::{ColumnDistribution, TableGenerator};
+
+    use super::*;
+
+    /// Tests verification of a proof over a generated table.
+    #[test]
+    fn verify_generated_table() {
+        let max_nu = 4;
+        let sigma = max_nu;
+        let rng = &mut test_rng();
+        let public_parameters = PublicParameters::test_rand(max_nu, rng);
+        let ps = ProverSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+
+        let accessor = TableGenerator::new(200)
+            .column("a", ColumnDistribution::UniformBigInt(0..=3))
+            .column("b", ColumnDistribution::VarChar { length: 0..=16 })
+            .build_accessor::<DoryEvaluationProof>("sxt.table".parse().unwrap(), prover_setup, rng);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let vs = VerifierSetup::from(&public_parameters);
+        let query_data = proof
+            .verify(
+                query.proof_expr(),
+                &accessor,
+                &DoryVerifierPublicSetup::new(&vs, sigma),
+            )
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+
+        assert!(proof_of_sql_verifier::verify_proof(&proof, &pubs, &vk).is_ok());
+    }
+}
+
+mod empty_results {
+    use proof_of_sql::{
+        proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup},
+        sql::proof::QueryData,
+    };
+    use proof_of_sql_verifier::{verify_proof_with_result, HashVersion};
+
+    use super::*;
+
+    /// Tests that an empty result round-trips and verifies to a table without rows.
+    #[test]
+    fn filter_without_matching_rows() {
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = QueryExpr::try_new(
+            "SELECT a, b FROM table WHERE a = 4".parse().unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+
+        let bytes = pubs.try_to_bytes().unwrap();
+        let decoded = PublicInput::try_from(&bytes[..]).unwrap();
+        assert_eq!(decoded.try_to_bytes().unwrap(), bytes);
+        assert_eq!(
+            decoded.hash(HashVersion::LATEST).unwrap(),
+            pubs.hash(HashVersion::LATEST).unwrap()
+        );
+
+        let table = verify_proof_with_result(&proof, &decoded, &vk).unwrap();
+        assert!(table.is_empty());
+        assert_eq!(table.num_columns(), 2);
+        assert_eq!(table.rows().count(), 0);
+    }
+
+    /// Tests that an empty grouped result is rejected instead of panicking upstream.
+    #[test]
+    fn group_by_without_matching_rows() {
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = QueryExpr::try_new(
+            "SELECT a, COUNT(*) AS c FROM table WHERE a = 4 GROUP BY a"
+                .parse()
+                .unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = QueryData {
+            table: owned_table([bigint("a", [0; 0]), bigint("c", [0; 0])]),
+            verification_hash: [0; 32],
+        };
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+
+        assert!(matches!(
+            verify_proof_with_result(&proof, &pubs, &vk),
+            Err(VerifyError::UnverifiableEmptyResult)
+        ));
+    }
+}
+
+mod timestamp_normalization {
+    use proof_of_sql::{
+        base::database::{OwnedColumn, OwnedTable},
+        proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup},
+    };
+    use proof_of_sql_parser::posql_time::{PoSQLTimeUnit, PoSQLTimeZone};
+    use proof_of_sql_verifier::{verify_proof_with_config, HashVersion, TimestampNormalization};
+
+    use super::*;
+
+    /// Tests that a claim annotating the verified timestamps with another timezone only
+    /// verifies with UTC normalization.
+    #[test]
+    fn claims_in_other_timezones() {
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        let mut accessor =
+            OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+        accessor.add_table(
+            "sxt.events".parse().unwrap(),
+            owned_table([
+                bigint("a", [1, 2, 3]),
+                timestamptz(
+                    "t",
+                    PoSQLTimeUnit::Second,
+                    PoSQLTimeZone::Utc,
+                    [1_700_000_000, 1_700_003_600, 1_700_007_200],
+                ),
+            ]),
+            0,
+        );
+        let query = QueryExpr::try_new(
+            "SELECT a, t FROM events WHERE a >= 2".parse().unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let mut query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+        let utc_pubs = PublicInput::new(
+            query.proof_expr(),
+            query_commitments.clone(),
+            QueryData {
+                table: query_data.table.clone(),
+                verification_hash: query_data.verification_hash,
+            },
+        );
+
+        // The same instants, as displayed by a stack one hour east of UTC
+        let columns = query_data.table.inner_table().iter().map(|(name, column)| {
+            let column = match column {
+                OwnedColumn::TimestampTZ(unit, _, values) => OwnedColumn::TimestampTZ(
+                    *unit,
+                    PoSQLTimeZone::FixedOffset(3600),
+                    values.clone(),
+                ),
+                column => column.clone(),
+            };
+            (*name, column)
+        });
+        query_data.table = OwnedTable::try_from_iter(columns).unwrap();
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+
+        assert!(matches!(
+            verify_proof_with_config(&proof, &pubs, &vk, &VerifierConfig::new()),
+            Err(VerifyError::ResultPayloadMismatch)
+        ));
+        let config =
+            VerifierConfig::new().with_timestamp_normalization(TimestampNormalization::Utc);
+        assert!(verify_proof_with_config(&proof, &pubs, &vk, &config).is_ok());
+        // The claim keeps the timezone it was made with
+        assert!(matches!(
+            pubs.query_data().table.inner_table()[1],
+            OwnedColumn::TimestampTZ(_, PoSQLTimeZone::FixedOffset(3600), _)
+        ));
+        // By design, so does the statement hash, which differs from the one of the claim
+        // made in UTC although both verify with the normalization
+        assert!(verify_proof_with_config(&proof, &utc_pubs, &vk, &config).is_ok());
+        assert_ne!(
+            pubs.hash(HashVersion::LATEST).unwrap(),
+            utc_pubs.hash(HashVersion::LATEST).unwrap()
+        );
+        assert_ne!(
+            pubs.try_to_bytes().unwrap(),
+            utc_pubs.try_to_bytes().unwrap()
+        );
+    }
+}
+
+mod predicates {
+    use proof_of_sql::proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup};
+    use proof_of_sql_verifier::verify_proof_with_result;
+    use rstest::rstest;
+
+    use super::*;
+
+    /// Tests that plans with comparison and boolean predicates survive encoding and verify.
+    ///
+    /// The parser has no `IN` operator, so `IN (...)` lists are written as a disjunction
+    /// of equalities, which is how they reach the verifier.
+    #[rstest]
+    #[case::greater_than("a > 1", [2, 3, 2])]
+    #[case::less_than("a < 3", [1, 2, 2])]
+    #[case::greater_or_equal("a >= 2", [2, 3, 2])]
+    #[case::less_or_equal("a <= 2", [1, 2, 2])]
+    #[case::not_equal("a <> 2", [1, 3])]
+    #[case::not_equal_bang("a != 2", [1, 3])]
+    #[case::in_list("a = 1 OR a = 3 OR a = 5", [1, 3])]
+    #[case::range("a > 1 AND a < 3", [2, 2])]
+    #[case::negation("NOT (a = 2 OR b = 'hi')", [3])]
+    #[case::mixed("(a >= 3 OR b = 'hello') AND a <> 1", [2, 3])]
+    #[case::long_in_list(
+        "a = 10 OR a = 9 OR a = 8 OR a = 7 OR a = 6 OR a = 5 OR a = 4 OR a = 3 OR a = 2 OR a = 1",
+        [1, 2, 3, 2]
+    )]
+    fn verify_filter<const N: usize>(#[case] predicate: &str, #[case] expected: [i64; N]) {
+        let max_nu = 4;
+        let sigma = max_nu;
+        let public_parameters = PublicParameters::test_rand(max_nu, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, sigma);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, sigma);
+
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = QueryExpr::try_new(
+            format!("SELECT a FROM table WHERE {predicate}")
+                .parse()
+                .unwrap(),
+            "sxt".parse().unwrap(),
+            &accessor,
+        )
+        .unwrap();
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(query.proof_expr(), &accessor, &verifier_setup)
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let vk = VerificationKey::new(&public_parameters, sigma);
+
+        let bytes = pubs.try_to_bytes().unwrap();
+        let decoded = PublicInput::try_from(&bytes[..]).unwrap();
+        assert_eq!(decoded, pubs);
+        assert_eq!(decoded.try_to_bytes().unwrap(), bytes);
+        #[cfg(feature = "json")]
+        {
+            let json = serde_json::to_vec(&pubs).unwrap();
+            assert_eq!(serde_json::from_slice::<PublicInput>(&json).unwrap(), pubs);
+        }
+
+        let proof = Proof::try_from(&proof.to_bytes()[..]).unwrap();
+        let table = verify_proof_with_result(&proof, &decoded, &vk).unwrap();
+        assert_eq!(table.table(), &owned_table([bigint("a", expected)]));
+    }
+}
+
+#[cfg(feature = "std")]
+mod submission {
+    use proof_of_sql::proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup};
+    use proof_of_sql_verifier::{
+        load_submission, save_submission, SubmissionError, VkRefOrBytes, VK_FILE,
+    };
+
+    use super::*;
+
+    /// Tests that submissions round-trip with included and referenced keys.
+    #[test]
+    fn round_trip() {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(
+                query.proof_expr(),
+                &accessor,
+                &DoryVerifierPublicSetup::new(&vs, 4),
+            )
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let vk = VerificationKey::new(&public_parameters, 4);
+
+        let dir = std::env::temp_dir().join(format!("posql-submission-{}", std::process::id()));
+        save_submission(&dir, &proof, &pubs, &VkRefOrBytes::from(&vk)).unwrap();
+        let (loaded_proof, loaded_pubs, loaded_vk) = load_submission(&dir).unwrap();
+        assert_eq!(loaded_proof, proof);
+        assert_eq!(loaded_pubs, pubs);
+        assert_eq!(loaded_vk.fingerprint(), vk.fingerprint());
+        let VkRefOrBytes::Bytes(bytes) = loaded_vk else {
+            panic!("expected an included key")
+        };
+        let loaded_vk = VerificationKey::try_from(&bytes[..]).unwrap();
+        assert!(
+            proof_of_sql_verifier::verify_proof(&loaded_proof, &loaded_pubs, &loaded_vk).is_ok()
+        );
+
+        let reference = VkRefOrBytes::Ref(vk.fingerprint());
+        save_submission(&dir, &proof, &pubs, &reference).unwrap();
+        assert_eq!(load_submission(&dir).unwrap().2, reference);
+
+        std::fs::write(dir.join(VK_FILE), vk.to_bytes()).unwrap();
+        assert!(matches!(
+            load_submission(&dir),
+            Err(SubmissionError::AmbiguousVk)
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+mod verify_and_extract {
+    use proof_of_sql::proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup};
+    use proof_of_sql_verifier::{verify_and_extract, ProofStage, Statement};
+
+    use super::*;
+
+    /// Tests that the result is extracted from the proof without any claimed query data.
+    #[test]
+    fn extracts_verified_result() {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(
+                query.proof_expr(),
+                &accessor,
+                &DoryVerifierPublicSetup::new(&vs, 4),
+            )
+            .unwrap();
+        let query_commitments = compute_query_commitments(&query, &accessor);
+        let proof = Proof::new(proof);
+        let pubs = PublicInput::new(query.proof_expr(), query_commitments, query_data);
+        let vk = VerificationKey::new(&public_parameters, 4);
+
+        let pubs_size = pubs.try_to_bytes().unwrap().len();
+        let statement = pubs.into_statement();
+        let bytes = statement.try_to_bytes().unwrap();
+        assert!(bytes.len() < pubs_size);
+        let statement = Statement::try_from(&bytes[..]).unwrap();
+        assert_eq!(statement.try_to_bytes().unwrap(), bytes);
+
+        let table = verify_and_extract(&proof, &statement, &vk).unwrap();
+        assert_eq!(
+            table.into_table(),
+            owned_table([varchar("b", ["hello", "world"])])
+        );
+    }
+
+    /// Tests that extraction fails against altered commitments or another key.
+    #[test]
+    fn rejects_invalid_statement() {
+        let rng = &mut test_rng();
+        let public_parameters = PublicParameters::test_rand(4, rng);
+        let ps = ProverSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = Proof::new(VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        ));
+        let vk = VerificationKey::new(&public_parameters, 4);
+
+        let altered_accessor: OwnedTableTestAccessor<DoryEvaluationProof> =
+            build_altered_accessor(prover_setup);
+        let altered = Statement::new(
+            query.proof_expr(),
+            compute_query_commitments(&query, &altered_accessor),
+        );
+        assert!(matches!(
+            verify_and_extract(&proof, &altered, &vk),
+            Err(VerifyError::ProofRejected { .. })
+        ));
+
+        let statement = Statement::new(
+            query.proof_expr(),
+            compute_query_commitments(&query, &accessor),
+        );
+        let other_parameters = PublicParameters::test_rand(4, rng);
+        let other_vk = VerificationKey::new(&other_parameters, 4);
+        assert!(matches!(
+            verify_and_extract(&proof, &statement, &other_vk),
+            Err(VerifyError::ProofRejected {
+                stage: ProofStage::Opening
+            })
+        ));
+    }
+}
+
+#[cfg(feature = "std")]
+mod verify_bundle {
+    use std::{num::NonZeroUsize, thread};
+
+    use proof_of_sql::{
+        proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup},
+        sql::proof::QueryData,
+    };
+    use proof_of_sql_verifier::{verify_bundle, BundleMode, ItemStatus};
+
+    use super::*;
+
+    /// Builds a valid proof and public input, and a public input without commitments
+    /// that fails verification before any cryptographic check.
+    fn build_items() -> (Proof, PublicInput, PublicInput, VerificationKey) {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let accessor = build_accessor::<DoryEvaluationProof>(prover_setup);
+        let query = build_query(&accessor);
+        let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+            query.proof_expr(),
+            &accessor,
+            &prover_setup,
+        );
+        let query_data = proof
+            .verify(
+                query.proof_expr(),
+                &accessor,
+                &DoryVerifierPublicSetup::new(&vs, 4),
+            )
+            .unwrap();
+        let valid = PublicInput::new(
+            query.proof_expr(),
+            compute_query_commitments(&query, &accessor),
+            QueryData {
+                table: query_data.table.clone(),
+                verification_hash: query_data.verification_hash,
+            },
+        );
+        let invalid = PublicInput::new(query.proof_expr(), Default::default(), query_data);
+        (
+            Proof::new(proof),
+            valid,
+            invalid,
+            VerificationKey::new(&public_parameters, 4),
+        )
+    }
+
+    /// Tests that every item is verified and timed when collecting all outcomes.
+    #[test]
+    fn collects_all_outcomes() {
+        let (proof, valid, invalid, vk) = build_items();
+        let items = [(&proof, &valid), (&proof, &invalid), (&proof, &valid)];
+
+        let report = verify_bundle(&items, &vk, BundleMode::CollectAll);
+
+        assert!(!report.is_verified());
+        assert!(matches!(report.first_failure(), Some((1, _))));
+        let items = report.items();
+        assert!(matches!(items[0].status, ItemStatus::Verified));
+        assert!(matches!(items[2].status, ItemStatus::Verified));
+        assert!(items[0].elapsed > items[1].elapsed);
+
+        let report = verify_bundle(&[(&proof, &valid)], &vk, BundleMode::CollectAll);
+        assert!(report.is_verified());
+        assert!(verify_bundle(&[], &vk, BundleMode::CollectAll).is_verified());
+    }
+
+    /// Tests that the items following a failure are skipped when aborting on failures.
+    #[test]
+    fn aborts_on_first_failure() {
+        let (proof, valid, invalid, vk) = build_items();
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let mut items = vec![(&proof, &invalid)];
+        items.extend(std::iter::repeat_n((&proof, &valid), 2 * threads));
+
+        let report = verify_bundle(&items, &vk, BundleMode::AbortOnFirstFailure);
+
+        assert!(matches!(report.first_failure(), Some((0, _))));
+        let skipped = report
+            .items()
+            .iter()
+            .filter(|item| matches!(item.status, ItemStatus::Skipped))
+            .count();
+        // Only the items picked while the first one was failing are verified
+        assert!(skipped >= items.len() - threads);
+        assert!(report.items()[1..]
+            .iter()
+            .all(|item| !matches!(item.status, ItemStatus::Failed(_))));
+    }
+}
+
+/// Differential tests of [`verify_proof`] against upstream verification, catching checks
+/// of this crate that would reject claims upstream accepts, or the other way round.
+///
+/// The corpus is slow to verify in debug builds, run it with
+/// `cargo test --release --features "test rand" --test integration differential`.
+#[cfg(all(feature = "test", feature = "rand"))]
+mod differential {
+    use proof_of_sql::{
+        base::database::{OwnedColumn, OwnedTable},
+        proof_primitive::dory::{
+            DoryCommitment, DoryScalar, DoryVerifierPublicSetup, VerifierSetup,
+        },
+        sql::proof::QueryData,
+    };
+    use proof_of_sql_verifier::{verify_proof, ColumnDistribution, TableGenerator};
+
+    use super::*;
+
+    const QUERIES: [&str; 5] = [
+        "SELECT b FROM table WHERE a = 2",
+        "SELECT a, b FROM table WHERE c = true",
+        "SELECT a FROM table WHERE a >= 1",
+        "SELECT * FROM table WHERE b = 'y'",
+        "SELECT a, b FROM table WHERE NOT (a = 0 OR c = false)",
+    ];
+
+    /// The ways a valid public input is tampered with before being verified.
+    #[derive(Clone, Copy, Debug)]
+    enum Mutation {
+        None,
+        ClaimedRows,
+        VerificationHash,
+        Commitments,
+        Plan,
+    }
+
+    /// Returns whether upstream accepts the claim, i.e. whether the proof verifies and
+    /// attests the claimed result and verification hash.
+    fn upstream_accepts(
+        proof: &VerifiableQueryResult<DoryEvaluationProof>,
+        pubs: &PublicInput,
+        setup: &DoryVerifierPublicSetup<'_>,
+    ) -> bool {
+        proof
+            .verify(pubs.expr(), pubs.commitments(), setup)
+            .is_ok_and(|verified| {
+                verified.table == pubs.query_data().table
+                    && verified.verification_hash == pubs.query_data().verification_hash
+            })
+    }
+
+    /// Returns the claimed result with one more row, or one less when `table` has rows.
+    fn tamper_rows(table: &OwnedTable<DoryScalar>) -> OwnedTable<DoryScalar> {
+        let num_rows = table.num_rows();
+        OwnedTable::try_from_iter(table.inner_table().iter().map(|(name, column)| {
+            let column = if num_rows > 0 {
+                column.slice(0, num_rows - 1)
+            } else {
+                match column {
+                    OwnedColumn::BigInt(_) => OwnedColumn::BigInt(vec![0]),
+                    OwnedColumn::VarChar(_) => OwnedColumn::VarChar(vec!["y".to_string()]),
+                    OwnedColumn::Boolean(_) => OwnedColumn::Boolean(vec![true]),
+                    _ => unreachable!("generated tables only have these types"),
+                }
+            };
+            (*name, column)
+        }))
+        .unwrap()
+    }
+
+    /// Tests that this crate and upstream take the same decisions over a corpus of
+    /// generated tables, queries and tampered claims.
+    #[test]
+    fn decisions_match_upstream() {
+        let rng = &mut test_rng();
+        let public_parameters = PublicParameters::test_rand(4, rng);
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let verifier_setup = DoryVerifierPublicSetup::new(&vs, 4);
+        let vk = VerificationKey::new(&public_parameters, 4);
+        let generator = |rows| {
+            TableGenerator::new(rows)
+                .column("a", ColumnDistribution::UniformBigInt(0..=3))
+                .column(
+                    "b",
+                    ColumnDistribution::Choice(vec!["x".into(), "y".into(), "z".into()]),
+                )
+                .column(
+                    "c",
+                    ColumnDistribution::Boolean {
+                        numerator: 1,
+                        denominator: 2,
+                    },
+                )
+        };
+
+        let mut decisions = 0;
+        for rows in [1, 12] {
+            let accessor = generator(rows).build_accessor::<DoryEvaluationProof>(
+                "sxt.table".parse().unwrap(),
+                prover_setup,
+                rng,
+            );
+            let other_accessor = generator(rows).build_accessor::<DoryEvaluationProof>(
+                "sxt.table".parse().unwrap(),
+                prover_setup,
+                rng,
+            );
+            for (index, sql) in QUERIES.iter().enumerate() {
+                let parse = |sql: &str| {
+                    QueryExpr::<DoryCommitment>::try_new(
+                        sql.parse().unwrap(),
+                        "sxt".parse().unwrap(),
+                        &accessor,
+                    )
+                    .unwrap()
+                };
+                let query = parse(sql);
+                let other_query = parse(QUERIES[(index + 1) % QUERIES.len()]);
+                let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+                    query.proof_expr(),
+                    &accessor,
+                    &prover_setup,
+                );
+                let claim = || {
+                    proof
+                        .verify(query.proof_expr(), &accessor, &verifier_setup)
+                        .unwrap()
+                };
+
+                for mutation in [
+                    Mutation::None,
+                    Mutation::ClaimedRows,
+                    Mutation::VerificationHash,
+                    Mutation::Commitments,
+                    Mutation::Plan,
+                ] {
+                    let QueryData {
+                        mut table,
+                        mut verification_hash,
+                    } = claim();
+                    let mut expr = query.proof_expr();
+                    let mut commitments = compute_query_commitments(&query, &accessor);
+                    match mutation {
+                        Mutation::None => {}
+                        Mutation::ClaimedRows => table = tamper_rows(&table),
+                        Mutation::VerificationHash => verification_hash[0] ^= 1,
+                        Mutation::Commitments => {
+                            commitments = compute_query_commitments(&query, &other_accessor)
+                        }
+                        Mutation::Plan => expr = other_query.proof_expr(),
+                    }
+                    let query_data = QueryData {
+                        table,
+                        verification_hash,
+                    };
+                    let pubs = PublicInput::new(expr, commitments, query_data);
+
+                    let upstream = upstream_accepts(&proof, &pubs, &verifier_setup);
+                    let ours = verify_proof(&Proof::new(proof.clone()), &pubs, &vk).is_ok();
+                    assert_eq!(
+                        ours, upstream,
+                        "diverging decisions for {sql:?} over {rows} rows with {mutation:?}"
+                    );
+                    assert_eq!(ours, matches!(mutation, Mutation::None));
+                    decisions += 1;
+                }
+            }
+        }
+        assert_eq!(decisions, 2 * QUERIES.len() * 5);
+    }
+}
+
+/// Differential tests of the CBOR and JSON encodings of public inputs, the same checks
+/// the `pubs_codecs` fuzz target runs on arbitrary inputs.
+#[cfg(feature = "json")]
+mod codecs {
+    use proof_of_sql::proof_primitive::dory::{DoryVerifierPublicSetup, VerifierSetup};
+    use proof_of_sql_parser::posql_time::{PoSQLTimeUnit, PoSQLTimeZone};
+    use proof_of_sql_verifier::{ColumnId, HashVersion};
+
+    use super::*;
+
+    /// Decodes a public input from CBOR and, if it is valid, checks that it survives a
+    /// round-trip through JSON. Returns whether the input was valid.
+    fn check_round_trip(bytes: &[u8]) -> bool {
+        let Ok(pubs) = PublicInput::try_from(bytes) else {
+            return false;
+        };
+        let json = serde_json::to_vec(&pubs).unwrap();
+        let decoded: PublicInput = serde_json::from_slice(&json).unwrap();
+        assert_eq!(decoded, pubs);
+        assert_eq!(
+            decoded.try_to_bytes().unwrap(),
+            pubs.try_to_bytes().unwrap()
+        );
+        for version in [HashVersion::V1, HashVersion::V2, HashVersion::V3] {
+            assert_eq!(decoded.hash(version).unwrap(), pubs.hash(version).unwrap());
+        }
+        assert_eq!(decoded.json_hash().unwrap(), pubs.json_hash().unwrap());
+        true
+    }
+
+    /// Builds encoded public inputs covering every column type, omitted columns and
+    /// paged results.
+    fn build_corpus() -> Vec<Vec<u8>> {
+        let public_parameters = PublicParameters::test_rand(4, &mut test_rng());
+        let ps = ProverSetup::from(&public_parameters);
+        let vs = VerifierSetup::from(&public_parameters);
+        let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+        let mut accessor =
+            OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+        accessor.add_table(
+            "sxt.table".parse().unwrap(),
+            owned_table([
+                bigint("a", [1, 2, 3, 2]),
+                varchar("b", ["hi", "hello", "there", "world"]),
+                int128("c", [i128::MAX, i128::MIN, 0, -1]),
+                boolean("d", [true, false, true, true]),
+                decimal75("e", 12, 2, [1, -2, 3, 4]),
+                timestamptz(
+                    "f",
+                    PoSQLTimeUnit::Millisecond,
+                    PoSQLTimeZone::Utc,
+                    [1, 2, 3, i64::MIN],
+                ),
+                smallint("g", [1_i16, 2, 3, -4]),
+                int("h", [1, 2, 3, -4]),
+                scalar("i", [1, 2, 3, -4]),
+            ]),
+            0,
+        );
+
+        let mut corpus = Vec::new();
+        for (sql, omitted) in [
+            ("SELECT * FROM table WHERE a = 2", "c"),
+            ("SELECT c, e FROM table WHERE d = true", "e"),
+            ("SELECT b FROM table WHERE a = 4", "b"),
+        ] {
+            let query = QueryExpr::try_new(sql.parse().unwrap(), "sxt".parse().unwrap(), &accessor)
+                .unwrap();
+            let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+                query.proof_expr(),
+                &accessor,
+                &prover_setup,
+            );
+            let query_data = proof
+                .verify(
+                    query.proof_expr(),
+                    &accessor,
+                    &DoryVerifierPublicSetup::new(&vs, 4),
+                )
+                .unwrap();
+            let pubs = PublicInput::new(
+                query.proof_expr(),
+                compute_query_commitments(&query, &accessor),
+                query_data,
+            );
+            corpus.push(pubs.try_to_bytes().unwrap());
+            let omitted = PublicInput::try_from(&corpus[corpus.len() - 1][..])
+                .unwrap()
+                .omit_columns([omitted.parse::<ColumnId>().unwrap()])
+                .unwrap();
+            corpus.push(omitted.try_to_bytes().unwrap());
+            corpus.push(pubs.page_result(2).unwrap().try_to_bytes().unwrap());
+        }
+        corpus
+    }
+
+    /// Tests that valid public inputs decode to the same statement from either codec.
+    #[test]
+    fn cbor_and_json_agree() {
+        let corpus = build_corpus();
+        assert_eq!(corpus.len(), 9);
+        assert!(corpus.iter().all(|bytes| check_round_trip(bytes)));
+    }
+
+    /// Tests that corrupted public inputs that still decode survive the round-trip too.
+    #[test]
+    fn cbor_and_json_agree_on_mutations() {
+        // A xorshift generator, so that failures are reproducible
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+        let mut valid = 0;
+        for seed in build_corpus() {
+            for _ in 0..16 {
+                let mut bytes = seed.clone();
+                let index = next() % bytes.len();
+                bytes[index] ^= 1 << (next() % 8);
+                valid += usize::from(check_round_trip(&bytes));
+            }
+        }
+        // Some mutations, e.g. of result values, keep the input valid
+        assert!(valid > 0);
+    }
 }
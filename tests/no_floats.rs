@@ -0,0 +1,55 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks that no floating point type is used by the library.
+//!
+//! The clippy lints forbidden in `lib.rs` catch float arithmetic, but not floats that are
+//! only stored or formatted, so the sources are also scanned for the primitive types.
+
+use std::{fs, path::Path};
+
+const FLOAT_TYPES: [&str; 2] = ["f32", "f64"];
+
+fn visit(dir: &Path, findings: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            visit(&path, findings);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            let source = fs::read_to_string(&path).unwrap();
+            for (line_number, line) in source.lines().enumerate() {
+                let has_float = line
+                    .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .any(|token| FLOAT_TYPES.contains(&token));
+                if has_float {
+                    findings.push(format!("{}:{}", path.display(), line_number + 1));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn library_sources_do_not_use_floats() {
+    let mut findings = Vec::new();
+    visit(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+        &mut findings,
+    );
+    assert!(
+        findings.is_empty(),
+        "floating point types found in: {findings:?}"
+    );
+}
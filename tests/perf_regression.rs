@@ -0,0 +1,176 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks that fixed verification workloads are not slower than their stored baselines.
+//!
+//! Timings depend on the machine, so the test is ignored by default and meant to be run
+//! in release mode on the machine the baselines were recorded on, typically before
+//! bumping upstream dependencies:
+//!
+//! ```text
+//! cargo test --release --test perf_regression -- --ignored
+//! ```
+//!
+//! Setting `PERF_BASELINE_UPDATE=1` records the current timings as the new baselines
+//! instead of comparing against them.
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use ark_std::test_rng;
+use proof_of_sql::{
+    base::{
+        commitment::{QueryCommitments, QueryCommitmentsExt},
+        database::{owned_table_utility::*, OwnedTableTestAccessor, TestAccessor},
+    },
+    proof_primitive::dory::{
+        DoryEvaluationProof, DoryProverPublicSetup, DoryVerifierPublicSetup, ProverSetup,
+        PublicParameters, VerifierSetup,
+    },
+    sql::{
+        parse::QueryExpr,
+        proof::{ProofPlan, VerifiableQueryResult},
+    },
+};
+use proof_of_sql_verifier::{verify_proof, Proof, PublicInput, VerificationKey};
+use serde_json::{Map, Value};
+
+/// The slowdown tolerated before a workload is reported, in percent of its baseline.
+const TOLERANCE_PERCENT: u64 = 20;
+
+/// The number of timed runs of each workload, of which the median is kept.
+const RUNS: usize = 9;
+
+/// Proves a filter query over a table of `rows` rows.
+fn setup(rows: i64) -> (Proof, PublicInput, VerificationKey) {
+    let public_parameters = PublicParameters::test_rand(6, &mut test_rng());
+    let ps = ProverSetup::from(&public_parameters);
+    let prover_setup = DoryProverPublicSetup::new(&ps, 4);
+    let vs = VerifierSetup::from(&public_parameters);
+    let vk = VerificationKey::new(&public_parameters, 4);
+
+    let mut accessor =
+        OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(prover_setup);
+    accessor.add_table(
+        "sxt.table".parse().unwrap(),
+        owned_table([
+            bigint("a", 0..rows),
+            varchar("b", (0..rows).map(|i| i.to_string())),
+        ]),
+        0,
+    );
+    let query = QueryExpr::try_new(
+        "SELECT a, b FROM table WHERE a > 5".parse().unwrap(),
+        "sxt".parse().unwrap(),
+        &accessor,
+    )
+    .unwrap();
+    let proof = VerifiableQueryResult::<DoryEvaluationProof>::new(
+        query.proof_expr(),
+        &accessor,
+        &prover_setup,
+    );
+    let query_data = proof
+        .verify(
+            query.proof_expr(),
+            &accessor,
+            &DoryVerifierPublicSetup::new(&vs, 4),
+        )
+        .unwrap();
+    let columns = query.proof_expr().get_column_references();
+    let commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+    let pubs = PublicInput::new(query.proof_expr(), commitments, query_data);
+    (Proof::new(proof), pubs, vk)
+}
+
+/// Returns the median duration of `RUNS` runs of `workload`, after a warm-up run.
+fn measure(mut workload: impl FnMut()) -> Duration {
+    workload();
+    let mut durations: Vec<_> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            workload();
+            start.elapsed()
+        })
+        .collect();
+    durations.sort();
+    durations[RUNS / 2]
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros().try_into().unwrap()
+}
+
+fn baselines_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/perf/baselines.json")
+}
+
+#[test]
+#[ignore = "timings are machine dependent, run explicitly in release mode"]
+fn perf_regression() {
+    let (proof, pubs, vk) = setup(1024);
+    let proof_bytes = proof.to_bytes();
+    let pubs_bytes = pubs.try_to_bytes().unwrap();
+    let timings = [
+        (
+            "verify_filter_1024",
+            measure(|| verify_proof(&proof, &pubs, &vk).unwrap()),
+        ),
+        (
+            "decode_proof_1024",
+            measure(|| {
+                Proof::try_from(&proof_bytes[..]).unwrap();
+            }),
+        ),
+        (
+            "decode_pubs_1024",
+            measure(|| {
+                PublicInput::try_from(&pubs_bytes[..]).unwrap();
+            }),
+        ),
+    ];
+
+    if std::env::var_os("PERF_BASELINE_UPDATE").is_some() {
+        let baselines: Map<String, Value> = timings
+            .iter()
+            .map(|(name, duration)| (name.to_string(), micros(*duration).into()))
+            .collect();
+        let json = serde_json::to_string_pretty(&baselines).unwrap();
+        fs::write(baselines_path(), json + "\n").unwrap();
+        return;
+    }
+
+    let baselines: Map<String, Value> =
+        serde_json::from_str(&fs::read_to_string(baselines_path()).unwrap()).unwrap();
+    let mut regressions = Vec::new();
+    for (name, duration) in timings {
+        let baseline = baselines
+            .get(name)
+            .and_then(Value::as_u64)
+            .unwrap_or_else(|| panic!("no baseline for {name}, record one first"));
+        let measured = micros(duration);
+        if measured * 100 > baseline * (100 + TOLERANCE_PERCENT) {
+            regressions.push(format!("{name}: {measured}us, baseline {baseline}us"));
+        }
+    }
+    assert!(
+        regressions.is_empty(),
+        "workloads regressed by more than {TOLERANCE_PERCENT}%:\n{}",
+        regressions.join("\n")
+    );
+}
@@ -0,0 +1,178 @@
+// Copyright 2024, Horizen Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Property tests of the statement hash, which on-chain attestations rely on: any
+//! change to the plan, the commitments or the claimed result must change the hash,
+//! while encodings of the same statement must hash identically.
+
+use std::sync::OnceLock;
+
+use ark_std::test_rng;
+use proof_of_sql::{
+    base::{
+        commitment::{QueryCommitments, QueryCommitmentsExt},
+        database::{owned_table_utility::*, OwnedTable, OwnedTableTestAccessor, TestAccessor},
+    },
+    proof_primitive::dory::{
+        DoryEvaluationProof, DoryProverPublicSetup, DoryScalar, ProverSetup, PublicParameters,
+    },
+    sql::{
+        parse::QueryExpr,
+        proof::{ProofPlan, QueryData},
+    },
+};
+use proof_of_sql_verifier::{HashVersion, PublicInput};
+use proptest::prelude::*;
+
+const ROWS: usize = 4;
+
+/// The setup the commitments are computed with, shared across cases since generating it
+/// dominates the test time.
+fn prover_setup() -> &'static ProverSetup<'static> {
+    static SETUP: OnceLock<(&'static PublicParameters, ProverSetup<'static>)> = OnceLock::new();
+    &SETUP
+        .get_or_init(|| {
+            let public_parameters =
+                Box::leak(Box::new(PublicParameters::test_rand(3, &mut test_rng())));
+            (public_parameters, ProverSetup::from(&*public_parameters))
+        })
+        .1
+}
+
+/// Builds the public input of `sql` over tables whose columns hold `values`, claiming
+/// `result`.
+fn public_input(sql: &str, values: [i64; ROWS], result: OwnedTable<DoryScalar>) -> PublicInput {
+    let setup = DoryProverPublicSetup::new(prover_setup(), 2);
+    let mut accessor = OwnedTableTestAccessor::<DoryEvaluationProof>::new_empty_with_setup(setup);
+    for table in ["sxt.tab", "sxt.other"] {
+        accessor.add_table(
+            table.parse().unwrap(),
+            owned_table([bigint("a", values), bigint("b", [0; ROWS])]),
+            0,
+        );
+    }
+    let query =
+        QueryExpr::try_new(sql.parse().unwrap(), "sxt".parse().unwrap(), &accessor).unwrap();
+    let columns = query.proof_expr().get_column_references();
+    let commitments = QueryCommitments::from_accessor_with_max_bounds(columns, &accessor);
+    let query_data = QueryData {
+        table: result,
+        verification_hash: [0; 32],
+    };
+    PublicInput::new(query.proof_expr(), commitments, query_data)
+}
+
+fn hash(pubs: &PublicInput) -> [u8; 32] {
+    pubs.hash(HashVersion::LATEST).unwrap()
+}
+
+fn result(values: Vec<i64>) -> OwnedTable<DoryScalar> {
+    owned_table([bigint("a", values)])
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    /// Plans filtering on different literals hash differently.
+    #[test]
+    fn plan_changes_change_the_hash(x in any::<i64>(), y in any::<i64>()) {
+        prop_assume!(x != y);
+        let filter = |literal| {
+            let sql = format!("SELECT a FROM tab WHERE a = {literal}");
+            public_input(&sql, [0; ROWS], result(vec![]))
+        };
+        prop_assert_ne!(hash(&filter(x)), hash(&filter(y)));
+    }
+
+    /// Commitments to different table contents hash differently.
+    #[test]
+    fn commitment_changes_change_the_hash(
+        values in any::<[i64; ROWS]>(),
+        row in 0..ROWS,
+        delta in 1..i64::MAX,
+    ) {
+        let sql = "SELECT a FROM tab WHERE b = 0";
+        let mut changed = values;
+        changed[row] = changed[row].wrapping_add(delta);
+        let left = public_input(sql, values, result(vec![]));
+        let right = public_input(sql, changed, result(vec![]));
+        prop_assert_ne!(hash(&left), hash(&right));
+    }
+
+    /// Claimed results differing in a single cell hash differently.
+    #[test]
+    fn result_cell_changes_change_the_hash(
+        cells in prop::collection::vec(any::<i64>(), 1..8),
+        row in any::<prop::sample::Index>(),
+        delta in 1..i64::MAX,
+    ) {
+        let sql = "SELECT a FROM tab WHERE b = 0";
+        let row = row.index(cells.len());
+        let mut changed = cells.clone();
+        changed[row] = changed[row].wrapping_add(delta);
+        let left = public_input(sql, [0; ROWS], result(cells));
+        let right = public_input(sql, [0; ROWS], result(changed));
+        prop_assert_ne!(hash(&left), hash(&right));
+    }
+
+    /// Flipping any byte of the encoding either makes it invalid, or yields another
+    /// statement with another hash, unless it decodes to the same canonical encoding.
+    #[test]
+    fn byte_flips_change_the_hash(index in any::<prop::sample::Index>(), mask in 1..=u8::MAX) {
+        let pubs = public_input(
+            "SELECT a FROM tab WHERE b = 0",
+            [1, 2, 3, 4],
+            result(vec![1, 2, 3, 4]),
+        );
+        let bytes = pubs.try_to_bytes().unwrap();
+        let mut flipped = bytes.clone();
+        flipped[index.index(bytes.len())] ^= mask;
+        if let Ok(decoded) = PublicInput::try_from(&flipped[..]) {
+            let same_statement = decoded.try_to_bytes().unwrap() == bytes;
+            prop_assert_eq!(hash(&decoded) == hash(&pubs), same_statement);
+        }
+    }
+
+    /// Statements equal after canonicalization hash identically, whatever the insertion
+    /// order of their commitments or the case of their identifiers.
+    #[test]
+    fn canonically_equal_statements_hash_identically(
+        values in any::<[i64; ROWS]>(),
+        swap in any::<bool>(),
+        uppercase in any::<bool>(),
+    ) {
+        let sql = "SELECT a FROM tab WHERE b = 0";
+        let with_other_table = |sql: &str, swap: bool| {
+            let pubs = public_input(sql, values, result(vec![]));
+            let mut commitments = pubs.commitments().clone();
+            let commitment = commitments[0].clone();
+            commitments.insert("sxt.other".parse().unwrap(), commitment);
+            if swap {
+                commitments.reverse();
+            }
+            let query_data = QueryData {
+                table: pubs.query_data().table.clone(),
+                verification_hash: pubs.query_data().verification_hash,
+            };
+            PublicInput::new(pubs.expr(), commitments, query_data)
+        };
+        let canonical = with_other_table(sql, false);
+        let cased = if uppercase { sql.to_uppercase() } else { sql.to_string() };
+        let pubs = with_other_table(&cased, swap);
+        prop_assert_eq!(hash(&pubs), hash(&canonical));
+        let decoded = PublicInput::try_from(&pubs.try_to_bytes().unwrap()[..]).unwrap();
+        prop_assert_eq!(hash(&decoded), hash(&canonical));
+    }
+}